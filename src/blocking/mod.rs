@@ -0,0 +1,288 @@
+//!Blocking client
+//!
+//!Wraps [Client](../client/struct.Client.html) together with own small runtime,
+//!allowing to perform requests without writing async code.
+//!
+//!Available only with feature `blocking`
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::{blocking, client};
+//!
+//!let client = blocking::Client::default();
+//!
+//!let req = client::Request::get("https://google.com").expect("To create request").empty();
+//!let mut result = client.send(req).expect("Successful");
+//!assert!(result.is_success());
+//!
+//!let html = result.text().expect("To read HTML");
+//!println!("Google page:\n{}", html);
+//!```
+
+use core::ops::{Deref, DerefMut};
+use core::fmt;
+use std::error::Error as StdError;
+use std::sync::Arc;
+use std::fs;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::client::{self, config, response};
+use crate::extractor;
+
+#[derive(Debug)]
+///Describes failure of blocking request.
+pub enum Error {
+    ///Request failed.
+    Request(client::Error),
+    ///Request is timed out.
+    Timeout,
+}
+
+impl From<client::Error> for Error {
+    #[inline]
+    fn from(error: client::Error) -> Self {
+        Error::Request(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Request(error) => fmt::Display::fmt(error, f),
+            Error::Timeout => f.write_str("Request is timed out"),
+        }
+    }
+}
+
+impl StdError for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Request(error) => Some(error),
+            Error::Timeout => None,
+        }
+    }
+}
+
+///Blocking HTTP Client
+///
+///Owns runtime with single worker thread, on which requests are executed.
+pub struct Client<C=config::DefaultCfg> where C: config::Config + 'static {
+    inner: client::Client<C>,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl Default for Client {
+    ///Creates Client with default configuration.
+    fn default() -> Self {
+        Client::<config::DefaultCfg>::new()
+    }
+}
+
+impl<C: config::Config> fmt::Debug for Client<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Blocking {:?}", self.inner)
+    }
+}
+
+impl<C: config::Config> Client<C> where <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+                                        <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+                                        <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    ///Creates new instance of client with specified configuration.
+    ///
+    ///# Panics
+    ///
+    ///- If unable to create runtime.
+    pub fn new() -> Self {
+        Self::with_client(client::Client::new())
+    }
+
+    ///Creates new instance using provided async client.
+    ///
+    ///# Panics
+    ///
+    ///- If unable to create runtime.
+    pub fn with_client(inner: client::Client<C>) -> Self {
+        let runtime = tokio::runtime::Builder::new().threaded_scheduler()
+                                                    .core_threads(1)
+                                                    .thread_name("yukikaze-blocking")
+                                                    .enable_all()
+                                                    .build()
+                                                    .expect("To create runtime");
+
+        Self {
+            inner,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    #[inline]
+    ///Returns reference to underlying async client.
+    pub fn inner(&self) -> &client::Client<C> {
+        &self.inner
+    }
+
+    #[inline]
+    fn wrap(&self, inner: response::Response) -> Response {
+        Response {
+            inner,
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    fn timed<F: core::future::Future<Output=client::RequestResult>>(&self, ongoing: F) -> Result<Response, Error> {
+        //Timer is created within runtime
+        match self.runtime.handle().block_on(self.inner.with_timeout(ongoing)) {
+            Some(result) => result.map(|resp| self.wrap(resp)).map_err(Error::from),
+            None => Err(Error::Timeout),
+        }
+    }
+
+    ///Sends request, and returns response.
+    pub fn request(&self, req: client::Request) -> Result<Response, Error> {
+        let result = self.runtime.handle().block_on(self.inner.request(req));
+        result.map(|resp| self.wrap(resp)).map_err(Error::from)
+    }
+
+    ///Sends request and returns response. Timed version.
+    pub fn send(&self, req: client::Request) -> Result<Response, Error> {
+        self.timed(self.inner.request(req))
+    }
+
+    ///Sends request and returns response, while handling redirects.
+    pub fn redirect_request(&self, req: client::Request) -> Result<Response, Error> {
+        let result = self.runtime.handle().block_on(self.inner.redirect_request(req));
+        result.map(|resp| self.wrap(resp)).map_err(Error::from)
+    }
+
+    ///Sends request and returns response, while handling redirects. Timed version.
+    pub fn send_redirect(&self, req: client::Request) -> Result<Response, Error> {
+        self.timed(self.inner.redirect_request(req))
+    }
+}
+
+///Blocking HTTP Response
+///
+///Implements `Deref` and `DerefMut` to access regular yukikaze's response.
+pub struct Response {
+    inner: response::Response,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl fmt::Debug for Response {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+impl Response {
+    #[inline]
+    ///Returns underlying async response.
+    ///
+    ///Note that body of such response can be read only within runtime.
+    pub fn into_inner(self) -> response::Response {
+        self.inner
+    }
+
+    ///Extracts Response's body as raw bytes.
+    pub fn body(&mut self) -> Result<bytes::Bytes, extractor::BodyReadError> {
+        let body = self.inner.body();
+        self.runtime.handle().block_on(body)
+    }
+
+    ///Extracts Response's body as text
+    pub fn text(&mut self) -> Result<String, extractor::BodyReadError> {
+        let text = self.inner.text();
+        self.runtime.handle().block_on(text)
+    }
+
+    ///Extracts Response's body as JSON
+    pub fn json<J: serde::de::DeserializeOwned>(&mut self) -> Result<J, extractor::BodyReadError> {
+        let json = self.inner.json();
+        self.runtime.handle().block_on(json)
+    }
+
+    ///Extracts Response's body as JSON, keeping raw body.
+    pub fn json_with_raw<J: serde::de::DeserializeOwned>(&mut self) -> Result<(Result<J, extractor::BodyReadError>, bytes::Bytes), extractor::BodyReadError> {
+        let json = self.inner.json_with_raw();
+        self.runtime.handle().block_on(json)
+    }
+
+    ///Extracts Response's body as raw bytes, using specified limit instead of `Content-Length`
+    pub fn body_limited(&mut self, limit: Option<usize>) -> Result<bytes::Bytes, extractor::BodyReadError> {
+        let body = self.inner.body_limited(limit);
+        self.runtime.handle().block_on(body)
+    }
+
+    ///Extracts Response's body as text, using specified limit instead of `Content-Length`
+    pub fn text_limited(&mut self, limit: Option<usize>) -> Result<String, extractor::BodyReadError> {
+        let text = self.inner.text_limited(limit);
+        self.runtime.handle().block_on(text)
+    }
+
+    ///Extracts Response's body as JSON, using specified limit instead of `Content-Length`
+    pub fn json_limited<J: serde::de::DeserializeOwned>(&mut self, limit: Option<usize>) -> Result<J, extractor::BodyReadError> {
+        let json = self.inner.json_limited(limit);
+        self.runtime.handle().block_on(json)
+    }
+
+    ///Extracts Response's body into file
+    pub fn file(&mut self, file: fs::File) -> Result<fs::File, extractor::BodyReadError> {
+        let file = self.inner.file(file);
+        self.runtime.handle().block_on(file)
+    }
+
+    #[cfg(feature = "encoding")]
+    ///Extracts Response's body into file, transcoding text into UTF-8.
+    pub fn file_utf8(&mut self, file: fs::File) -> Result<fs::File, extractor::BodyReadError> {
+        let file = self.inner.file_utf8(file);
+        self.runtime.handle().block_on(file)
+    }
+
+    ///Extracts Response's body into file at `path`, replacing it only on success.
+    pub fn file_atomic(&mut self, path: &std::path::Path) -> Result<fs::File, extractor::BodyReadError> {
+        let file = self.inner.file_atomic(path);
+        self.runtime.handle().block_on(file)
+    }
+
+    ///Extracts Response's body, keeping it in memory unless it exceeds `threshold`.
+    pub fn body_spooled(&mut self, threshold: usize) -> Result<extractor::Spooled, extractor::BodyReadError> {
+        let body = self.inner.body_spooled(threshold);
+        self.runtime.handle().block_on(body)
+    }
+
+    ///Extracts Response's body into file, while also returning it as raw bytes.
+    pub fn body_tee(&mut self, file: fs::File) -> Result<(fs::File, Option<bytes::Bytes>), extractor::BodyReadError> {
+        let tee = self.inner.body_tee(file);
+        self.runtime.handle().block_on(tee)
+    }
+
+    ///Extracts Response's body as raw bytes.
+    pub fn body_notify<N: extractor::Notifier>(&mut self, notify: N) -> Result<bytes::Bytes, extractor::BodyReadError> {
+        let body = self.inner.body_notify(notify);
+        self.runtime.handle().block_on(body)
+    }
+
+    ///Extracts Response's body into file
+    pub fn file_notify<N: extractor::Notifier>(&mut self, file: fs::File, notify: N) -> Result<fs::File, extractor::BodyReadError> {
+        let file = self.inner.file_notify(file, notify);
+        self.runtime.handle().block_on(file)
+    }
+}
+
+impl Deref for Response {
+    type Target = response::Response;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl DerefMut for Response {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
@@ -0,0 +1,101 @@
+//!Cache of alternative services.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::header;
+use crate::connector::stats::host_key;
+
+struct Entry {
+    service: header::AltService,
+    expires: Instant,
+}
+
+#[derive(Default)]
+///Alternative services, advertised by origins, identified by `<scheme>://<authority>`
+pub(crate) struct Cache {
+    origins: Mutex<HashMap<String, Vec<Entry>>>,
+}
+
+impl Cache {
+    ///Records alternative services from response's headers.
+    ///
+    ///As per RFC, newly advertised services replace the previous ones.
+    pub(crate) fn update(&self, uri: &hyper::Uri, headers: &http::HeaderMap) {
+        let mut values = headers.get_all(header::ALT_SVC).iter().filter_map(|value| value.to_str().ok()).peekable();
+        if values.peek().is_none() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut services = Vec::new();
+        let mut clear = false;
+
+        for value in values {
+            match value.parse::<header::AltSvc>() {
+                Ok(header::AltSvc::Clear) => clear = true,
+                Ok(header::AltSvc::Services(list)) => services.extend(list.into_iter().map(|service| Entry {
+                    expires: now + service.max_age,
+                    service,
+                })),
+                Err(_) => continue,
+            }
+        }
+
+        let mut origins = self.origins.lock().unwrap_or_else(|error| error.into_inner());
+        match clear || services.is_empty() {
+            true => origins.remove(&host_key(uri)),
+            false => origins.insert(host_key(uri), services),
+        };
+    }
+
+    ///Returns fresh services, with `max_age` set to the remaining time.
+    pub(crate) fn services(&self) -> HashMap<String, Vec<header::AltService>> {
+        let now = Instant::now();
+        let mut origins = self.origins.lock().unwrap_or_else(|error| error.into_inner());
+
+        origins.retain(|_, services| {
+            services.retain(|entry| entry.expires > now);
+            !services.is_empty()
+        });
+
+        origins.iter().map(|(origin, services)| {
+            let services = services.iter().map(|entry| header::AltService {
+                max_age: entry.expires.duration_since(now),
+                ..entry.service.clone()
+            }).collect();
+
+            (origin.clone(), services)
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cache;
+    use crate::header;
+
+    #[test]
+    fn should_record_and_clear_services() {
+        let cache = Cache::default();
+        let uri = "https://example.com/path".parse().unwrap();
+
+        let mut headers = http::HeaderMap::new();
+        cache.update(&uri, &headers);
+        assert!(cache.services().is_empty());
+
+        headers.insert(header::ALT_SVC, header::HeaderValue::from_static("h3=\":443\"; ma=60, h2=\":8443\"; ma=0"));
+        cache.update(&uri, &headers);
+
+        let services = cache.services();
+        let services = services.get("https://example.com").expect("To have origin");
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].protocol, "h3");
+        assert!(services[0].max_age.as_secs() <= 60);
+
+        headers.insert(header::ALT_SVC, header::HeaderValue::from_static("clear"));
+        cache.update(&uri, &headers);
+        assert!(cache.services().is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+//!Authentication on `401 Unauthorized` challenges.
+//!
+//!When client has [Authenticator](trait.Authenticator.html), response with status `401` is passed to it
+//!with parsed `WWW-Authenticate` challenges. If it provides credentials, request is re-sent once with
+//!them as `Authorization` header.
+//!
+//!Request is re-sent at most once, and only if credentials differ from the ones request already had,
+//!so that rejected credentials never result in retry loop.
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::client;
+//!use yukikaze::client::auth;
+//!
+//!use std::sync::Arc;
+//!
+//!let client = client::Client::builder().authenticator(Arc::new(auth::Basic::new("user", Some("pass"))))
+//!                                      .build();
+//!```
+
+use core::fmt;
+
+use super::request::Request;
+use crate::header::{AuthChallenge, HeaderValue};
+
+///Provides credentials in response to authentication challenges.
+///
+///Closures with matching signature implement it as well.
+pub trait Authenticator: Send + Sync {
+    ///Computes value of `Authorization` header for `request`, that is rejected with `challenges`.
+    ///
+    ///`request` is copy of rejected request, without extensions.
+    ///Returns `None` if none of challenges can be satisfied, in which case `401` response is returned as it is.
+    fn authenticate(&self, request: &Request, challenges: &[AuthChallenge]) -> Option<HeaderValue>;
+}
+
+impl<F: Fn(&Request, &[AuthChallenge]) -> Option<HeaderValue> + Send + Sync> Authenticator for F {
+    #[inline]
+    fn authenticate(&self, request: &Request, challenges: &[AuthChallenge]) -> Option<HeaderValue> {
+        (self)(request, challenges)
+    }
+}
+
+#[derive(Clone)]
+///Authenticator of `Basic` scheme, defined in [RFC7617](https://tools.ietf.org/html/rfc7617).
+///
+///Credentials are provided only if server offers `Basic` challenge.
+pub struct Basic {
+    value: HeaderValue,
+}
+
+impl Basic {
+    ///Creates new authenticator with provided credentials.
+    pub fn new<U: fmt::Display, P: fmt::Display>(username: U, password: Option<P>) -> Self {
+        let auth = match password {
+            Some(password) => format!("{}:{}", username, password),
+            None => format!("{}:", username)
+        };
+        let value = format!("Basic {}", data_encoding::BASE64.encode(auth.as_bytes()));
+
+        Self {
+            value: HeaderValue::from_str(&value).expect("Base64 to be valid header value"),
+        }
+    }
+}
+
+impl Authenticator for Basic {
+    fn authenticate(&self, _: &Request, challenges: &[AuthChallenge]) -> Option<HeaderValue> {
+        match challenges.iter().any(|challenge| challenge.is_scheme("basic")) {
+            true => Some(self.value.clone()),
+            false => None,
+        }
+    }
+}
+
+impl fmt::Debug for Basic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Basic { .. }")
+    }
+}
@@ -0,0 +1,273 @@
+//!Circuit breaker.
+//!
+//!Breaker tracks failures of requests per host, identified by `<scheme>://<authority>`.
+//!Once failure rate exceeds threshold, breaker opens and requests to the host fail immediately with
+//![Error::CircuitOpen](../errors/enum.Error.html#variant.CircuitOpen), sparing dying backend.
+//!After cool-down, breaker lets few trial requests through, closing on success and opening again on failure.
+//!
+//!Failure is any error or response with `5xx` status.
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::client;
+//!use yukikaze::client::breaker;
+//!
+//!use core::time;
+//!
+//!let settings = breaker::Settings {
+//!    failure_rate: 0.3,
+//!    cool_down: time::Duration::from_secs(10),
+//!    ..breaker::Settings::default()
+//!};
+//!let client = client::Client::builder().circuit_breaker(Some(settings)).build();
+//!assert!(client.circuit_states().is_empty());
+//!```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use core::time;
+
+use super::{errors, response, RequestResult};
+use crate::connector::stats::host_key;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+///Settings of circuit breaker.
+pub struct Settings {
+    ///Ratio of failed requests within window, that opens breaker.
+    ///
+    ///Should be within `0..=1`
+    pub failure_rate: f32,
+    ///Minimal number of requests within window, before failure rate is considered.
+    pub min_requests: usize,
+    ///Duration of window, after which statistics of closed breaker are reset.
+    pub window: time::Duration,
+    ///Time for which breaker stays open, before allowing trial requests.
+    pub cool_down: time::Duration,
+    ///Number of concurrent trial requests, when breaker is half-open.
+    pub half_open_requests: usize,
+}
+
+impl Default for Settings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            failure_rate: 0.5,
+            min_requests: 20,
+            window: time::Duration::from_secs(60),
+            cool_down: time::Duration::from_secs(30),
+            half_open_requests: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///State of breaker.
+pub enum State {
+    ///Requests are allowed.
+    Closed,
+    ///Requests are rejected.
+    Open,
+    ///Limited number of trial requests are allowed.
+    HalfOpen,
+}
+
+struct Host {
+    state: State,
+    //Start of window, when closed, or time of state change otherwise.
+    since: Instant,
+    requests: usize,
+    failures: usize,
+    trials: usize,
+}
+
+impl Host {
+    fn new(now: Instant) -> Self {
+        Self {
+            state: State::Closed,
+            since: now,
+            requests: 0,
+            failures: 0,
+            trials: 0,
+        }
+    }
+
+    fn set_state(&mut self, state: State, now: Instant) {
+        self.state = state;
+        self.since = now;
+        self.requests = 0;
+        self.failures = 0;
+        self.trials = 0;
+    }
+}
+
+///Breakers of all hosts.
+pub(crate) struct Breakers {
+    settings: Option<Settings>,
+    hosts: Mutex<HashMap<String, Host>>,
+}
+
+impl Breakers {
+    pub(crate) fn new(settings: Option<Settings>) -> Self {
+        Self {
+            settings,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    ///Checks whether request to `uri` is allowed, returning ticket to record its result.
+    pub(crate) fn check(self: &Arc<Self>, uri: &hyper::Uri) -> Result<Option<Ticket>, errors::Error> {
+        let settings = match self.settings {
+            Some(settings) => settings,
+            None => return Ok(None),
+        };
+
+        let host = host_key(uri);
+        let now = Instant::now();
+        let mut hosts = self.hosts.lock().unwrap_or_else(|error| error.into_inner());
+        let breaker = hosts.entry(host.clone()).or_insert_with(|| Host::new(now));
+
+        let is_trial = match breaker.state {
+            State::Closed => false,
+            State::Open if now.duration_since(breaker.since) >= settings.cool_down => {
+                breaker.set_state(State::HalfOpen, now);
+                true
+            },
+            State::Open => return Err(errors::Error::CircuitOpen(host)),
+            //Trials, that never finished (e.g. cancelled), are forgotten after cool-down.
+            State::HalfOpen if now.duration_since(breaker.since) >= settings.cool_down => {
+                breaker.set_state(State::HalfOpen, now);
+                true
+            },
+            State::HalfOpen if breaker.trials < settings.half_open_requests => true,
+            State::HalfOpen => return Err(errors::Error::CircuitOpen(host)),
+        };
+
+        if is_trial {
+            breaker.trials += 1;
+        }
+
+        Ok(Some(Ticket {
+            breakers: self.clone(),
+            host,
+            is_trial,
+        }))
+    }
+
+    fn record(&self, host: &str, is_trial: bool, is_failure: bool) {
+        let settings = match self.settings {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        let now = Instant::now();
+        let mut hosts = self.hosts.lock().unwrap_or_else(|error| error.into_inner());
+        let breaker = match hosts.get_mut(host) {
+            Some(breaker) => breaker,
+            None => return,
+        };
+
+        match breaker.state {
+            State::HalfOpen if is_trial => match is_failure {
+                true => breaker.set_state(State::Open, now),
+                false => breaker.set_state(State::Closed, now),
+            },
+            State::Closed => {
+                if now.duration_since(breaker.since) >= settings.window {
+                    breaker.set_state(State::Closed, now);
+                }
+
+                breaker.requests += 1;
+                if is_failure {
+                    breaker.failures += 1;
+                }
+
+                if breaker.requests >= settings.min_requests && breaker.failures as f32 >= breaker.requests as f32 * settings.failure_rate {
+                    breaker.set_state(State::Open, now);
+                }
+            },
+            //Results of requests, that started before state change, are ignored.
+            _ => (),
+        }
+    }
+
+    fn release(&self, host: &str) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|error| error.into_inner());
+        if let Some(breaker) = hosts.get_mut(host) {
+            if breaker.state == State::HalfOpen {
+                breaker.trials = breaker.trials.saturating_sub(1);
+            }
+        }
+    }
+
+    ///Returns states of breakers per host.
+    pub(crate) fn states(&self) -> HashMap<String, State> {
+        let hosts = self.hosts.lock().unwrap_or_else(|error| error.into_inner());
+        hosts.iter().map(|(host, breaker)| (host.clone(), breaker.state)).collect()
+    }
+}
+
+///Permission to send request, which result must be recorded.
+///
+///Dropping it without result frees trial slot of half-open breaker.
+pub(crate) struct Ticket {
+    breakers: Arc<Breakers>,
+    host: String,
+    is_trial: bool,
+}
+
+impl Ticket {
+    ///Records result of request.
+    pub(crate) fn finish(mut self, result: &RequestResult) {
+        let is_failure = match result {
+            Ok(response) => response.status_class() == response::StatusClass::ServerError,
+            Err(_) => true,
+        };
+
+        self.breakers.record(&self.host, self.is_trial, is_failure);
+        //Trial slot is freed by state change
+        self.is_trial = false;
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        if self.is_trial {
+            self.breakers.release(&self.host);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Breakers, Settings, State};
+
+    use std::sync::Arc;
+
+    #[test]
+    fn should_open_and_close_breaker() {
+        let breakers = Arc::new(Breakers::new(Some(Settings {
+            failure_rate: 0.5,
+            min_requests: 4,
+            cool_down: core::time::Duration::from_millis(0),
+            ..Settings::default()
+        })));
+        let uri = "http://example.com/path".parse().unwrap();
+
+        for is_failure in &[false, true, false, true] {
+            let ticket = breakers.check(&uri).expect("To allow").expect("To have ticket");
+            breakers.record(&ticket.host, ticket.is_trial, *is_failure);
+        }
+        assert_eq!(breakers.states().get("http://example.com"), Some(&State::Open));
+
+        //Cool-down is elapsed immediately, so single trial is allowed
+        let ticket = breakers.check(&uri).expect("To allow trial").expect("To have ticket");
+        assert!(ticket.is_trial);
+        assert_eq!(breakers.states().get("http://example.com"), Some(&State::HalfOpen));
+        breakers.record(&ticket.host, ticket.is_trial, false);
+        assert_eq!(breakers.states().get("http://example.com"), Some(&State::Closed));
+
+        assert!(Arc::new(Breakers::new(None)).check(&uri).expect("To allow").is_none());
+    }
+}
@@ -0,0 +1,236 @@
+//!Runtime configuration of client.
+//!
+//![Config](../config/trait.Config.html) describes configuration at type level, which is awkward
+//!when settings are known only at runtime (e.g. loaded from file).
+//![ClientBuilder](struct.ClientBuilder.html) allows to override parts of it per client instance,
+//!while the rest is still taken from `Config`.
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::client;
+//!
+//!use core::time;
+//!
+//!let client = client::Client::builder().timeout(time::Duration::from_secs(5))
+//!                                      .max_redirects(3)
+//!                                      .build();
+//!assert_eq!(client.timeout(), time::Duration::from_secs(5));
+//!```
+
+use core::marker::PhantomData;
+use core::time;
+use core::fmt;
+use std::sync::Arc;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use super::{alt_svc, auth, breaker, config, redirects, shutdown, Client, Pools};
+use crate::connector::{proxy, stats};
+
+///Builder of [Client](../struct.Client.html) with runtime configuration.
+///
+///Initially each setting is taken from `Config` and can be overridden.
+pub struct ClientBuilder<C: config::Config + 'static = config::DefaultCfg> {
+    timeout: time::Duration,
+    max_redirects: usize,
+    redirect_policy: config::RedirectPolicy,
+    redirect_cache: Option<config::RedirectCache>,
+    circuit_breaker: Option<breaker::Settings>,
+    authenticator: Option<Arc<dyn auth::Authenticator>>,
+    connector: C::Connector,
+    proxy: Option<proxy::Proxy>,
+    default_headers: http::HeaderMap,
+    base_url: Option<http::Uri>,
+    default_query: Vec<(String, String)>,
+    #[cfg(feature = "log")]
+    logger: Option<super::log::Logger>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<super::metrics::Metrics>,
+    _config: PhantomData<C>,
+}
+
+impl<C: config::Config> ClientBuilder<C> {
+    ///Creates new builder with settings of `Config`.
+    pub fn new() -> Self {
+        Self {
+            timeout: C::timeout(),
+            max_redirects: C::max_redirect_num(),
+            redirect_policy: C::redirect_policy(),
+            redirect_cache: C::redirect_cache(),
+            circuit_breaker: C::circuit_breaker(),
+            authenticator: None,
+            connector: C::Connector::default(),
+            proxy: C::proxy(),
+            default_headers: http::HeaderMap::new(),
+            base_url: C::base_url(),
+            default_query: C::default_query(),
+            #[cfg(feature = "log")]
+            logger: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+            _config: PhantomData,
+        }
+    }
+
+    #[inline]
+    ///Overrides [Config::timeout](../config/trait.Config.html#method.timeout).
+    ///
+    ///Zero duration disables timeout.
+    pub fn timeout(mut self, timeout: time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    #[inline]
+    ///Overrides [Config::max_redirect_num](../config/trait.Config.html#method.max_redirect_num).
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        self.max_redirects = max;
+        self
+    }
+
+    #[inline]
+    ///Overrides [Config::redirect_policy](../config/trait.Config.html#method.redirect_policy).
+    pub fn redirect_policy(mut self, policy: config::RedirectPolicy) -> Self {
+        self.redirect_policy = policy;
+        self
+    }
+
+    #[inline]
+    ///Overrides [Config::redirect_cache](../config/trait.Config.html#method.redirect_cache).
+    pub fn redirect_cache(mut self, limits: Option<config::RedirectCache>) -> Self {
+        self.redirect_cache = limits;
+        self
+    }
+
+    #[inline]
+    ///Overrides [Config::circuit_breaker](../config/trait.Config.html#method.circuit_breaker).
+    pub fn circuit_breaker(mut self, settings: Option<breaker::Settings>) -> Self {
+        self.circuit_breaker = settings;
+        self
+    }
+
+    #[inline]
+    ///Sets [authenticator](../auth/index.html), that handles `401 Unauthorized` challenges.
+    pub fn authenticator(mut self, authenticator: Arc<dyn auth::Authenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    #[inline]
+    ///Sets connector instance, instead of default one.
+    ///
+    ///Connector is cloned for each connection pool.
+    pub fn connector(mut self, connector: C::Connector) -> Self {
+        self.connector = connector;
+        self
+    }
+
+    #[inline]
+    ///Overrides [Config::proxy](../config/trait.Config.html#method.proxy).
+    pub fn proxy(mut self, proxy: Option<proxy::Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    #[inline]
+    ///Sets headers, that are added to each request.
+    ///
+    ///See [Client::with_default_headers](../struct.Client.html#method.with_default_headers).
+    pub fn default_headers(mut self, headers: http::HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    #[inline]
+    ///Overrides [Config::base_url](../config/trait.Config.html#method.base_url).
+    pub fn base_url(mut self, base_url: Option<http::Uri>) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    #[inline]
+    ///Overrides [Config::default_query](../config/trait.Config.html#method.default_query).
+    pub fn default_query(mut self, query: Vec<(String, String)>) -> Self {
+        self.default_query = query;
+        self
+    }
+
+    #[cfg(feature = "log")]
+    #[inline]
+    ///Enables logging of requests, using provided logger.
+    pub fn logger(mut self, logger: super::log::Logger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    #[cfg(feature = "metrics")]
+    #[inline]
+    ///Enables collection of request metrics.
+    pub fn metrics(mut self, metrics: super::metrics::Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+}
+
+impl<C: config::Config> ClientBuilder<C> where <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+                                               <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+                                               <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    ///Creates client.
+    pub fn build(self) -> Client<C> {
+        let stats = Arc::new(stats::Registry::new(C::instrument_connections(), C::max_connections_per_host()));
+        let pools = Pools {
+            direct: Client::<C>::build_hyper(&self.connector, &stats, None),
+            proxied: Vec::new(),
+        };
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = self.metrics.as_ref() {
+            metrics.attach(&stats);
+        }
+
+        Client {
+            inner: Arc::new(std::sync::RwLock::new(Some(pools))),
+            connector: self.connector,
+            timeout: self.timeout,
+            max_redirects: self.max_redirects,
+            redirect_policy: self.redirect_policy,
+            proxy: self.proxy.map(Arc::new),
+            lifecycle: Arc::new(shutdown::Lifecycle::default()),
+            stats,
+            alt_svc: Arc::new(alt_svc::Cache::default()),
+            redirects: Arc::new(redirects::Cache::new(self.redirect_cache)),
+            breakers: Arc::new(breaker::Breakers::new(self.circuit_breaker)),
+            authenticator: self.authenticator,
+            default_headers: Arc::new(self.default_headers),
+            base_url: self.base_url,
+            default_query: Arc::new(self.default_query),
+            #[cfg(feature = "log")]
+            logger: self.logger.map(Arc::new),
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+            _config: PhantomData
+        }
+    }
+}
+
+impl<C: config::Config> Default for ClientBuilder<C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: config::Config> fmt::Debug for ClientBuilder<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ClientBuilder").field("timeout", &self.timeout)
+                                       .field("max_redirects", &self.max_redirects)
+                                       .field("redirect_policy", &self.redirect_policy)
+                                       .field("redirect_cache", &self.redirect_cache)
+                                       .field("circuit_breaker", &self.circuit_breaker)
+                                       .field("proxy", &self.proxy)
+                                       .field("default_headers", &self.default_headers)
+                                       .field("base_url", &self.base_url)
+                                       .finish()
+    }
+}
@@ -0,0 +1,139 @@
+//!Server's capabilities, advertised in response to `OPTIONS` request.
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::{matsu, client};
+//!
+//!async fn sync() {
+//!    let client = client::Client::default();
+//!
+//!    let capabilities = matsu!(client.capabilities("https://dav.example.com/files/")).expect("To get response");
+//!    if capabilities.allows_method(&http::Method::PUT) && capabilities.supports_ranges() {
+//!        //Upload can be resumed
+//!    }
+//!}
+//!```
+
+use crate::header;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+///Headers, that describe supported operations, of response to `OPTIONS` request.
+pub struct Capabilities {
+    ///Status of response.
+    pub status: http::StatusCode,
+    ///Methods of `Allow`.
+    pub allow: Vec<http::Method>,
+    ///Range units of `Accept-Ranges`, e.g. `bytes`.
+    ///
+    ///Unit `none` means that ranges are not supported.
+    pub accept_ranges: Vec<String>,
+    ///Media types of `Accept-Patch`, which are accepted by `PATCH`.
+    pub accept_patch: Vec<mime::Mime>,
+    ///Compliance classes of `DAV`, as defined in [RFC4918](https://tools.ietf.org/html/rfc4918#section-10.1), e.g. `1`, `2` or `access-control`.
+    pub dav: Vec<String>,
+}
+
+impl Capabilities {
+    ///Parses response's status and headers.
+    ///
+    ///Invalid values are ignored.
+    pub fn new(status: http::StatusCode, headers: &http::HeaderMap) -> Self {
+        let mut result = Self {
+            status,
+            ..Self::default()
+        };
+
+        for method in list(headers, header::ALLOW) {
+            if let Ok(method) = method.parse() {
+                result.allow.push(method);
+            }
+        }
+
+        result.accept_ranges.extend(list(headers, header::ACCEPT_RANGES).map(str::to_ascii_lowercase));
+
+        for mime in list(headers, header::HeaderName::from_static("accept-patch")) {
+            if let Ok(mime) = mime.parse() {
+                result.accept_patch.push(mime);
+            }
+        }
+
+        result.dav.extend(list(headers, header::HeaderName::from_static("dav")).map(str::to_owned));
+
+        result
+    }
+
+    #[inline]
+    ///Returns whether response is successful, i.e. status is successful.
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    #[inline]
+    ///Returns whether `method` is listed in `Allow`.
+    pub fn allows_method(&self, method: &http::Method) -> bool {
+        self.allow.contains(method)
+    }
+
+    ///Returns whether byte ranges are supported.
+    pub fn supports_ranges(&self) -> bool {
+        self.accept_ranges.iter().any(|unit| unit == "bytes")
+    }
+
+    ///Returns whether `PATCH` accepts media type `mime`, comparing it without parameters.
+    pub fn accepts_patch(&self, mime: &mime::Mime) -> bool {
+        self.accept_patch.iter().any(|accepted| accepted.essence_str() == mime.essence_str())
+    }
+
+    #[inline]
+    ///Returns whether server supports WebDAV.
+    pub fn is_dav(&self) -> bool {
+        !self.dav.is_empty()
+    }
+
+    ///Returns whether WebDAV compliance `class` is supported.
+    pub fn is_dav_class(&self, class: &str) -> bool {
+        self.dav.iter().any(|supported| supported.eq_ignore_ascii_case(class))
+    }
+}
+
+///Iterates over elements of header's list.
+fn list(headers: &http::HeaderMap, name: header::HeaderName) -> impl Iterator<Item=&str> {
+    headers.get_all(name).into_iter()
+                         .filter_map(|value| value.to_str().ok())
+                         .flat_map(header::split_list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Capabilities;
+    use crate::header;
+
+    #[test]
+    fn should_parse_capabilities() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(header::ALLOW, header::HeaderValue::from_static("GET, HEAD, PUT,PROPFIND"));
+        headers.insert(header::ACCEPT_RANGES, header::HeaderValue::from_static("Bytes"));
+        headers.insert("accept-patch", header::HeaderValue::from_static("application/merge-patch+json, text/example;charset=\"utf-8,x\""));
+        headers.insert("dav", header::HeaderValue::from_static("1, 2"));
+        headers.append("dav", header::HeaderValue::from_static("access-control"));
+
+        let capabilities = Capabilities::new(http::StatusCode::OK, &headers);
+        assert!(capabilities.is_success());
+        assert!(capabilities.allows_method(&http::Method::PUT));
+        assert!(capabilities.allows_method(&http::Method::from_bytes(b"PROPFIND").expect("Valid method")));
+        assert!(!capabilities.allows_method(&http::Method::DELETE));
+        assert!(capabilities.supports_ranges());
+        assert_eq!(capabilities.accept_patch.len(), 2);
+        assert!(capabilities.accepts_patch(&"application/merge-patch+json".parse().expect("Valid mime")));
+        assert!(capabilities.accepts_patch(&"text/example".parse().expect("Valid mime")));
+        assert!(capabilities.is_dav());
+        assert!(capabilities.is_dav_class("2"));
+        assert!(capabilities.is_dav_class("Access-Control"));
+        assert!(!capabilities.is_dav_class("3"));
+
+        let capabilities = Capabilities::new(http::StatusCode::OK, &http::HeaderMap::new());
+        assert!(!capabilities.supports_ranges());
+        assert!(!capabilities.is_dav());
+    }
+}
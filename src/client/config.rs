@@ -15,13 +15,146 @@ pub type DefaultConnector = crate::connector::rustls::HttpsConnector;
 ///Default connector, which is used by [DefaultCfg](struct.DefaultCfg.html)
 pub type DefaultConnector = crate::connector::HttpConnector;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Describes how to handle method of request on redirect.
+pub enum RedirectPolicy {
+    ///Follows RFC 7231 strictly.
+    ///
+    ///Only `303 See Other` changes method to `GET`, while all other redirections
+    ///preserve method and body.
+    Strict,
+    ///Follows behavior of browsers.
+    ///
+    ///In addition to `303 See Other`, `301 Moved Permanently` and `302 Found`
+    ///change `POST` request to `GET`, dropping its body.
+    BrowserCompat,
+}
+
+impl RedirectPolicy {
+    ///Returns whether redirect with `status` should change `method` to `GET`, dropping body.
+    pub fn is_to_get(self, status: http::StatusCode, method: &http::Method) -> bool {
+        match status {
+            http::StatusCode::SEE_OTHER => method != http::Method::HEAD,
+            http::StatusCode::MOVED_PERMANENTLY | http::StatusCode::FOUND => match self {
+                RedirectPolicy::Strict => false,
+                RedirectPolicy::BrowserCompat => method == http::Method::POST,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Limits of cache of permanent redirects.
+///
+///See [Config::redirect_cache](trait.Config.html#method.redirect_cache).
+pub struct RedirectCache {
+    ///Max number of cached redirects.
+    ///
+    ///Once reached, redirect, that expires first, is evicted.
+    pub max_entries: usize,
+    ///Time for which redirect is remembered.
+    pub ttl: time::Duration,
+}
+
+impl Default for RedirectCache {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_entries: 256,
+            ttl: time::Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Tuning of HTTP/2 connections.
+///
+///See [Config::http2](trait.Config.html#method.http2).
+///Defaults are the same as hyper's.
+///
+///Note that max number of concurrent streams is advertised by server, and client follows it.
+pub struct Http2 {
+    ///Initial window size of stream-level flow control in bytes.
+    ///
+    ///`None` uses default of 64kb - 1.
+    pub initial_stream_window_size: Option<u32>,
+    ///Initial window size of connection-level flow control in bytes.
+    ///
+    ///`None` uses default of 64kb - 1.
+    pub initial_connection_window_size: Option<u32>,
+    ///Whether to estimate bandwidth-delay product and adjust windows accordingly.
+    ///
+    ///When enabled, initial window sizes are ignored.
+    pub adaptive_window: bool,
+    ///Max size of frame in bytes.
+    ///
+    ///`None` uses default of 16kb.
+    pub max_frame_size: Option<u32>,
+    ///Interval of `PING` frames, that keep connection alive.
+    ///
+    ///`None` disables keep-alive.
+    pub keep_alive_interval: Option<time::Duration>,
+    ///Time to wait for acknowledgement of keep-alive `PING`, after which connection is closed.
+    pub keep_alive_timeout: time::Duration,
+    ///Whether to send keep-alive `PING` frames, when connection has no open streams.
+    pub keep_alive_while_idle: bool,
+}
+
+impl Http2 {
+    ///Applies settings to hyper's builder.
+    pub(crate) fn apply(&self, builder: &mut hyper::client::Builder) {
+        builder.http2_initial_stream_window_size(self.initial_stream_window_size)
+               .http2_initial_connection_window_size(self.initial_connection_window_size)
+               .http2_adaptive_window(self.adaptive_window)
+               .http2_max_frame_size(self.max_frame_size)
+               .http2_keep_alive_interval(self.keep_alive_interval)
+               .http2_keep_alive_timeout(self.keep_alive_timeout)
+               .http2_keep_alive_while_idle(self.keep_alive_while_idle);
+    }
+}
+
+impl Default for Http2 {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            initial_stream_window_size: None,
+            initial_connection_window_size: None,
+            adaptive_window: false,
+            max_frame_size: None,
+            keep_alive_interval: None,
+            keep_alive_timeout: time::Duration::from_secs(20),
+            keep_alive_while_idle: false,
+        }
+    }
+}
+
+///Sets `HOST` header with host, and optionally port, taken from URI, if not present.
+pub(crate) fn set_host(request: &mut super::request::Request) {
+    if !request.headers().contains_key(header::HOST) {
+        let host = request.uri().host().and_then(|host| match request.uri().port().map(|port| port.as_u16()) {
+            None | Some(80) | Some(443) => header::HeaderValue::from_str(host).ok(),
+            Some(port) => {
+                let mut buffer = utils::BytesWriter::with_capacity(host.len() + 5);
+                let _ = write!(&mut buffer, "{}:{}", host, port);
+
+                http::header::HeaderValue::from_maybe_shared(buffer.freeze()).ok()
+            },
+        });
+
+        if let Some(host) = host {
+            request.headers_mut().insert(header::HOST, host);
+        }
+    }
+}
+
 ///Generic config trait.
 ///
 ///Each method describes single aspect of configuration
 ///and provided with sane defaults
 pub trait Config {
     ///Connector type.
-    type Connector: hyper::service::Service<hyper::Uri> + Default + Clone + Send + Sync;
+    type Connector: crate::connector::Connect + Default + Send + Sync;
     ///Timer type.
     type Timer: async_timer::oneshot::Oneshot;
 
@@ -33,6 +166,18 @@ pub trait Config {
         true
     }
 
+    #[inline]
+    ///Specifies encodings, with their quality, that are requested via `Accept-Encoding`.
+    ///
+    ///It is used only when [decompress](#method.decompress) is enabled and request has neither `Accept-Encoding` nor `Range`.
+    ///Order of encodings is preserved, which allows to prefer cheaper compression (e.g. gzip over brotli)
+    ///or to exclude some of them. Encodings, that cannot be decompressed, should not be included.
+    ///
+    ///Defaults to all [decompressable](../../header/struct.AcceptEncoding.html#method.decompressable) encodings.
+    fn accepted_encodings() -> header::AcceptEncoding {
+        header::AcceptEncoding::decompressable()
+    }
+
     #[inline]
     ///Specifies request timeout.
     ///
@@ -43,12 +188,25 @@ pub trait Config {
         time::Duration::from_secs(30)
     }
 
+    #[inline]
+    ///Generates user agent for request.
+    ///
+    ///It is called for each request without `User-Agent`, which allows
+    ///to rotate user agents. Returning `None` means that user agent is not set.
+    ///
+    ///By default returns `Yukikaze/<lib version>`
+    fn user_agent(_request: &super::request::Request) -> Option<header::HeaderValue> {
+        Some(header::HeaderValue::from_static(concat!("Yukikaze/", env!("CARGO_PKG_VERSION"))))
+    }
+
     ///Specifies how to set user agent
     ///
-    ///By default it set's `Yukikaze/<lib version>`, if agent is not present
+    ///By default it set's agent from [user_agent](#method.user_agent), if agent is not present
     fn default_user_agent(request: &mut super::request::Request) {
         if !request.headers().contains_key(header::USER_AGENT) {
-            request.headers_mut().insert(header::USER_AGENT, header::HeaderValue::from_static(concat!("Yukikaze/", env!("CARGO_PKG_VERSION"))));
+            if let Some(agent) = Self::user_agent(request) {
+                request.headers_mut().insert(header::USER_AGENT, agent);
+            }
         }
     }
 
@@ -66,21 +224,205 @@ pub trait Config {
     fn default_headers(request: &mut super::request::Request) {
         Self::default_user_agent(request);
 
-        if !request.headers().contains_key(header::HOST) {
-            let host = request.uri().host().and_then(|host| match request.uri().port().map(|port| port.as_u16()) {
-                None | Some(80) | Some(443) => header::HeaderValue::from_str(host).ok(),
-                Some(port) => {
-                    let mut buffer = utils::BytesWriter::with_capacity(host.len() + 5);
-                    let _ = write!(&mut buffer, "{}:{}", host, port);
+        set_host(request);
+    }
 
-                    http::header::HeaderValue::from_maybe_shared(buffer.freeze()).ok()
-                },
-            });
+    #[inline]
+    ///Returns trace context to propagate with request.
+    ///
+    ///It is called for each request without `traceparent` header, allowing to inject
+    ///context of currently active span (e.g. taken from `tracing` subscriber).
+    ///Returned context should describe caller's span, as it is sent as it is.
+    ///
+    ///By default returns `None`.
+    fn trace_context(_request: &super::request::Request) -> Option<header::TraceContext> {
+        None
+    }
 
-            if let Some(host) = host {
-                request.headers_mut().insert(header::HOST, host);
-            }
-        }
+    #[inline]
+    ///Allows to finalize request right before it is sent out.
+    ///
+    ///It is called after all default headers are set, including on each redirect.
+    ///Which makes it suitable for signing requests.
+    ///
+    ///By default does nothing.
+    fn finalize_request(_request: &mut super::request::Request) {
+    }
+
+    #[inline]
+    ///Specifies default limit on size of response's body, when reading it into memory.
+    ///
+    ///Unlike `Content-Length`, which is used as limit otherwise, it is set
+    ///by user, and therefore can be used to protect from unreasonably big bodies.
+    ///When both are present, the smallest one is used.
+    ///
+    ///By default it is `None`, meaning `Content-Length` or 4kb, if length is unknown, is used.
+    fn default_body_limit() -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    ///Specifies maximum number of simultaneously open connections per host.
+    ///
+    ///Once limit is reached, new connections to the same host wait until one of existing connections is closed,
+    ///so that single slow host cannot exhaust process's sockets.
+    ///Host is identified by `<scheme>://<authority>` and limit is shared by all pools of client, including proxied.
+    ///
+    ///Cannot be zero. By default it is `None`, meaning no limit.
+    fn max_connections_per_host() -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    ///Specifies maximum capacity, that is allocated upfront, when response's body is read into memory.
+    ///
+    ///Buffer starts with capacity of body's limit, but not above this cap, and grows as data arrives.
+    ///Hence server cannot cause huge allocation just by declaring big `Content-Length`.
+    ///
+    ///By default it is [DEFAULT_BUFFER_CAP](../../extractor/constant.DEFAULT_BUFFER_CAP.html).
+    fn body_buffer_cap() -> usize {
+        crate::extractor::DEFAULT_BUFFER_CAP
+    }
+
+    #[inline]
+    ///Specifies limit on ratio of decompressed body's size to its compressed size.
+    ///
+    ///Reading of body, that exceeds it, fails with `BodyReadError::DecompressionBomb`.
+    ///Ratio is verified only once decompressed size exceeds
+    ///[RATIO_THRESHOLD](../../extractor/constant.RATIO_THRESHOLD.html).
+    ///
+    ///By default it is [DEFAULT_MAX_RATIO](../../extractor/constant.DEFAULT_MAX_RATIO.html).
+    ///`None` disables limit.
+    fn max_decompression_ratio() -> Option<u32> {
+        Some(crate::extractor::DEFAULT_MAX_RATIO)
+    }
+
+    #[inline]
+    ///Specifies whether to verify that received body matches `Content-Length`.
+    ///
+    ///When enabled, reading body fails with `BodyReadError::TruncatedBody` or `BodyReadError::ExcessBody`,
+    ///instead of returning data as it is.
+    ///
+    ///By default it is `false`.
+    fn verify_content_length() -> bool {
+        false
+    }
+
+    #[inline]
+    ///Specifies max number of headers in response.
+    ///
+    ///Responses with more headers are rejected with [Error::HeaderLimit](../errors/enum.Error.html#variant.HeaderLimit).
+    ///Note that hyper itself rejects responses with more than 100 headers.
+    ///
+    ///By default it is `None`, meaning no additional limit.
+    fn max_response_headers() -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    ///Specifies max size of response's headers in bytes.
+    ///
+    ///Size of each header is counted as length of name and value, plus 4 bytes for separators.
+    ///Responses with bigger headers are rejected with [Error::HeaderLimit](../errors/enum.Error.html#variant.HeaderLimit).
+    ///Additionally hyper's read buffer is limited to this size (but not less than 8kb),
+    ///so that excessive headers are not buffered at all.
+    ///
+    ///By default it is `None`, meaning hyper's default buffer limit (~400kb).
+    fn max_header_size() -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    ///Specifies base URL, which relative URIs of requests are resolved against.
+    ///
+    ///URI is relative, if it has neither scheme nor authority, e.g. `Request::get("/v1/users")`.
+    ///Its path is appended to the path of base URL, so base `https://example.com/api`
+    ///turns `/v1/users` into `https://example.com/api/v1/users`. Absolute URIs are left as they are.
+    ///
+    ///Can be overridden by [ClientBuilder::base_url](../builder/struct.ClientBuilder.html#method.base_url).
+    ///
+    ///By default it is `None`.
+    fn base_url() -> Option<http::Uri> {
+        None
+    }
+
+    #[inline]
+    ///Specifies query parameters, that are added to each request with relative URI (e.g. API key).
+    ///
+    ///Parameters, that already present in request's query, are not added.
+    ///
+    ///Can be overridden by [ClientBuilder::default_query](../builder/struct.ClientBuilder.html#method.default_query).
+    ///
+    ///By default it is empty.
+    fn default_query() -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    #[inline]
+    ///Specifies proxy, used for requests.
+    ///
+    ///Hosts, matching proxy's [NoProxy](../../connector/proxy/struct.NoProxy.html) list, are accessed directly.
+    ///Proxy can be overridden per request by [Builder::via_proxy](../request/struct.Builder.html#method.via_proxy).
+    ///
+    ///Connector must support it, see [proxy](../../connector/proxy/index.html) for details.
+    ///
+    ///By default it is `None`.
+    fn proxy() -> Option<crate::connector::proxy::Proxy> {
+        None
+    }
+
+    #[inline]
+    ///Specifies maximum number of connection pools, that client keeps for proxies.
+    ///
+    ///Client creates separate pool for each proxy, through which requests are sent.
+    ///Once limit is reached, the oldest pool is dropped in favour of new one.
+    ///
+    ///Cannot be zero. By default it is `16`.
+    fn max_proxy_pools() -> usize {
+        16
+    }
+
+    #[inline]
+    ///Specifies encoding of cookies in requests and responses.
+    ///
+    ///Applied to cookies, which request's [Builder](../request/struct.Builder.html) has no explicit encoding,
+    ///and to [Response::cookies_iter](../response/struct.Response.html#method.cookies_iter).
+    ///
+    ///By default cookies are percent-encoded.
+    fn cookie_encoding() -> header::CookieEncoding {
+        header::CookieEncoding::Percent
+    }
+
+    #[inline]
+    ///Specifies whether to collect timings of requests.
+    ///
+    ///When enabled, [Timings](../../connector/timings/struct.Timings.html) are stored in response's extensions.
+    ///
+    ///Defaults to false.
+    fn collect_timings() -> bool {
+        false
+    }
+
+    #[inline]
+    ///Specifies whether to instrument connections.
+    ///
+    ///When enabled, client collects [statistics](../struct.Client.html#method.pool_stats) of
+    ///connection pool and delivers [events](#method.on_connection_event) of connections.
+    ///It requires to track every request and connection, hence it is opt-in.
+    ///
+    ///Defaults to false.
+    fn instrument_connections() -> bool {
+        false
+    }
+
+    #[inline]
+    ///Receives [events](../../connector/events/enum.ConnectionEvent.html) of connections' lifecycle.
+    ///
+    ///It is invoked synchronously within connection's task, hence it should not block.
+    ///Events are delivered only if [instrument_connections](#method.instrument_connections) is enabled.
+    ///
+    ///By default does nothing.
+    fn on_connection_event(_event: &crate::connector::events::ConnectionEvent<'_>) {
     }
 
     #[inline]
@@ -91,6 +433,49 @@ pub trait Config {
         8
     }
 
+    #[inline]
+    ///Returns policy of handling request's method on redirects.
+    ///
+    ///By default it is `RedirectPolicy::Strict`.
+    fn redirect_policy() -> RedirectPolicy {
+        RedirectPolicy::Strict
+    }
+
+    #[inline]
+    ///Returns limits of cache of permanent redirects, if it is enabled.
+    ///
+    ///When enabled, `301 Moved Permanently` and `308 Permanent Redirect` of `GET` and `HEAD` requests
+    ///are remembered by [Client::redirect_request](../struct.Client.html#method.redirect_request),
+    ///so that subsequent requests are sent to the new location directly.
+    ///
+    ///Can be overridden by [ClientBuilder::redirect_cache](../builder/struct.ClientBuilder.html#method.redirect_cache).
+    ///
+    ///By default it is `None`.
+    fn redirect_cache() -> Option<RedirectCache> {
+        None
+    }
+
+    #[inline]
+    ///Returns settings of per host [circuit breaker](../breaker/index.html), if it is enabled.
+    ///
+    ///Can be overridden by [ClientBuilder::circuit_breaker](../builder/struct.ClientBuilder.html#method.circuit_breaker).
+    ///
+    ///By default it is `None`.
+    fn circuit_breaker() -> Option<super::breaker::Settings> {
+        None
+    }
+
+    #[inline]
+    ///Returns tuning of HTTP/2 connections.
+    ///
+    ///Defaults are suitable for most cases, but high-throughput downloads over connections with high latency
+    ///benefit from bigger windows or [adaptive](struct.Http2.html#structfield.adaptive_window) one.
+    ///
+    ///Applied before [config_hyper](#method.config_hyper), which can override it.
+    fn http2() -> Http2 {
+        Http2::default()
+    }
+
     #[inline]
     ///Allows to hook hyper's Client configuration.
     ///
@@ -115,3 +500,23 @@ impl Config for DefaultCfg {
     type Connector = DefaultConnector;
     type Timer = DefaultTimer;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RedirectPolicy;
+
+    use http::{Method, StatusCode};
+
+    #[test]
+    fn redirect_policy_should_change_method() {
+        assert!(RedirectPolicy::Strict.is_to_get(StatusCode::SEE_OTHER, &Method::POST));
+        assert!(RedirectPolicy::Strict.is_to_get(StatusCode::SEE_OTHER, &Method::PUT));
+        assert!(!RedirectPolicy::Strict.is_to_get(StatusCode::FOUND, &Method::POST));
+        assert!(!RedirectPolicy::Strict.is_to_get(StatusCode::MOVED_PERMANENTLY, &Method::POST));
+
+        assert!(RedirectPolicy::BrowserCompat.is_to_get(StatusCode::FOUND, &Method::POST));
+        assert!(RedirectPolicy::BrowserCompat.is_to_get(StatusCode::MOVED_PERMANENTLY, &Method::POST));
+        assert!(!RedirectPolicy::BrowserCompat.is_to_get(StatusCode::FOUND, &Method::PUT));
+        assert!(!RedirectPolicy::BrowserCompat.is_to_get(StatusCode::TEMPORARY_REDIRECT, &Method::POST));
+    }
+}
@@ -0,0 +1,199 @@
+//!Persistence of cookies in Netscape `cookies.txt` format, which is used by curl and wget.
+//!
+//!Allows CLI tools to keep session across runs: jar is loaded at startup, passed to requests via
+//![Builder::set_cookie_jar](../request/struct.Builder.html#method.set_cookie_jar), updated from responses
+//!and saved at exit.
+//!
+//!Each line describes single cookie with tab-separated fields: domain, whether it applies to subdomains, path,
+//!whether it is secure, expiration time as unix timestamp (`0` for session cookie), name and value.
+//!`HttpOnly` cookies are prefixed with `#HttpOnly_`.
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::client::cookies;
+//!
+//!let mut jar = cookies::load("cookies.txt").expect("To load cookies");
+//!jar.add(cookie::Cookie::build("session", "id").domain("example.com").finish());
+//!cookies::save(&jar, "cookies.txt").expect("To save cookies");
+//!```
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+#[inline]
+fn bool_field(value: bool) -> &'static str {
+    match value {
+        true => "TRUE",
+        false => "FALSE",
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs()).unwrap_or(0)
+}
+
+///Parses single line, returning `None` for comments, invalid or expired cookies.
+fn parse_line(line: &str, now: u64) -> Option<cookie::Cookie<'static>> {
+    let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+        Some(line) => (line, true),
+        None => (line, false),
+    };
+    if line.starts_with('#') {
+        return None;
+    }
+
+    let mut fields = line.split('\t');
+    let domain = fields.next()?;
+    let _subdomains = fields.next()?;
+    let path = fields.next()?;
+    let secure = fields.next()?.eq_ignore_ascii_case("TRUE");
+    let expires = fields.next()?.trim().parse::<u64>().ok()?;
+    let name = fields.next()?;
+    let value = fields.next().unwrap_or("").trim_end_matches('\r');
+
+    if name.is_empty() || (expires != 0 && expires <= now) {
+        return None;
+    }
+
+    let mut cookie = cookie::Cookie::build(name.to_owned(), value.to_owned()).domain(domain.trim_start_matches('.').to_owned())
+                                                                         .path(path.to_owned())
+                                                                         .secure(secure)
+                                                                         .http_only(http_only)
+                                                                         .finish();
+
+    if expires != 0 {
+        //Cookie's date type is not exposed, so it is obtained by parsing
+        let expires = SystemTime::UNIX_EPOCH + core::time::Duration::from_secs(expires);
+        let parsed = cookie::Cookie::parse(format!("expires=; Expires={}", httpdate::fmt_http_date(expires))).ok()?;
+        cookie.set_expires(parsed.expires());
+    }
+
+    Some(cookie)
+}
+
+///Parses cookies in Netscape format.
+///
+///Comments, malformed lines and expired cookies are skipped.
+///Cookies are added to jar as new, so that they are sent with request.
+pub fn parse(text: &str) -> cookie::CookieJar {
+    let now = now();
+    let mut jar = cookie::CookieJar::new();
+
+    for line in text.lines() {
+        if let Some(cookie) = parse_line(line, now) {
+            jar.add(cookie);
+        }
+    }
+
+    jar
+}
+
+///Serializes cookies of jar in Netscape format.
+///
+///Cookies without domain are skipped, as they cannot be matched to site once loaded.
+///Cookie with `Max-Age` expires relative to the current time.
+pub fn serialize(jar: &cookie::CookieJar) -> String {
+    let now = now();
+    let mut result = String::from("# Netscape HTTP Cookie File\n");
+
+    for cookie in jar.iter() {
+        let domain = match cookie.domain() {
+            Some(domain) if !domain.is_empty() => domain,
+            _ => continue,
+        };
+
+        let expires = match (cookie.expires(), cookie.max_age()) {
+            (_, Some(max_age)) => now.saturating_add(max_age.whole_seconds().max(0) as u64).max(1),
+            (Some(expires), None) => expires.unix_timestamp().max(1) as u64,
+            (None, None) => 0,
+        };
+
+        if cookie.http_only().unwrap_or(false) {
+            result.push_str(HTTP_ONLY_PREFIX);
+        }
+
+        result.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                                 domain,
+                                 bool_field(true),
+                                 cookie.path().unwrap_or("/"),
+                                 bool_field(cookie.secure().unwrap_or(false)),
+                                 expires,
+                                 cookie.name(),
+                                 cookie.value()));
+    }
+
+    result
+}
+
+///Loads cookies from file at `path`.
+///
+///Missing file results in empty jar.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<cookie::CookieJar> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(parse(&text)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(cookie::CookieJar::new()),
+        Err(error) => Err(error),
+    }
+}
+
+///Saves cookies to file at `path` atomically.
+///
+///Cookies are written into temporary file `<path>.part`, which replaces `path` after being synced to disk.
+pub fn save<P: AsRef<Path>>(jar: &cookie::CookieJar, path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    let part = crate::utils::part_path(path);
+
+    let result = fs::File::create(&part).and_then(|mut file| {
+        file.write_all(serialize(jar).as_bytes())?;
+        file.sync_all()
+    }).and_then(|_| fs::rename(&part, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&part);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, serialize};
+
+    #[test]
+    fn should_serialize_and_parse_cookies() {
+        let text = "# Netscape HTTP Cookie File\n\
+                    .example.com\tTRUE\t/\tFALSE\t0\tsession\tabc\n\
+                    #HttpOnly_api.example.com\tFALSE\t/v1\tTRUE\t4102444800\ttoken\tx=y\n\
+                    example.com\tTRUE\t/\tFALSE\t1000\texpired\tvalue\n\
+                    malformed line\n";
+        let jar = parse(text);
+
+        let session = jar.get("session").expect("To have session");
+        assert_eq!(session.domain(), Some("example.com"));
+        assert_eq!(session.expires(), None);
+
+        let token = jar.get("token").expect("To have token");
+        assert_eq!(token.value(), "x=y");
+        assert_eq!(token.path(), Some("/v1"));
+        assert_eq!(token.secure(), Some(true));
+        assert_eq!(token.http_only(), Some(true));
+        assert_eq!(token.expires().map(|expires| expires.unix_timestamp()), Some(4102444800));
+
+        assert!(jar.get("expired").is_none());
+        assert_eq!(jar.delta().count(), 2);
+
+        let mut jar = parse(&serialize(&jar));
+        assert_eq!(jar.iter().count(), 2);
+        assert_eq!(jar.get("token").and_then(|token| token.expires()).map(|expires| expires.unix_timestamp()), Some(4102444800));
+
+        jar.add(cookie::Cookie::new("no-domain", "value"));
+        let text = serialize(&jar);
+        assert!(text.contains("#HttpOnly_api.example.com\tTRUE\t/v1\tTRUE\t4102444800\ttoken\tx=y\n"));
+        assert!(!text.contains("no-domain"));
+    }
+}
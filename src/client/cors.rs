@@ -0,0 +1,169 @@
+//!CORS preflight.
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::{matsu, client};
+//!
+//!async fn check_cors() {
+//!    let client = client::Client::default();
+//!
+//!    let headers = [http::header::CONTENT_TYPE, http::header::AUTHORIZATION];
+//!    let preflight = matsu!(client.preflight("https://api.example.com/v1/users", http::Method::PUT, &headers)).expect("To get response");
+//!    assert!(preflight.allows_method(&http::Method::PUT));
+//!    assert!(headers.iter().all(|header| preflight.allows_header(header)));
+//!}
+//!```
+
+use core::time;
+
+use crate::header;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+///`Access-Control-Allow-*` headers of response to preflight request.
+pub struct Preflight {
+    ///Status of response.
+    pub status: http::StatusCode,
+    ///Value of `Access-Control-Allow-Origin`.
+    pub allow_origin: Option<String>,
+    ///Methods of `Access-Control-Allow-Methods`.
+    ///
+    ///Wildcard is kept as method `*`.
+    pub allow_methods: Vec<http::Method>,
+    ///Header names of `Access-Control-Allow-Headers`.
+    ///
+    ///Wildcard is kept as `*`, which is not valid header name, hence tracked separately.
+    pub allow_headers: Vec<header::HeaderName>,
+    ///Whether `Access-Control-Allow-Headers` contains wildcard.
+    pub allow_any_header: bool,
+    ///Whether `Access-Control-Allow-Credentials` is `true`.
+    pub allow_credentials: bool,
+    ///Header names of `Access-Control-Expose-Headers`.
+    pub expose_headers: Vec<header::HeaderName>,
+    ///Value of `Access-Control-Max-Age`.
+    pub max_age: Option<time::Duration>,
+}
+
+impl Preflight {
+    ///Parses response's status and headers.
+    ///
+    ///Invalid values are ignored.
+    pub fn new(status: http::StatusCode, headers: &http::HeaderMap) -> Self {
+        let mut result = Self {
+            status,
+            ..Self::default()
+        };
+
+        result.allow_origin = headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).and_then(|value| value.to_str().ok()).map(|value| value.trim().to_owned());
+        result.allow_credentials = headers.get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).and_then(|value| value.to_str().ok()).map(|value| value.trim().eq_ignore_ascii_case("true")).unwrap_or(false);
+        result.max_age = headers.get(header::ACCESS_CONTROL_MAX_AGE).and_then(|value| value.to_str().ok()).and_then(|value| value.trim().parse().ok()).map(time::Duration::from_secs);
+
+        for method in list(headers, header::ACCESS_CONTROL_ALLOW_METHODS) {
+            if let Ok(method) = method.parse() {
+                result.allow_methods.push(method);
+            }
+        }
+
+        for name in list(headers, header::ACCESS_CONTROL_ALLOW_HEADERS) {
+            match name {
+                "*" => result.allow_any_header = true,
+                name => if let Ok(name) = name.parse() {
+                    result.allow_headers.push(name);
+                },
+            }
+        }
+
+        for name in list(headers, header::ACCESS_CONTROL_EXPOSE_HEADERS) {
+            if let Ok(name) = name.parse() {
+                result.expose_headers.push(name);
+            }
+        }
+
+        result
+    }
+
+    ///Returns whether preflight succeeded, i.e. status is successful.
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    ///Returns whether `origin` is allowed.
+    ///
+    ///Wildcard allows any origin, unless credentials are allowed, as browsers reject such combination.
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        match self.allow_origin.as_deref() {
+            Some("*") => !self.allow_credentials,
+            Some(allowed) => allowed.eq_ignore_ascii_case(origin),
+            None => false,
+        }
+    }
+
+    ///Returns whether `method` is allowed.
+    ///
+    ///CORS-safelisted methods (`GET`, `HEAD` and `POST`) are always allowed.
+    pub fn allows_method(&self, method: &http::Method) -> bool {
+        match *method {
+            http::Method::GET | http::Method::HEAD | http::Method::POST => true,
+            _ => self.allow_methods.iter().any(|allowed| allowed == method || (!self.allow_credentials && allowed.as_str() == "*")),
+        }
+    }
+
+    ///Returns whether request header `name` is allowed.
+    pub fn allows_header(&self, name: &header::HeaderName) -> bool {
+        (self.allow_any_header && !self.allow_credentials) || self.allow_headers.contains(name)
+    }
+}
+
+///Iterates over elements of comma-separated header list.
+fn list(headers: &http::HeaderMap, name: header::HeaderName) -> impl Iterator<Item=&str> {
+    headers.get_all(name).into_iter()
+                         .filter_map(|value| value.to_str().ok())
+                         .flat_map(|value| value.split(','))
+                         .map(str::trim)
+                         .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Preflight;
+    use crate::header;
+
+    #[test]
+    fn should_parse_preflight_headers() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, header::HeaderValue::from_static("https://example.com"));
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, header::HeaderValue::from_static("PUT, DELETE"));
+        headers.append(header::ACCESS_CONTROL_ALLOW_METHODS, header::HeaderValue::from_static("PATCH"));
+        headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, header::HeaderValue::from_static("Content-Type,X-Api-Key, "));
+        headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, header::HeaderValue::from_static("true"));
+        headers.insert(header::ACCESS_CONTROL_MAX_AGE, header::HeaderValue::from_static("600"));
+
+        let preflight = Preflight::new(http::StatusCode::NO_CONTENT, &headers);
+        assert!(preflight.is_success());
+        assert!(preflight.allows_origin("https://example.com"));
+        assert!(!preflight.allows_origin("https://evil.com"));
+        assert_eq!(preflight.allow_methods, [http::Method::PUT, http::Method::DELETE, http::Method::PATCH]);
+        assert!(preflight.allows_method(&http::Method::GET));
+        assert!(!preflight.allows_method(&http::Method::OPTIONS));
+        assert!(preflight.allows_header(&header::CONTENT_TYPE));
+        assert!(preflight.allows_header(&header::HeaderName::from_static("x-api-key")));
+        assert!(!preflight.allows_header(&header::AUTHORIZATION));
+        assert_eq!(preflight.max_age, Some(core::time::Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn should_not_allow_wildcard_with_credentials() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, header::HeaderValue::from_static("*"));
+        headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, header::HeaderValue::from_static("*"));
+
+        let preflight = Preflight::new(http::StatusCode::OK, &headers);
+        assert!(preflight.allows_origin("https://example.com"));
+        assert!(preflight.allows_header(&header::AUTHORIZATION));
+
+        headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, header::HeaderValue::from_static("true"));
+        let preflight = Preflight::new(http::StatusCode::OK, &headers);
+        assert!(!preflight.allows_origin("https://example.com"));
+        assert!(!preflight.allows_header(&header::AUTHORIZATION));
+    }
+}
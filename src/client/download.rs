@@ -0,0 +1,212 @@
+//!Conditional download of files.
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::{matsu, client};
+//!
+//!use std::path::Path;
+//!
+//!async fn update() {
+//!    let client = client::Client::default();
+//!
+//!    let path = Path::new("index.json");
+//!    let changed = matsu!(client.download_if_changed("https://example.com/index.json", path, Path::new("index.json.meta"))).expect("To download");
+//!    if changed {
+//!        println!("Index is updated");
+//!    }
+//!}
+//!```
+
+use core::fmt;
+use std::error::Error;
+use std::path::Path;
+use std::{fs, io};
+
+use super::{request, response};
+use crate::extractor::BodyReadError;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+///Validators of downloaded file, that are stored alongside it.
+pub struct DownloadMeta {
+    ///ETag of file.
+    pub etag: Option<etag::EntityTag>,
+    ///Last modification date of file.
+    pub last_modified: Option<httpdate::HttpDate>,
+}
+
+impl DownloadMeta {
+    ///Creates new instance from response's headers.
+    pub fn from_response(response: &response::Response) -> Self {
+        Self {
+            etag: response.etag(),
+            last_modified: response.last_modified(),
+        }
+    }
+
+    ///Returns whether there is no validator.
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    ///Loads metadata from file.
+    ///
+    ///Unknown or invalid entries are ignored.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let mut result = Self::default();
+
+        for line in text.lines() {
+            let mut parts = line.splitn(2, ':');
+            let name = parts.next().unwrap_or("").trim();
+            let value = parts.next().unwrap_or("").trim();
+
+            if name.eq_ignore_ascii_case("etag") {
+                result.etag = value.parse().ok();
+            } else if name.eq_ignore_ascii_case("last-modified") {
+                result.last_modified = value.parse().ok();
+            }
+        }
+
+        Ok(result)
+    }
+
+    ///Stores metadata into file, replacing it atomically.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let part = crate::utils::part_path(path);
+        fs::write(&part, self.to_string())?;
+        let result = fs::rename(&part, path);
+
+        if result.is_err() {
+            let _ = fs::remove_file(&part);
+        }
+
+        result
+    }
+
+    fn apply(&self, mut builder: request::Builder) -> request::Builder {
+        if let Some(etag) = self.etag.as_ref() {
+            builder = builder.set_etag(etag, request::tags::IfNoneMatch);
+        }
+        if let Some(date) = self.last_modified {
+            builder = builder.set_date(date, request::tags::IfModifiedSince);
+        }
+
+        builder
+    }
+}
+
+impl fmt::Display for DownloadMeta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(etag) = self.etag.as_ref() {
+            writeln!(f, "etag: {}", etag)?;
+        }
+        if let Some(date) = self.last_modified.as_ref() {
+            writeln!(f, "last-modified: {}", date)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+///Describes failure to download file.
+pub enum DownloadError {
+    ///Failed to perform request.
+    Call(super::errors::CallError),
+    ///Failed to read body.
+    Body(BodyReadError),
+    ///Failed to access file system.
+    Io(io::Error),
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DownloadError::Call(error) => fmt::Display::fmt(error, f),
+            DownloadError::Body(error) => fmt::Display::fmt(error, f),
+            DownloadError::Io(error) => write!(f, "File system error: {}", error),
+        }
+    }
+}
+
+impl Error for DownloadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DownloadError::Call(error) => Some(error),
+            DownloadError::Body(error) => Some(error),
+            DownloadError::Io(error) => Some(error),
+        }
+    }
+}
+
+impl_call_error!(DownloadError);
+
+impl From<BodyReadError> for DownloadError {
+    #[inline]
+    fn from(error: BodyReadError) -> Self {
+        DownloadError::Body(error)
+    }
+}
+
+impl From<io::Error> for DownloadError {
+    #[inline]
+    fn from(error: io::Error) -> Self {
+        DownloadError::Io(error)
+    }
+}
+
+///Creates conditional request, using metadata only if file exists.
+pub(crate) fn create_request(uri: &str, path: &Path, meta: &Path) -> Result<request::Request, http::uri::InvalidUri> {
+    let builder = request::Request::get(uri)?;
+
+    let builder = match path.exists() {
+        true => match DownloadMeta::load(meta) {
+            Ok(meta) => meta.apply(builder),
+            Err(_) => builder,
+        },
+        false => builder,
+    };
+
+    Ok(builder.empty())
+}
+
+///Writes response's body into `path`, via temporary file.
+pub(crate) async fn store(response: response::Response, path: &Path, meta: &Path) -> Result<bool, DownloadError> {
+    if response.status() == http::StatusCode::NOT_MODIFIED {
+        return Ok(false);
+    }
+
+    let mut response = response.error_for_status()?;
+    matsu!(response.file_atomic(path))?;
+
+    let validators = DownloadMeta::from_response(&response);
+    match validators.is_empty() {
+        true => match fs::remove_file(meta) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error.into()),
+            _ => (),
+        },
+        false => validators.save(meta)?,
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DownloadMeta;
+
+    #[test]
+    fn should_load_saved_meta() {
+        let path = std::env::temp_dir().join(format!("yukikaze-meta-{}", crate::utils::uuid_v4()));
+
+        let meta = DownloadMeta {
+            etag: Some(etag::EntityTag::strong("lolka")),
+            last_modified: Some(httpdate::HttpDate::from(std::time::UNIX_EPOCH)),
+        };
+        meta.save(&path).expect("To save");
+        assert_eq!(DownloadMeta::load(&path).expect("To load"), meta);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
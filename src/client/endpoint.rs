@@ -0,0 +1,229 @@
+//!Typed API definitions.
+//!
+//![Endpoint](trait.Endpoint.html) describes single API call: its method, path, query, body
+//!and type of response, which allows to invoke it via [Client::call](../struct.Client.html#method.call).
+//!
+//!Path is usually relative, hence client is expected to have
+//![base URL](../config/trait.Config.html#method.base_url).
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::{matsu, client};
+//!use yukikaze::client::endpoint::Endpoint;
+//!
+//!use std::borrow::Cow;
+//!
+//!#[derive(serde::Deserialize)]
+//!struct Issue {
+//!    title: String,
+//!}
+//!
+//!#[derive(serde::Serialize)]
+//!struct IssuesQuery {
+//!    state: &'static str,
+//!}
+//!
+//!struct ListIssues {
+//!    repo: String,
+//!    query: IssuesQuery,
+//!}
+//!
+//!impl Endpoint for ListIssues {
+//!    type Query = IssuesQuery;
+//!    type Body = ();
+//!    type Response = Vec<Issue>;
+//!
+//!    const METHOD: http::Method = http::Method::GET;
+//!    const PATH: &'static str = "/repos/{repo}/issues";
+//!
+//!    fn path_param(&self, name: &str) -> Option<Cow<'_, str>> {
+//!        match name {
+//!            "repo" => Some(Cow::Borrowed(&self.repo)),
+//!            _ => None,
+//!        }
+//!    }
+//!
+//!    fn query(&self) -> Option<&Self::Query> {
+//!        Some(&self.query)
+//!    }
+//!}
+//!
+//!async fn list_issues() {
+//!    let client = client::Client::builder().base_url(Some("https://api.github.com".parse().unwrap())).build();
+//!
+//!    let endpoint = ListIssues {
+//!        repo: "DoumanAsh/yukikaze".to_owned(),
+//!        query: IssuesQuery { state: "open" },
+//!    };
+//!    let issues = matsu!(client.call(&endpoint)).expect("To list issues");
+//!    for issue in issues {
+//!        println!("{}", issue.title);
+//!    }
+//!}
+//!```
+
+use std::borrow::Cow;
+use std::io::Write;
+
+use percent_encoding::utf8_percent_encode;
+
+use super::{errors, request};
+use crate::utils;
+
+///Description of API call.
+pub trait Endpoint {
+    ///Type of query, serialized as url-encoded form.
+    type Query: serde::Serialize;
+    ///Type of body, serialized as JSON.
+    type Body: serde::Serialize;
+    ///Type of response, deserialized from JSON.
+    ///
+    ///Empty body is deserialized as `null`, so `()` or `Option` can be used for responses without content.
+    type Response: serde::de::DeserializeOwned;
+
+    ///Method of request.
+    const METHOD: http::Method;
+    ///Template of path, with parameters enclosed in braces, e.g. `/users/{id}`.
+    const PATH: &'static str;
+
+    #[inline]
+    ///Returns value of path's parameter, that is percent-encoded as single segment.
+    ///
+    ///By default there are no parameters.
+    fn path_param(&self, _name: &str) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    #[inline]
+    ///Returns query, if any.
+    ///
+    ///By default there is no query.
+    fn query(&self) -> Option<&Self::Query> {
+        None
+    }
+
+    #[inline]
+    ///Returns body, if any.
+    ///
+    ///By default there is no body.
+    fn body(&self) -> Option<&Self::Body> {
+        None
+    }
+
+    #[inline]
+    ///Allows to customize request, before body is set (e.g. to add headers).
+    ///
+    ///By default does nothing.
+    fn prepare(&self, builder: request::Builder) -> request::Builder {
+        builder
+    }
+}
+
+///Substitutes parameters of `E::PATH`, returning path with query.
+fn render_uri<E: Endpoint>(endpoint: &E) -> Result<String, errors::EndpointError> {
+    let mut buffer = utils::BytesWriter::with_smol_capacity();
+    let mut rest = E::PATH;
+
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        let name = &rest[start+1..end];
+        let value = match endpoint.path_param(name) {
+            Some(value) => value,
+            None => return Err(errors::EndpointError::MissingParam(name)),
+        };
+
+        let _ = write!(buffer, "{}{}", &rest[..start], utf8_percent_encode(&value, utils::enc::USER_INFO_ENCODE_SET));
+        rest = &rest[end+1..];
+    }
+    let _ = buffer.write_all(rest.as_bytes());
+
+    if let Some(query) = endpoint.query() {
+        let query = serde_urlencoded::to_string(query)?;
+        if !query.is_empty() {
+            let _ = write!(buffer, "?{}", query);
+        }
+    }
+
+    match String::from_utf8(buffer.into_inner().to_vec()) {
+        Ok(uri) => Ok(uri),
+        Err(_) => unreach!(),
+    }
+}
+
+///Creates request to invoke `endpoint`.
+pub fn create_request<E: Endpoint>(endpoint: &E) -> Result<request::Request, errors::EndpointError> {
+    let uri = render_uri(endpoint)?;
+    let builder = endpoint.prepare(request::Request::new(E::METHOD, uri)?);
+
+    match endpoint.body() {
+        Some(body) => builder.json(body).map_err(errors::EndpointError::Json),
+        None => Ok(builder.empty()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Endpoint, create_request};
+    use crate::client::errors::EndpointError;
+
+    use std::borrow::Cow;
+
+    #[derive(serde::Serialize)]
+    struct Query {
+        q: &'static str,
+    }
+
+    struct GetFile {
+        owner: &'static str,
+        path: &'static str,
+        query: Option<Query>,
+    }
+
+    impl Endpoint for GetFile {
+        type Query = Query;
+        type Body = ();
+        type Response = ();
+
+        const METHOD: http::Method = http::Method::GET;
+        const PATH: &'static str = "/users/{owner}/files/{path}";
+
+        fn path_param(&self, name: &str) -> Option<Cow<'_, str>> {
+            match name {
+                "owner" => Some(Cow::Borrowed(self.owner)),
+                "path" if !self.path.is_empty() => Some(Cow::Borrowed(self.path)),
+                _ => None,
+            }
+        }
+
+        fn query(&self) -> Option<&Self::Query> {
+            self.query.as_ref()
+        }
+    }
+
+    #[test]
+    fn should_render_endpoint_uri() {
+        let endpoint = GetFile {
+            owner: "yuki kaze",
+            path: "docs/README.md",
+            query: Some(Query { q: "a&b" }),
+        };
+        let request = create_request(&endpoint).expect("To create request");
+        assert_eq!(request.uri(), "/users/yuki%20kaze/files/docs%2FREADME.md?q=a%26b");
+        assert_eq!(request.method(), http::Method::GET);
+
+        let endpoint = GetFile {
+            owner: "yukikaze",
+            path: "",
+            query: None,
+        };
+        match create_request(&endpoint) {
+            Err(EndpointError::MissingParam("path")) => (),
+            result => panic!("Unexpected result: {:?}", result.map(|_| ())),
+        }
+    }
+}
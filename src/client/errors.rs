@@ -0,0 +1,631 @@
+//!Client errors.
+
+use std::error::Error as StdError;
+use std::io;
+use core::fmt;
+
+use crate::connector::{ConnectError, ConnectStage};
+use crate::connector::proxy::ProxyAuthError;
+use super::response::errors::StatusError;
+
+#[derive(Debug)]
+#[non_exhaustive]
+///Describes failure to perform HTTP request.
+///
+///Each variant, except `Shutdown`, `HeaderLimit` and `CircuitOpen`, contains original `hyper::Error`, while variant itself
+///classifies failure, allowing to decide whether request can be re-tried.
+///
+///New variants may be added in future, hence matching must include wildcard arm.
+pub enum Error {
+    ///Failed to resolve host name.
+    Dns(hyper::Error),
+    ///Failed to establish TCP connection.
+    Connect(hyper::Error),
+    ///Failed to perform TLS handshake.
+    Tls(hyper::Error),
+    ///Operation timed out.
+    Timeout(hyper::Error),
+    ///HTTP protocol violation, including unexpected closure of connection.
+    Protocol(hyper::Error),
+    ///Failed to transfer body.
+    Body(hyper::Error),
+    ///Other error, that cannot be classified.
+    Other(hyper::Error),
+    ///Client is shut down and no longer accepts requests.
+    Shutdown,
+    ///Response's headers exceed limits, set by [Config](../config/trait.Config.html#method.max_response_headers).
+    HeaderLimit {
+        ///Number of headers in response.
+        count: usize,
+        ///Total size of headers in bytes.
+        size: usize,
+    },
+    ///Request is rejected by open [circuit breaker](../breaker/index.html) of host, contains host.
+    CircuitOpen(String),
+}
+
+impl Error {
+    #[inline]
+    ///Returns reference to underlying hyper's error, if any.
+    pub fn hyper(&self) -> Option<&hyper::Error> {
+        match self {
+            Error::Dns(error) => Some(error),
+            Error::Connect(error) => Some(error),
+            Error::Tls(error) => Some(error),
+            Error::Timeout(error) => Some(error),
+            Error::Protocol(error) => Some(error),
+            Error::Body(error) => Some(error),
+            Error::Other(error) => Some(error),
+            Error::Shutdown | Error::HeaderLimit { .. } | Error::CircuitOpen(_) => None,
+        }
+    }
+
+    #[inline]
+    ///Returns whether error happened before connection has been established.
+    ///
+    ///In this case it is guaranteed that request hasn't been sent.
+    pub fn is_connect(&self) -> bool {
+        matches!(self, Error::Dns(_) | Error::Connect(_) | Error::Tls(_))
+    }
+
+    ///Returns failure to authenticate with proxy, if connection failed because of it.
+    pub fn proxy_auth(&self) -> Option<&ProxyAuthError> {
+        match self {
+            Error::Connect(error) => find_io_error(error).and_then(ConnectError::from_io).and_then(|error| ProxyAuthError::from_io(error.io())),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    ///Returns whether error is timeout.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Error::Timeout(_))
+    }
+}
+
+fn find_io_error(error: &hyper::Error) -> Option<&io::Error> {
+    let mut source = error.source();
+
+    while let Some(error) = source {
+        if let Some(error) = error.downcast_ref::<io::Error>() {
+            return Some(error);
+        }
+
+        source = error.source();
+    }
+
+    None
+}
+
+impl From<hyper::Error> for Error {
+    fn from(error: hyper::Error) -> Self {
+        let io = find_io_error(&error);
+
+        if error.is_connect() {
+            match io.and_then(ConnectError::from_io).map(ConnectError::stage) {
+                Some(ConnectStage::Dns) => Error::Dns(error),
+                Some(ConnectStage::Tls) => Error::Tls(error),
+                Some(ConnectStage::Tcp) | Some(ConnectStage::Proxy) | None => match io.map(io::Error::kind) {
+                    Some(io::ErrorKind::TimedOut) => Error::Timeout(error),
+                    _ => Error::Connect(error),
+                },
+            }
+        } else if error.is_timeout() || io.map(|io| io.kind() == io::ErrorKind::TimedOut).unwrap_or(false) {
+            Error::Timeout(error)
+        } else if error.is_parse() || error.is_incomplete_message() || error.is_closed() || error.is_canceled() {
+            Error::Protocol(error)
+        } else if error.is_body_write_aborted() || io.is_some() {
+            Error::Body(error)
+        } else {
+            Error::Other(error)
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Dns(error) => write!(f, "DNS error: {}", error),
+            Error::Connect(error) => write!(f, "Connection error: {}", error),
+            Error::Tls(error) => write!(f, "TLS error: {}", error),
+            Error::Timeout(error) => write!(f, "Timeout: {}", error),
+            Error::Protocol(error) => write!(f, "Protocol error: {}", error),
+            Error::Body(error) => write!(f, "Body error: {}", error),
+            Error::Other(error) => write!(f, "{}", error),
+            Error::Shutdown => f.write_str("Client is shut down"),
+            Error::HeaderLimit { count, size } => write!(f, "Response's headers exceed limit: {} headers of {} bytes", count, size),
+            Error::CircuitOpen(host) => write!(f, "Circuit breaker of '{}' is open", host),
+        }
+    }
+}
+
+impl StdError for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.hyper().map(|error| error as &(dyn StdError + 'static))
+    }
+}
+
+#[derive(Debug)]
+///Describes failure to get response in high-level methods of [Client](../struct.Client.html).
+///
+///Errors of specific methods are built on top of it, adding their own failures.
+pub enum CallError {
+    ///Invalid URI.
+    Uri(http::uri::InvalidUri),
+    ///Call is not completed within [Client::timeout](../struct.Client.html#method.timeout).
+    Timeout,
+    ///Failed to perform request.
+    Request(Error),
+    ///Server responded with unsuccessful status.
+    Status(StatusError),
+}
+
+impl CallError {
+    #[inline]
+    ///Returns status code, if server responded with unsuccessful one.
+    pub fn status(&self) -> Option<http::StatusCode> {
+        match self {
+            CallError::Status(error) => Some(error.status),
+            _ => None,
+        }
+    }
+}
+
+impl From<http::uri::InvalidUri> for CallError {
+    #[inline]
+    fn from(error: http::uri::InvalidUri) -> Self {
+        CallError::Uri(error)
+    }
+}
+
+impl From<Error> for CallError {
+    #[inline]
+    fn from(error: Error) -> Self {
+        CallError::Request(error)
+    }
+}
+
+impl From<StatusError> for CallError {
+    #[inline]
+    fn from(error: StatusError) -> Self {
+        CallError::Status(error)
+    }
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CallError::Uri(error) => write!(f, "Invalid URI: {}", error),
+            CallError::Timeout => f.write_str("Call is not completed in time"),
+            CallError::Request(error) => fmt::Display::fmt(error, f),
+            CallError::Status(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl StdError for CallError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            CallError::Uri(error) => Some(error),
+            CallError::Timeout => None,
+            CallError::Request(error) => Some(error),
+            CallError::Status(error) => Some(error),
+        }
+    }
+}
+
+///Describes failure to fetch page of [Paginator](../paginate/struct.Paginator.html).
+pub type PageError = CallError;
+///Describes failure of [Client::prewarm](../struct.Client.html#method.prewarm).
+///
+///[Request](enum.CallError.html#variant.Request) contains last error, if no connection is established.
+pub type PrewarmError = CallError;
+///Describes failure of [Client::preflight](../struct.Client.html#method.preflight).
+pub type PreflightError = CallError;
+///Describes failure of [Client::capabilities](../struct.Client.html#method.capabilities).
+pub type CapabilitiesError = CallError;
+
+#[derive(Debug)]
+///Describes failure to establish tunnel via [Client::connect_tunnel](../struct.Client.html#method.connect_tunnel).
+pub enum TunnelError {
+    ///Failed to perform CONNECT request, including refusal by server.
+    Call(CallError),
+    ///Failed to upgrade connection.
+    Upgrade(hyper::Error),
+}
+
+impl_call_error!(TunnelError);
+
+impl fmt::Display for TunnelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TunnelError::Call(error) => fmt::Display::fmt(error, f),
+            TunnelError::Upgrade(error) => write!(f, "Failed to upgrade connection: {}", error),
+        }
+    }
+}
+
+impl StdError for TunnelError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            TunnelError::Call(error) => Some(error),
+            TunnelError::Upgrade(error) => Some(error),
+        }
+    }
+}
+
+#[derive(Debug)]
+///Describes failure of [Client::grpc_web_unary](../struct.Client.html#method.grpc_web_unary).
+pub enum GrpcWebError {
+    ///Failed to perform request.
+    Call(CallError),
+    ///Failed to read response's body.
+    Body(crate::extractor::BodyReadError),
+    ///Response's body is malformed.
+    Frame(super::grpc_web::FrameError),
+    ///Response has no `grpc-status`.
+    MissingStatus,
+    ///Call is completed with unsuccessful gRPC status.
+    Grpc(super::grpc_web::Status),
+    ///Successful call has other than single message in reply.
+    MessageCount(usize),
+}
+
+impl_call_error!(GrpcWebError);
+
+impl From<crate::extractor::BodyReadError> for GrpcWebError {
+    #[inline]
+    fn from(error: crate::extractor::BodyReadError) -> Self {
+        GrpcWebError::Body(error)
+    }
+}
+
+impl From<super::grpc_web::FrameError> for GrpcWebError {
+    #[inline]
+    fn from(error: super::grpc_web::FrameError) -> Self {
+        GrpcWebError::Frame(error)
+    }
+}
+
+impl fmt::Display for GrpcWebError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GrpcWebError::Call(error) => fmt::Display::fmt(error, f),
+            GrpcWebError::Body(error) => fmt::Display::fmt(error, f),
+            GrpcWebError::Frame(error) => fmt::Display::fmt(error, f),
+            GrpcWebError::MissingStatus => f.write_str("gRPC-web response has no grpc-status"),
+            GrpcWebError::Grpc(status) => fmt::Display::fmt(status, f),
+            GrpcWebError::MessageCount(len) => write!(f, "Unary gRPC-web call replied with {} messages", len),
+        }
+    }
+}
+
+impl StdError for GrpcWebError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            GrpcWebError::Call(error) => Some(error),
+            GrpcWebError::Body(error) => Some(error),
+            GrpcWebError::Frame(error) => Some(error),
+            GrpcWebError::MissingStatus | GrpcWebError::Grpc(_) | GrpcWebError::MessageCount(_) => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+///Describes failure of [Client::send_with_budget](../struct.Client.html#method.send_with_budget).
+pub enum BudgetError {
+    ///Response is not received within budget.
+    Expired {
+        ///Total budget of request.
+        budget: core::time::Duration,
+        ///Number of times request has been awaited.
+        attempts: usize,
+    },
+    ///Failed to perform request.
+    Request(Error),
+}
+
+impl BudgetError {
+    #[inline]
+    ///Returns whether budget is exhausted.
+    pub fn is_expired(&self) -> bool {
+        match self {
+            BudgetError::Expired { .. } => true,
+            BudgetError::Request(_) => false,
+        }
+    }
+}
+
+impl From<Error> for BudgetError {
+    #[inline]
+    fn from(error: Error) -> Self {
+        BudgetError::Request(error)
+    }
+}
+
+impl fmt::Display for BudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BudgetError::Expired { budget, attempts } => write!(f, "Response is not received within {} ms after {} attempts", budget.as_millis(), attempts),
+            BudgetError::Request(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl StdError for BudgetError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            BudgetError::Expired { .. } => None,
+            BudgetError::Request(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "websocket")]
+#[derive(Debug)]
+///Describes failure to open websocket via [Client::websocket](../struct.Client.html#method.websocket).
+pub enum WebsocketError {
+    ///Failed to perform upgrade request.
+    Call(CallError),
+    ///Server's response is not valid websocket handshake.
+    Handshake(crate::upgrade::websocket::WebsocketUpgradeError),
+    ///Failed to upgrade connection.
+    Upgrade(hyper::Error),
+}
+
+#[cfg(feature = "websocket")]
+impl_call_error!(WebsocketError);
+
+#[cfg(feature = "websocket")]
+impl From<crate::upgrade::websocket::WebsocketUpgradeError> for WebsocketError {
+    #[inline]
+    fn from(error: crate::upgrade::websocket::WebsocketUpgradeError) -> Self {
+        WebsocketError::Handshake(error)
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl fmt::Display for WebsocketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WebsocketError::Call(error) => fmt::Display::fmt(error, f),
+            WebsocketError::Handshake(error) => write!(f, "Invalid websocket handshake: {}", error),
+            WebsocketError::Upgrade(error) => write!(f, "Failed to upgrade connection: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl StdError for WebsocketError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            WebsocketError::Call(error) => Some(error),
+            WebsocketError::Handshake(error) => Some(error),
+            WebsocketError::Upgrade(error) => Some(error),
+        }
+    }
+}
+
+#[derive(Debug)]
+///Describes failure of [Client::fetch_limited](../struct.Client.html#method.fetch_limited).
+pub enum FetchError {
+    ///Failed to perform request.
+    Call(CallError),
+    ///Body exceeds limit.
+    ///
+    ///Contains size of body, if it is known from `Content-Length`.
+    TooLarge(Option<usize>),
+    ///Failed to read body.
+    Body(crate::extractor::BodyReadError),
+}
+
+impl_call_error!(FetchError);
+
+impl From<crate::extractor::BodyReadError> for FetchError {
+    #[inline]
+    fn from(error: crate::extractor::BodyReadError) -> Self {
+        match error {
+            crate::extractor::BodyReadError::Overflow(_) => FetchError::TooLarge(None),
+            error => FetchError::Body(error),
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::Call(error) => fmt::Display::fmt(error, f),
+            FetchError::TooLarge(Some(size)) => write!(f, "Body of {} bytes exceeds limit", size),
+            FetchError::TooLarge(None) => f.write_str("Body exceeds limit"),
+            FetchError::Body(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl StdError for FetchError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            FetchError::Call(error) => Some(error),
+            FetchError::TooLarge(_) => None,
+            FetchError::Body(error) => Some(error),
+        }
+    }
+}
+
+#[derive(Debug)]
+///Describes failure of [Client::call](../struct.Client.html#method.call).
+pub enum EndpointError {
+    ///Endpoint provides no value for path's parameter.
+    MissingParam(&'static str),
+    ///Failed to serialize query.
+    Query(serde_urlencoded::ser::Error),
+    ///Failed to serialize body.
+    Json(serde_json::Error),
+    ///Failed to perform request.
+    Call(CallError),
+    ///Failed to read or deserialize response's body.
+    Body(crate::extractor::BodyReadError),
+}
+
+impl_call_error!(EndpointError);
+
+impl From<serde_urlencoded::ser::Error> for EndpointError {
+    #[inline]
+    fn from(error: serde_urlencoded::ser::Error) -> Self {
+        EndpointError::Query(error)
+    }
+}
+
+impl From<crate::extractor::BodyReadError> for EndpointError {
+    #[inline]
+    fn from(error: crate::extractor::BodyReadError) -> Self {
+        EndpointError::Body(error)
+    }
+}
+
+impl fmt::Display for EndpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EndpointError::MissingParam(name) => write!(f, "Missing value of path parameter '{}'", name),
+            EndpointError::Query(error) => write!(f, "Failed to serialize query: {}", error),
+            EndpointError::Json(error) => write!(f, "Failed to serialize body: {}", error),
+            EndpointError::Call(error) => fmt::Display::fmt(error, f),
+            EndpointError::Body(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl StdError for EndpointError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            EndpointError::MissingParam(_) => None,
+            EndpointError::Query(error) => Some(error),
+            EndpointError::Json(error) => Some(error),
+            EndpointError::Call(error) => Some(error),
+            EndpointError::Body(error) => Some(error),
+        }
+    }
+}
+
+#[derive(Debug)]
+///Describes failure of convenience methods, such as [Client::get](../struct.Client.html#method.get).
+pub enum SimpleError {
+    ///Failed to serialize body.
+    Json(serde_json::Error),
+    ///Failed to perform request.
+    Call(CallError),
+    ///Failed to read or deserialize response's body.
+    Body(crate::extractor::BodyReadError),
+}
+
+impl_call_error!(SimpleError);
+
+impl From<crate::extractor::BodyReadError> for SimpleError {
+    #[inline]
+    fn from(error: crate::extractor::BodyReadError) -> Self {
+        SimpleError::Body(error)
+    }
+}
+
+impl fmt::Display for SimpleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimpleError::Json(error) => write!(f, "Failed to serialize body: {}", error),
+            SimpleError::Call(error) => fmt::Display::fmt(error, f),
+            SimpleError::Body(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl StdError for SimpleError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            SimpleError::Json(error) => Some(error),
+            SimpleError::Call(error) => Some(error),
+            SimpleError::Body(error) => Some(error),
+        }
+    }
+}
+
+#[derive(Debug)]
+///Describes failure of [Client::send_expect](../struct.Client.html#method.send_expect).
+pub enum ApiError<E> {
+    ///Failed to perform request.
+    ///
+    ///If server responded with unsuccessful status, but body cannot be deserialized as error,
+    ///then [Status](enum.CallError.html#variant.Status) contains body, limited to 1kb.
+    Call(CallError),
+    ///Server responded with unsuccessful status and error, described by body.
+    Api {
+        ///Status code.
+        status: http::StatusCode,
+        ///Response's headers.
+        headers: http::HeaderMap,
+        ///Deserialized body.
+        error: E,
+    },
+    ///Failed to read or deserialize response's body.
+    Body(crate::extractor::BodyReadError),
+}
+
+impl<E> ApiError<E> {
+    ///Returns status code, if server responded.
+    pub fn status(&self) -> Option<http::StatusCode> {
+        match self {
+            ApiError::Api { status, .. } => Some(*status),
+            ApiError::Call(error) => error.status(),
+            _ => None,
+        }
+    }
+}
+
+impl<E> From<CallError> for ApiError<E> {
+    #[inline]
+    fn from(error: CallError) -> Self {
+        ApiError::Call(error)
+    }
+}
+
+impl<E> From<Error> for ApiError<E> {
+    #[inline]
+    fn from(error: Error) -> Self {
+        ApiError::Call(error.into())
+    }
+}
+
+impl<E> From<crate::extractor::BodyReadError> for ApiError<E> {
+    #[inline]
+    fn from(error: crate::extractor::BodyReadError) -> Self {
+        ApiError::Body(error)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for ApiError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::Call(error) => fmt::Display::fmt(error, f),
+            ApiError::Api { status, error, .. } => write!(f, "API error {}: {:?}", status, error),
+            ApiError::Body(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl<E: fmt::Debug> StdError for ApiError<E> {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            ApiError::Call(error) => Some(error),
+            ApiError::Api { .. } => None,
+            ApiError::Body(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//!Unary calls over [gRPC-web](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-WEB.md) protocol.
+//!
+//!Messages are length-prefixed frames of already encoded protobuf payload,
+//!while status of call is sent in trailer frame at the end of body.
+//!
+//!Compressed frames are not supported.
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::{matsu, client};
+//!
+//!async fn call(request: &[u8]) {
+//!    let client = client::Client::default();
+//!
+//!    //`request` is protobuf encoded message
+//!    let reply = matsu!(client.grpc_web_unary("https://api.example.com/fleet.Fleet/GetShip", request)).expect("To get reply");
+//!    println!("Reply of {} bytes", reply.len());
+//!}
+//!```
+
+use core::fmt;
+use std::error::Error as StdError;
+
+use crate::header;
+use super::request;
+
+///Content type of gRPC-web requests with protobuf messages.
+pub const CONTENT_TYPE: &str = "application/grpc-web+proto";
+
+const COMPRESSED_FLAG: u8 = 0x01;
+const TRAILERS_FLAG: u8 = 0x80;
+const PREFIX_LEN: usize = 5;
+
+///Status code of successful call.
+pub const OK: u32 = 0;
+
+///Creates frame of message with `payload`.
+pub fn frame(payload: &[u8]) -> bytes::Bytes {
+    let mut frame = Vec::with_capacity(PREFIX_LEN + payload.len());
+    frame.push(0);
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame.into()
+}
+
+///Creates `POST` request to gRPC-web method at `uri`, with single message `payload`.
+pub fn request<U: AsRef<str>>(uri: U, payload: &[u8]) -> Result<request::Request, http::uri::InvalidUri> {
+    Ok(request::Request::post(uri)?.set_header(header::CONTENT_TYPE, CONTENT_TYPE)
+                                   .set_header(header::ACCEPT, CONTENT_TYPE)
+                                   .set_header(header::HeaderName::from_static("x-grpc-web"), "1")
+                                   .body(Some(frame(payload))))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+///Status of call, sent in `grpc-status` and `grpc-message`.
+pub struct Status {
+    ///Status code, where [OK](constant.OK.html) means success.
+    pub code: u32,
+    ///Error's description, percent-decoded.
+    pub message: String,
+}
+
+impl Status {
+    ///Extracts status out of headers, if `grpc-status` is present and valid.
+    pub fn from_headers(headers: &http::HeaderMap) -> Option<Self> {
+        let code = headers.get("grpc-status")?.to_str().ok()?.trim().parse().ok()?;
+        let message = headers.get("grpc-message").and_then(|message| message.to_str().ok())
+                                                 .map(|message| percent_encoding::percent_decode_str(message).decode_utf8_lossy().into_owned())
+                                                 .unwrap_or_default();
+
+        Some(Self {
+            code,
+            message,
+        })
+    }
+
+    #[inline]
+    ///Returns whether call is successful.
+    pub fn is_ok(&self) -> bool {
+        self.code == OK
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.message.is_empty() {
+            true => write!(f, "gRPC status {}", self.code),
+            false => write!(f, "gRPC status {}: {}", self.code, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Describes malformed gRPC-web body.
+pub enum FrameError {
+    ///Body ends in the middle of frame.
+    Truncated,
+    ///Frame is compressed.
+    Compressed,
+    ///Message's frame follows trailers.
+    AfterTrailers,
+    ///Trailers are not valid headers.
+    InvalidTrailers,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameError::Truncated => f.write_str("gRPC-web body ends in the middle of frame"),
+            FrameError::Compressed => f.write_str("Compressed gRPC-web frames are not supported"),
+            FrameError::AfterTrailers => f.write_str("gRPC-web message follows trailers"),
+            FrameError::InvalidTrailers => f.write_str("gRPC-web trailers are malformed"),
+        }
+    }
+}
+
+impl StdError for FrameError {
+}
+
+#[derive(Debug, Clone, Default)]
+///Parsed body of gRPC-web response.
+pub struct Reply {
+    ///Payloads of messages.
+    pub messages: Vec<bytes::Bytes>,
+    ///Trailers, sent in the last frame.
+    pub trailers: http::HeaderMap,
+}
+
+impl Reply {
+    ///Splits `body` into messages and trailers.
+    pub fn parse(mut body: bytes::Bytes) -> Result<Self, FrameError> {
+        let mut result = Self::default();
+        let mut has_trailers = false;
+
+        while !body.is_empty() {
+            if body.len() < PREFIX_LEN {
+                return Err(FrameError::Truncated);
+            }
+
+            let flags = body[0];
+            let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+            if body.len() - PREFIX_LEN < len {
+                return Err(FrameError::Truncated);
+            }
+
+            let mut payload = body.split_to(PREFIX_LEN + len);
+            let payload = payload.split_off(PREFIX_LEN);
+
+            if flags & COMPRESSED_FLAG != 0 {
+                return Err(FrameError::Compressed);
+            } else if flags & TRAILERS_FLAG != 0 {
+                parse_trailers(&payload, &mut result.trailers)?;
+                has_trailers = true;
+            } else if has_trailers {
+                return Err(FrameError::AfterTrailers);
+            } else {
+                result.messages.push(payload);
+            }
+        }
+
+        Ok(result)
+    }
+
+    #[inline]
+    ///Returns status out of trailers.
+    pub fn status(&self) -> Option<Status> {
+        Status::from_headers(&self.trailers)
+    }
+}
+
+///Parses trailers, encoded as HTTP/1 headers.
+fn parse_trailers(payload: &[u8], trailers: &mut http::HeaderMap) -> Result<(), FrameError> {
+    for line in payload.split(|byte| *byte == b'\n') {
+        let line = match line.last() {
+            Some(b'\r') => &line[..line.len() - 1],
+            _ => line,
+        };
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let idx = line.iter().position(|byte| *byte == b':').ok_or(FrameError::InvalidTrailers)?;
+        let name = header::HeaderName::from_bytes(&line[..idx]).map_err(|_| FrameError::InvalidTrailers)?;
+        let value = header::HeaderValue::from_bytes(trim_ascii(&line[idx + 1..])).map_err(|_| FrameError::InvalidTrailers)?;
+        trailers.append(name, value);
+    }
+
+    Ok(())
+}
+
+fn trim_ascii(mut bytes: &[u8]) -> &[u8] {
+    while let Some((first, rest)) = bytes.split_first() {
+        match first.is_ascii_whitespace() {
+            true => bytes = rest,
+            false => break,
+        }
+    }
+
+    while let Some((last, rest)) = bytes.split_last() {
+        match last.is_ascii_whitespace() {
+            true => bytes = rest,
+            false => break,
+        }
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{frame, FrameError, Reply, Status};
+
+    #[test]
+    fn should_parse_reply() {
+        let mut body = frame(b"yukikaze").to_vec();
+        body.extend_from_slice(&frame(b""));
+        let trailers = b"grpc-status: 5\r\ngrpc-message: Ship%20sunk\r\n";
+        body.push(0x80);
+        body.extend_from_slice(&(trailers.len() as u32).to_be_bytes());
+        body.extend_from_slice(trailers);
+
+        let reply = Reply::parse(body.clone().into()).expect("To parse reply");
+        assert_eq!(reply.messages, [&b"yukikaze"[..], &b""[..]]);
+        assert_eq!(reply.status(), Some(Status { code: 5, message: "Ship sunk".to_owned() }));
+        assert!(!reply.status().unwrap().is_ok());
+
+        assert_eq!(Reply::parse(body[..body.len() - 1].to_vec().into()).unwrap_err(), FrameError::Truncated);
+        assert_eq!(Reply::parse(vec![1, 0, 0, 0, 0].into()).unwrap_err(), FrameError::Compressed);
+        assert_eq!(Reply::parse(vec![0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0].into()).unwrap_err(), FrameError::AfterTrailers);
+
+        let reply = Reply::parse(bytes::Bytes::new()).expect("To parse empty reply");
+        assert!(reply.messages.is_empty());
+        assert_eq!(reply.status(), None);
+    }
+}
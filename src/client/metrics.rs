@@ -0,0 +1,270 @@
+//!Metrics of requests.
+//!
+//!Available only with feature `metrics`
+//!
+//![Metrics](struct.Metrics.html) registers its collectors in user provided `prometheus::Registry`
+//!and is updated by client for each sent request, so that metrics are exposed alongside
+//!the rest of application's metrics.
+//!
+//!Following metrics are provided, prefixed by namespace (`yukikaze` by default):
+//!
+//!- `requests_total` - Counter of responses by status class (`1xx`...`5xx`) and failed requests (`error`);
+//!- `request_duration_seconds` - Histogram of time until response's head is received;
+//!- `request_body_bytes_total` - Counter of sent body bytes;
+//!- `response_body_bytes_total` - Counter of received body bytes, as they are read by response's extractors;
+//!- `pool_requests_total`, `pool_connections_opened_total`, `pool_connections_reused_total`,
+//!  `pool_connections_alive` - Connection pool statistics of client, if [instrumented](../config/trait.Config.html#method.instrument_connections).
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::client;
+//!
+//!let registry = prometheus::Registry::new();
+//!let metrics = client::metrics::Metrics::new(&registry).expect("To register metrics");
+//!let client = client::Client::default().with_metrics(metrics.clone());
+//!
+//!let families = registry.gather();
+//!assert!(families.iter().any(|family| family.get_name() == "yukikaze_pool_requests_total"));
+//!```
+
+use core::fmt;
+use std::time::Instant;
+use std::sync::{Arc, RwLock, Weak};
+
+use prometheus::core::{Collector, Desc};
+
+use super::{request, response, RequestResult};
+use crate::connector::stats;
+use crate::extractor;
+
+///Upper bounds of duration histogram's buckets, in seconds.
+const DURATION_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+const ERROR_CLASS: &str = "error";
+
+///Collector of connection pool statistics, which are read from client on each scrape.
+struct PoolCollector {
+    pool: RwLock<Option<Weak<stats::Registry>>>,
+    requests: prometheus::IntCounter,
+    opened: prometheus::IntCounter,
+    reused: prometheus::IntCounter,
+    alive: prometheus::IntGauge,
+}
+
+impl PoolCollector {
+    fn new(namespace: &str) -> Result<Self, prometheus::Error> {
+        Ok(Self {
+            pool: RwLock::new(None),
+            requests: prometheus::IntCounter::with_opts(prometheus::Opts::new("pool_requests_total", "Number of requests sent through connection pool.").namespace(namespace))?,
+            opened: prometheus::IntCounter::with_opts(prometheus::Opts::new("pool_connections_opened_total", "Number of opened connections.").namespace(namespace))?,
+            reused: prometheus::IntCounter::with_opts(prometheus::Opts::new("pool_connections_reused_total", "Number of requests, that re-used existing connection.").namespace(namespace))?,
+            alive: prometheus::IntGauge::with_opts(prometheus::Opts::new("pool_connections_alive", "Number of currently open connections.").namespace(namespace))?,
+        })
+    }
+}
+
+#[inline]
+fn sync_counter(counter: &prometheus::IntCounter, value: usize) {
+    let value = value as u64;
+    let current = counter.get();
+    if value > current {
+        counter.inc_by(value - current);
+    }
+}
+
+impl Collector for PoolCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        let mut result = Vec::with_capacity(4);
+        result.extend(self.requests.desc());
+        result.extend(self.opened.desc());
+        result.extend(self.reused.desc());
+        result.extend(self.alive.desc());
+        result
+    }
+
+    fn collect(&self) -> Vec<prometheus::proto::MetricFamily> {
+        let pool = self.pool.read().ok().and_then(|pool| pool.as_ref().and_then(Weak::upgrade)).map(|pool| pool.stats());
+        let pool = match pool {
+            Some(pool) => pool,
+            None => return Vec::new(),
+        };
+
+        sync_counter(&self.requests, pool.requests);
+        sync_counter(&self.opened, pool.opened);
+        sync_counter(&self.reused, pool.reused);
+        self.alive.set(pool.alive as i64);
+
+        let mut result = Vec::with_capacity(4);
+        result.extend(self.requests.collect());
+        result.extend(self.opened.collect());
+        result.extend(self.reused.collect());
+        result.extend(self.alive.collect());
+        result
+    }
+}
+
+struct Inner {
+    namespace: String,
+    requests: prometheus::IntCounterVec,
+    duration: prometheus::Histogram,
+    sent: prometheus::IntCounter,
+    received: prometheus::IntCounter,
+    pool: Arc<PoolCollector>,
+}
+
+///Shares pool collector with registry, while client is attached to it via `Metrics`.
+struct SharedPool(Arc<PoolCollector>);
+
+impl Collector for SharedPool {
+    #[inline]
+    fn desc(&self) -> Vec<&Desc> {
+        self.0.desc()
+    }
+
+    #[inline]
+    fn collect(&self) -> Vec<prometheus::proto::MetricFamily> {
+        self.0.collect()
+    }
+}
+
+#[derive(Clone)]
+///Metrics of client's requests.
+///
+///Clones share the same collectors.
+///If attached to multiple clients, requests of all of them are counted, while pool statistics
+///are taken from the client, that is attached last.
+pub struct Metrics(Arc<Inner>);
+
+impl Metrics {
+    #[inline]
+    ///Creates new instance with namespace `yukikaze` and registers it in `registry`.
+    pub fn new(registry: &prometheus::Registry) -> Result<Self, prometheus::Error> {
+        Self::with_namespace(registry, "yukikaze")
+    }
+
+    ///Creates new instance with provided namespace, which is used as prefix of metrics' names,
+    ///and registers it in `registry`.
+    ///
+    ///Fails if namespace is not valid metric name or metrics with the same names are already registered.
+    pub fn with_namespace(registry: &prometheus::Registry, namespace: &str) -> Result<Self, prometheus::Error> {
+        let requests = prometheus::IntCounterVec::new(prometheus::Opts::new("requests_total", "Number of requests by status class of response.").namespace(namespace), &["class"])?;
+        let duration = prometheus::Histogram::with_opts(prometheus::HistogramOpts::new("request_duration_seconds", "Time until response's head is received.").namespace(namespace)
+                                                                                                                                                       .buckets(DURATION_BUCKETS.to_vec()))?;
+        let sent = prometheus::IntCounter::with_opts(prometheus::Opts::new("request_body_bytes_total", "Number of sent body bytes.").namespace(namespace))?;
+        let received = prometheus::IntCounter::with_opts(prometheus::Opts::new("response_body_bytes_total", "Number of received body bytes.").namespace(namespace))?;
+        let pool = Arc::new(PoolCollector::new(namespace)?);
+
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+        registry.register(Box::new(sent.clone()))?;
+        registry.register(Box::new(received.clone()))?;
+        registry.register(Box::new(SharedPool(pool.clone())))?;
+
+        Ok(Self(Arc::new(Inner {
+            namespace: namespace.to_owned(),
+            requests,
+            duration,
+            sent,
+            received,
+            pool,
+        })))
+    }
+
+    #[inline]
+    ///Returns number of requests, that resulted in response with status of specified class.
+    pub fn requests(&self, class: response::StatusClass) -> u64 {
+        self.0.requests.with_label_values(&[class_label(class)]).get()
+    }
+
+    #[inline]
+    ///Returns number of failed requests.
+    pub fn errors(&self) -> u64 {
+        self.0.requests.with_label_values(&[ERROR_CLASS]).get()
+    }
+
+    pub(crate) fn attach(&self, stats: &Arc<stats::Registry>) {
+        if let Ok(mut pool) = self.0.pool.pool.write() {
+            *pool = Some(Arc::downgrade(stats));
+        }
+    }
+
+    #[inline]
+    ///Creates body size of response, which also accounts received bytes in metrics.
+    pub(crate) fn body_size(&self) -> extractor::BodySize {
+        extractor::BodySize::with_received_total(self.0.received.clone())
+    }
+
+    pub(crate) fn start(&self, request: &request::Request) -> Entry {
+        let sent = request.body.as_ref().and_then(|body| body.len()).unwrap_or(0);
+        self.0.sent.inc_by(sent);
+
+        Entry {
+            metrics: self.clone(),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Metrics").field("namespace", &self.0.namespace).finish()
+    }
+}
+
+#[inline]
+fn class_label(class: response::StatusClass) -> &'static str {
+    match class {
+        response::StatusClass::Informational => "1xx",
+        response::StatusClass::Success => "2xx",
+        response::StatusClass::Redirect => "3xx",
+        response::StatusClass::ClientError => "4xx",
+        response::StatusClass::ServerError => "5xx",
+    }
+}
+
+///Ongoing request's metrics entry.
+pub(crate) struct Entry {
+    metrics: Metrics,
+    start: Instant,
+}
+
+impl Entry {
+    pub(crate) fn finish(self, result: &RequestResult) {
+        let inner = &self.metrics.0;
+        inner.duration.observe(self.start.elapsed().as_secs_f64());
+
+        let class = match result {
+            Ok(response) => class_label(response.status_class()),
+            Err(_) => ERROR_CLASS,
+        };
+        inner.requests.with_label_values(&[class]).inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metrics;
+
+    #[test]
+    fn should_register_in_registry() {
+        let registry = prometheus::Registry::new();
+        let metrics = Metrics::with_namespace(&registry, "test").expect("To register metrics");
+        metrics.0.duration.observe(0.003);
+        metrics.0.duration.observe(0.2);
+        metrics.0.duration.observe(20.0);
+
+        let families = registry.gather();
+        let duration = families.iter().find(|family| family.get_name() == "test_request_duration_seconds").expect("To have duration");
+        let histogram = duration.get_metric()[0].get_histogram();
+        assert_eq!(histogram.get_sample_count(), 3);
+        let buckets = histogram.get_bucket();
+        assert_eq!(buckets[0].get_cumulative_count(), 1);
+        assert_eq!(buckets[5].get_cumulative_count(), 2);
+        assert_eq!(buckets[10].get_cumulative_count(), 2);
+        //Pool statistics are not available until client is attached.
+        assert!(!families.iter().any(|family| family.get_name().starts_with("test_pool_")));
+
+        //Names are already taken.
+        assert!(Metrics::with_namespace(&registry, "test").is_err());
+    }
+}
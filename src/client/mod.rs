@@ -58,23 +58,141 @@
 use core::marker::PhantomData;
 use core::future::Future;
 use core::fmt;
+use std::io::Write;
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::header;
+use crate::{extractor, header};
+use crate::utils::OptionExt;
+use crate::connector::{events, proxy, stats, timings, Connect};
 
+///Implements conversions into error with `Call` variant, containing [CallError](errors/enum.CallError.html).
+macro_rules! impl_call_error {
+    ($error:ident) => {
+        impl From<$crate::client::errors::CallError> for $error {
+            #[inline]
+            fn from(error: $crate::client::errors::CallError) -> Self {
+                $error::Call(error)
+            }
+        }
+
+        impl From<http::uri::InvalidUri> for $error {
+            #[inline]
+            fn from(error: http::uri::InvalidUri) -> Self {
+                $error::Call(error.into())
+            }
+        }
+
+        impl From<$crate::client::Error> for $error {
+            #[inline]
+            fn from(error: $crate::client::Error) -> Self {
+                $error::Call(error.into())
+            }
+        }
+
+        impl From<$crate::client::response::errors::StatusError> for $error {
+            #[inline]
+            fn from(error: $crate::client::response::errors::StatusError) -> Self {
+                $error::Call(error.into())
+            }
+        }
+    };
+}
+
+mod alt_svc;
+mod redirects;
+pub mod auth;
+pub mod builder;
+pub mod capabilities;
 pub mod config;
+pub mod cookies;
+pub mod cors;
+pub mod download;
+pub mod endpoint;
+pub mod breaker;
+pub mod errors;
+pub mod grpc_web;
+#[cfg(feature = "log")]
+pub mod log;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod paginate;
 pub mod request;
 pub mod response;
+pub(crate) mod shutdown;
+pub mod upload;
+#[cfg(feature = "webdav")]
+pub mod webdav;
 
+pub use builder::ClientBuilder;
+pub use errors::Error;
 pub use request::Request;
 pub use response::Response;
 
+type HyperClient<C> = hyper::Client<stats::Instrumented<<C as config::Config>::Connector>, request::TransportBody>;
+
+///Hyper's clients per route, each with its own connection pool.
+struct Pools<C: config::Config> {
+    direct: HyperClient<C>,
+    //Clients of proxies in order of creation, limited by `Config::max_proxy_pools`
+    proxied: Vec<(proxy::ProxyUri, HyperClient<C>)>,
+}
+
 ///HTTP Client
+///
+///Client is cheap to clone, as clones share connection pools, statistics and state of shutdown.
+///Hence it can be stored in application's state and cloned into tasks.
+///Note that shutting down any clone shuts down all of them.
 pub struct Client<C=config::DefaultCfg> where C: config::Config + 'static {
-    inner: hyper::Client<C::Connector>,
+    inner: Arc<std::sync::RwLock<Option<Pools<C>>>>,
+    connector: C::Connector,
+    timeout: core::time::Duration,
+    max_redirects: usize,
+    redirect_policy: config::RedirectPolicy,
+    proxy: Option<Arc<proxy::Proxy>>,
+    lifecycle: Arc<shutdown::Lifecycle>,
+    stats: Arc<stats::Registry>,
+    alt_svc: Arc<alt_svc::Cache>,
+    redirects: Arc<redirects::Cache>,
+    breakers: Arc<breaker::Breakers>,
+    authenticator: Option<Arc<dyn auth::Authenticator>>,
+    default_headers: Arc<http::HeaderMap>,
+    base_url: Option<http::Uri>,
+    default_query: Arc<Vec<(String, String)>>,
+    #[cfg(feature = "log")]
+    logger: Option<Arc<self::log::Logger>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<self::metrics::Metrics>,
     _config: PhantomData<C>
 }
 
+impl<C: config::Config> Clone for Client<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            connector: self.connector.clone(),
+            timeout: self.timeout,
+            max_redirects: self.max_redirects,
+            redirect_policy: self.redirect_policy,
+            proxy: self.proxy.clone(),
+            lifecycle: self.lifecycle.clone(),
+            stats: self.stats.clone(),
+            alt_svc: self.alt_svc.clone(),
+            redirects: self.redirects.clone(),
+            breakers: self.breakers.clone(),
+            authenticator: self.authenticator.clone(),
+            default_headers: self.default_headers.clone(),
+            base_url: self.base_url.clone(),
+            default_query: self.default_query.clone(),
+            #[cfg(feature = "log")]
+            logger: self.logger.clone(),
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics.clone(),
+            _config: PhantomData
+        }
+    }
+}
+
 impl Default for Client {
     ///Creates Client with default configuration.
     fn default() -> Self {
@@ -82,14 +200,195 @@ impl Default for Client {
     }
 }
 
+impl Client {
+    #[inline]
+    ///Creates builder of client with [default](config/struct.DefaultCfg.html) config.
+    ///
+    ///Use [ClientBuilder::new](builder/struct.ClientBuilder.html#method.new) for custom config.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+}
+
 impl<C: config::Config> fmt::Debug for Client<C> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Yukikaze {{ HyperClient={:?} }}", self.inner)
+        match self.inner.read() {
+            Ok(inner) => write!(f, "Yukikaze {{ HyperClient={:?} }}", inner.as_ref().map(|pools| &pools.direct)),
+            Err(_) => f.write_str("Yukikaze { HyperClient=<poisoned> }"),
+        }
     }
 }
 
 ///Alias to result of sending request.
-pub type RequestResult = Result<response::Response, hyper::Error>;
+pub type RequestResult = Result<response::Response, Error>;
+
+///Headers, that must not be sent to different origin on redirect.
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie", "cookie2", "www-authenticate"];
+
+fn default_port(scheme: Option<&http::uri::Scheme>) -> Option<u16> {
+    match scheme.map(http::uri::Scheme::as_str) {
+        Some("http") | Some("ws") => Some(80),
+        Some("https") | Some("wss") => Some(443),
+        _ => None,
+    }
+}
+
+///Returns whether url-encoded `query` contains parameter `key`.
+fn has_query_key(query: &str, key: &str) -> bool {
+    query.split('&').filter_map(|pair| pair.split('=').next()).any(|name| match percent_encoding::percent_decode_str(&name.replace('+', " ")).decode_utf8() {
+        Ok(name) => name == key,
+        Err(_) => false,
+    })
+}
+
+///Compares origins (scheme, host and port) of URIs.
+fn is_same_origin(left: &hyper::Uri, right: &hyper::Uri) -> bool {
+    let left_port = left.port_u16().or_else(|| default_port(left.scheme()));
+    let right_port = right.port_u16().or_else(|| default_port(right.scheme()));
+
+    left.scheme() == right.scheme() && left_port == right_port && match (left.host(), right.host()) {
+        (Some(left), Some(right)) => left.eq_ignore_ascii_case(right),
+        _ => false,
+    }
+}
+
+///Verifies that response's headers are within limits of config.
+///Accounts response's connection, which is re-used, if it has been used before.
+fn on_connection_used(response: response::Response) -> response::Response {
+    if let Some(info) = response.extensions().get::<stats::ConnectionInfo>() {
+        info.on_response();
+    }
+
+    response
+}
+
+fn check_headers<C: config::Config>(response: response::Response) -> RequestResult {
+    let max_count = C::max_response_headers();
+    let max_size = C::max_header_size();
+
+    if max_count.is_none() && max_size.is_none() {
+        return Ok(response);
+    }
+
+    let count = response.headers().len();
+    let size = response.headers().iter().map(|(name, value)| name.as_str().len() + value.len() + 4).sum();
+
+    match max_count.map(|max| count > max).unwrap_or(false) || max_size.map(|max| size > max).unwrap_or(false) {
+        true => Err(Error::HeaderLimit { count, size }),
+        false => Ok(response),
+    }
+}
+
+#[cfg(not(feature = "log"))]
+///Placeholder for log entry, when logging is disabled.
+struct LogEntry;
+
+#[cfg(not(feature = "log"))]
+impl LogEntry {
+    #[inline(always)]
+    fn finish(self, _: &RequestResult) {
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+///Placeholder for metrics entry, when metrics are disabled.
+struct MetricsEntry;
+
+#[cfg(not(feature = "metrics"))]
+impl MetricsEntry {
+    #[inline(always)]
+    fn finish(self, _: &RequestResult) {
+    }
+}
+
+///Request's state, that is transferred into response.
+struct ResponseCtx {
+    in_flight: Option<shutdown::InFlight>,
+    context: Option<request::Context>,
+    no_decompress: bool,
+    body_limit: Option<usize>,
+    buffer_cap: usize,
+    max_ratio: Option<u32>,
+    verify_len: bool,
+    start: Option<std::time::Instant>,
+    size: extractor::BodySize,
+    cookie_encoding: header::CookieEncoding,
+    uri: hyper::Uri,
+    original_uri: hyper::Uri,
+    fragment: Option<request::Fragment>,
+    #[cfg(feature = "carry_extensions")]
+    extensions: http::Extensions,
+}
+
+impl ResponseCtx {
+    fn new<C: config::Config>(req: &mut request::Request, in_flight: shutdown::InFlight, size: extractor::BodySize) -> Self {
+        size.set_sent(req.body.as_ref().and_then(|body| body.len()).unwrap_or(0));
+
+        Self {
+            in_flight: Some(in_flight),
+            context: req.extensions_mut().remove::<request::Context>(),
+            no_decompress: req.extensions().get::<request::NoDecompress>().is_some(),
+            body_limit: C::default_body_limit(),
+            buffer_cap: C::body_buffer_cap(),
+            max_ratio: C::max_decompression_ratio(),
+            verify_len: C::verify_content_length() && req.method() != hyper::Method::HEAD,
+            start: match C::collect_timings() {
+                true => Some(std::time::Instant::now()),
+                false => None,
+            },
+            size,
+            cookie_encoding: C::cookie_encoding(),
+            uri: req.uri().clone(),
+            original_uri: req.uri().clone(),
+            fragment: req.extensions().get::<request::Fragment>().cloned(),
+            #[cfg(feature = "carry_extensions")]
+            extensions: req.extract_extensions(),
+        }
+    }
+
+    fn finish(&mut self, response: response::Response) -> response::Response {
+        let timings = match (self.start, response.extensions().get::<stats::ConnectionTimings>()) {
+            (Some(start), Some(state)) => Some(timings::Timings::new(start, state.0.clone())),
+            _ => None,
+        };
+
+        #[cfg(feature = "carry_extensions")]
+        let mut response = response.replace_extensions(&mut self.extensions);
+        #[cfg(not(feature = "carry_extensions"))]
+        let mut response = response;
+
+        if self.no_decompress {
+            response.extensions_mut().insert(request::NoDecompress);
+        }
+        if let Some(limit) = self.body_limit {
+            response.extensions_mut().insert(response::BodyLimit(limit));
+        }
+        response.extensions_mut().insert(response::BufferCap(self.buffer_cap));
+        response.extensions_mut().insert(response::MaxDecompressionRatio(self.max_ratio));
+        if self.verify_len {
+            response.extensions_mut().insert(response::VerifyContentLength);
+        }
+        if let Some(timings) = timings {
+            response.extensions_mut().insert(timings);
+        }
+        response.extensions_mut().insert(self.size.clone());
+        response.extensions_mut().insert(self.cookie_encoding);
+        response.extensions_mut().insert(response::RequestUri(self.uri.clone()));
+        response.extensions_mut().insert(response::OriginalUri(self.original_uri.clone()));
+        if let Some(fragment) = self.fragment.take() {
+            response.extensions_mut().insert(fragment);
+        }
+        if let Some(context) = self.context.take() {
+            response.extensions_mut().insert(context);
+        }
+        //Response is considered in flight until its body is dropped
+        if let Some(in_flight) = self.in_flight.take() {
+            response.extensions_mut().insert(in_flight);
+        }
+
+        response
+    }
+}
 
 use tokio::io::{AsyncRead, AsyncWrite};
 
@@ -100,49 +399,909 @@ impl<C: config::Config> Client<C> where <C::Connector as hyper::service::Service
     ///Creates new instance of client with specified configuration.
     ///
     ///Use `Default` if you'd like to use [default](config/struct.DefaultCfg.html) config.
+    ///Use [ClientBuilder](builder/struct.ClientBuilder.html) to override config at runtime.
     pub fn new() -> Client<C> {
-        let inner = C::config_hyper(&mut hyper::Client::builder()).build(C::Connector::default());
+        builder::ClientBuilder::new().build()
+    }
 
-        Self {
-            inner,
-            _config: PhantomData
+    #[inline]
+    ///Returns timeout of requests.
+    ///
+    ///See [Config::timeout](config/trait.Config.html#method.timeout).
+    pub fn timeout(&self) -> core::time::Duration {
+        self.timeout
+    }
+
+    #[inline]
+    ///Returns maximum number of redirects to follow.
+    ///
+    ///See [Config::max_redirect_num](config/trait.Config.html#method.max_redirect_num).
+    pub fn max_redirects(&self) -> usize {
+        self.max_redirects
+    }
+
+    ///Sets headers, that are added to each request, unless request already has them.
+    ///
+    ///These headers are applied before [Config::default_headers](config/trait.Config.html#method.default_headers),
+    ///which allows to specify headers, that can be known only at runtime (e.g. API key).
+    pub fn with_default_headers(mut self, headers: http::HeaderMap) -> Self {
+        self.default_headers = Arc::new(headers);
+        self
+    }
+
+    #[inline]
+    ///Returns headers, that are added to each request.
+    pub fn default_headers(&self) -> &http::HeaderMap {
+        &self.default_headers
+    }
+
+    #[inline]
+    ///Returns base URL, which relative URIs are resolved against.
+    ///
+    ///See [Config::base_url](config/trait.Config.html#method.base_url).
+    pub fn base_url(&self) -> Option<&http::Uri> {
+        self.base_url.as_ref()
+    }
+
+    #[cfg(feature = "log")]
+    ///Enables logging of requests, using provided logger.
+    ///
+    ///Available only with feature `log`
+    pub fn with_logger(mut self, logger: self::log::Logger) -> Self {
+        self.logger = Some(Arc::new(logger));
+        self
+    }
+
+    #[cfg(feature = "log")]
+    #[inline(always)]
+    fn log_start(&self, request: &request::Request) -> Option<self::log::Entry> {
+        self.logger.as_ref().map(|logger| logger.start(request))
+    }
+
+    #[cfg(not(feature = "log"))]
+    #[inline(always)]
+    fn log_start(&self, _: &request::Request) -> Option<LogEntry> {
+        None
+    }
+
+    #[cfg(feature = "metrics")]
+    ///Enables collection of request metrics.
+    ///
+    ///Available only with feature `metrics`
+    pub fn with_metrics(mut self, metrics: self::metrics::Metrics) -> Self {
+        metrics.attach(&self.stats);
+        self.metrics = Some(metrics);
+        self
+    }
+
+    #[cfg(feature = "metrics")]
+    #[inline(always)]
+    fn metrics_start(&self, request: &request::Request) -> Option<self::metrics::Entry> {
+        self.metrics.as_ref().map(|metrics| metrics.start(request))
+    }
+
+    #[cfg(feature = "metrics")]
+    #[inline]
+    fn body_size(&self) -> extractor::BodySize {
+        self.metrics.as_ref().map_or_else(extractor::BodySize::new, self::metrics::Metrics::body_size)
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline(always)]
+    fn body_size(&self) -> extractor::BodySize {
+        extractor::BodySize::new()
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline(always)]
+    fn metrics_start(&self, _: &request::Request) -> Option<MetricsEntry> {
+        None
+    }
+
+    #[inline]
+    ///Returns statistics of connection pool.
+    ///
+    ///Allows to verify that connections are re-used.
+    ///Statistics are collected only if [Config::instrument_connections](config/trait.Config.html#method.instrument_connections) is enabled.
+    pub fn pool_stats(&self) -> stats::PoolStats {
+        self.stats.stats()
+    }
+
+    #[inline]
+    ///Returns whether client is shut down.
+    pub fn is_shutdown(&self) -> bool {
+        self.lifecycle.is_closed()
+    }
+
+    #[inline]
+    ///Returns number of requests in flight.
+    ///
+    ///Request is in flight until its response, including body, is dropped.
+    pub fn in_flight(&self) -> usize {
+        self.lifecycle.in_flight()
+    }
+
+    ///Shuts down client.
+    ///
+    ///Client stops accepting new requests, failing them with [Error::Shutdown](errors/enum.Error.html#variant.Shutdown),
+    ///and waits for in-flight requests to complete, up to `deadline`.
+    ///Afterwards connection pool is torn down, closing idle connections.
+    ///
+    ///Returns whether all in-flight requests completed before deadline.
+    pub async fn shutdown(&self, deadline: std::time::Instant) -> bool {
+        use async_timer::Oneshot;
+
+        const POLL_INTERVAL: core::time::Duration = core::time::Duration::from_millis(10);
+
+        self.lifecycle.close();
+
+        let mut now = std::time::Instant::now();
+        while self.lifecycle.in_flight() > 0 && now < deadline {
+            let wait = core::cmp::min(deadline - now, POLL_INTERVAL);
+            matsu!(C::Timer::new(wait));
+            now = std::time::Instant::now();
+        }
+
+        let inner = match self.inner.write() {
+            Ok(mut inner) => inner.take(),
+            Err(error) => error.into_inner().take(),
+        };
+        drop(inner);
+
+        self.lifecycle.in_flight() == 0
+    }
+
+    fn build_hyper(connector: &C::Connector, stats: &Arc<stats::Registry>, proxy: Option<proxy::ProxyUri>) -> HyperClient<C> {
+        let is_proxied = proxy.is_some();
+        let connector = connector.clone().with_proxy(proxy);
+        let events = match C::instrument_connections() {
+            true => C::on_connection_event,
+            false => events::ignore,
+        };
+        let connector = stats::Instrumented::new(connector, stats.clone(), C::collect_timings(), is_proxied, events);
+        let mut builder = hyper::Client::builder();
+        if let Some(size) = C::max_header_size() {
+            //hyper requires at least 8kb buffer
+            builder.http1_max_buf_size(core::cmp::max(size, 8192));
+        }
+        C::http2().apply(&mut builder);
+
+        C::config_hyper(&mut builder).build(connector)
+    }
+
+    ///Selects proxy to reach `uri`, if any.
+    fn route(&self, uri: &hyper::Uri, via: Option<&request::ViaProxy>) -> Option<proxy::ProxyUri> {
+        let config = self.proxy.as_ref();
+
+        let proxy = match via {
+            Some(via) => match config {
+                Some(config) => config.select(uri, via.0.as_ref()),
+                None => via.0.as_ref(),
+            },
+            None => config?.route(uri),
+        };
+
+        proxy.cloned()
+    }
+
+    ///Sends request by hyper's client, that reaches its URI.
+    fn dispatch(&self, mut req: request::Request, via: Option<&request::ViaProxy>) -> Result<hyper::client::ResponseFuture, Error> {
+        let proxy = match self.route(req.uri(), via) {
+            Some(proxy) => proxy,
+            None => return match self.inner.read().unwrap_or_else(|error| error.into_inner()).as_ref() {
+                Some(pools) => Ok(pools.direct.request(req.into())),
+                None => Err(Error::Shutdown),
+            },
+        };
+
+        //Plain HTTP request is sent to proxy itself, while tunneled one must not reveal credentials to target
+        if req.uri().scheme_str() != Some("https") {
+            if let Some(value) = proxy.basic_auth().and_then(|value| http::HeaderValue::from_str(&value).ok()) {
+                req.headers_mut().entry(header::PROXY_AUTHORIZATION).or_insert(value);
+            }
+        }
+
+        match self.inner.read().unwrap_or_else(|error| error.into_inner()).as_ref() {
+            None => return Err(Error::Shutdown),
+            Some(pools) => if let Some((_, inner)) = pools.proxied.iter().find(|(uri, _)| *uri == proxy) {
+                return Ok(inner.request(req.into()));
+            },
+        }
+
+        //Client for new proxy is created under write lock
+
+        let mut pools = self.inner.write().unwrap_or_else(|error| error.into_inner());
+        let pools = match pools.as_mut() {
+            Some(pools) => pools,
+            None => return Err(Error::Shutdown),
+        };
+
+        let idx = match pools.proxied.iter().position(|(uri, _)| *uri == proxy) {
+            Some(idx) => idx,
+            None => {
+                if pools.proxied.len() >= core::cmp::max(C::max_proxy_pools(), 1) {
+                    pools.proxied.remove(0);
+                }
+                let inner = Self::build_hyper(&self.connector, &self.stats, Some(proxy.clone()));
+                pools.proxied.push((proxy, inner));
+                pools.proxied.len() - 1
+            }
+        };
+
+        Ok(pools.proxied[idx].1.request(req.into()))
+    }
+
+    ///Awaits `ongoing`, limited by [timeout](#method.timeout), returning `None` if it expires.
+    pub(crate) async fn with_timeout<F: Future>(&self, ongoing: F) -> Option<F::Output> {
+        match self.timeout {
+            timeout if timeout.as_secs() == 0 && timeout.subsec_nanos() == 0 => Some(matsu!(ongoing)),
+            timeout => {
+                let job = unsafe { async_timer::Timed::<_, C::Timer>::new_unchecked(ongoing, timeout) };
+                matsu!(job).ok()
+            }
+        }
+    }
+
+    ///Registers new request, unless client is shut down.
+    fn begin(&self) -> Result<shutdown::InFlight, Error> {
+        match self.lifecycle.start() {
+            Some(in_flight) => Ok(in_flight),
+            None => Err(Error::Shutdown),
+        }
+    }
+
+    #[inline]
+    ///Returns alternative services, advertised via `Alt-Svc` header by contacted origins.
+    ///
+    ///Origins are identified by `<scheme>://<authority>` and only fresh services are returned,
+    ///with `max_age` being the remaining time.
+    pub fn alt_services(&self) -> std::collections::HashMap<String, Vec<header::AltService>> {
+        self.alt_svc.services()
+    }
+
+    #[inline]
+    ///Returns permanent redirects, remembered by [redirect_request](#method.redirect_request).
+    ///
+    ///Redirects are identified by original URI and only fresh ones are returned.
+    ///It is always empty, unless [Config::redirect_cache](config/trait.Config.html#method.redirect_cache) is enabled.
+    pub fn permanent_redirects(&self) -> std::collections::HashMap<String, hyper::Uri> {
+        self.redirects.redirects()
+    }
+
+    #[inline]
+    ///Forgets all remembered permanent redirects.
+    pub fn clear_permanent_redirects(&self) {
+        self.redirects.clear()
+    }
+
+    #[inline]
+    ///Returns states of [circuit breakers](breaker/index.html) per host.
+    ///
+    ///Hosts are identified by `<scheme>://<authority>`.
+    ///It is always empty, unless [Config::circuit_breaker](config/trait.Config.html#method.circuit_breaker) is enabled.
+    pub fn circuit_states(&self) -> std::collections::HashMap<String, breaker::State> {
+        self.breakers.states()
+    }
+
+    ///Downloads file into `path`, unless it is not modified since last download.
+    ///
+    ///Validators (`ETag` and `Last-Modified`) of downloaded file are stored in `meta` file,
+    ///and sent as conditional headers on the next download, as long as file exists.
+    ///Body is written into temporary file `<path>.part`, which replaces `path` only on success.
+    ///
+    ///Redirects are followed, and download is limited by [Client::timeout](#method.timeout),
+    ///which includes reading of body.
+    ///
+    ///Returns whether file has been changed.
+    pub async fn download_if_changed<U: AsRef<str>>(&self, uri: U, path: &Path, meta: &Path) -> Result<bool, download::DownloadError> {
+        let req = download::create_request(uri.as_ref(), path, meta)?;
+
+        let ongoing = async move {
+            let response = matsu!(self.redirect_request(req))?;
+            matsu!(download::store(response, path, meta))
+        };
+
+        match matsu!(self.with_timeout(ongoing)) {
+            Some(result) => result,
+            None => {
+                let _ = std::fs::remove_file(crate::utils::part_path(path));
+                Err(errors::CallError::Timeout.into())
+            },
+        }
+    }
+
+    ///Fetches body of `uri`, as long as it doesn't exceed `max_size`.
+    ///
+    ///`HEAD` request is sent first to check `Content-Length`, aborting early if body is too large.
+    ///If server doesn't allow `HEAD`, it is skipped.
+    ///Then body is fetched via `GET` and read until `max_size`, which is applied to decompressed body.
+    ///
+    ///Redirects are followed, and fetch is limited by [Client::timeout](#method.timeout),
+    ///which includes both requests and reading of body.
+    pub async fn fetch_limited<U: AsRef<str>>(&self, uri: U, max_size: usize) -> Result<(response::Response, bytes::Bytes), errors::FetchError> {
+        let head = request::Request::head(uri.as_ref())?.empty();
+        let get = request::Request::get(uri.as_ref())?.empty();
+
+        let ongoing = async move {
+            let response = matsu!(self.redirect_request(head))?;
+            //Server may not allow HEAD, in which case we can only rely on GET
+            if let (true, Some(len)) = (response.is_success(), response.content_len()) {
+                if len > max_size {
+                    return Err(errors::FetchError::TooLarge(Some(len)));
+                }
+            }
+
+            let mut response = matsu!(self.redirect_request(get))?.error_for_status()?;
+            match response.content_len() {
+                Some(len) if len > max_size => Err(errors::FetchError::TooLarge(Some(len))),
+                _ => {
+                    let body = matsu!(response.body_limited(Some(max_size)))?;
+                    Ok((response, body))
+                }
+            }
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::CallError::Timeout.into()))
+    }
+
+    ///Creates paginator, starting with `initial` request.
+    ///
+    ///After each page is received, `next_fn` is invoked to create request for the next page
+    ///(e.g. from `Link` header or cursor in body), returning `None` when there are no more pages.
+    pub fn paginate<F: FnMut(&response::Response) -> Option<request::Request>>(&self, initial: request::Request, next_fn: F) -> paginate::Paginator<'_, C, F> {
+        paginate::Paginator::new(self, initial, next_fn)
+    }
+
+    ///Establishes tunnel to `authority` (i.e. `host:port`) by issuing CONNECT request.
+    ///
+    ///Request is sent using client's connector and default headers, limited by [Client::timeout](#method.timeout).
+    ///On success returns raw bidirectional stream, on top of which any protocol can be used.
+    ///If server responds with non-successful status, then [CallError::Status](errors/enum.CallError.html#variant.Status) is returned.
+    pub async fn connect_tunnel<U: AsRef<str>>(&self, authority: U) -> Result<crate::upgrade::Upgraded, errors::TunnelError> {
+        let req = request::Request::connect(authority)?.no_decompress().empty();
+
+        let ongoing = async move {
+            let response = matsu!(self.request(req))?;
+            if !response.is_success() {
+                let (parts, _) = response.into_inner().into_parts();
+                return Err(response::errors::StatusError {
+                    status: parts.status,
+                    headers: Box::new(parts.headers),
+                    body: None,
+                }.into());
+            }
+
+            let mut response = response.into_inner();
+            //Stream keeps request in flight, instead of response
+            let in_flight = response.extensions_mut().remove::<shutdown::InFlight>();
+            matsu!(response.into_body().on_upgrade()).map(|upgraded| crate::upgrade::Upgraded::new(upgraded, in_flight)).map_err(errors::TunnelError::Upgrade)
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::CallError::Timeout.into()))
+    }
+
+    ///Opens up to `num` connections to `authority` and keeps them in pool, ready for subsequent requests.
+    ///
+    ///`authority` is either `host[:port]`, which implies `https`, or URL, of which only scheme and authority are used.
+    ///Connections are opened by concurrent `HEAD /` requests, so that handshakes, including TLS, are complete
+    ///before burst of traffic. Already idle connections are re-used, hence pool ends up with at least `num` connections,
+    ///unless host limits them, or it uses HTTP/2, which multiplexes requests over single connection.
+    ///
+    ///Limited by [Client::timeout](#method.timeout).
+    ///Returns number of successful requests, failing only if none succeeds.
+    pub async fn prewarm<U: AsRef<str>>(&self, authority: U, num: usize) -> Result<usize, errors::PrewarmError> {
+        use core::task::Poll;
+
+        let authority = authority.as_ref();
+        let uri = match authority.contains("://") {
+            true => authority.parse::<hyper::Uri>()?,
+            false => format!("https://{}", authority).parse::<hyper::Uri>()?,
+        };
+        let uri = match (uri.scheme_str(), uri.authority()) {
+            (Some(scheme), Some(authority)) => format!("{}://{}/", scheme, authority),
+            _ => format!("https://{}/", authority),
+        };
+
+        let mut ongoing = Vec::with_capacity(num);
+        for _ in 0..num {
+            let req = request::Request::head(&uri)?.empty();
+            ongoing.push(Box::pin(self.request(req)));
         }
+
+        let ongoing = async move {
+            let mut warmed = 0;
+            let mut last_error = None;
+
+            matsu!(core::future::poll_fn(|ctx| {
+                let mut idx = 0;
+                while idx < ongoing.len() {
+                    match ongoing[idx].as_mut().poll(ctx) {
+                        Poll::Ready(result) => {
+                            drop(ongoing.swap_remove(idx));
+                            match result {
+                                Ok(_) => warmed += 1,
+                                Err(error) => last_error = Some(error),
+                            }
+                        },
+                        Poll::Pending => idx += 1,
+                    }
+                }
+
+                match ongoing.is_empty() {
+                    true => Poll::Ready(()),
+                    false => Poll::Pending,
+                }
+            }));
+
+            match (warmed, last_error) {
+                (0, Some(error)) => Err(errors::PrewarmError::Request(error)),
+                (warmed, _) => Ok(warmed),
+            }
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::PrewarmError::Timeout))
+    }
+
+    ///Invokes API `endpoint`, returning its deserialized response.
+    ///
+    ///Request is created by [create_request](endpoint/fn.create_request.html), redirects are followed
+    ///and call is limited by [Client::timeout](#method.timeout), which includes reading of body.
+    ///Body is read within [Response::body_limit](response/struct.Response.html#method.body_limit).
+    ///
+    ///Unsuccessful status results in [CallError::Status](errors/enum.CallError.html#variant.Status),
+    ///which contains beginning of response's body.
+    pub async fn call<E: endpoint::Endpoint>(&self, endpoint: &E) -> Result<E::Response, errors::EndpointError> {
+        const ERROR_BODY_LIMIT: usize = 1024;
+
+        let req = endpoint::create_request(endpoint)?;
+
+        let ongoing = async move {
+            let response = matsu!(self.redirect_request(req))?;
+            let mut response = matsu!(response.error_for_status_body(ERROR_BODY_LIMIT))?;
+            let body = matsu!(response.body())?;
+
+            match body.is_empty() {
+                true => extractor::json_from_slice(b"null"),
+                false => extractor::json_from_slice(&body),
+            }.map_err(errors::EndpointError::Body)
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::CallError::Timeout.into()))
+    }
+
+    ///Sends request, following redirects, and fails on unsuccessful status.
+    ///
+    ///If `read_json` is true, body is deserialized as JSON, otherwise response is returned as it is.
+    async fn simple<T: serde::de::DeserializeOwned>(&self, req: request::Request, read_json: bool) -> Result<(response::Response, Option<T>), errors::SimpleError> {
+        const ERROR_BODY_LIMIT: usize = 1024;
+
+        let ongoing = async move {
+            let response = matsu!(self.redirect_request(req))?;
+            let mut response = matsu!(response.error_for_status_body(ERROR_BODY_LIMIT))?;
+            if !read_json {
+                return Ok((response, None));
+            }
+
+            let body = matsu!(response.body())?;
+            let json = match body.is_empty() {
+                true => extractor::json_from_slice(b"null"),
+                false => extractor::json_from_slice(&body),
+            }?;
+            Ok((response, Some(json)))
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::CallError::Timeout.into()))
+    }
+
+    ///Sends `GET` request to `uri`, following redirects.
+    ///
+    ///Unsuccessful status results in [CallError::Status](errors/enum.CallError.html#variant.Status),
+    ///which contains beginning of response's body.
+    ///Request is limited by [Client::timeout](#method.timeout), which doesn't include reading of body.
+    pub async fn get<U: AsRef<str>>(&self, uri: U) -> Result<response::Response, errors::SimpleError> {
+        let req = request::Request::get(uri)?.empty();
+        matsu!(self.simple::<()>(req, false)).map(|(response, _)| response)
+    }
+
+    ///Sends `GET` request to `uri`, following redirects, and deserializes JSON body.
+    ///
+    ///Unsuccessful status results in [CallError::Status](errors/enum.CallError.html#variant.Status).
+    ///Request is limited by [Client::timeout](#method.timeout), which includes reading of body.
+    ///Body is read within [Response::body_limit](response/struct.Response.html#method.body_limit).
+    pub async fn get_json<U: AsRef<str>, T: serde::de::DeserializeOwned>(&self, uri: U) -> Result<T, errors::SimpleError> {
+        let req = request::Request::get(uri)?.empty();
+        matsu!(self.simple(req, true)).map(|(_, json)| json.unreach_none())
+    }
+
+    ///Sends `POST` request to `uri` with JSON `body`, following redirects, and deserializes JSON body of response.
+    ///
+    ///Unsuccessful status results in [CallError::Status](errors/enum.CallError.html#variant.Status).
+    ///Request is limited by [Client::timeout](#method.timeout), which includes reading of body.
+    ///Body is read within [Response::body_limit](response/struct.Response.html#method.body_limit).
+    pub async fn post_json<U: AsRef<str>, B: serde::Serialize, T: serde::de::DeserializeOwned>(&self, uri: U, body: &B) -> Result<T, errors::SimpleError> {
+        let req = request::Request::post(uri)?.json(body).map_err(errors::SimpleError::Json)?;
+        matsu!(self.simple(req, true)).map(|(_, json)| json.unreach_none())
     }
 
-    fn apply_headers(request: &mut request::Request) {
+    ///Sends `PUT` request to `uri` with raw `body`, following redirects.
+    ///
+    ///Unsuccessful status results in [CallError::Status](errors/enum.CallError.html#variant.Status),
+    ///which contains beginning of response's body.
+    ///Request is limited by [Client::timeout](#method.timeout), which doesn't include reading of body.
+    pub async fn put_bytes<U: AsRef<str>, B: Into<bytes::Bytes>>(&self, uri: U, body: B) -> Result<response::Response, errors::SimpleError> {
+        let req = request::Request::put(uri)?.body(Some(body));
+        matsu!(self.simple::<()>(req, false)).map(|(response, _)| response)
+    }
+
+    ///Sends request, following redirects, and deserializes JSON body as `T` on success, or as `E` otherwise.
+    ///
+    ///Unsuccessful status results in [ApiError::Api](errors/enum.ApiError.html#variant.Api) with deserialized error,
+    ///or [CallError::Status](errors/enum.CallError.html#variant.Status), if body is not valid `E`.
+    ///Empty body is deserialized as `null`.
+    ///
+    ///Request is limited by [Client::timeout](#method.timeout), which includes reading of body.
+    ///Body is read within [Response::body_limit](response/struct.Response.html#method.body_limit).
+    pub async fn send_expect<T: serde::de::DeserializeOwned, E: serde::de::DeserializeOwned>(&self, req: request::Request) -> Result<T, errors::ApiError<E>> {
+        const ERROR_BODY_LIMIT: usize = 1024;
+
+        let ongoing = async move {
+            let mut response = matsu!(self.redirect_request(req))?;
+            let body = matsu!(response.body())?;
+            let json = match body.is_empty() {
+                true => &b"null"[..],
+                false => &body[..],
+            };
+
+            if response.is_success() {
+                return extractor::json_from_slice(json).map_err(errors::ApiError::Body);
+            }
+
+            match extractor::json_from_slice(json) {
+                Ok(error) => Err(errors::ApiError::Api {
+                    status: response.status(),
+                    headers: response.headers().clone(),
+                    error,
+                }),
+                Err(_) => Err(errors::ApiError::Call(response::errors::StatusError {
+                    status: response.status(),
+                    headers: Box::new(response.headers().clone()),
+                    body: Some(body.slice(..core::cmp::min(body.len(), ERROR_BODY_LIMIT))),
+                }.into())),
+            }
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::CallError::Timeout.into()))
+    }
+
+    ///Sends CORS preflight request, asking whether `method` with request `headers` is allowed on `uri`.
+    ///
+    ///Issues OPTIONS request with `Access-Control-Request-Method` and `Access-Control-Request-Headers`,
+    ///limited by [Client::timeout](#method.timeout), without following redirects.
+    ///`Origin` is expected to be set by [Config::default_headers](config/trait.Config.html#method.default_headers).
+    ///
+    ///Response is not checked for successful status, use [Preflight::is_success](cors/struct.Preflight.html#method.is_success).
+    pub async fn preflight<U: AsRef<str>>(&self, uri: U, method: http::Method, headers: &[header::HeaderName]) -> Result<cors::Preflight, errors::PreflightError> {
+        let req = request::Request::options(uri)?.cors_preflight(&method, headers).empty();
+
+        let ongoing = async move {
+            let response = matsu!(self.request(req))?;
+            Ok(cors::Preflight::new(response.status(), response.headers()))
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::PreflightError::Timeout))
+    }
+
+    ///Probes operations, supported by server for `uri`, by `OPTIONS` request.
+    ///
+    ///Parses `Allow`, `Accept-Ranges`, `Accept-Patch` and `DAV` headers of response.
+    ///Limited by [Client::timeout](#method.timeout), without following redirects.
+    ///Use `*` as path of `uri` to ask about server in general.
+    ///
+    ///Response is not checked for successful status, use [Capabilities::is_success](capabilities/struct.Capabilities.html#method.is_success).
+    pub async fn capabilities<U: AsRef<str>>(&self, uri: U) -> Result<capabilities::Capabilities, errors::CapabilitiesError> {
+        let req = request::Request::options(uri)?.empty();
+
+        let ongoing = async move {
+            let response = matsu!(self.request(req))?;
+            Ok(capabilities::Capabilities::new(response.status(), response.headers()))
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::CapabilitiesError::Timeout))
+    }
+
+    ///Performs unary call of gRPC-web method at `uri` with protobuf encoded message `payload`, returning encoded reply.
+    ///
+    ///Call is limited by [Client::timeout](#method.timeout), which includes reading of body,
+    ///and body is read within [Response::body_limit](response/struct.Response.html#method.body_limit).
+    ///
+    ///Status is taken from trailers or, if body has no trailers, from headers.
+    ///Unsuccessful status results in [GrpcWebError::Grpc](errors/enum.GrpcWebError.html#variant.Grpc).
+    pub async fn grpc_web_unary<U: AsRef<str>>(&self, uri: U, payload: &[u8]) -> Result<bytes::Bytes, errors::GrpcWebError> {
+        const ERROR_BODY_LIMIT: usize = 1024;
+
+        let req = grpc_web::request(uri, payload)?;
+
+        let ongoing = async move {
+            let response = matsu!(self.request(req))?;
+            let mut response = matsu!(response.error_for_status_body(ERROR_BODY_LIMIT))?;
+            let body = matsu!(response.body())?;
+            let mut reply = grpc_web::Reply::parse(body)?;
+
+            let status = reply.status().or_else(|| grpc_web::Status::from_headers(response.headers()));
+            match status {
+                Some(status) if status.is_ok() => match reply.messages.len() {
+                    1 => Ok(reply.messages.remove(0)),
+                    len => Err(errors::GrpcWebError::MessageCount(len)),
+                },
+                Some(status) => Err(errors::GrpcWebError::Grpc(status)),
+                None => Err(errors::GrpcWebError::MissingStatus),
+            }
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::CallError::Timeout.into()))
+    }
+
+    #[cfg(feature = "websocket")]
+    ///Opens websocket connection to `uri`.
+    ///
+    ///Performs whole handshake: prepares upgrade request with optional `opts`, sends it,
+    ///verifies server's response and finishes upgrade, limited by [Client::timeout](#method.timeout).
+    ///
+    ///Returns handshake's response, negotiated parameters and upgraded stream.
+    pub async fn websocket<U: AsRef<str>>(&self, uri: U, opts: Option<crate::upgrade::WebsocketUpgradeOpts>) -> Result<(response::Response, crate::upgrade::WebsocketHandshake, crate::upgrade::Upgraded), errors::WebsocketError> {
+        let websocket = match opts {
+            Some(opts) => crate::upgrade::WebsocketUpgrade::with_opts(opts),
+            None => crate::upgrade::WebsocketUpgrade::new(),
+        };
+        let req = request::Request::get(uri)?.upgrade(&websocket);
+
+        let ongoing = async move {
+            let response = matsu!(self.request(req))?;
+            matsu!(response.upgrade(&websocket))?.map_err(errors::WebsocketError::Upgrade)
+        };
+
+        matsu!(self.with_timeout(ongoing)).unwrap_or(Err(errors::CallError::Timeout.into()))
+    }
+
+    ///Resolves relative URI of request against base URL, adding default query.
+    fn resolve_uri(&self, request: &mut request::Request) {
+        let uri = request.uri();
+        if uri.scheme().is_some() || uri.authority().is_some() || (self.base_url.is_none() && self.default_query.is_empty()) {
+            return;
+        }
+
+        let query = uri.query().unwrap_or("");
+        let extra = self.default_query.iter().filter(|(key, _)| !has_query_key(query, key)).collect::<Vec<_>>();
+
+        let mut buffer = crate::utils::BytesWriter::with_smol_capacity();
+        if let Some(base) = self.base_url.as_ref() {
+            let _ = write!(buffer, "{}", base.path().trim_end_matches('/'));
+        }
+        let _ = write!(buffer, "{}", uri.path());
+        match (query.is_empty(), extra.is_empty()) {
+            (true, true) => (),
+            (false, true) => {
+                let _ = write!(buffer, "?{}", query);
+            },
+            (is_empty, false) => {
+                let extra = serde_urlencoded::to_string(&extra).unwrap_or_default();
+                let _ = match is_empty {
+                    true => write!(buffer, "?{}", extra),
+                    false => write!(buffer, "?{}&{}", query, extra),
+                };
+            },
+        }
+
+        let path = match http::uri::PathAndQuery::from_maybe_shared(buffer.into_inner().freeze()) {
+            Ok(path) => path,
+            Err(_) => return,
+        };
+        let mut parts = match self.base_url.as_ref() {
+            Some(base) => base.clone().into_parts(),
+            None => http::uri::Parts::default(),
+        };
+        parts.path_and_query = Some(path);
+
+        if let Ok(uri) = http::Uri::from_parts(parts) {
+            *request.uri_mut() = uri;
+        }
+    }
+
+    fn apply_headers(&self, request: &mut request::Request) {
+        let headers = request.headers_mut();
+        for key in self.default_headers.keys() {
+            if let http::header::Entry::Vacant(entry) = headers.entry(key) {
+                let mut values = self.default_headers.get_all(key).iter();
+                if let Some(value) = values.next() {
+                    let mut entry = entry.insert_entry(value.clone());
+                    for value in values {
+                        entry.append(value.clone());
+                    }
+                }
+            }
+        }
+
         C::default_headers(request);
 
-        #[cfg(feature = "compu")]
-        {
-            const DEFAULT_COMPRESS: &'static str = "br, gzip, deflate";
+        if let Some(jar) = request.cookies.take() {
+            request.headers_mut().insert(header::COOKIE, request::cookie_header(&jar, C::cookie_encoding()));
+        }
 
-            if C::decompress() {
-                let headers = request.headers_mut();
-                if !headers.contains_key(header::ACCEPT_ENCODING) && headers.contains_key(header::RANGE) {
-                    headers.insert(header::ACCEPT_ENCODING, header::HeaderValue::from_static(DEFAULT_COMPRESS));
+        if C::decompress() && request.extensions().get::<request::NoDecompress>().is_none() {
+            let headers = request.headers_mut();
+            //Range requests should not be compressed
+            if !headers.contains_key(header::ACCEPT_ENCODING) && !headers.contains_key(header::RANGE) {
+                let accept = C::accepted_encodings();
+                if !accept.is_empty() {
+                    headers.insert(header::ACCEPT_ENCODING, accept.to_header_value());
                 }
             }
         }
+
+        if !request.headers().contains_key(header::TRACEPARENT) {
+            if let Some(ctx) = C::trace_context(request) {
+                ctx.apply(request.headers_mut());
+            }
+        }
+
+        C::finalize_request(request);
     }
 
-    ///Sends request, and returns response
-    pub async fn request(&self, mut req: request::Request) -> RequestResult {
-        Self::apply_headers(&mut req);
+    ///Re-sends request once with credentials of authenticator, if it is rejected with `401 Unauthorized`.
+    ///
+    ///Retry is skipped if authenticator provides no credentials or the same ones, that are already rejected.
+    async fn authenticated<F: Fn(request::Request) -> R, R: Future<Output=RequestResult>>(&self, mut req: request::Request, send: F) -> RequestResult {
+        let authenticator = match self.authenticator.as_ref() {
+            Some(authenticator) if req.is_replayable() => authenticator,
+            _ => return matsu!(send(req)),
+        };
 
-        #[cfg(feature = "carry_extensions")]
-        let mut extensions = req.extract_extensions();
+        self.resolve_uri(&mut req);
+        let mut retry = req.duplicate();
+        let res = matsu!(send(req))?;
+        if res.status() != http::StatusCode::UNAUTHORIZED {
+            return Ok(res);
+        }
 
-        let ongoing = self.inner.request(req.into());
-        let ongoing = matsu!(ongoing).map(|res| response::Response::new(res));
+        let challenges = header::AuthChallenge::from_headers(res.headers().get_all(header::WWW_AUTHENTICATE));
+        match authenticator.authenticate(&retry, &challenges) {
+            Some(value) if retry.headers().get(header::AUTHORIZATION) != Some(&value) => {
+                retry.headers_mut().insert(header::AUTHORIZATION, value);
+                matsu!(send(retry))
+            },
+            _ => Ok(res),
+        }
+    }
 
-        #[cfg(feature = "carry_extensions")]
-        {
-            ongoing.map(move |resp| resp.replace_extensions(&mut extensions))
+    ///Sends request, and returns response
+    ///
+    ///If client has [authenticator](auth/index.html), request may be re-sent once on `401 Unauthorized`.
+    pub async fn request(&self, req: request::Request) -> RequestResult {
+        matsu!(self.authenticated(req, |req| self.request_once(req)))
+    }
+
+    async fn request_once(&self, mut req: request::Request) -> RequestResult {
+        self.resolve_uri(&mut req);
+        let in_flight = self.begin()?;
+        let ticket = self.breakers.check(req.uri())?;
+        let via = req.extensions().get::<request::ViaProxy>().cloned();
+        self.apply_headers(&mut req);
+        let mut ctx = ResponseCtx::new::<C>(&mut req, in_flight, self.body_size());
+        let _guard = self.stats.on_request(req.uri());
+        let log = self.log_start(&req);
+        let metrics = self.metrics_start(&req);
+        let uri = req.uri().clone();
+
+        let result = match self.dispatch(req, via.as_ref()) {
+            Ok(ongoing) => matsu!(ongoing).map_err(Error::from),
+            Err(error) => Err(error),
+        };
+        let result = result.and_then(|res| check_headers::<C>(on_connection_used(response::Response::new(res)))).map(|res| ctx.finish(res));
+        if let Ok(response) = result.as_ref() {
+            self.alt_svc.update(&uri, response.headers());
         }
-        #[cfg(not(feature = "carry_extensions"))]
-        {
-            ongoing
+        if let Some(ticket) = ticket {
+            ticket.finish(&result);
+        }
+        if let Some(log) = log {
+            log.finish(&result);
+        }
+        if let Some(metrics) = metrics {
+            metrics.finish(&result);
         }
+
+        result
+    }
+
+    ///Sends request, while concurrently sending its copy to shadow target, which result is ignored.
+    ///
+    ///`rewrite` maps request's URI to URI of shadow target, or returns `None` to skip mirroring.
+    ///Copy is created via [Request::duplicate](request/struct.Request.html#method.duplicate).
+    ///`Host` is re-set, if shadow target has different authority.
+    ///
+    ///Both requests are driven within the same task and response is returned as soon as primary
+    ///request completes, dropping shadow request, if it is still ongoing.
+    ///Hence slow shadow target never delays primary response.
+    pub async fn mirror<F: FnOnce(&http::Uri) -> Option<http::Uri>>(&self, mut req: request::Request, rewrite: F) -> RequestResult {
+        self.resolve_uri(&mut req);
+        let shadow = rewrite(req.uri()).map(|uri| {
+            let mut shadow = req.duplicate();
+            if shadow.uri().authority() != uri.authority() {
+                shadow.headers_mut().remove(header::HOST);
+            }
+            *shadow.uri_mut() = uri;
+            shadow
+        });
+
+        let shadow = async move {
+            if let Some(shadow) = shadow {
+                let _ = matsu!(self.request(shadow));
+            }
+        };
+        let primary = self.request(req);
+
+        let mut shadow = core::pin::pin!(shadow);
+        let mut primary = core::pin::pin!(primary);
+        let mut shadow_done = false;
+
+        matsu!(core::future::poll_fn(|ctx| {
+            if !shadow_done {
+                shadow_done = shadow.as_mut().poll(ctx).is_ready();
+            }
+            primary.as_mut().poll(ctx)
+        }))
+    }
+
+    ///Sends request and, if response is not received within `hedge_delay`, sends its duplicate,
+    ///up to `max_hedges` times, returning whichever response arrives first.
+    ///
+    ///Ongoing requests are dropped, cancelling them, once any succeeds.
+    ///Failed request doesn't stop others, and error is returned only when every request fails.
+    ///
+    ///Only [retryable](request/struct.Request.html#method.is_retryable) requests are hedged, others are sent once.
+    ///Unlike [send](#method.send), it is not limited by [timeout](#method.timeout).
+    pub async fn send_hedged(&self, mut req: request::Request, hedge_delay: core::time::Duration, max_hedges: usize) -> RequestResult {
+        use async_timer::Oneshot;
+        use core::task::Poll;
+
+        self.resolve_uri(&mut req);
+        if max_hedges == 0 || !req.is_retryable() {
+            return matsu!(self.request(req));
+        }
+
+        let template = req.duplicate();
+        let mut hedges = max_hedges;
+        let mut timer = C::Timer::new(hedge_delay);
+        let mut ongoing = vec![Box::pin(self.request(req))];
+        let mut last_error = None;
+
+        matsu!(core::future::poll_fn(|ctx| {
+            while hedges > 0 && core::pin::Pin::new(&mut timer).poll(ctx).is_ready() {
+                hedges -= 1;
+                ongoing.push(Box::pin(self.request(template.duplicate())));
+                timer = C::Timer::new(hedge_delay);
+            }
+
+            let mut idx = 0;
+            while idx < ongoing.len() {
+                match ongoing[idx].as_mut().poll(ctx) {
+                    Poll::Ready(Ok(response)) => return Poll::Ready(Ok(response)),
+                    Poll::Ready(Err(error)) => {
+                        drop(ongoing.swap_remove(idx));
+                        last_error = Some(error);
+                    },
+                    Poll::Pending => idx += 1,
+                }
+            }
+
+            match (ongoing.is_empty(), last_error.take()) {
+                (true, Some(error)) => Poll::Ready(Err(error)),
+                (_, error) => {
+                    last_error = error;
+                    Poll::Pending
+                }
+            }
+        }))
     }
 
     ///Sends request and returns response. Timed version.
@@ -150,36 +1309,19 @@ impl<C: config::Config> Client<C> where <C::Connector as hyper::service::Service
     ///On timeout error it returns `async_timer::Expired` as `Error`
     ///`Expired` implements `Future` that can be used to re-spawn ongoing request again.
     ///
-    ///If request resolves in time returns `Result<response::Response, hyper::Error>` as `Ok`
+    ///If request resolves in time returns `Result<response::Response, Error>` as `Ok`
     ///variant.
-    pub async fn send(&self, mut req: request::Request) -> Result<RequestResult, async_timer::Expired<impl Future<Output=RequestResult>, C::Timer>> {
-        Self::apply_headers(&mut req);
-
-        #[cfg(feature = "carry_extensions")]
-        let mut extensions = req.extract_extensions();
-
-        let ongoing = self.inner.request(req.into());
-        let ongoing = async {
-            let res = matsu!(ongoing);
-            res.map(|resp| response::Response::new(resp))
-        };
+    ///
+    ///Same as [request](#method.request), it may re-send request once on `401 Unauthorized`.
+    pub async fn send(&self, req: request::Request) -> Result<RequestResult, async_timer::Expired<impl Future<Output=RequestResult> + '_, C::Timer>> {
+        let ongoing = self.request(req);
 
-        let timeout = C::timeout();
+        let timeout = self.timeout;
         match timeout.as_secs() == 0 && timeout.subsec_nanos() == 0 {
-            #[cfg(not(feature = "carry_extensions"))]
             true => Ok(matsu!(ongoing)),
-            #[cfg(feature = "carry_extensions")]
-            true => Ok(matsu!(ongoing).map(move |resp| resp.replace_extensions(&mut extensions))),
             false => {
                 let job = unsafe { async_timer::Timed::<_, C::Timer>::new_unchecked(ongoing, timeout) };
-                #[cfg(not(feature = "carry_extensions"))]
-                {
-                    matsu!(job)
-                }
-                #[cfg(feature = "carry_extensions")]
-                {
-                    matsu!(job).map(move |res| res.map(move |resp| resp.replace_extensions(&mut extensions)))
-                }
+                matsu!(job)
             }
         }
     }
@@ -189,10 +1331,10 @@ impl<C: config::Config> Client<C> where <C::Connector as hyper::service::Service
     ///On timeout error it returns `async_timer::Expired` as `Error`
     ///`Expired` implements `Future` that can be used to re-spawn ongoing request again.
     ///
-    ///If request resolves in time returns `Result<response::Response, hyper::Error>` as `Ok`
+    ///If request resolves in time returns `Result<response::Response, Error>` as `Ok`
     ///variant.
     pub async fn send_redirect(&'static self, req: request::Request) -> Result<RequestResult, async_timer::Expired<impl Future<Output=RequestResult> + 'static, C::Timer>> {
-        let timeout = C::timeout();
+        let timeout = self.timeout;
         match timeout.as_secs() == 0 && timeout.subsec_nanos() == 0 {
             true => Ok(matsu!(self.redirect_request(req))),
             false => {
@@ -208,72 +1350,153 @@ impl<C: config::Config> Client<C> where <C::Connector as hyper::service::Service
         }
     }
 
+    ///Sends request and returns response, re-spawning it on expiration until `budget` is spent.
+    ///
+    ///Request is awaited in slices of [timeout](#method.timeout), each limited by remaining budget,
+    ///so that total time never exceeds `budget`. If timeout is disabled, single slice of `budget` is used.
+    ///
+    ///Zero `budget` disables limit.
+    ///
+    ///On exhausted budget returns `BudgetError::Expired`, dropping ongoing request.
+    pub async fn send_with_budget(&self, req: request::Request, budget: core::time::Duration) -> Result<response::Response, errors::BudgetError> {
+        if budget.as_secs() == 0 && budget.subsec_nanos() == 0 {
+            return matsu!(self.request(req)).map_err(errors::BudgetError::Request);
+        }
+
+        let slice = |remaining: core::time::Duration| match self.timeout.as_secs() == 0 && self.timeout.subsec_nanos() == 0 {
+            true => remaining,
+            false => core::cmp::min(self.timeout, remaining),
+        };
+
+        let started = std::time::Instant::now();
+        let mut attempts = 1;
+        let ongoing = self.request(req);
+        let mut job = unsafe { async_timer::Timed::<_, C::Timer>::new_unchecked(ongoing, slice(budget)) };
+
+        loop {
+            let expired = match matsu!(job) {
+                Ok(result) => return result.map_err(errors::BudgetError::Request),
+                Err(expired) => expired,
+            };
+
+            let remaining = match budget.checked_sub(started.elapsed()) {
+                Some(remaining) if remaining.as_secs() > 0 || remaining.subsec_nanos() > 0 => remaining,
+                _ => return Err(errors::BudgetError::Expired {
+                    budget,
+                    attempts,
+                }),
+            };
+
+            attempts += 1;
+            job = unsafe { async_timer::Timed::<_, C::Timer>::new_unchecked(expired.into_inner(), slice(remaining)) };
+        }
+    }
+
     ///Sends request and returns response, while handling redirects.
-    pub async fn redirect_request(&self, mut req: request::Request) -> RequestResult {
+    ///
+    ///If client has [authenticator](auth/index.html), request may be re-sent once on `401 Unauthorized`.
+    pub async fn redirect_request(&self, req: request::Request) -> RequestResult {
+        matsu!(self.authenticated(req, |req| self.redirect_request_once(req)))
+    }
+
+    async fn redirect_request_once(&self, mut req: request::Request) -> RequestResult {
         use http::{Method, StatusCode};
 
-        Self::apply_headers(&mut req);
+        self.resolve_uri(&mut req);
+        let original_uri = req.uri().clone();
 
-        let mut rem_redirect = C::max_redirect_num();
+        let mut rem_redirect = self.max_redirects;
+        if self.redirects.is_cacheable(req.method()) {
+            if let Some((location, hops)) = self.redirects.resolve(req.uri(), rem_redirect.saturating_sub(1)) {
+                if !is_same_origin(req.uri(), &location) {
+                    for name in SENSITIVE_HEADERS {
+                        req.headers_mut().remove(*name);
+                    }
+                    req.headers_mut().remove(header::HOST);
+                }
+
+                req.parts.uri = location;
+                rem_redirect -= hops;
+            }
+        }
+
+        let in_flight = self.begin()?;
+        let mut ticket = self.breakers.check(req.uri())?;
+        let via = req.extensions().get::<request::ViaProxy>().cloned();
+        self.apply_headers(&mut req);
+        let mut ctx = ResponseCtx::new::<C>(&mut req, in_flight, self.body_size());
+        //Redirect may be already resolved from cache
+        ctx.original_uri = original_uri;
 
         let mut method = req.parts.method.clone();
-        let uri = req.parts.uri.clone();
+        let mut uri = req.parts.uri.clone();
         let mut headers = req.parts.headers.clone();
         let mut body = req.body.clone();
-        #[cfg(feature = "carry_extensions")]
-        let mut extensions = req.extract_extensions();
+        let mut trailers = req.trailers.clone();
+        let version = req.parts.version;
 
         loop {
-            let ongoing = self.inner.request(req.into());
-            let res = matsu!(ongoing).map(|resp| response::Response::new(resp))?;
+            let guard = self.stats.on_request(req.uri());
+            let log = self.log_start(&req);
+            let metrics = self.metrics_start(&req);
+            let res = match self.dispatch(req, via.as_ref()) {
+                Ok(ongoing) => matsu!(ongoing).map_err(Error::from),
+                Err(error) => Err(error),
+            };
+            let res = res.and_then(|res| check_headers::<C>(on_connection_used(response::Response::new(res))));
+            drop(guard);
+            if let Some(ticket) = ticket.take() {
+                ticket.finish(&res);
+            }
+            if let Some(log) = log {
+                log.finish(&res);
+            }
+            if let Some(metrics) = metrics {
+                metrics.finish(&res);
+            }
+            let res = res?;
+            self.alt_svc.update(&uri, res.headers());
+
+            if res.status_class() != response::StatusClass::Redirect {
+                return Ok(ctx.finish(res));
+            }
+
+            let is_cacheable = match res.status() {
+                StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT => self.redirects.is_cacheable(&method),
+                _ => false,
+            };
 
             match res.status() {
-                StatusCode::SEE_OTHER => {
-                    rem_redirect -= 1;
+                status @ StatusCode::SEE_OTHER | status @ StatusCode::MOVED_PERMANENTLY | status @ StatusCode::FOUND | status @ StatusCode::TEMPORARY_REDIRECT | status @ StatusCode::PERMANENT_REDIRECT => {
+                    rem_redirect = rem_redirect.saturating_sub(1);
                     match rem_redirect {
-                        #[cfg(feature = "carry_extensions")]
-                        0 => return Ok(res.replace_extensions(&mut extensions)),
-                        #[cfg(not(feature = "carry_extensions"))]
-                        0 => return Ok(res),
-                        _ => {
-                            //All requests should be changed to GET with no body.
+                        0 => return Ok(ctx.finish(res)),
+                        _ => if self.redirect_policy.is_to_get(status, &method) {
+                            //Request should be changed to GET with no body.
                             //In most cases it is result of successful POST.
                             body = None;
+                            trailers = None;
                             method = Method::GET;
-                        }
+                            headers.remove(header::CONTENT_LENGTH);
+                            headers.remove(header::CONTENT_TYPE);
+                            headers.remove(header::CONTENT_ENCODING);
+                            headers.remove(header::TRANSFER_ENCODING);
+                        },
                     }
                 },
-                StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => {
-                    rem_redirect -= 1;
-                    match rem_redirect {
-                        #[cfg(feature = "carry_extensions")]
-                        0 => return Ok(res.replace_extensions(&mut extensions)),
-                        #[cfg(not(feature = "carry_extensions"))]
-                        0 => return Ok(res),
-                        _ => (),
-                    }
-                }
-                #[cfg(feature = "carry_extensions")]
-                _ => return Ok(res.replace_extensions(&mut extensions)),
-                #[cfg(not(feature = "carry_extensions"))]
-                _ => return Ok(res),
+                _ => return Ok(ctx.finish(res)),
             }
 
             let location = match res.headers().get(header::LOCATION).and_then(|loc| loc.to_str().ok()).and_then(|loc| loc.parse::<hyper::Uri>().ok()) {
                 Some(loc) => match loc.scheme().is_some() {
                     //We assume that if scheme is present then it is absolute redirect
                     true => {
-                        //Well, it is unlikely that host would be empty, but just in case, right?
-                        if let Some(prev_host) = uri.authority().map(|part| part.host()) {
-                            match loc.authority().map(|part| part.host() == prev_host).unwrap_or(false) {
-                                true => (),
-                                false => {
-                                    headers.remove("authorization");
-                                    headers.remove("cookie");
-                                    headers.remove("cookie2");
-                                    headers.remove("www-authenticate");
-                                }
+                        if !is_same_origin(&uri, &loc) {
+                            for name in SENSITIVE_HEADERS {
+                                headers.remove(*name);
                             }
+                            //Host is set from original URI, so it needs to be re-set by hyper
+                            headers.remove(header::HOST);
                         }
 
                         loc
@@ -292,21 +1515,54 @@ impl<C: config::Config> Client<C> where <C::Connector as hyper::service::Service
                         hyper::Uri::from_parts(loc_parts).expect("Create redirect URI")
                     },
                 },
-                #[cfg(feature = "carry_extensions")]
-                None => return Ok(res.replace_extensions(&mut extensions)),
-                #[cfg(not(feature = "carry_extensions"))]
-                None => return Ok(res),
+                None => return Ok(ctx.finish(res)),
             };
 
+            if is_cacheable {
+                self.redirects.insert(&uri, location.clone());
+            }
+
             let (mut parts, _) = hyper::Request::<()>::new(()).into_parts();
             parts.method = method.clone();
-            parts.uri = location;
+            parts.uri = location.clone();
+            parts.version = version;
+            ctx.uri = location.clone();
+            //Fragment of original URI is retained, unless location has its own.
+            if let Some(fragment) = res.headers().get(header::LOCATION).and_then(|loc| loc.to_str().ok()).and_then(request::Fragment::from_uri) {
+                ctx.fragment = Some(fragment);
+            }
+            uri = location;
             parts.headers = headers.clone();
 
             req = request::Request {
                 parts,
-                body: body.clone()
+                body: body.clone(),
+                trailers: trailers.clone(),
+                cookies: None,
             };
+
+            config::set_host(&mut req);
+            C::finalize_request(&mut req);
+            ticket = self.breakers.check(req.uri())?;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_same_origin;
+
+    #[test]
+    fn should_compare_origin() {
+        let origin = "http://example.com/path".parse().unwrap();
+
+        assert!(is_same_origin(&origin, &"http://EXAMPLE.com:80/other".parse().unwrap()));
+        assert!(!is_same_origin(&origin, &"https://example.com/path".parse().unwrap()));
+        assert!(!is_same_origin(&origin, &"https://example.com:8443/path".parse().unwrap()));
+        assert!(!is_same_origin(&origin, &"http://example.com:8080/path".parse().unwrap()));
+        assert!(!is_same_origin(&origin, &"http://other.com/path".parse().unwrap()));
+
+        let origin = "https://example.com/path".parse().unwrap();
+        assert!(is_same_origin(&origin, &"https://example.com:443/".parse().unwrap()));
+    }
+}
@@ -0,0 +1,169 @@
+//!Pagination of requests.
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::{matsu, client, header};
+//!
+//!async fn example() {
+//!    let client = client::Client::default();
+//!
+//!    let req = client::Request::get("https://api.github.com/repos/DoumanAsh/yukikaze/issues").expect("To create request").empty();
+//!    let mut pages = client.paginate(req, |response| {
+//!        let next = response.headers().get(header::LINK)?.to_str().ok()?;
+//!        let next = next.split(',').find(|link| link.contains("rel=\"next\""))?;
+//!        let next = next.split(';').next()?.trim().trim_start_matches('<').trim_end_matches('>');
+//!        client::Request::get(next).ok().map(|req| req.empty())
+//!    });
+//!
+//!    while let Some(page) = matsu!(pages.next()) {
+//!        let mut page = page.expect("To get page");
+//!        println!("{}", matsu!(page.text()).expect("To read page"));
+//!    }
+//!}
+//!```
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task;
+use core::fmt;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use super::{config, errors, request, response, Client, RequestResult};
+use crate::utils::OptionExt;
+
+type Page<'a> = Pin<Box<dyn Future<Output=Option<RequestResult>> + Send + 'a>>;
+
+///Sequence of pages, fetched one by one.
+///
+///Each page is fetched with redirects handled as [Client::redirect_request](../struct.Client.html#method.redirect_request)
+///and is limited by [Client::timeout](../struct.Client.html#method.timeout).
+///
+///Pagination stops when callback returns `None` or after first error.
+///
+///Pages can be consumed as `Stream` or one by one via [next](#method.next).
+pub struct Paginator<'a, C: config::Config + 'static, F> {
+    client: &'a Client<C>,
+    next: Option<request::Request>,
+    ongoing: Option<Page<'a>>,
+    next_fn: F,
+    page: usize,
+}
+
+impl<'a, C: config::Config, F> Paginator<'a, C, F> {
+    pub(crate) fn new(client: &'a Client<C>, initial: request::Request, next_fn: F) -> Self {
+        Self {
+            client,
+            next: Some(initial),
+            ongoing: None,
+            next_fn,
+            page: 0,
+        }
+    }
+
+    #[inline]
+    ///Returns number of pages fetched so far.
+    pub fn pages(&self) -> usize {
+        self.page
+    }
+
+    #[inline]
+    ///Returns whether there are no more pages to fetch.
+    pub fn is_finished(&self) -> bool {
+        self.next.is_none() && self.ongoing.is_none()
+    }
+}
+
+impl<'a, C: config::Config, F> Paginator<'a, C, F> where F: FnMut(&response::Response) -> Option<request::Request> + Unpin,
+                                                     C: Sync, C::Timer: Send,
+                                                     <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+                                                     <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+                                                     <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    fn poll_page(&mut self, ctx: &mut task::Context<'_>) -> task::Poll<Option<Result<response::Response, errors::PageError>>> {
+        if self.ongoing.is_none() {
+            let req = match self.next.take() {
+                Some(req) => req,
+                None => return task::Poll::Ready(None),
+            };
+
+            let client = self.client;
+            self.ongoing = Some(Box::pin(client.with_timeout(client.redirect_request(req))));
+        }
+
+        let result = match self.ongoing.as_mut().unreach_none().as_mut().poll(ctx) {
+            task::Poll::Ready(result) => result,
+            task::Poll::Pending => return task::Poll::Pending,
+        };
+        self.ongoing = None;
+
+        let response = match result {
+            Some(Ok(response)) => response,
+            Some(Err(error)) => return task::Poll::Ready(Some(Err(error.into()))),
+            None => return task::Poll::Ready(Some(Err(errors::PageError::Timeout))),
+        };
+
+        self.page += 1;
+        self.next = (self.next_fn)(&response);
+
+        task::Poll::Ready(Some(Ok(response)))
+    }
+
+    #[inline]
+    ///Fetches next page.
+    ///
+    ///Returns `None` when there are no more pages.
+    pub async fn next(&mut self) -> Option<Result<response::Response, errors::PageError>> {
+        matsu!(Next(self))
+    }
+
+    ///Fetches all remaining pages, invoking callback on each of them.
+    ///
+    ///Stops on first error.
+    pub async fn for_each<R: Future<Output=()>, CB: FnMut(response::Response) -> R>(mut self, mut cb: CB) -> Result<usize, errors::PageError> {
+        while let Some(page) = matsu!(self.next()) {
+            matsu!(cb(page?));
+        }
+
+        Ok(self.page)
+    }
+}
+
+impl<'a, C: config::Config, F> futures_core::Stream for Paginator<'a, C, F> where F: FnMut(&response::Response) -> Option<request::Request> + Unpin,
+                                                                              C: Sync, C::Timer: Send,
+                                                                              <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+                                                                              <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+                                                                              <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    type Item = Result<response::Response, errors::PageError>;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Option<Self::Item>> {
+        self.get_mut().poll_page(ctx)
+    }
+}
+
+impl<C: config::Config, F> fmt::Debug for Paginator<'_, C, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Paginator").field("page", &self.page)
+                                   .field("next", &self.next.as_ref().map(|req| req.uri()))
+                                   .finish()
+    }
+}
+
+struct Next<'a, 'b, C: config::Config + 'static, F>(&'b mut Paginator<'a, C, F>);
+
+impl<'a, 'b, C: config::Config, F> Future for Next<'a, 'b, C, F> where F: FnMut(&response::Response) -> Option<request::Request> + Unpin,
+                                                                  C: Sync, C::Timer: Send,
+                                                                  <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+                                                                  <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+                                                                  <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    type Output = Option<Result<response::Response, errors::PageError>>;
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        self.0.poll_page(ctx)
+    }
+}
@@ -0,0 +1,136 @@
+//!Cache of permanent redirects.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use super::config;
+
+struct Entry {
+    location: hyper::Uri,
+    expires: Instant,
+}
+
+///Permanent redirects, learned from responses, identified by original URI.
+pub(crate) struct Cache {
+    limits: Option<config::RedirectCache>,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl Cache {
+    pub(crate) fn new(limits: Option<config::RedirectCache>) -> Self {
+        Self {
+            limits,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[inline]
+    ///Returns whether redirects of request with `method` can be cached.
+    pub(crate) fn is_cacheable(&self, method: &http::Method) -> bool {
+        self.limits.is_some() && (method == http::Method::GET || method == http::Method::HEAD)
+    }
+
+    ///Remembers that `uri` is permanently moved to `location`.
+    pub(crate) fn insert(&self, uri: &hyper::Uri, location: hyper::Uri) {
+        let limits = match self.limits {
+            Some(limits) if limits.max_entries > 0 => limits,
+            _ => return,
+        };
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|error| error.into_inner());
+        let key = uri.to_string();
+
+        if entries.len() >= limits.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, entry| entry.expires > now);
+
+            if entries.len() >= limits.max_entries {
+                let oldest = entries.iter().min_by_key(|(_, entry)| entry.expires).map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+
+        entries.insert(key, Entry {
+            location,
+            expires: now + limits.ttl,
+        });
+    }
+
+    ///Follows cached redirects of `uri`, up to `max` times.
+    ///
+    ///Returns final location and number of followed redirects, if `uri` is known to be moved.
+    pub(crate) fn resolve(&self, uri: &hyper::Uri, max: usize) -> Option<(hyper::Uri, usize)> {
+        self.limits?;
+
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap_or_else(|error| error.into_inner());
+        let mut result = None;
+        let mut key = uri.to_string();
+
+        for hops in 1..=max {
+            match entries.get(&key) {
+                Some(entry) if entry.expires > now => {
+                    key = entry.location.to_string();
+                    result = Some((entry.location.clone(), hops));
+                },
+                _ => break,
+            }
+        }
+
+        result
+    }
+
+    ///Returns fresh redirects, as map of original URI to new location.
+    pub(crate) fn redirects(&self) -> HashMap<String, hyper::Uri> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|error| error.into_inner());
+
+        entries.retain(|_, entry| entry.expires > now);
+        entries.iter().map(|(uri, entry)| (uri.clone(), entry.location.clone())).collect()
+    }
+
+    #[inline]
+    ///Forgets all redirects.
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap_or_else(|error| error.into_inner()).clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cache;
+    use crate::client::config::RedirectCache;
+
+    #[test]
+    fn should_follow_and_evict_redirects() {
+        let cache = Cache::new(Some(RedirectCache {
+            max_entries: 2,
+            ..RedirectCache::default()
+        }));
+        assert!(cache.is_cacheable(&http::Method::GET));
+        assert!(!cache.is_cacheable(&http::Method::POST));
+
+        let first: hyper::Uri = "http://example.com/a".parse().unwrap();
+        cache.insert(&first, "http://example.com/b".parse().unwrap());
+        cache.insert(&"http://example.com/b".parse().unwrap(), "https://example.com/c".parse().unwrap());
+
+        let (location, hops) = cache.resolve(&first, 8).expect("To resolve");
+        assert_eq!(location, "https://example.com/c");
+        assert_eq!(hops, 2);
+        assert_eq!(cache.resolve(&first, 1).expect("To resolve").0, "http://example.com/b");
+
+        cache.insert(&"http://example.com/d".parse().unwrap(), "http://example.com/e".parse().unwrap());
+        assert_eq!(cache.redirects().len(), 2);
+
+        cache.clear();
+        assert!(cache.resolve(&first, 8).is_none());
+
+        let disabled = Cache::new(None);
+        disabled.insert(&first, "http://example.com/b".parse().unwrap());
+        assert!(!disabled.is_cacheable(&http::Method::GET));
+        assert!(disabled.redirects().is_empty());
+    }
+}
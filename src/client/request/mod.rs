@@ -9,23 +9,171 @@ use crate::{header, utils};
 use http::header::HeaderValue;
 use bytes::BufMut;
 
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
 pub mod tags;
 pub mod multipart;
+pub mod template;
+mod reader;
+
+pub(crate) type HyperRequest = hyper::Request<TransportBody>;
+
+///Names of headers, that must not be sent as trailers.
+///
+///These are headers, that control framing, routing, authentication or are processed before body,
+///and connection-specific headers, which are forbidden in HTTP/2.
+///Conditional `If-*` headers are forbidden as well.
+const FORBIDDEN_TRAILERS: &[&str] = &[
+    "authorization", "cache-control", "connection", "content-encoding", "content-length", "content-range",
+    "content-type", "cookie", "expect", "host", "keep-alive", "max-forwards", "pragma", "proxy-authorization",
+    "proxy-connection", "range", "te", "trailer", "transfer-encoding", "upgrade",
+];
+
+#[derive(Debug, Clone, Copy)]
+///Extension that disables decompression of request.
+///
+///When present, client doesn't set `Accept-Encoding` and response's body is read as it is.
+///Client propagates it into response's extensions.
+pub struct NoDecompress;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+///Extension, that contains fragment of request's URI, without `#`.
+///
+///Fragment is never sent to server, hence it is kept separately, when request is created from URI with fragment.
+///On redirect, it is retained, unless `Location` has its own, as specified by [RFC7231](https://tools.ietf.org/html/rfc7231#section-7.1.2).
+///Client propagates it into response's extensions.
+pub struct Fragment(pub String);
+
+impl Fragment {
+    ///Extracts non-empty fragment of `uri`.
+    pub(crate) fn from_uri(uri: &str) -> Option<Self> {
+        match uri.find('#') {
+            Some(idx) if idx + 1 < uri.len() => Some(Fragment(uri[idx + 1..].to_owned())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+///Extension, that overrides proxy of request.
+///
+///Set by [Builder::via_proxy](struct.Builder.html#method.via_proxy).
+pub struct ViaProxy(pub Option<crate::connector::proxy::ProxyUri>);
 
-pub(crate) type HyperRequest = hyper::Request<hyper::Body>;
+#[derive(Default)]
+///Request's context, that client always propagates into response.
+///
+///Set via [Builder::with_ctx](struct.Builder.html#method.with_ctx).
+pub(crate) struct Context(pub(crate) http::Extensions);
+
+#[derive(Debug, Clone)]
+///Request's body.
+pub(crate) enum Body {
+    Full(bytes::Bytes),
+    Multipart(multipart::Body),
+    Reader(reader::Body),
+}
+
+impl Body {
+    #[inline]
+    ///Returns length of body, if it is known.
+    pub(crate) fn len(&self) -> Option<u64> {
+        match self {
+            Body::Full(body) => Some(body.len() as u64),
+            Body::Multipart(body) => Some(body.len()),
+            Body::Reader(body) => body.len(),
+        }
+    }
+
+    #[inline]
+    ///Returns whether body can be sent more than once.
+    pub(crate) fn is_replayable(&self) -> bool {
+        !matches!(self, Body::Reader(_))
+    }
+}
+
+impl From<Body> for hyper::Body {
+    #[inline]
+    fn from(val: Body) -> Self {
+        match val {
+            Body::Full(body) => body.into(),
+            Body::Multipart(body) => body.into_hyper(),
+            Body::Reader(body) => body.into_hyper(),
+        }
+    }
+}
+
+///Body, that is passed to hyper, transmitting trailers after data.
+pub(crate) struct TransportBody {
+    body: hyper::Body,
+    trailers: Option<http::HeaderMap>,
+}
+
+impl http_body::Body for TransportBody {
+    type Data = bytes::Bytes;
+    type Error = hyper::Error;
+
+    #[inline]
+    fn poll_data(mut self: core::pin::Pin<&mut Self>, ctx: &mut core::task::Context<'_>) -> core::task::Poll<Option<Result<Self::Data, Self::Error>>> {
+        core::pin::Pin::new(&mut self.body).poll_data(ctx)
+    }
+
+    #[inline]
+    fn poll_trailers(mut self: core::pin::Pin<&mut Self>, _: &mut core::task::Context<'_>) -> core::task::Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        core::task::Poll::Ready(Ok(self.trailers.take()))
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.trailers.is_none() && self.body.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.body.size_hint()
+    }
+}
 
 #[derive(Debug)]
 ///Http request.
 pub struct Request {
     pub(crate) parts: http::request::Parts,
-    pub(crate) body: Option<bytes::Bytes>,
+    pub(crate) body: Option<Body>,
+    pub(crate) trailers: Option<http::HeaderMap>,
+    //Cookies, which encoding is not set explicitly, are encoded by client according to its config.
+    pub(crate) cookies: Option<cookie::CookieJar>,
 }
 
 impl Request {
     ///Creates new request.
+    ///
+    ///With feature `idna`, international domain name is converted into punycode.
+    #[allow(clippy::new_ret_no_self)]
     pub fn new<U: AsRef<str>>(method: hyper::Method, uri: U) -> Result<Builder, http::uri::InvalidUri> {
-        let uri = uri.as_ref().parse::<hyper::Uri>()?;
-        Ok(Builder::new(uri, method))
+        let raw = uri.as_ref();
+        #[cfg(feature = "idna")]
+        let uri = crate::utils::uri_to_ascii(raw).parse::<hyper::Uri>()?;
+        #[cfg(not(feature = "idna"))]
+        let uri = raw.parse::<hyper::Uri>()?;
+        let mut builder = Builder::new(uri, method);
+        if let Some(fragment) = Fragment::from_uri(raw) {
+            builder.parts.extensions.insert(fragment);
+        }
+        Ok(builder)
+    }
+
+    ///Creates new request, which URI is expanded from [template](template/index.html) using `vars`.
+    ///
+    ///```rust
+    ///use yukikaze::client::Request;
+    ///
+    ///let vars = [("tenant", "yukikaze"), ("limit", "10")];
+    ///let request = Request::from_template(http::Method::GET, "https://api.example.com/{tenant}/items{?limit,cursor}", &vars[..]).expect("To create request").empty();
+    ///assert_eq!(request.uri(), "https://api.example.com/yukikaze/items?limit=10");
+    ///```
+    pub fn from_template<V: template::Variables + ?Sized>(method: hyper::Method, template: &str, vars: &V) -> Result<Builder, template::TemplateError> {
+        let uri = template::expand(template, vars)?;
+        Self::new(method, uri).map_err(Into::into)
     }
 
     ///Creates HEAD request.
@@ -53,6 +201,16 @@ impl Request {
         Self::new(hyper::Method::DELETE, uri)
     }
 
+    ///Creates OPTIONS request.
+    pub fn options<U: AsRef<str>>(uri: U) -> Result<Builder, http::uri::InvalidUri> {
+        Self::new(hyper::Method::OPTIONS, uri)
+    }
+
+    ///Creates CONNECT request to establish tunnel to `authority` (i.e. `host:port`).
+    pub fn connect<U: AsRef<str>>(authority: U) -> Result<Builder, http::uri::InvalidUri> {
+        Self::new(hyper::Method::CONNECT, authority)
+    }
+
     #[inline]
     ///Returns reference to method.
     pub fn method(&self) -> &http::Method {
@@ -89,6 +247,54 @@ impl Request {
         &mut self.parts.uri
     }
 
+    ///Creates copy of request, without extensions.
+    ///
+    ///Body is shared, rather than copied.
+    ///Note that body, read from [AsyncRead](struct.Builder.html#method.body_reader), can be sent only by one of copies.
+    pub fn duplicate(&self) -> Self {
+        let (mut parts, _) = hyper::Request::<()>::new(()).into_parts();
+        parts.method = self.parts.method.clone();
+        parts.uri = self.parts.uri.clone();
+        parts.version = self.parts.version;
+        parts.headers = self.parts.headers.clone();
+
+        Self {
+            parts,
+            body: self.body.clone(),
+            trailers: self.trailers.clone(),
+            cookies: self.cookies.clone(),
+        }
+    }
+
+    ///Returns whether request can be safely re-sent.
+    ///
+    ///It is true for idempotent methods, or when request has [Idempotency-Key](struct.Builder.html#method.idempotency_key),
+    ///unless its body is read from [AsyncRead](struct.Builder.html#method.body_reader), which cannot be sent twice.
+    pub fn is_retryable(&self) -> bool {
+        if !self.is_replayable() {
+            return false;
+        }
+
+        match self.parts.method {
+            http::Method::GET | http::Method::HEAD | http::Method::OPTIONS | http::Method::TRACE | http::Method::PUT | http::Method::DELETE => true,
+            _ => self.parts.headers.contains_key(IDEMPOTENCY_KEY),
+        }
+    }
+
+    #[inline]
+    ///Returns whether body can be sent more than once.
+    pub(crate) fn is_replayable(&self) -> bool {
+        self.body.as_ref().map(Body::is_replayable).unwrap_or(true)
+    }
+
+    #[inline]
+    ///Retrieves reference to context value of type `T`, if any.
+    ///
+    ///See [Builder::with_ctx](struct.Builder.html#method.with_ctx)
+    pub fn ctx<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.parts.extensions.get::<Context>().and_then(|ctx| ctx.0.get())
+    }
+
     #[inline]
     ///Retrieves reference to http extension map
     pub fn extensions(&self) -> &http::Extensions {
@@ -101,6 +307,14 @@ impl Request {
         &mut self.parts.extensions
     }
 
+    #[inline]
+    ///Returns trailers, that are sent after body.
+    ///
+    ///See [Builder::trailers](struct.Builder.html#method.trailers).
+    pub fn trailers(&self) -> Option<&http::HeaderMap> {
+        self.trailers.as_ref()
+    }
+
     #[inline]
     ///Extracts extensions out and leaves empty in `Self`
     pub fn extract_extensions(&mut self) -> http::Extensions {
@@ -110,10 +324,17 @@ impl Request {
     }
 }
 
-impl Into<HyperRequest> for Request {
-    fn into(self) -> HyperRequest {
-        let body = self.body.map(|body| body.into()).unwrap_or_else(hyper::Body::empty);
-        HyperRequest::from_parts(self.parts, body)
+impl From<Request> for HyperRequest {
+    fn from(mut val: Request) -> Self {
+        if let Some(jar) = val.cookies.take() {
+            val.parts.headers.insert(http::header::COOKIE, cookie_header(&jar, header::CookieEncoding::default()));
+        }
+
+        let body = TransportBody {
+            body: val.body.map(|body| body.into()).unwrap_or_else(hyper::Body::empty),
+            trailers: val.trailers,
+        };
+        HyperRequest::from_parts(val.parts, body)
     }
 }
 
@@ -124,6 +345,8 @@ impl Into<HyperRequest> for Request {
 pub struct Builder {
     parts: http::request::Parts,
     cookies: Option<cookie::CookieJar>,
+    cookie_encoding: Option<header::CookieEncoding>,
+    trailers: Option<http::HeaderMap>,
 }
 
 impl Builder {
@@ -140,7 +363,9 @@ impl Builder {
 
         Self {
             parts,
-            cookies: None
+            cookies: None,
+            cookie_encoding: None,
+            trailers: None,
         }
     }
 
@@ -198,42 +423,62 @@ impl Builder {
     ///
     ///- On attempt to set invalid header value.
     pub fn set_header_if_none<K: header::IntoHeaderName, V>(mut self, key: K, value: V) -> Self where HeaderValue: TryFrom<V> {
-        match self.headers().entry(key) {
-            http::header::Entry::Vacant(entry) => match HeaderValue::try_from(value) {
+        if let http::header::Entry::Vacant(entry) = self.headers().entry(key) {
+            match HeaderValue::try_from(value) {
                 Ok(value) => {
                     entry.insert(value);
                 },
                 Err(_) => panic!("Attempt to set invalid header value")
-            },
-            _ => (),
+            }
         }
 
         self
     }
 
+    #[inline]
+    ///Extends request's headers with provided map.
+    ///
+    ///Headers that already exist are replaced with values from `headers`.
+    pub fn headers_extend(mut self, headers: http::HeaderMap) -> Self {
+        self.headers().extend(headers);
+        self
+    }
+
+    #[cfg(feature = "typed_headers")]
+    #[inline]
+    ///Sets typed header to request.
+    ///
+    ///If header exists, it replaces it.
+    ///
+    ///Available only with feature `typed_headers`
+    pub fn typed_header<H: headers::Header>(mut self, header: H) -> Self {
+        use headers::HeaderMapExt;
+
+        self.headers().typed_insert(header);
+        self
+    }
+
     ///Sets ETag value into corresponding header.
     ///
     ///If it is set, then value is appended to existing header as per standard after
     ///semicolon.
     pub fn set_etag<E: tags::EtagMode>(mut self, etag: &etag::EntityTag, _: E) -> Self {
-        let mut buffer = utils::BytesWriter::with_smol_capacity();
+        let mut buffer = utils::HeaderValueWriter::new();
         let _ = match self.headers().remove(E::header_name()) {
             Some(old) => write!(&mut buffer, "{}, {}", old.to_str().expect("Invalid ETag!"), etag),
             None => write!(&mut buffer, "{}", etag),
         };
 
-        let value = unsafe { http::header::HeaderValue::from_maybe_shared_unchecked(buffer.freeze()) };
-        self.headers().insert(E::header_name(), value);
+        self.headers().insert(E::header_name(), buffer.finish());
         self
     }
 
     ///Sets HttpDate value into corresponding header.
     pub fn set_date<E: tags::DateMode>(mut self, date: httpdate::HttpDate, _: E) -> Self {
-        let mut buffer = utils::BytesWriter::with_smol_capacity();
+        let mut buffer = utils::HeaderValueWriter::new();
         let _ = write!(&mut buffer, "{}", date);
-        let value = unsafe { http::header::HeaderValue::from_maybe_shared_unchecked(buffer.freeze()) };
 
-        self.headers().insert(E::header_name(), value);
+        self.headers().insert(E::header_name(), buffer.finish());
         self
     }
 
@@ -242,27 +487,35 @@ impl Builder {
     ///If jar already exists, the cookies from jar
     ///are appended.
     pub fn set_cookie_jar(mut self, jar: cookie::CookieJar) -> Self {
-        if self.cookies.is_none() {
-            self.cookies = Some(jar);
-        } else {
-            let self_jar = self.cookies.as_mut().unwrap();
-
-            for cookie in jar.iter().cloned() {
+        match self.cookies.as_mut() {
+            Some(self_jar) => for cookie in jar.iter().cloned() {
                 self_jar.add(cookie.into_owned());
-            }
+            },
+            None => self.cookies = Some(jar),
         }
 
         self
     }
 
+    #[inline]
+    ///Sets encoding of cookies, overriding [Config::cookie_encoding](../config/trait.Config.html#method.cookie_encoding).
+    pub fn cookie_encoding(mut self, encoding: header::CookieEncoding) -> Self {
+        self.cookie_encoding = Some(encoding);
+        self
+    }
+
     ///Adds cookie.
+    ///
+    ///It is encoded according to [cookie_encoding](#method.cookie_encoding).
+    ///If encoding is not set explicitly, `Cookie` header is set once request is sent, using encoding of client's config.
     pub fn add_cookie(mut self, cookie: cookie::Cookie<'static>) -> Self {
-        if self.cookies.is_none() {
-            let mut jar = cookie::CookieJar::new();
-            jar.add(cookie);
-            self.cookies = Some(jar);
-        } else {
-            self.cookies.as_mut().unwrap().add(cookie.into_owned());
+        match self.cookies.as_mut() {
+            Some(jar) => jar.add(cookie.into_owned()),
+            None => {
+                let mut jar = cookie::CookieJar::new();
+                jar.add(cookie);
+                self.cookies = Some(jar);
+            }
         }
 
         self
@@ -276,6 +529,43 @@ impl Builder {
         self.set_header(http::header::CONTENT_LENGTH, len)
     }
 
+    #[inline]
+    ///Sets `User-Agent` header.
+    ///
+    ///It replaces previous one, if there was any, and takes precedence
+    ///over [Config::user_agent](../config/trait.Config.html#method.user_agent).
+    ///
+    ///# Panics
+    ///
+    ///- On attempt to set invalid header value.
+    pub fn user_agent<V>(self, value: V) -> Self where HeaderValue: TryFrom<V> {
+        self.set_header(header::USER_AGENT, value)
+    }
+
+    ///Sets `Idempotency-Key` header, generating random UUID if key is not provided.
+    ///
+    ///Makes request [retryable](struct.Request.html#method.is_retryable), as server is expected to
+    ///process it only once.
+    ///
+    ///# Panics
+    ///
+    ///- On attempt to set invalid header value.
+    pub fn idempotency_key(mut self, key: Option<&str>) -> Self {
+        let value = match key {
+            Some(key) => match HeaderValue::from_str(key) {
+                Ok(value) => value,
+                Err(_) => panic!("Attempt to set invalid header value"),
+            },
+            None => match HeaderValue::from_maybe_shared(bytes::Bytes::from(utils::uuid_v4())) {
+                Ok(value) => value,
+                Err(_) => unreach!(),
+            },
+        };
+
+        self.headers().insert(IDEMPOTENCY_KEY, value);
+        self
+    }
+
     #[inline]
     ///Sets `Accept-Encoding` header.
     ///
@@ -284,30 +574,139 @@ impl Builder {
         self.set_header(header::ACCEPT_ENCODING, encoding.as_str())
     }
 
+    ///Attaches context value to the request.
+    ///
+    ///Unlike regular extensions, context is always propagated into final response,
+    ///including one received after redirects, regardless of `carry_extensions` feature.
+    ///Retrieve it via [Response::ctx](../response/struct.Response.html#method.ctx).
+    ///
+    ///Only one value per type is stored, previous value is replaced.
+    pub fn with_ctx<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        match self.extensions_mut().get_mut::<Context>() {
+            Some(ctx) => {
+                ctx.0.insert(value);
+            },
+            None => {
+                let mut ctx = Context::default();
+                ctx.0.insert(value);
+                self.extensions_mut().insert(ctx);
+            }
+        }
+
+        self
+    }
+
+    #[inline]
+    ///Sets `traceparent` and `tracestate` headers from trace context.
+    ///
+    ///Takes precedence over [Config::trace_context](../config/trait.Config.html#method.trace_context).
+    pub fn trace_context(mut self, ctx: &header::TraceContext) -> Self {
+        ctx.apply(self.headers());
+        self
+    }
+
+    ///Sets `Access-Control-Request-Method` and `Access-Control-Request-Headers` headers of CORS preflight request.
+    ///
+    ///Header names are sent in lower case, sorted and without duplicates.
+    ///`Access-Control-Request-Headers` is omitted, if `headers` is empty.
+    pub fn cors_preflight(mut self, method: &http::Method, headers: &[header::HeaderName]) -> Self {
+        let value = match HeaderValue::from_str(method.as_str()) {
+            Ok(value) => value,
+            Err(_) => unreach!(),
+        };
+        self.headers().insert(header::ACCESS_CONTROL_REQUEST_METHOD, value);
+
+        if !headers.is_empty() {
+            let mut names = headers.iter().map(header::HeaderName::as_str).collect::<Vec<_>>();
+            names.sort_unstable();
+            names.dedup();
+
+            match HeaderValue::from_str(&names.join(",")) {
+                Ok(value) => self.headers().insert(header::ACCESS_CONTROL_REQUEST_HEADERS, value),
+                Err(_) => unreach!(),
+            };
+        }
+
+        self
+    }
+
+    ///Sets trailers, that are sent after body.
+    ///
+    ///Trailers can be transmitted only over HTTP/2, hence request is marked as HTTP/2 and
+    ///client must use HTTP/2 exclusively (e.g. `http2_only` in [Config::config_hyper](../config/trait.Config.html#method.config_hyper)).
+    ///Otherwise sending request fails, instead of silently dropping trailers.
+    ///
+    ///# Panics
+    ///
+    ///- If any trailer is not allowed to be sent after body (e.g. `Content-Length` or `Authorization`).
+    pub fn trailers(mut self, trailers: http::HeaderMap) -> Self {
+        if let Some(name) = trailers.keys().find(|name| FORBIDDEN_TRAILERS.contains(&name.as_str()) || name.as_str().starts_with("if-")) {
+            panic!("Header '{}' is not allowed in trailers", name);
+        }
+
+        if trailers.is_empty() {
+            self.trailers = None;
+            return self;
+        }
+
+        self.parts.version = http::Version::HTTP_2;
+        self.trailers = Some(trailers);
+        self
+    }
+
+    #[inline]
+    ///Disables decompression for this request.
+    ///
+    ///Client will neither set `Accept-Encoding` nor decompress response's body.
+    ///Useful when you need to get response's body exactly as server sent it.
+    pub fn no_decompress(mut self) -> Self {
+        self.extensions_mut().insert(NoDecompress);
+        self
+    }
+
+    #[inline]
+    ///Overrides proxy for this request, including its redirects.
+    ///
+    ///`None` means that request is sent directly, regardless of [Config::proxy](../config/trait.Config.html#method.proxy),
+    ///while specified proxy is used for any host, except ones matching `NoProxy` list of config's proxy.
+    pub fn via_proxy(mut self, proxy: Option<crate::connector::proxy::ProxyUri>) -> Self {
+        self.extensions_mut().insert(ViaProxy(proxy));
+        self
+    }
+
+    #[inline]
+    ///Sets `Accept-Encoding` header with list of encodings and their quality.
+    ///
+    ///Replaces previous value, if any.
+    pub fn accept_encodings(mut self, encodings: &header::AcceptEncoding) -> Self {
+        self.headers().insert(header::ACCEPT_ENCODING, encodings.to_header_value());
+        self
+    }
+
     ///Sets `Content-Disposition` header.
     ///
     ///Replaces previous value, if any.
+    ///Control characters of file name are percent-encoded, as they are not allowed in header.
     pub fn content_disposition(mut self, disp: &header::ContentDisposition) -> Self {
-        let mut buffer = utils::BytesWriter::with_smol_capacity();
+        let mut buffer = utils::HeaderValueWriter::new();
 
         let _ = write!(&mut buffer, "{}", disp);
-        let value = unsafe { http::header::HeaderValue::from_maybe_shared_unchecked(buffer.freeze()) };
 
-        self.headers().insert(header::CONTENT_DISPOSITION, value);
+        self.headers().insert(header::CONTENT_DISPOSITION, buffer.finish());
         self
     }
 
     ///Adds basic authentication header.
     pub fn basic_auth<U: fmt::Display, P: fmt::Display>(mut self, username: U, password: Option<P>) -> Self {
-        const BASIC: &'static str = "Basic ";
+        const BASIC: &str = "Basic ";
 
         let auth = match password {
             Some(password) => format!("{}:{}", username, password),
             None => format!("{}:", username)
         };
-        let encode_len = data_encoding::BASE64.encode_len(auth.as_bytes().len());
+        let encode_len = data_encoding::BASE64.encode_len(auth.len());
         let header_value = unsafe {
-            let mut header_value = bytes::BytesMut::with_capacity(encode_len + BASIC.as_bytes().len());
+            let mut header_value = bytes::BytesMut::with_capacity(encode_len + BASIC.len());
             header_value.put_slice(BASIC.as_bytes());
             {
                 let dest = &mut *(&mut header_value.bytes_mut()[..encode_len] as *mut [core::mem::MaybeUninit<u8>] as *mut [u8]);
@@ -326,15 +725,12 @@ impl Builder {
     ///
     ///Generally tokens already contain only valid symbols for header.
     ///So the function doesn't encode it using base64.
+    ///
+    ///Panics if token contains control characters.
     pub fn bearer_auth(mut self, token: &str) -> Self {
-        const TYPE: &'static str = "Bearer ";
-
-        let header_value = unsafe {
-            let mut header_value = bytes::BytesMut::with_capacity(token.as_bytes().len() + TYPE.as_bytes().len());
-            header_value.put_slice(TYPE.as_bytes());
-            header_value.put_slice(token.as_bytes());
-            http::header::HeaderValue::from_maybe_shared_unchecked(header_value.freeze())
-        };
+        let mut header_value = utils::HeaderValueWriter::new();
+        let _ = write!(&mut header_value, "Bearer {}", token);
+        let header_value = header_value.try_finish().expect("Bearer token to be valid header value");
 
         let _ = self.headers().insert(http::header::AUTHORIZATION, header_value);
 
@@ -352,7 +748,7 @@ impl Builder {
         let path = uri_parts.path_and_query;
 
         let mut buffer = utils::BytesWriter::with_smol_capacity();
-        let query = serde_urlencoded::to_string(&query).expect("To url-encode");
+        let query = serde_urlencoded::to_string(query).expect("To url-encode");
 
         let _ = match path {
             Some(path) => write!(buffer, "{}?{}", path.path(), query),
@@ -368,13 +764,68 @@ impl Builder {
         self
     }
 
+    fn append_query(mut self, query: &str) -> Self {
+        if query.is_empty() {
+            return self;
+        }
+
+        let mut uri_parts = self.parts.uri.into_parts();
+        let path = uri_parts.path_and_query;
+
+        let mut buffer = utils::BytesWriter::with_smol_capacity();
+
+        let _ = match path.as_ref().and_then(|path| path.query().map(|existing| (path.path(), existing))) {
+            Some((path, existing)) if !existing.is_empty() => write!(buffer, "{}?{}&{}", path, existing, query),
+            Some((path, _)) => write!(buffer, "{}?{}", path, query),
+            None => match path {
+                Some(path) => write!(buffer, "{}?{}", path.path(), query),
+                None => write!(buffer, "/?{}", query),
+            }
+        };
+
+        uri_parts.path_and_query = Some(http::uri::PathAndQuery::from_maybe_shared(buffer.into_inner().freeze()).expect("To create path and query"));
+
+        self.parts.uri = match http::Uri::from_parts(uri_parts) {
+            Ok(uri) => uri,
+            Err(error) => panic!("Unable to set query for URI: {}", error)
+        };
+        self
+    }
+
+    #[inline]
+    ///Appends query pairs to request's query, preserving existing one, if any.
+    ///
+    ///Keys and values are percent-encoded.
+    ///
+    ///# Panics
+    ///
+    ///- If URI creation fails
+    pub fn query_pairs<K: AsRef<str>, V: AsRef<str>>(self, pairs: &[(K, V)]) -> Self {
+        let pairs = pairs.iter().map(|(key, value)| (key.as_ref(), value.as_ref())).collect::<Vec<_>>();
+        let query = serde_urlencoded::to_string(&pairs).expect("To url-encode");
+        self.append_query(&query)
+    }
+
+    #[inline]
+    ///Appends single query pair to request's query, preserving existing one, if any.
+    ///
+    ///Key and value are percent-encoded.
+    ///
+    ///# Panics
+    ///
+    ///- If URI creation fails
+    pub fn append_query_pair<K: AsRef<str>, V: AsRef<str>>(self, key: K, value: V) -> Self {
+        self.query_pairs(&[(key, value)])
+    }
+
     ///Prepares upgrade for the request.
     ///
     ///Existing mechanisms:
     ///
     ///- [Websocket](../../upgrade/websocket/index.html)
-    pub fn upgrade<U: crate::upgrade::Upgrade>(mut self, _: U, options: U::Options) -> Request {
-        U::prepare_request(&mut self.parts.headers, &mut self.parts.extensions, options);
+    ///- [Custom](../../upgrade/trait.Upgrade.html)
+    pub fn upgrade<U: crate::upgrade::Upgrade>(mut self, upgrade: &U) -> Request {
+        upgrade.prepare_request(&mut self.parts.headers, &mut self.parts.extensions);
         self.empty()
     }
 
@@ -382,81 +833,101 @@ impl Builder {
     ///
     ///Adds `Content-Length` if not specified by user.
     ///Following RFC, adds zero length only for `PUT` and `POST` requests
-    pub fn body<B: Into<bytes::Bytes>>(mut self, body: Option<B>) -> Request {
-        use bytes::Buf;
-        use crate::utils::enc::USER_INFO_ENCODE_SET;
-        use percent_encoding::{utf8_percent_encode};
-
-        // set cookies
-        if let Some(jar) = self.cookies.take() {
-            let mut buffer = utils::BytesWriter::new();
-
-            for cook in jar.delta() {
-                let name = utf8_percent_encode(cook.name(), USER_INFO_ENCODE_SET);
-                let value = utf8_percent_encode(cook.value(), USER_INFO_ENCODE_SET);
-                let _ = write!(&mut buffer, "; {}={}", name, value);
-            }
-
-            let mut buffer = buffer.into_inner();
-            buffer.advance(2);
-            let cookie = unsafe { http::header::HeaderValue::from_maybe_shared_unchecked(buffer.freeze()) };
-
-            let _ = self.headers().insert(http::header::COOKIE, cookie);
-        }
+    pub fn body<B: Into<bytes::Bytes>>(self, body: Option<B>) -> Request {
+        self.with_body(body.map(|body| Body::Full(body.into())))
+    }
 
-        let body = body.map(|body| body.into());
+    fn with_body(mut self, body: Option<Body>) -> Request {
+        //Cookies without explicit encoding are encoded on send, according to client's config.
+        let cookies = match (self.cookies.take(), self.cookie_encoding) {
+            (Some(jar), Some(encoding)) => {
+                let _ = self.headers().insert(http::header::COOKIE, cookie_header(&jar, encoding));
+                None
+            },
+            (cookies, None) => cookies,
+            (None, Some(_)) => None,
+        };
 
         //We automatically insert Content-Length: 0 for empty requests
         //with POST/PUT and removed it otherwise.
         //For everything else we just add Content-Length unless it is already in
         match body.as_ref() {
             None => match self.parts.method {
-                hyper::Method::PUT | hyper::Method::POST => match self.parts.headers.entry(http::header::CONTENT_LENGTH) {
-                    http::header::Entry::Vacant(value) => {
-                        value.insert(utils::content_len_value(0));
-                    },
-                    _ => (),
+                hyper::Method::PUT | hyper::Method::POST => if let http::header::Entry::Vacant(value) = self.parts.headers.entry(http::header::CONTENT_LENGTH) {
+                    value.insert(utils::content_len_value(0));
                 },
                 _ => {
                     self.parts.headers.remove(http::header::CONTENT_LENGTH);
                 },
             },
-            Some(body) => match self.parts.headers.entry(http::header::CONTENT_LENGTH) {
-                http::header::Entry::Vacant(value) => {
-                    value.insert(utils::content_len_value(body.len() as u64));
-                },
-                _ => (),
+            Some(body) => if let (http::header::Entry::Vacant(value), Some(len)) = (self.parts.headers.entry(http::header::CONTENT_LENGTH), body.len()) {
+                value.insert(utils::content_len_value(len));
             },
         }
 
         Request {
             parts: self.parts,
             body,
+            trailers: self.trailers,
+            cookies,
         }
     }
 
+    #[inline]
+    ///Creates request with body, that is read from `reader` while being sent.
+    ///
+    ///Sets `Content-Length` to `len`, if it is known, otherwise body is sent with chunked transfer.
+    ///Reader is read by chunks of 64kb, use [body_reader_chunked](#method.body_reader_chunked) to change it.
+    ///
+    ///Such body can be sent only once, hence request is not [retryable](struct.Request.html#method.is_retryable),
+    ///and it cannot be re-sent on redirect, that preserves body.
+    pub fn body_reader<R: tokio::io::AsyncRead + Send + 'static>(self, reader: R, len: Option<u64>) -> Request {
+        self.body_reader_chunked(reader, len, reader::DEFAULT_CHUNK_SIZE)
+    }
+
+    ///Creates request with body, that is read from `reader` by chunks of `chunk_size`.
+    ///
+    ///See [body_reader](#method.body_reader).
+    ///
+    ///Panics if `chunk_size` is zero.
+    pub fn body_reader_chunked<R: tokio::io::AsyncRead + Send + 'static>(self, reader: R, len: Option<u64>, chunk_size: usize) -> Request {
+        self.with_body(Some(Body::Reader(reader::Body::new(reader, len, chunk_size))))
+    }
+
     ///Creates request with Form payload.
     pub fn form<F: serde::Serialize>(self, body: &F) -> Result<Request, serde_urlencoded::ser::Error> {
-        let body = serde_urlencoded::to_string(&body)?;
+        let body = serde_urlencoded::to_string(body)?;
+        Ok(self.set_header_if_none(header::CONTENT_TYPE, "application/x-www-form-urlencoded").body(Some(body)))
+    }
+
+    #[cfg(feature = "nested_form")]
+    #[inline]
+    ///Creates request with nested form body, i.e. `a[b]=1&tags[0]=x`.
+    ///
+    ///Available only with feature `nested_form`
+    pub fn form_nested<F: serde::Serialize>(self, body: &F) -> Result<Request, serde_qs::Error> {
+        let body = serde_qs::to_string(&body)?;
         Ok(self.set_header_if_none(header::CONTENT_TYPE, "application/x-www-form-urlencoded").body(Some(body)))
     }
 
     ///Creates request with JSON payload.
     pub fn json<J: serde::Serialize>(self, body: &J) -> serde_json::Result<Request> {
         let mut buffer = utils::BytesWriter::new();
-        let _ = serde_json::to_writer(&mut buffer, &body)?;
+        serde_json::to_writer(&mut buffer, &body)?;
         let body = buffer.into_inner().freeze();
         Ok(self.set_header_if_none(header::CONTENT_TYPE, "application/json").body(Some(body)))
     }
 
     ///Creates request with multipart body.
+    ///
+    ///Body is streamed by form's chunks, without copying its content.
     pub fn multipart(self, body: multipart::Form) -> Request {
         let mut content_type = utils::BytesWriter::with_capacity(30 + body.boundary.len());
         let _ = write!(&mut content_type, "multipart/form-data; boundary={}", body.boundary);
         let content_type = unsafe { http::header::HeaderValue::from_maybe_shared_unchecked(content_type.freeze()) };
 
-        let (_, body) = body.finish();
-        self.set_header_if_none(header::CONTENT_TYPE, content_type).body(Some(body))
+        let body = body.into_body();
+        self.set_header_if_none(header::CONTENT_TYPE, content_type).with_body(Some(Body::Multipart(body)))
     }
 
     ///Creates request with no body.
@@ -466,3 +937,98 @@ impl Builder {
         self.body::<bytes::Bytes>(None)
     }
 }
+
+///Creates `Cookie` header out of jar's cookies.
+pub(crate) fn cookie_header(jar: &cookie::CookieJar, encoding: header::CookieEncoding) -> HeaderValue {
+    use bytes::Buf;
+    use crate::utils::enc::USER_INFO_ENCODE_SET;
+    use percent_encoding::{utf8_percent_encode};
+
+    #[inline]
+    fn is_raw_safe(text: &str) -> bool {
+        text.bytes().all(|byte| byte > 0x20 && byte < 0x7f && byte != b';' && byte != b',')
+    }
+
+    let mut buffer = utils::BytesWriter::new();
+
+    for cook in jar.delta() {
+        let _ = match encoding {
+            header::CookieEncoding::Raw if is_raw_safe(cook.name()) && is_raw_safe(cook.value()) => write!(&mut buffer, "; {}={}", cook.name(), cook.value()),
+            _ => {
+                let name = utf8_percent_encode(cook.name(), USER_INFO_ENCODE_SET);
+                let value = utf8_percent_encode(cook.value(), USER_INFO_ENCODE_SET);
+                write!(&mut buffer, "; {}={}", name, value)
+            }
+        };
+    }
+
+    let mut buffer = buffer.into_inner();
+    if buffer.len() >= 2 {
+        buffer.advance(2);
+    }
+    unsafe { http::header::HeaderValue::from_maybe_shared_unchecked(buffer.freeze()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Request;
+
+    #[test]
+    fn should_append_query_pairs() {
+        let request = Request::get("https://example.com/path?a=1").unwrap()
+                                                                    .query_pairs(&[("b", "x y"), ("c&", "ы")])
+                                                                    .append_query_pair("d", "4")
+                                                                    .empty();
+        assert_eq!(request.uri().query(), Some("a=1&b=x+y&c%26=%D1%8B&d=4"));
+        assert_eq!(request.uri().path(), "/path");
+
+        let request = Request::get("https://example.com").unwrap().append_query_pair("a", "1").empty();
+        assert_eq!(request.uri(), "https://example.com/?a=1");
+    }
+
+    #[test]
+    fn should_set_idempotency_key() {
+        let request = Request::post("https://example.com").unwrap().empty();
+        assert!(!request.is_retryable());
+
+        let request = Request::post("https://example.com").unwrap().idempotency_key(None).empty();
+        assert!(request.is_retryable());
+        let key = request.headers().get("idempotency-key").expect("To have key").to_str().unwrap();
+        assert_eq!(key.len(), 36);
+        assert_eq!(&key[14..15], "4");
+
+        let other = Request::post("https://example.com").unwrap().idempotency_key(None).empty();
+        assert_ne!(other.headers().get("idempotency-key").unwrap().to_str().unwrap(), key);
+
+        let request = Request::post("https://example.com").unwrap().idempotency_key(Some("key")).empty();
+        assert_eq!(request.headers().get("idempotency-key").unwrap(), "key");
+    }
+
+    #[test]
+    fn should_set_trailers() {
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("x-checksum", http::HeaderValue::from_static("abc"));
+        trailers.insert("x-status", http::HeaderValue::from_static("ok"));
+
+        let request = Request::post("https://example.com").unwrap().trailers(trailers).body(Some("data"));
+        assert_eq!(request.trailers().unwrap().get("x-checksum").unwrap(), "abc");
+        assert_eq!(request.parts.version, http::Version::HTTP_2);
+    }
+
+    #[test]
+    fn should_encode_invalid_header_bytes() {
+        use crate::header::{ContentDisposition, Filename};
+
+        let disp = ContentDisposition::Attachment(Filename::with_name("evil.txt\r\nSet-Cookie: a=1".to_owned()));
+        let request = Request::post("https://example.com").unwrap().content_disposition(&disp).empty();
+        assert_eq!(request.headers().get(http::header::CONTENT_DISPOSITION).unwrap(), "attachment; filename=\"evil.txt%0D%0ASet-Cookie: a=1\"");
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_forbid_content_length_trailer() {
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert(http::header::CONTENT_LENGTH, http::HeaderValue::from_static("4"));
+        Request::post("https://example.com").unwrap().trailers(trailers);
+    }
+}
@@ -1,26 +1,83 @@
 //!Multipart support
 
-use bytes::Bytes;
+use bytes::{Buf, Bytes};
 use mime::Mime;
 use mime_guess;
 
+use core::{cmp, task};
+use core::pin::Pin;
+use std::collections::VecDeque;
 use std::path;
 use std::fs;
 use std::io;
-use std::io::Write;
+use std::io::{Read, Write};
 
 use crate::header::{ContentDisposition, Filename};
 use crate::utils::BytesWriter;
 
-const DEFAULT_BOUNDARY: &'static str = "yuki";
+mod ser;
+
+pub use ser::SerializeError;
+
+const DEFAULT_BOUNDARY: &str = "yuki";
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+///File part of form, created via [Form::from_serialize](struct.Form.html#method.from_serialize).
+///
+///When serialized by other serializers, it is represented as tuple of name, mime and content.
+pub struct File {
+    ///Name of file.
+    pub name: String,
+    ///Type of file's content.
+    pub mime: Mime,
+    ///Content of file.
+    pub data: Bytes,
+}
+
+impl File {
+    #[inline]
+    ///Creates new instance.
+    pub fn new<N: Into<String>, D: Into<Bytes>>(name: N, mime: Mime, data: D) -> Self {
+        Self {
+            name: name.into(),
+            mime,
+            data: data.into(),
+        }
+    }
+}
+
+impl serde::Serialize for File {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Data<'a>(&'a [u8]);
+
+        impl serde::Serialize for Data<'_> {
+            #[inline]
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.0)
+            }
+        }
+
+        serializer.serialize_newtype_struct(ser::FILE_TOKEN, &(self.name.as_str(), self.mime.as_ref(), Data(&self.data)))
+    }
+}
 
 ///Multipart Form.
 ///
 ///Default boundary is `yuki`.
+///
+///Form is stored as sequence of segments (part headers and chunks of content),
+///so that adding large content doesn't require to grow and copy single buffer.
+///Content is split into chunks of [chunk_size](#method.set_chunk_size).
 pub struct Form {
     ///Boundary to use.
     pub boundary: &'static str,
+    chunk_size: usize,
+    //Pending small writes, which are not yet moved into parts.
     storage: BytesWriter,
+    parts: Vec<Bytes>,
+    //Length of parts.
+    len: u64,
 }
 
 impl Form {
@@ -39,32 +96,121 @@ impl Form {
 
         Self {
             boundary,
-            storage: BytesWriter::new()
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            storage: BytesWriter::new(),
+            parts: Vec::new(),
+            len: 0,
+        }
+    }
+
+    ///Creates form out of struct or map, using its fields as form's fields.
+    ///
+    ///Field names are taken from serde, so `#[serde(rename)]` is respected.
+    ///Plain values are converted to text, `None` fields are skipped and sequences produce
+    ///field per element. Bytes (e.g. via `serde_bytes`) are written as they are,
+    ///while [File](struct.File.html) fields are added as file parts.
+    ///
+    ///Nested structs and maps are not supported.
+    pub fn from_serialize<T: serde::Serialize>(value: &T) -> Result<Self, SerializeError> {
+        let mut form = Self::new();
+        value.serialize(ser::FormSerializer {
+            form: &mut form,
+        })?;
+        Ok(form)
+    }
+
+    #[inline]
+    ///Sets maximum size of chunk, in which content is stored and streamed.
+    ///
+    ///Default is 64KiB.
+    ///
+    ///# Panic
+    ///
+    ///If size is zero.
+    pub fn set_chunk_size(&mut self, size: usize) {
+        assert_ne!(size, 0, "Chunk size cannot be zero");
+        self.chunk_size = size;
+    }
+
+    #[inline]
+    ///Returns length of form's body, so far.
+    pub fn len(&self) -> u64 {
+        self.len + self.storage.len() as u64
+    }
+
+    #[inline]
+    ///Returns whether nothing is added to the form.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn flush(&mut self) {
+        if !self.storage.is_empty() {
+            let part = self.storage.freeze();
+            self.len += part.len() as u64;
+            self.parts.push(part);
+        }
+    }
+
+    fn push_data(&mut self, data: Bytes) {
+        if data.len() < self.chunk_size {
+            let _ = self.storage.write(&data);
+        } else {
+            self.flush();
+            self.len += data.len() as u64;
+            self.parts.push(data);
         }
     }
 
+    fn write_data(&mut self, data: &[u8]) {
+        match data.len() < self.chunk_size {
+            true => {
+                let _ = self.storage.write(data);
+            },
+            false => for chunk in data.chunks(self.chunk_size) {
+                self.push_data(Bytes::copy_from_slice(chunk));
+            },
+        }
+    }
+
+    fn write_file_head(&mut self, field_name: String, file_name: Filename, mime: &Mime) {
+        let content_disposition = ContentDisposition::FormData(Some(field_name), file_name);
+        let _ = write!(&mut self.storage, "--{}\r\nContent-Disposition: {}\r\n", self.boundary, content_disposition);
+        let _ = write!(&mut self.storage, "Content-Type: {}\r\n\r\n", mime);
+    }
+
+    #[inline]
+    fn write_part_end(&mut self) {
+        let _ = write!(&mut self.storage, "\r\n--{}\r\n", self.boundary);
+    }
+
     ///Adds new field with jsut name.
     pub fn add_field(&mut self, name: String, data: &[u8]) {
         let content_disposition = ContentDisposition::FormData(Some(name), Filename::new());
         let _ = write!(&mut self.storage, "--{}\r\nContent-Disposition: {}\r\n\r\n", self.boundary, content_disposition);
-        let _ = self.storage.write(data);
-        let _ = write!(&mut self.storage, "\r\n--{}\r\n", self.boundary);
+        self.write_data(data);
+        self.write_part_end();
     }
 
     ///Adds new field with file.
     pub fn add_file_field(&mut self, field_name: String, file_name: String, mime: &Mime, data: &[u8]) {
-        let content_disposition = ContentDisposition::FormData(Some(field_name), Filename::with_name(file_name));
-        let _ = write!(&mut self.storage, "--{}\r\nContent-Disposition: {}\r\n", self.boundary, content_disposition);
-        let _ = write!(&mut self.storage, "Content-Type: {}\r\n\r\n", mime);
-        let _ = self.storage.write(data);
-        let _ = write!(&mut self.storage, "\r\n--{}\r\n", self.boundary);
+        self.write_file_head(field_name, Filename::with_name(file_name), mime);
+        self.write_data(data);
+        self.write_part_end();
+    }
+
+    ///Adds new field with file, which content is stored without copying.
+    pub fn add_file_bytes(&mut self, field_name: String, file_name: String, mime: &Mime, data: Bytes) {
+        self.write_file_head(field_name, Filename::with_name(file_name), mime);
+        self.push_data(data);
+        self.write_part_end();
     }
 
     ///Adds file to the form.
     ///
     ///# Note
     ///
-    ///It reads entire file into buffer.
+    ///It reads entire file into memory, by chunks of `chunk_size`.
     ///
     ///# IO Error
     ///
@@ -72,63 +218,298 @@ impl Form {
     ///then content of storage shall be restored to its state
     ///before starting the operation.
     pub fn add_file<P: AsRef<path::Path>>(&mut self, field_name: String, path: P) -> io::Result<()> {
-        let original_len = self.storage.len();
-
         let path = path.as_ref();
 
-        let mut file = fs::File::open(&path)?;
+        let mut file = fs::File::open(path)?;
         let file_name = match path.file_name().and_then(|file_name| file_name.to_str()) {
             Some(file_name) => Filename::with_name(file_name.to_string()),
             None => Filename::new(),
         };
         let file_meta = file.metadata()?;
-        let file_len = file_meta.len() as usize;
+        let mut file_len = file_meta.len();
         let mime = mime_guess::from_path(path).first_or_octet_stream();
 
-        let content_disposition = ContentDisposition::FormData(Some(field_name), file_name);
-        let _ = write!(&mut self.storage, "--{}\r\nContent-Disposition: {}\r\n", self.boundary, content_disposition);
-        let _ = write!(&mut self.storage, "Content-Type: {}\r\n\r\n", mime);
-
-        self.storage.reserve(file_len);
-        //If error happens we must clean up
-        if let Err(error) = io::copy(&mut file, &mut self.storage) {
-            self.storage.split_off(original_len);
-            return Err(error);
+        self.flush();
+        let original = (self.parts.len(), self.len);
+
+        self.write_file_head(field_name, file_name, &mime);
+
+        loop {
+            let size = match file_len {
+                0 => self.chunk_size,
+                len => cmp::min(len, self.chunk_size as u64) as usize,
+            };
+
+            match read_chunk(&mut file, size) {
+                Ok(chunk) if chunk.is_empty() => break,
+                Ok(chunk) => {
+                    file_len = file_len.saturating_sub(chunk.len() as u64);
+                    self.push_data(chunk);
+                },
+                //If error happens we must clean up
+                Err(error) => {
+                    self.parts.truncate(original.0);
+                    self.len = original.1;
+                    self.storage = BytesWriter::new();
+                    return Err(error);
+                }
+            }
         }
 
-        let _ = write!(&mut self.storage, "\r\n--{}\r\n", self.boundary);
+        self.write_part_end();
 
         Ok(())
     }
 
+    fn into_parts(mut self) -> (u64, Vec<Bytes>) {
+        let mut tail = std::mem::take(&mut self.storage).into_inner();
+        let tail_len = tail.len();
+        //Each part ends with boundary, which is written into storage
+        if tail_len == 0 {
+            return (self.len, self.parts);
+        }
+
+        tail[tail_len-2] = 45; //'-'
+        tail[tail_len-1] = 45;
+        tail.extend_from_slice("\r\n".as_bytes());
+
+        self.len += tail.len() as u64;
+        self.parts.push(tail.freeze());
+
+        (self.len, self.parts)
+    }
+
     ///Finishes creating form and produces body with its length
+    ///
+    ///Form's content is copied into single buffer, unless it consists of single chunk.
+    ///Use [into_body](#method.into_body) to avoid it.
     pub fn finish(self) -> (u64, Bytes) {
-        let mut bytes = self.storage.into_inner();
-        let len = bytes.len();
-        if len == 0 {
-            return (0, bytes.freeze());
+        let (len, mut parts) = self.into_parts();
+
+        match parts.len() {
+            0 => (0, Bytes::new()),
+            1 => (len, parts.pop().unwrap()),
+            _ => {
+                let mut result = bytes::BytesMut::with_capacity(len as usize);
+                for part in parts {
+                    result.extend_from_slice(&part);
+                }
+                (len, result.freeze())
+            }
+        }
+    }
+
+    ///Finishes creating form and produces streaming body.
+    pub fn into_body(self) -> Body {
+        let chunk_size = self.chunk_size;
+        let (len, parts) = self.into_parts();
+
+        Body {
+            parts: parts.into(),
+            len,
+            chunk_size,
+        }
+    }
+}
+
+impl Default for Form {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read_chunk(file: &mut fs::File, size: usize) -> io::Result<Bytes> {
+    let mut chunk = bytes::BytesMut::new();
+    chunk.resize(size, 0);
+
+    let mut filled = 0;
+    while filled < size {
+        match file.read(&mut chunk[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
         }
+    }
+
+    chunk.truncate(filled);
+    Ok(chunk.freeze())
+}
+
+#[derive(Debug, Clone)]
+///Streaming body of [Form](struct.Form.html).
+///
+///Each chunk consists of segments of form, which size is no more than form's chunk size.
+///Cloning is cheap as segments are shared.
+pub struct Body {
+    parts: VecDeque<Bytes>,
+    //Remaining length.
+    len: u64,
+    chunk_size: usize,
+}
+
+impl Body {
+    #[inline]
+    ///Returns remaining length of body.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    #[inline]
+    ///Returns whether body is finished.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    ///Returns next segment, limited by `size`.
+    fn next_segment(&mut self, size: usize) -> Option<Bytes> {
+        let mut segment = self.parts.pop_front()?;
+        if segment.len() > size {
+            self.parts.push_front(segment.split_off(size));
+        }
+
+        self.len -= segment.len() as u64;
+        Some(segment)
+    }
+
+    fn next_chunk(&mut self) -> Option<Chunk> {
+        let mut chunk = Chunk {
+            parts: VecDeque::new(),
+            remaining: 0,
+        };
+
+        while chunk.remaining < self.chunk_size {
+            match self.next_segment(self.chunk_size - chunk.remaining) {
+                Some(segment) => {
+                    chunk.remaining += segment.len();
+                    chunk.parts.push_back(segment);
+                },
+                None => break,
+            }
+        }
+
+        match chunk.parts.is_empty() {
+            true => None,
+            false => Some(chunk),
+        }
+    }
+
+    ///Converts into hyper's body, which writes segments without copying them.
+    pub(crate) fn into_hyper(self) -> hyper::Body {
+        hyper::Body::wrap_stream(Segments(self))
+    }
+}
+
+impl http_body::Body for Body {
+    type Data = Chunk;
+    type Error = core::convert::Infallible;
+
+    #[inline]
+    fn poll_data(mut self: Pin<&mut Self>, _: &mut task::Context<'_>) -> task::Poll<Option<Result<Self::Data, Self::Error>>> {
+        task::Poll::Ready(self.next_chunk().map(Ok))
+    }
+
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, _: &mut task::Context<'_>) -> task::Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        task::Poll::Ready(Ok(None))
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.is_empty()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        http_body::SizeHint::with_exact(self.len)
+    }
+}
+
+struct Segments(Body);
 
-        bytes[len-2] = 45; //'-'
-        bytes[len-1] = 45;
+impl futures_core::Stream for Segments {
+    type Item = Result<Bytes, core::convert::Infallible>;
 
-        bytes.extend_from_slice("\r\n".as_bytes());
-        let len = len as u64 + 2;
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, _: &mut task::Context<'_>) -> task::Poll<Option<Self::Item>> {
+        let size = self.0.chunk_size;
+        task::Poll::Ready(self.0.next_segment(size).map(Ok))
+    }
 
-        (len, bytes.freeze())
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.parts.len(), None)
+    }
+}
+
+#[derive(Debug)]
+///Chunk of [Body](struct.Body.html), which supports vectored writes.
+pub struct Chunk {
+    parts: VecDeque<Bytes>,
+    remaining: usize,
+}
+
+impl Buf for Chunk {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    #[inline]
+    fn bytes(&self) -> &[u8] {
+        self.parts.front().map(|part| part.as_ref()).unwrap_or(&[])
+    }
+
+    fn bytes_vectored<'a>(&'a self, dst: &mut [io::IoSlice<'a>]) -> usize {
+        let mut idx = 0;
+        for (slot, part) in dst.iter_mut().zip(self.parts.iter()) {
+            *slot = io::IoSlice::new(part);
+            idx += 1;
+        }
+        idx
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        assert!(cnt <= self.remaining, "Cannot advance past remaining bytes");
+        self.remaining -= cnt;
+
+        while cnt > 0 {
+            let part = self.parts.front_mut().expect("To have part");
+            match part.len() > cnt {
+                true => {
+                    part.advance(cnt);
+                    return;
+                },
+                false => {
+                    cnt -= part.len();
+                    self.parts.pop_front();
+                }
+            }
+        }
+    }
+}
+
+impl From<Chunk> for Bytes {
+    fn from(mut chunk: Chunk) -> Self {
+        match chunk.parts.len() {
+            0 => Bytes::new(),
+            1 => chunk.parts.pop_front().unwrap(),
+            _ => chunk.to_bytes(),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Form;
+    use super::{File, Form};
     use mime::TEXT_PLAIN;
     use std::{fs, str};
     use std::io::Read;
 
     #[test]
     fn multipart_form_add_simple_field() {
-        const EXPECTED: &'static str = "--yuki\r\nContent-Disposition: form-data; name=\"SimpleField\"\r\n\r\nsimple test\r\n--yuki--\r\n";
+        const EXPECTED: &str = "--yuki\r\nContent-Disposition: form-data; name=\"SimpleField\"\r\n\r\nsimple test\r\n--yuki--\r\n";
 
         let mut form = Form::new();
         form.add_field("SimpleField".to_string(), "simple test".as_bytes());
@@ -141,7 +522,7 @@ mod tests {
 
     #[test]
     fn multipart_form_add_file() {
-        const FILE_NAME: &'static str = "Cargo.toml";
+        const FILE_NAME: &str = "Cargo.toml";
 
         let mut file_body = String::new();
         let mut file = fs::File::open(FILE_NAME).expect("to open file");
@@ -160,7 +541,7 @@ mod tests {
 
     #[test]
     fn multipart_form_add_multiple_fields() {
-        const EXPECTED: &'static str = "--yuki\r\nContent-Disposition: form-data; name=\"SimpleField\"\r\n\r\nsimple test\r\n--yuki\r\n--yuki\r\nContent-Disposition: form-data; name=\"SimpleFile\"; filename=\"File.txt\"\r\nContent-Type: text/plain\r\n\r\nsimple file\r\n--yuki--\r\n";
+        const EXPECTED: &str = "--yuki\r\nContent-Disposition: form-data; name=\"SimpleField\"\r\n\r\nsimple test\r\n--yuki\r\n--yuki\r\nContent-Disposition: form-data; name=\"SimpleFile\"; filename=\"File.txt\"\r\nContent-Type: text/plain\r\n\r\nsimple file\r\n--yuki--\r\n";
 
         let mut form = Form::new();
         form.add_field("SimpleField".to_string(), "simple test".as_bytes());
@@ -172,4 +553,73 @@ mod tests {
         assert_eq!(str_body, EXPECTED);
     }
 
+    #[test]
+    fn multipart_form_from_serialize() {
+        const EXPECTED: &str = "--yuki\r\nContent-Disposition: form-data; name=\"user-name\"\r\n\r\nYuki\r\n--yuki\r\n--yuki\r\nContent-Disposition: form-data; name=\"age\"\r\n\r\n16\r\n--yuki\r\n--yuki\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\na\r\n--yuki\r\n--yuki\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\nb\r\n--yuki\r\n--yuki\r\nContent-Disposition: form-data; name=\"avatar\"; filename=\"File.txt\"\r\nContent-Type: text/plain\r\n\r\nsimple file\r\n--yuki--\r\n";
+
+        #[derive(serde::Serialize)]
+        struct Profile {
+            #[serde(rename = "user-name")]
+            name: &'static str,
+            age: u8,
+            nickname: Option<String>,
+            tags: Vec<&'static str>,
+            avatar: File,
+        }
+
+        let profile = Profile {
+            name: "Yuki",
+            age: 16,
+            nickname: None,
+            tags: vec!["a", "b"],
+            avatar: File::new("File.txt", TEXT_PLAIN, "simple file"),
+        };
+
+        let (len, body) = Form::from_serialize(&profile).expect("To serialize").finish();
+        let str_body = str::from_utf8(&body).expect("To get str slice of body");
+        assert_eq!(len, EXPECTED.len() as u64);
+        assert_eq!(str_body, EXPECTED);
+
+        assert!(Form::from_serialize(&5).is_err());
+    }
+
+    #[tokio::test]
+    async fn multipart_form_into_body() {
+        use bytes::Buf;
+        use http_body::Body;
+
+        let mut form = Form::new();
+        form.set_chunk_size(16);
+        form.add_field("SimpleField".to_string(), "simple test".as_bytes());
+        form.add_file_field("SimpleFile".to_string(), "File.txt".to_string(), &TEXT_PLAIN, &[b'y'; 40]);
+
+        let mut expected = Form::new();
+        expected.add_field("SimpleField".to_string(), "simple test".as_bytes());
+        expected.add_file_field("SimpleFile".to_string(), "File.txt".to_string(), &TEXT_PLAIN, &[b'y'; 40]);
+        let (expected_len, expected) = expected.finish();
+        assert_eq!(form.len() + 2, expected_len);
+
+        let mut body = form.into_body();
+        assert_eq!(body.len(), expected_len);
+
+        let mut result = Vec::new();
+        while let Some(Ok(mut chunk)) = body.data().await {
+            assert!(chunk.remaining() <= 16);
+
+            let mut slices = [std::io::IoSlice::new(&[]); 4];
+            let num = chunk.bytes_vectored(&mut slices);
+            let vectored: Vec<u8> = slices[..num].iter().flat_map(|slice| slice.iter().cloned()).collect();
+            assert_eq!(vectored.len(), chunk.remaining());
+
+            while chunk.has_remaining() {
+                let len = chunk.bytes().len();
+                result.extend_from_slice(chunk.bytes());
+                chunk.advance(len);
+            }
+            assert!(result.ends_with(&vectored));
+        }
+
+        assert!(body.is_end_stream());
+        assert_eq!(result, expected);
+    }
 }
@@ -0,0 +1,506 @@
+//!Serialization of `Form` out of serde's data model.
+
+use core::fmt;
+
+use serde::ser::{self, Impossible, Serialize};
+
+use super::Form;
+
+///Name of newtype struct, that marks file part.
+pub(super) const FILE_TOKEN: &str = "$yukikaze::multipart::File";
+
+#[derive(Debug)]
+///Describes failure to create `Form` out of value.
+pub enum SerializeError {
+    ///Value cannot be represented as form, contains name of failed serde's method.
+    Unsupported(&'static str),
+    ///Error from `Serialize` implementation.
+    Custom(String),
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SerializeError::Unsupported(method) => write!(f, "Value cannot be serialized as form via {}. Expected struct or map with plain values", method),
+            SerializeError::Custom(error) => f.write_str(error),
+        }
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+impl ser::Error for SerializeError {
+    #[inline]
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerializeError::Custom(msg.to_string())
+    }
+}
+
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*) -> $ret:ty;)+) => {
+        $(
+            #[inline]
+            fn $method(self, $(_: $arg),*) -> Result<$ret, Self::Error> {
+                Err(SerializeError::Unsupported(stringify!($method)))
+            }
+        )+
+    }
+}
+
+macro_rules! unsupported_generic {
+    ($($method:ident($($arg:ty),*);)+) => {
+        $(
+            #[inline]
+            fn $method<T: ?Sized + Serialize>(self, $(_: $arg,)* _: &T) -> Result<Self::Ok, Self::Error> {
+                Err(SerializeError::Unsupported(stringify!($method)))
+            }
+        )+
+    }
+}
+
+macro_rules! unsupported_compound {
+    () => {
+        unsupported! {
+            serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+            serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+            serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+        }
+        unsupported_generic! {
+            serialize_newtype_variant(&'static str, u32, &'static str);
+        }
+    }
+}
+
+///Value of form's field.
+enum Part {
+    Text(String),
+    Bytes(Vec<u8>),
+    Skip,
+}
+
+macro_rules! scalar_to_string {
+    ($($method:ident($ty:ty);)+) => {
+        $(
+            #[inline]
+            fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(Part::Text(value.to_string()))
+            }
+        )+
+    }
+}
+
+///Serializes plain value.
+struct ScalarSerializer;
+
+impl ser::Serializer for ScalarSerializer {
+    type Ok = Part;
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<Part, SerializeError>;
+    type SerializeTuple = Impossible<Part, SerializeError>;
+    type SerializeTupleStruct = Impossible<Part, SerializeError>;
+    type SerializeTupleVariant = Impossible<Part, SerializeError>;
+    type SerializeMap = Impossible<Part, SerializeError>;
+    type SerializeStruct = Impossible<Part, SerializeError>;
+    type SerializeStructVariant = Impossible<Part, SerializeError>;
+
+    scalar_to_string! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+    }
+
+    #[inline]
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Part::Bytes(value.to_vec()))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Part::Skip)
+    }
+
+    #[inline]
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Part::Skip)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(Part::Skip)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(Part::Text(variant.to_owned()))
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    unsupported! {
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+    }
+    unsupported_compound!();
+}
+
+///Serializes top level struct or map.
+pub(super) struct FormSerializer<'a> {
+    pub(super) form: &'a mut Form,
+}
+
+impl<'a> ser::Serializer for FormSerializer<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<(), SerializeError>;
+    type SerializeTuple = Impossible<(), SerializeError>;
+    type SerializeTupleStruct = Impossible<(), SerializeError>;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    unsupported! {
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_bytes(&[u8]) -> ();
+        serialize_none() -> ();
+        serialize_unit() -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+    }
+    unsupported_compound!();
+
+    #[inline]
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(MapSerializer {
+            form: self.form,
+            key: None,
+        })
+    }
+
+    #[inline]
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self)
+    }
+}
+
+impl<'a> ser::SerializeStruct for FormSerializer<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    #[inline]
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+        value.serialize(FieldSerializer {
+            form: self.form,
+            name: key,
+        })
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+pub(super) struct MapSerializer<'a> {
+    form: &'a mut Form,
+    key: Option<String>,
+}
+
+impl<'a> ser::SerializeMap for MapSerializer<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        match key.serialize(ScalarSerializer)? {
+            Part::Text(key) => {
+                self.key = Some(key);
+                Ok(())
+            },
+            _ => Err(SerializeError::Unsupported("serialize_key")),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = match self.key.take() {
+            Some(key) => key,
+            None => return Err(SerializeError::Unsupported("serialize_value")),
+        };
+
+        value.serialize(FieldSerializer {
+            form: self.form,
+            name: &key,
+        })
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+macro_rules! forward_scalar {
+    ($($method:ident($ty:ty);)+) => {
+        $(
+            #[inline]
+            fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+                let part = ScalarSerializer.$method(value)?;
+                self.add(part);
+                Ok(())
+            }
+        )+
+    }
+}
+
+///Serializes value of single field.
+///
+///Sequences are serialized as multiple fields with the same name.
+struct FieldSerializer<'a, 'b> {
+    form: &'a mut Form,
+    name: &'b str,
+}
+
+impl<'a, 'b> FieldSerializer<'a, 'b> {
+    fn add(self, part: Part) {
+        match part {
+            Part::Text(text) => self.form.add_field(self.name.to_owned(), text.as_bytes()),
+            Part::Bytes(bytes) => self.form.add_field(self.name.to_owned(), &bytes),
+            Part::Skip => (),
+        }
+    }
+}
+
+impl<'a, 'b> ser::Serializer for FieldSerializer<'a, 'b> {
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Impossible<(), SerializeError>;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = Impossible<(), SerializeError>;
+    type SerializeStruct = Impossible<(), SerializeError>;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    forward_scalar! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_unit_struct(&'static str);
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_unit_variant(self, name: &'static str, index: u32, variant: &'static str) -> Result<Self::Ok, Self::Error> {
+        let part = ScalarSerializer.serialize_unit_variant(name, index, variant)?;
+        self.add(part);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+        match name == FILE_TOKEN {
+            true => value.serialize(FileSerializer {
+                form: self.form,
+                name: self.name,
+                parts: Vec::with_capacity(3),
+            }),
+            false => value.serialize(self),
+        }
+    }
+
+    #[inline]
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self)
+    }
+
+    unsupported! {
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+    }
+    unsupported_compound!();
+}
+
+impl<'a, 'b> ser::SerializeSeq for FieldSerializer<'a, 'b> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let part = value.serialize(ScalarSerializer)?;
+        FieldSerializer {
+            form: self.form,
+            name: self.name,
+        }.add(part);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b> ser::SerializeTuple for FieldSerializer<'a, 'b> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    #[inline]
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+///Serializes `File` as tuple of file name, mime and content.
+struct FileSerializer<'a, 'b> {
+    form: &'a mut Form,
+    name: &'b str,
+    parts: Vec<Part>,
+}
+
+impl<'a, 'b> ser::Serializer for FileSerializer<'a, 'b> {
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<(), SerializeError>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Impossible<(), SerializeError>;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = Impossible<(), SerializeError>;
+    type SerializeStruct = Impossible<(), SerializeError>;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    unsupported! {
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_bytes(&[u8]) -> ();
+        serialize_none() -> ();
+        serialize_unit() -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+    }
+    unsupported_generic! {
+        serialize_some();
+        serialize_newtype_struct(&'static str);
+    }
+    unsupported_compound!();
+
+    #[inline]
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self)
+    }
+}
+
+impl<'a, 'b> ser::SerializeTuple for FileSerializer<'a, 'b> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    #[inline]
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.parts.push(value.serialize(ScalarSerializer)?);
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<(), Self::Error> {
+        let data = self.parts.pop();
+        let mime = self.parts.pop();
+        let file_name = self.parts.pop();
+
+        match (file_name, mime, data) {
+            (Some(Part::Text(file_name)), Some(Part::Text(mime)), Some(Part::Bytes(data))) => {
+                let mime = mime.parse().unwrap_or(mime::APPLICATION_OCTET_STREAM);
+                self.form.add_file_field(self.name.to_owned(), file_name, &mime, &data);
+                Ok(())
+            },
+            _ => Err(SerializeError::Unsupported(FILE_TOKEN)),
+        }
+    }
+}
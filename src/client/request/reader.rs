@@ -0,0 +1,110 @@
+use core::pin::Pin;
+use core::task;
+use core::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::AsyncRead;
+
+///Default size of chunk, that is read from source at once.
+pub(crate) const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+type Source = Pin<Box<dyn AsyncRead + Send>>;
+
+#[derive(Clone)]
+///Body, that is read from `AsyncRead` source, while being sent.
+///
+///Source can be read only once, hence copies of body share it and
+///only the first one to be sent transmits data, while others fail.
+pub(crate) struct Body {
+    source: Arc<Mutex<Option<Source>>>,
+    len: Option<u64>,
+    chunk_size: usize,
+}
+
+impl Body {
+    pub(crate) fn new<R: AsyncRead + Send + 'static>(source: R, len: Option<u64>, chunk_size: usize) -> Self {
+        assert_ne!(chunk_size, 0, "Chunk size cannot be zero");
+
+        Self {
+            source: Arc::new(Mutex::new(Some(Box::pin(source)))),
+            len,
+            chunk_size,
+        }
+    }
+
+    #[inline]
+    ///Returns length of body, if it is known.
+    pub(crate) fn len(&self) -> Option<u64> {
+        self.len
+    }
+
+    ///Converts into hyper's body, taking source out.
+    pub(crate) fn into_hyper(self) -> hyper::Body {
+        let source = match self.source.lock() {
+            Ok(mut source) => source.take(),
+            Err(_) => None,
+        };
+
+        hyper::Body::wrap_stream(Chunks {
+            is_consumed: source.is_none(),
+            source,
+            buffer: bytes::BytesMut::new(),
+            chunk_size: self.chunk_size,
+        })
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Reader").field("len", &self.len)
+                                .field("chunk_size", &self.chunk_size)
+                                .finish()
+    }
+}
+
+///Stream of chunks, read from source.
+struct Chunks {
+    source: Option<Source>,
+    ///Whether source is already taken by other copy of body.
+    is_consumed: bool,
+    buffer: bytes::BytesMut,
+    chunk_size: usize,
+}
+
+impl futures_core::Stream for Chunks {
+    type Item = Result<bytes::Bytes, io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let source = match this.source.as_mut() {
+            Some(source) => source,
+            None if this.is_consumed => {
+                this.is_consumed = false;
+                return task::Poll::Ready(Some(Err(io::Error::other("Body's reader is already consumed"))));
+            },
+            None => return task::Poll::Ready(None),
+        };
+
+        if this.buffer.capacity() < this.chunk_size {
+            this.buffer.reserve(this.chunk_size);
+        }
+        this.buffer.resize(this.chunk_size, 0);
+
+        match source.as_mut().poll_read(ctx, &mut this.buffer[..]) {
+            task::Poll::Ready(Ok(0)) => {
+                this.source = None;
+                task::Poll::Ready(None)
+            },
+            task::Poll::Ready(Ok(size)) => {
+                this.buffer.truncate(size);
+                task::Poll::Ready(Some(Ok(this.buffer.split().freeze())))
+            },
+            task::Poll::Ready(Err(error)) => {
+                this.source = None;
+                task::Poll::Ready(Some(Err(error)))
+            },
+            task::Poll::Pending => task::Poll::Pending,
+        }
+    }
+}
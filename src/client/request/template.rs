@@ -0,0 +1,279 @@
+//!URI templates, as defined in [RFC 6570](https://tools.ietf.org/html/rfc6570).
+//!
+//!Expressions of levels 1 to 3 are supported, i.e. all operators, but without value modifiers (prefix and explode).
+//!Undefined variables are skipped.
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::client::request::template;
+//!
+//!let vars = [("tenant", "yuki kaze"), ("limit", "10")];
+//!let uri = template::expand("https://api.example.com/{tenant}/items{?limit,cursor}", &vars[..]).expect("To expand");
+//!assert_eq!(uri, "https://api.example.com/yuki%20kaze/items?limit=10");
+//!```
+
+use core::fmt::{self, Write};
+use core::hash::{BuildHasher, Hash};
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+///Characters, that are allowed by reserved expansion (`+` and `#`) without encoding.
+const RESERVED: &[u8] = b":/?#[]@!$&'()*+,;=";
+
+///Source of variables' values.
+pub trait Variables {
+    ///Returns value of variable, if it is defined.
+    fn get(&self, name: &str) -> Option<&str>;
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> Variables for [(K, V)] {
+    #[inline]
+    fn get(&self, name: &str) -> Option<&str> {
+        self.iter().find(|(key, _)| key.as_ref() == name).map(|(_, value)| value.as_ref())
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>, const N: usize> Variables for [(K, V); N] {
+    #[inline]
+    fn get(&self, name: &str) -> Option<&str> {
+        Variables::get(&self[..], name)
+    }
+}
+
+impl<K: Borrow<str> + Hash + Eq, V: AsRef<str>, S: BuildHasher> Variables for HashMap<K, V, S> {
+    #[inline]
+    fn get(&self, name: &str) -> Option<&str> {
+        HashMap::get(self, name).map(|value| value.as_ref())
+    }
+}
+
+impl<K: Borrow<str> + Ord, V: AsRef<str>> Variables for BTreeMap<K, V> {
+    #[inline]
+    fn get(&self, name: &str) -> Option<&str> {
+        BTreeMap::get(self, name).map(|value| value.as_ref())
+    }
+}
+
+#[derive(Debug)]
+///Describes failure to expand template.
+///
+///Position is byte offset of expression within template.
+pub enum TemplateError {
+    ///Expression is not closed with `}`.
+    Unclosed(usize),
+    ///Expression has invalid variable name.
+    InvalidExpression(usize),
+    ///Expression uses modifiers of level 4.
+    Unsupported(usize),
+    ///Expanded template is not valid URI.
+    InvalidUri(http::uri::InvalidUri),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::Unclosed(pos) => write!(f, "Expression at {} is not closed", pos),
+            TemplateError::InvalidExpression(pos) => write!(f, "Expression at {} is invalid", pos),
+            TemplateError::Unsupported(pos) => write!(f, "Expression at {} uses unsupported modifiers", pos),
+            TemplateError::InvalidUri(error) => write!(f, "Expanded template is invalid URI: {}", error),
+        }
+    }
+}
+
+impl Error for TemplateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TemplateError::InvalidUri(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<http::uri::InvalidUri> for TemplateError {
+    #[inline]
+    fn from(error: http::uri::InvalidUri) -> Self {
+        TemplateError::InvalidUri(error)
+    }
+}
+
+///Expansion rules of operator.
+struct Operator {
+    first: &'static str,
+    sep: char,
+    named: bool,
+    if_empty: &'static str,
+    reserved: bool,
+}
+
+impl Operator {
+    fn new(op: Option<char>) -> Self {
+        let (first, sep, named, if_empty, reserved) = match op {
+            Some('+') => ("", ',', false, "", true),
+            Some('#') => ("#", ',', false, "", true),
+            Some('.') => (".", '.', false, "", false),
+            Some('/') => ("/", '/', false, "", false),
+            Some(';') => (";", ';', true, "", false),
+            Some('?') => ("?", '&', true, "=", false),
+            Some('&') => ("&", '&', true, "=", false),
+            _ => ("", ',', false, "", false),
+        };
+
+        Self {
+            first,
+            sep,
+            named,
+            if_empty,
+            reserved,
+        }
+    }
+}
+
+fn encode(out: &mut String, value: &str, reserved: bool) {
+    let bytes = value.as_bytes();
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let byte = bytes[idx];
+        let is_triplet = byte == b'%' && bytes.len() > idx + 2 && bytes[idx + 1].is_ascii_hexdigit() && bytes[idx + 2].is_ascii_hexdigit();
+
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) || (reserved && (RESERVED.contains(&byte) || is_triplet)) {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{:02X}", byte);
+        }
+
+        idx += 1;
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'.' || byte == b'%')
+}
+
+fn expand_expression<V: Variables + ?Sized>(out: &mut String, expression: &str, pos: usize, vars: &V) -> Result<(), TemplateError> {
+    let op = match expression.chars().next() {
+        Some(op @ '+') | Some(op @ '#') | Some(op @ '.') | Some(op @ '/') | Some(op @ ';') | Some(op @ '?') | Some(op @ '&') => Some(op),
+        //Reserved for future extensions
+        Some('=') | Some(',') | Some('!') | Some('@') | Some('|') => return Err(TemplateError::InvalidExpression(pos)),
+        _ => None,
+    };
+    let operator = Operator::new(op);
+    let names = match op {
+        Some(op) => &expression[op.len_utf8()..],
+        None => expression,
+    };
+
+    let mut is_first = true;
+    for name in names.split(',') {
+        if name.ends_with('*') || name.contains(':') {
+            return Err(TemplateError::Unsupported(pos));
+        } else if !is_valid_name(name) {
+            return Err(TemplateError::InvalidExpression(pos));
+        }
+
+        let value = match vars.get(name) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        match is_first {
+            true => out.push_str(operator.first),
+            false => out.push(operator.sep),
+        }
+        is_first = false;
+
+        if operator.named {
+            out.push_str(name);
+            if value.is_empty() {
+                out.push_str(operator.if_empty);
+                continue;
+            }
+            out.push('=');
+        }
+
+        encode(out, value, operator.reserved);
+    }
+
+    Ok(())
+}
+
+///Expands `template`, using values of `vars`.
+pub fn expand<V: Variables + ?Sized>(template: &str, vars: &V) -> Result<String, TemplateError> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let pos = template.len() - rest.len() + start;
+        //Literals are allowed to contain reserved characters and percent encoded triplets.
+        encode(&mut out, &rest[..start], true);
+
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => return Err(TemplateError::Unclosed(pos)),
+        };
+
+        expand_expression(&mut out, &rest[start + 1..end], pos, vars)?;
+        rest = &rest[end + 1..];
+    }
+
+    encode(&mut out, rest, true);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, TemplateError};
+
+    #[test]
+    fn should_expand_rfc_examples() {
+        let vars = [
+            ("var", "value"), ("hello", "Hello World!"), ("path", "/foo/bar"), ("empty", ""),
+            ("x", "1024"), ("y", "768"),
+        ];
+
+        let cases = [
+            ("{var}", "value"),
+            ("{hello}", "Hello%20World%21"),
+            ("{+var}", "value"),
+            ("{+hello}", "Hello%20World!"),
+            ("{+path}/here", "/foo/bar/here"),
+            ("here?ref={+path}", "here?ref=/foo/bar"),
+            ("X{#var}", "X#value"),
+            ("X{#hello}", "X#Hello%20World!"),
+            ("map?{x,y}", "map?1024,768"),
+            ("{x,hello,y}", "1024,Hello%20World%21,768"),
+            ("{+x,hello,y}", "1024,Hello%20World!,768"),
+            ("{+path,x}/here", "/foo/bar,1024/here"),
+            ("{#x,hello,y}", "#1024,Hello%20World!,768"),
+            ("X{.var}", "X.value"),
+            ("X{.x,y}", "X.1024.768"),
+            ("{/var}", "/value"),
+            ("{/var,x}/here", "/value/1024/here"),
+            ("{;x,y}", ";x=1024;y=768"),
+            ("{;x,y,empty}", ";x=1024;y=768;empty"),
+            ("{?x,y}", "?x=1024&y=768"),
+            ("{?x,y,empty}", "?x=1024&y=768&empty="),
+            ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+            ("{&x,y,empty}", "&x=1024&y=768&empty="),
+            ("{?undef}", ""),
+            ("{undef,x}", "1024"),
+        ];
+
+        for (template, expected) in &cases {
+            assert_eq!(expand(template, &vars).expect("To expand"), *expected, "template {}", template);
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_template() {
+        let vars: [(&str, &str); 0] = [];
+
+        assert!(matches!(expand("/items/{id", &vars), Err(TemplateError::Unclosed(7))));
+        assert!(matches!(expand("/{}", &vars), Err(TemplateError::InvalidExpression(1))));
+        assert!(matches!(expand("/{!id}", &vars), Err(TemplateError::InvalidExpression(1))));
+        assert!(matches!(expand("/{list*}", &vars), Err(TemplateError::Unsupported(1))));
+        assert!(matches!(expand("/{var:3}", &vars), Err(TemplateError::Unsupported(1))));
+    }
+}
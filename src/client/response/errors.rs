@@ -28,3 +28,63 @@ impl From<mime::FromStrError> for ContentTypeError {
         ContentTypeError::Mime(err)
     }
 }
+
+///Describes response with unsuccessful status.
+///
+///Created by [Response::error_for_status](../struct.Response.html#method.error_for_status)
+#[derive(Debug)]
+pub struct StatusError {
+    ///Status code.
+    pub status: http::StatusCode,
+    ///Response's headers.
+    ///
+    ///Boxed to keep error small.
+    pub headers: Box<http::HeaderMap>,
+    ///Beginning of response's body, if it has been read.
+    pub body: Option<bytes::Bytes>,
+}
+
+impl Error for StatusError {}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HTTP status error: {}", self.status)?;
+
+        if let Some(body) = self.body.as_ref() {
+            write!(f, ". Body: {}", String::from_utf8_lossy(body))?;
+        }
+
+        Ok(())
+    }
+}
+
+///Describes timeout of reading response's body.
+///
+///Created by [Response::timed](../struct.Response.html#method.timed)
+#[derive(Debug)]
+pub struct BodyTimeout {
+    ///Status code.
+    pub status: http::StatusCode,
+    ///Response's headers.
+    pub headers: http::HeaderMap,
+    ///Number of bytes received before timeout, as they were sent (i.e. before decompression).
+    pub read: usize,
+    ///Expected length of body, if known.
+    pub content_len: Option<usize>,
+    ///Timeout, that expired.
+    pub timeout: core::time::Duration,
+}
+
+impl Error for BodyTimeout {}
+
+impl fmt::Display for BodyTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Reading body of response with status {} timed out after {:?}. Received {} bytes", self.status, self.timeout, self.read)?;
+
+        if let Some(len) = self.content_len {
+            write!(f, " out of {}", len)?;
+        }
+
+        Ok(())
+    }
+}
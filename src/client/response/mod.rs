@@ -5,6 +5,7 @@ use core::str::FromStr;
 use core::future::Future;
 use core::mem;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 use crate::{extractor, header, upgrade};
 
@@ -12,6 +13,77 @@ pub mod errors;
 
 pub(crate) type HyperResponse = hyper::Response<hyper::Body>;
 
+#[derive(Debug, Clone, Copy)]
+///Extension, that specifies limit on body's size.
+///
+///Set by client from [Config::default_body_limit](../config/trait.Config.html#method.default_body_limit)
+pub struct BodyLimit(pub usize);
+
+#[derive(Debug, Clone, Copy)]
+///Extension, that specifies maximum capacity, allocated upfront, when body is read into memory.
+///
+///Set by client from [Config::body_buffer_cap](../config/trait.Config.html#method.body_buffer_cap)
+pub struct BufferCap(pub usize);
+
+#[derive(Debug, Clone, Copy)]
+///Extension, that specifies limit on ratio of decompressed body's size to its compressed size.
+///
+///Set by client from [Config::max_decompression_ratio](../config/trait.Config.html#method.max_decompression_ratio)
+pub struct MaxDecompressionRatio(pub Option<u32>);
+
+#[derive(Debug, Clone, Copy)]
+///Extension, that enables verification of body's length against `Content-Length`.
+///
+///Set by client, when [Config::verify_content_length](../config/trait.Config.html#method.verify_content_length)
+///is enabled, unless request's method is `HEAD`.
+pub struct VerifyContentLength;
+
+#[derive(Debug, Clone)]
+///Extension, that contains URI of request, which response is received for.
+///
+///Set by client. When redirects are followed, it is URI of the last request.
+pub struct RequestUri(pub hyper::Uri);
+
+#[derive(Debug, Clone)]
+///Extension, that contains URI of original request, before any redirect.
+///
+///Set by client.
+pub struct OriginalUri(pub hyper::Uri);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Class of response's status.
+pub enum StatusClass {
+    ///Status code in range 100 to 199
+    Informational,
+    ///Status code in range 200 to 299
+    Success,
+    ///Status code in range 300 to 399
+    Redirect,
+    ///Status code in range 400 to 499
+    ClientError,
+    ///Status code in range 500 to 599
+    ServerError,
+}
+
+impl StatusClass {
+    ///Returns class of status code.
+    pub fn from_status(status: http::StatusCode) -> Self {
+        match status.as_u16() {
+            0..=199 => StatusClass::Informational,
+            200..=299 => StatusClass::Success,
+            300..=399 => StatusClass::Redirect,
+            400..=499 => StatusClass::ClientError,
+            _ => StatusClass::ServerError,
+        }
+    }
+
+    #[inline]
+    ///Returns whether class indicates error.
+    pub fn is_error(self) -> bool {
+        matches!(self, StatusClass::ClientError | StatusClass::ServerError)
+    }
+}
+
 #[derive(Debug)]
 ///HTTP Response
 pub struct Response {
@@ -27,12 +99,30 @@ impl Response {
         }
     }
 
+    #[inline]
+    ///Returns underlying hyper response.
+    pub(crate) fn into_inner(self) -> HyperResponse {
+        self.inner
+    }
+
     #[inline]
     ///Retrieves status code
     pub fn status(&self) -> http::StatusCode {
         self.inner.status()
     }
 
+    #[inline]
+    ///Retrieves class of status code
+    pub fn status_class(&self) -> StatusClass {
+        StatusClass::from_status(self.inner.status())
+    }
+
+    #[inline]
+    ///Returns whether Response's status is equal to `code`
+    pub fn is_status<C: Into<u16>>(&self, code: C) -> bool {
+        self.inner.status().as_u16() == code.into()
+    }
+
     #[inline]
     ///Returns whether Response's status is informational.
     ///
@@ -62,7 +152,7 @@ impl Response {
     ///
     ///The response status code is in range 400 to 599
     pub fn is_error(&self) -> bool {
-        self.is_client_error() || self.is_internal_error()
+        self.status_class().is_error()
     }
 
     #[inline]
@@ -78,7 +168,47 @@ impl Response {
     ///
     ///The response status code is in range 500 to 599
     pub fn is_internal_error(&self) -> bool {
-        self.inner.status().is_client_error()
+        self.inner.status().is_server_error()
+    }
+
+    ///Turns response into error, if its status is either client or server error.
+    ///
+    ///Error contains status and headers of response, but not its body.
+    pub fn error_for_status(self) -> Result<Self, errors::StatusError> {
+        match self.is_error() {
+            true => {
+                let (parts, _) = self.inner.into_parts();
+                Err(errors::StatusError {
+                    status: parts.status,
+                    headers: Box::new(parts.headers),
+                    body: None,
+                })
+            },
+            false => Ok(self),
+        }
+    }
+
+    ///Turns response into error, if its status is either client or server error.
+    ///
+    ///Error contains status, headers and up to `limit` bytes of body.
+    ///Body is decompressed according to `Content-Encoding`, but no charset decoding is performed.
+    pub async fn error_for_status_body(mut self, limit: usize) -> Result<Self, errors::StatusError> {
+        if !self.is_error() {
+            return Ok(self);
+        }
+
+        let body = match matsu!(self.body_limited(Some(limit))) {
+            Ok(body) => Some(body),
+            Err(extractor::BodyReadError::Overflow(body)) => Some(body.slice(..core::cmp::min(limit, body.len()))),
+            Err(_) => None,
+        };
+
+        let (parts, _) = self.inner.into_parts();
+        Err(errors::StatusError {
+            status: parts.status,
+            headers: Box::new(parts.headers),
+            body,
+        })
     }
 
     #[inline]
@@ -87,6 +217,37 @@ impl Response {
         self.inner.status() == http::StatusCode::SWITCHING_PROTOCOLS
     }
 
+    #[inline]
+    ///Returns URI, that produced response.
+    ///
+    ///When redirects are followed, it is URI of the last request, which should be used to resolve relative links.
+    ///Returns `None`, if response is not received by client.
+    pub fn uri(&self) -> Option<&hyper::Uri> {
+        self.extensions().get::<RequestUri>().map(|uri| &uri.0)
+    }
+
+    #[inline]
+    ///Returns URI of original request, before any redirect.
+    ///
+    ///Returns `None`, if response is not received by client.
+    pub fn original_uri(&self) -> Option<&hyper::Uri> {
+        self.extensions().get::<OriginalUri>().map(|uri| &uri.0)
+    }
+
+    ///Returns effective URI of response, including fragment of request's URI.
+    ///
+    ///When redirects are followed, it is URI of the last request, with fragment of original URI,
+    ///unless `Location` specifies its own.
+    ///Returns `None`, if response is not received by client.
+    pub fn effective_uri(&self) -> Option<String> {
+        let uri = self.uri()?;
+
+        match self.extensions().get::<crate::client::request::Fragment>() {
+            Some(fragment) => Some(format!("{}#{}", uri, fragment.0)),
+            None => Some(uri.to_string()),
+        }
+    }
+
     #[inline]
     ///Retrieves reference to http extension map
     pub fn extensions(&self) -> &http::Extensions {
@@ -99,6 +260,14 @@ impl Response {
         self.inner.extensions_mut()
     }
 
+    #[inline]
+    ///Retrieves reference to context value of type `T`, propagated from request.
+    ///
+    ///See [Builder::with_ctx](../request/struct.Builder.html#method.with_ctx)
+    pub fn ctx<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions().get::<crate::client::request::Context>().and_then(|ctx| ctx.0.get())
+    }
+
     #[cfg(feature = "carry_extensions")]
     #[inline]
     ///Retrieves mutable reference to http extension map
@@ -107,12 +276,70 @@ impl Response {
         self
     }
 
+    #[inline]
+    ///Retrieves timings of request, if client collects them.
+    ///
+    ///See [Config::collect_timings](../config/trait.Config.html#method.collect_timings)
+    pub fn timings(&self) -> Option<&crate::connector::timings::Timings> {
+        self.extensions().get()
+    }
+
+    #[inline]
+    ///Returns number of body bytes, transferred within this exchange, if response is produced by client.
+    ///
+    ///Counters are updated as body is being read.
+    pub fn body_size(&self) -> Option<&extractor::BodySize> {
+        self.extensions().get()
+    }
+
     #[inline]
     ///Access response's headers
     pub fn headers(&self) -> &http::HeaderMap {
         self.inner.headers()
     }
 
+    ///Retrieves all values of header `name`, that are valid strings.
+    ///
+    ///Values are returned as they are, one per header's line.
+    pub fn header_all_str<K: header::AsHeaderName>(&self, name: K) -> Vec<&str> {
+        self.inner.headers().get_all(name).iter().filter_map(|value| value.to_str().ok()).collect()
+    }
+
+    ///Retrieves elements of comma-separated header `name`, combining all its lines.
+    ///
+    ///Elements are split by [split_list](../../header/fn.split_list.html), which respects quoted strings and comments.
+    ///Suitable for list headers, such as `Vary`, `Accept-Ranges` or `Via`.
+    ///
+    ///```rust
+    ///use yukikaze::client::Response;
+    ///
+    ///let response = hyper::Response::builder().header("vary", "Accept-Encoding, Origin")
+    ///                                         .header("vary", "User-Agent")
+    ///                                         .body(hyper::Body::empty())
+    ///                                         .expect("To create response");
+    ///let response = Response::new(response);
+    ///
+    ///assert_eq!(response.header_all_str("vary"), ["Accept-Encoding, Origin", "User-Agent"]);
+    ///assert_eq!(response.header_list("vary"), ["Accept-Encoding", "Origin", "User-Agent"]);
+    ///```
+    pub fn header_list<K: header::AsHeaderName>(&self, name: K) -> Vec<&str> {
+        self.inner.headers().get_all(name).iter()
+                            .filter_map(|value| value.to_str().ok())
+                            .flat_map(header::split_list)
+                            .collect()
+    }
+
+    #[cfg(feature = "typed_headers")]
+    #[inline]
+    ///Retrieves typed header, if valid one is present.
+    ///
+    ///Available only with feature `typed_headers`
+    pub fn typed_header<H: headers::Header>(&self) -> Option<H> {
+        use headers::HeaderMapExt;
+
+        self.headers().typed_get()
+    }
+
     #[inline]
     ///Retrieves `Content-Type` as Mime, if any.
     pub fn mime(&self) -> Result<Option<mime::Mime>, errors::ContentTypeError> {
@@ -126,6 +353,25 @@ impl Response {
         }
     }
 
+    ///Determines MIME type of content, sniffing `body` when `Content-Type` is not informative.
+    ///
+    ///If `Content-Type` is missing, invalid or `application/octet-stream`, type is detected
+    ///by [extractor::sniff_mime](../../extractor/fn.sniff_mime.html) from `body`, which is
+    ///already read body or its prefix. Otherwise `Content-Type` is returned as it is.
+    ///
+    ///```rust
+    ///use yukikaze::client::Response;
+    ///
+    ///let response = Response::new(http::Response::new(hyper::Body::empty()));
+    ///assert_eq!(response.sniff_mime(b"{\"name\": \"Yukikaze\"}"), Some(mime::APPLICATION_JSON));
+    ///```
+    pub fn sniff_mime(&self, body: &[u8]) -> Option<mime::Mime> {
+        match self.mime() {
+            Ok(Some(mime)) if mime.essence_str() != mime::APPLICATION_OCTET_STREAM.essence_str() => Some(mime),
+            _ => extractor::sniff_mime(body),
+        }
+    }
+
     #[cfg(feature = "encoding")]
     ///Retrieves content's charset encoding, if any.
     ///
@@ -152,6 +398,22 @@ impl Response {
                   .and_then(|header| header.parse().ok())
     }
 
+    #[inline]
+    ///Retrieves limit on body's size, used when reading it into memory.
+    ///
+    ///It is minimum of `Content-Length` and [BodyLimit](struct.BodyLimit.html), if any is present.
+    pub fn body_limit(&self) -> Option<usize> {
+        let len = self.content_len();
+
+        match self.extensions().get::<BodyLimit>() {
+            Some(limit) => match len {
+                Some(len) => Some(core::cmp::min(len, limit.0)),
+                None => Some(limit.0),
+            },
+            None => len,
+        }
+    }
+
     #[inline]
     ///Retrieves `Content-Encoding`, if header is not present `ContentEncoding::Idenity` is
     ///assumed.
@@ -174,8 +436,26 @@ impl Response {
 
     #[inline]
     ///Creates iterator of cookie from `Set-Cookie` header.
-    pub fn cookies_iter(&self) -> extractor::CookieIter {
-        extractor::CookieIter::new(self.headers().get_all(header::SET_COOKIE).iter())
+    ///
+    ///Cookies are decoded according to [Config::cookie_encoding](../config/trait.Config.html#method.cookie_encoding).
+    pub fn cookies_iter(&self) -> extractor::CookieIter<'_> {
+        let encoding = self.extensions().get::<header::CookieEncoding>().copied().unwrap_or_default();
+        extractor::CookieIter::with_encoding(self.headers().get_all(header::SET_COOKIE).iter(), encoding)
+    }
+
+    #[inline]
+    ///Creates iterator of cookie from `Set-Cookie` header, that tolerates invalid cookies.
+    ///
+    ///See [LenientCookieIter](../../extractor/struct.LenientCookieIter.html) for details.
+    pub fn cookies_lenient(&self) -> extractor::LenientCookieIter<'_> {
+        let encoding = self.extensions().get::<header::CookieEncoding>().copied().unwrap_or_default();
+        extractor::LenientCookieIter::new(self.headers().get_all(header::SET_COOKIE).iter(), encoding)
+    }
+
+    #[inline]
+    ///Returns untouched values of `Set-Cookie` header.
+    pub fn raw_cookies(&self) -> header::ValueIter<'_, header::HeaderValue> {
+        self.headers().get_all(header::SET_COOKIE).iter()
     }
 
     #[inline]
@@ -218,12 +498,31 @@ impl Response {
                             .and_then(|header| header.trim().parse().ok())
     }
 
+    #[cfg(feature = "checksum")]
+    #[inline]
+    ///Retrieves checksum from `Digest` header, if supported one is present, or from `Content-MD5` header otherwise.
+    ///
+    ///Available only with feature `checksum`
+    pub fn digest_checksum(&self) -> Option<extractor::Checksum> {
+        self.inner.headers().get("digest")
+                            .and_then(|header| header.to_str().ok())
+                            .and_then(extractor::Checksum::from_digest_header)
+                            .or_else(|| self.inner.headers().get("content-md5")
+                                                            .and_then(|header| header.to_str().ok())
+                                                            .and_then(extractor::Checksum::from_content_md5))
+    }
+
     #[inline]
     ///Extracts hyper's body out of Response.
     ///
     ///Returns Content encoding, Length, if specified, and body itself.
+    ///
+    ///If request disabled decompression, encoding is always `ContentEncoding::Identity`
     pub fn extract_body(&mut self) -> (header::ContentEncoding, Option<usize>, hyper::Body) {
-        let encoding = self.content_encoding();
+        let encoding = match self.extensions().get::<crate::client::request::NoDecompress>() {
+            Some(_) => header::ContentEncoding::Identity,
+            None => self.content_encoding(),
+        };
         let buffer_size = self.content_len();
         let mut body = hyper::Body::empty();
 
@@ -232,42 +531,203 @@ impl Response {
         (encoding, buffer_size, body)
     }
 
+    ///Extracts body, that is accounted in `BodySize`, as it is read, together with its decompression.
+    fn extract_sized_body(&mut self) -> (extractor::Decompress, extractor::SizedBody<hyper::Body>) {
+        let size = self.body_size().cloned();
+        let max_ratio = match self.extensions().get::<MaxDecompressionRatio>() {
+            Some(ratio) => ratio.0,
+            None => Some(extractor::DEFAULT_MAX_RATIO),
+        };
+        let expected_len = self.expected_len();
+        let (encoding, _, body) = self.extract_body();
+
+        let body = extractor::SizedBody::raw(body, encoding, size.clone()).expected_len(expected_len);
+        let decompress = extractor::Decompress {
+            encoding,
+            max_ratio,
+            size,
+        };
+
+        (decompress, body)
+    }
+
+    ///Returns length, that body is verified against, if verification is enabled.
+    fn expected_len(&self) -> Option<u64> {
+        match self.status() {
+            http::StatusCode::NO_CONTENT | http::StatusCode::NOT_MODIFIED => None,
+            _ => match self.extensions().get::<VerifyContentLength>() {
+                Some(_) => self.content_len().map(|len| len as u64),
+                None => None,
+            },
+        }
+    }
+
     ///Extracts Response's body as raw bytes.
     pub fn body(&mut self) -> impl Future<Output=Result<bytes::Bytes, extractor::BodyReadError>> {
-        let (encoding, buffer_size, body) = self.extract_body();
+        let limit = self.body_limit();
+        self.body_limited(limit)
+    }
+
+    ///Extracts Response's body as raw bytes, using specified limit instead of `Content-Length`
+    ///
+    ///If limit is `None`, default 4kb is used.
+    ///
+    ///Buffer's initial capacity is limited by [BufferCap](struct.BufferCap.html), if present.
+    pub fn body_limited(&mut self, limit: Option<usize>) -> impl Future<Output=Result<bytes::Bytes, extractor::BodyReadError>> {
+        let cap = self.extensions().get::<BufferCap>().map(|cap| cap.0).unwrap_or(extractor::DEFAULT_BUFFER_CAP);
+        let (decompress, body) = self.extract_sized_body();
 
-        extractor::raw_bytes(body, encoding, buffer_size)
+        extractor::raw_bytes_capped_impl(body, decompress, limit, cap)
+    }
+
+    ///Extracts Response's body as chunks, in which it is received, without coalescing them.
+    ///
+    ///Suitable for consumers, that do not need contiguous buffer, as it avoids copying data.
+    ///Body is decompressed and limited the same way as [body](#method.body).
+    pub fn body_chunks(&mut self) -> impl Future<Output=Result<Vec<bytes::Bytes>, extractor::BodyReadError>> {
+        let limit = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::raw_chunks(body.decompress(&decompress), limit)
+    }
+
+    ///Extracts Response's body as raw bytes, detecting compression by magic bytes if `Content-Encoding` is absent.
+    ///
+    ///See [extractor::raw_bytes_sniffed](../../extractor/fn.raw_bytes_sniffed.html) for details.
+    pub fn body_sniffed(&mut self) -> impl Future<Output=Result<extractor::Sniffed, extractor::BodyReadError>> {
+        let limit = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::raw_bytes_sniffed_impl(body, decompress, limit)
+    }
+
+    ///Extracts Response's body as bytes, returning the rest of body, if limit is hit.
+    ///
+    ///Body is decompressed the same way as [body](#method.body), and so is the rest of it.
+    pub fn body_or_rest(&mut self) -> impl Future<Output=Result<Result<bytes::Bytes, extractor::OverflowWithRest<extractor::SizedBody<hyper::Body>>>, extractor::BodyReadError>> {
+        let limit = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::raw_bytes_or_rest(body.decompress(&decompress), limit)
     }
 
     ///Extracts Response's body as text
     pub fn text(&mut self) -> impl Future<Output=Result<String, extractor::BodyReadError>> {
-        let (encoding, buffer_size, body) = self.extract_body();
+        let limit = self.body_limit();
+        self.text_limited(limit)
+    }
+
+    ///Extracts Response's body as text, using specified limit instead of `Content-Length`
+    ///
+    ///If limit is `None`, default 4kb is used.
+    pub fn text_limited(&mut self, buffer_size: Option<usize>) -> impl Future<Output=Result<String, extractor::BodyReadError>> {
+        let (decompress, body) = self.extract_sized_body();
 
         #[cfg(feature = "encoding")]
         {
             let charset = self.charset_encoding().unwrap_or(encoding_rs::UTF_8);
-            extractor::text_charset(body, encoding, buffer_size, charset)
+            extractor::text_charset_impl(body, decompress, buffer_size, charset)
         }
 
         #[cfg(not(feature = "encoding"))]
         {
-            extractor::text(body, encoding, buffer_size)
+            extractor::text_impl(body, decompress, buffer_size)
         }
     }
 
     ///Extracts Response's body as JSON
     pub fn json<J: serde::de::DeserializeOwned>(&mut self) -> impl Future<Output=Result<J, extractor::BodyReadError>> {
-        let (encoding, buffer_size, body) = self.extract_body();
+        let limit = self.body_limit();
+        self.json_limited(limit)
+    }
+
+    ///Extracts Response's body as JSON, using specified limit instead of `Content-Length`
+    ///
+    ///If limit is `None`, default 4kb is used.
+    pub fn json_limited<J: serde::de::DeserializeOwned>(&mut self, buffer_size: Option<usize>) -> impl Future<Output=Result<J, extractor::BodyReadError>> {
+        let (decompress, body) = self.extract_sized_body();
 
         #[cfg(feature = "encoding")]
         {
             let charset = self.charset_encoding().unwrap_or(encoding_rs::UTF_8);
-            extractor::json_charset(body, encoding, buffer_size, charset)
+            extractor::json_charset_impl(body, decompress, buffer_size, charset)
         }
 
         #[cfg(not(feature = "encoding"))]
         {
-            extractor::json(body, encoding, buffer_size)
+            extractor::json_impl(body, decompress, buffer_size)
+        }
+    }
+
+    ///Extracts Response's body as stream of top-level JSON array's elements.
+    ///
+    ///Each element is deserialized as soon as it is received, hence memory is bounded by size of single element,
+    ///which is limited by [BodyLimit](struct.BodyLimit.html), if present, or by [ELEMENT_LIMIT](../../extractor/constant.ELEMENT_LIMIT.html).
+    ///
+    ///Body is expected to be UTF-8, as required by JSON, regardless of charset.
+    pub fn json_array_stream<J: serde::de::DeserializeOwned>(&mut self) -> extractor::JsonArrayStream<hyper::Body, J> {
+        let limit = self.extensions().get::<BodyLimit>().map(|limit| limit.0);
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::JsonArrayStream::new(body.decompress(&decompress), limit)
+    }
+
+    ///Extracts raw JSON value, located by [RFC6901](https://tools.ietf.org/html/rfc6901) JSON `pointer`, from Response's body.
+    ///
+    ///Body is scanned incrementally and reading stops once value is found, without deserializing whole document.
+    ///Size of value is limited by [BodyLimit](struct.BodyLimit.html), if present, or by [ELEMENT_LIMIT](../../extractor/constant.ELEMENT_LIMIT.html).
+    ///
+    ///Returns `None`, if there is no such value. Use [serde_json::from_slice](https://docs.rs/serde_json/1/serde_json/fn.from_slice.html) to deserialize it.
+    pub fn json_pointer<'a>(&mut self, pointer: &'a str) -> impl Future<Output=Result<Option<bytes::Bytes>, extractor::BodyReadError>> + 'a {
+        let limit = self.extensions().get::<BodyLimit>().map(|limit| limit.0);
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::json_pointer_impl(body, decompress, pointer, limit)
+    }
+
+    ///Extracts Response's body as multipart, such as `multipart/form-data`.
+    ///
+    ///Body is read into memory entirely, hence it is limited as [body](#method.body).
+    ///Fails with `MultipartError::NotMultipart`, if `Content-Type` is not multipart with boundary.
+    pub fn multipart(&mut self) -> impl Future<Output=Result<extractor::Multipart, extractor::BodyReadError>> {
+        let boundary = extractor::multipart_boundary(self.mime().ok().flatten().as_ref());
+        let body = self.body();
+
+        async move {
+            let boundary = boundary?;
+            let body = matsu!(body)?;
+            extractor::Multipart::parse(body, &boundary).map_err(extractor::BodyReadError::from)
+        }
+    }
+
+    #[cfg(feature = "nested_form")]
+    ///Extracts Response's body as nested form, i.e. `a[b]=1&tags[0]=x`.
+    ///
+    ///Available only with feature `nested_form`
+    pub fn form_nested<F: serde::de::DeserializeOwned>(&mut self) -> impl Future<Output=Result<F, extractor::BodyReadError>> {
+        let buffer_size = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::form_nested_impl(body, decompress, buffer_size)
+    }
+
+    ///Extracts Response's body as JSON, keeping raw body.
+    ///
+    ///Returns result of parsing together with raw bytes, which can be used when parsing fails.
+    ///Fails only if unable to read body.
+    pub fn json_with_raw<J: serde::de::DeserializeOwned>(&mut self) -> impl Future<Output=Result<(Result<J, extractor::BodyReadError>, bytes::Bytes), extractor::BodyReadError>> {
+        let buffer_size = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
+
+        #[cfg(feature = "encoding")]
+        {
+            let charset = self.charset_encoding().unwrap_or(encoding_rs::UTF_8);
+            extractor::json_charset_with_raw_impl(body, decompress, buffer_size, charset)
+        }
+
+        #[cfg(not(feature = "encoding"))]
+        {
+            extractor::json_with_raw_impl(body, decompress, buffer_size)
         }
     }
 
@@ -279,47 +739,221 @@ impl Response {
             debug_assert!(!meta.permissions().readonly(), "File is read-only");
         }
 
-        let (encoding, _, body) = self.extract_body();
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::file_impl(file, body, decompress)
+    }
+
+    #[cfg(feature = "encoding")]
+    ///Extracts Response's body into file, transcoding text into UTF-8.
+    ///
+    ///Charset is taken from `Content-Type`, and if it is omitted or is UTF-8 already,
+    ///body is written as it is. Therefore binary content is never altered.
+    ///
+    ///Available only with feature `encoding`
+    pub fn file_utf8(&mut self, file: fs::File) -> impl Future<Output=Result<fs::File, extractor::BodyReadError>> {
+        #[cfg(debug_assertions)]
+        {
+            let meta = file.metadata().expect("To be able to get metadata");
+            debug_assert!(!meta.permissions().readonly(), "File is read-only");
+        }
+
+        let charset = self.charset_encoding().unwrap_or(encoding_rs::UTF_8);
+        let (decompress, body) = self.extract_sized_body();
 
-        extractor::file(file, body, encoding)
+        extractor::file_charset_impl(file, body, decompress, charset)
+    }
+
+    ///Extracts Response's body into file at `path`, replacing it only on success.
+    ///
+    ///See [extractor::file_atomic](../../extractor/fn.file_atomic.html) for details.
+    pub fn file_atomic<'a>(&mut self, path: &'a Path) -> impl Future<Output=Result<fs::File, extractor::BodyReadError>> + 'a {
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::file_atomic_impl(path, body, decompress)
+    }
+
+    ///Returns name of file to save body, derived from `Content-Disposition` or last segment of request's URI.
+    ///
+    ///Name is sanitized by [Filename::file_name_os](../../header/enum.Filename.html#method.file_name_os),
+    ///hence it is safe to join with directory.
+    fn file_name(&self) -> Option<std::ffi::OsString> {
+        let name = match self.content_disposition() {
+            Some(header::ContentDisposition::Attachment(filename)) | Some(header::ContentDisposition::FormData(_, filename)) => filename.file_name_os(),
+            _ => None,
+        };
+
+        name.or_else(|| {
+            let segment = self.uri()?.path().rsplit('/').next()?;
+            //Segment is percent encoded, same as extended file name.
+            header::Filename::with_extended(None, segment.to_owned()).file_name_os()
+        })
+    }
+
+    ///Extracts Response's body into file within `dir`, returning path to the file.
+    ///
+    ///File name is taken from `Content-Disposition`, or from last segment of request's URI,
+    ///and falls back to `download`. Path traversal is not possible, as name is
+    ///[sanitized](../../header/enum.Filename.html#method.sanitized).
+    ///
+    ///Directory is created if it doesn't exist. Existing file is overwritten,
+    ///only once whole body is written, same as [file_atomic](#method.file_atomic).
+    pub async fn save_to_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<PathBuf, extractor::BodyReadError> {
+        let name = self.file_name().unwrap_or_else(|| "download".into());
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let path = dir.join(name);
+        matsu!(self.file_atomic(&path))?;
+
+        Ok(path)
+    }
+
+    #[cfg(feature = "checksum")]
+    ///Computes hex encoded digest of Response's body, without storing it in memory.
+    ///
+    ///Digest is computed over decompressed body.
+    ///
+    ///Available only with feature `checksum`
+    pub fn digest(&mut self, algorithm: extractor::Algorithm) -> impl Future<Output=Result<String, extractor::BodyReadError>> {
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::digest_impl(body, decompress, algorithm)
+    }
+
+    #[cfg(feature = "checksum")]
+    ///Extracts Response's body as raw bytes, verifying its checksum.
+    ///
+    ///Checksum is computed over body as it is received, before decompression.
+    ///
+    ///Available only with feature `checksum`
+    pub fn body_checksum(&mut self, checksum: extractor::Checksum) -> impl Future<Output=Result<bytes::Bytes, extractor::BodyReadError>> {
+        let limit = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::raw_bytes_checksum_impl(body, decompress, limit, checksum)
+    }
+
+    #[cfg(feature = "checksum")]
+    ///Extracts Response's body into file, verifying its checksum.
+    ///
+    ///Checksum is computed over body as it is received, before decompression.
+    ///
+    ///Available only with feature `checksum`
+    pub fn file_checksum(&mut self, file: fs::File, checksum: extractor::Checksum) -> impl Future<Output=Result<fs::File, extractor::BodyReadError>> {
+        #[cfg(debug_assertions)]
+        {
+            let meta = file.metadata().expect("To be able to get metadata");
+            debug_assert!(!meta.permissions().readonly(), "File is read-only");
+        }
+
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::file_checksum_impl(file, body, decompress, checksum)
+    }
+
+    ///Extracts Response's body, keeping it in memory unless it exceeds `threshold`.
+    ///
+    ///See [extractor::spooled](../../extractor/fn.spooled.html) for details.
+    pub fn body_spooled(&mut self, threshold: usize) -> impl Future<Output=Result<extractor::Spooled, extractor::BodyReadError>> {
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::spooled_impl(body, decompress, threshold)
+    }
+
+    ///Extracts Response's body into file, while also returning it as raw bytes.
+    ///
+    ///Limit on bytes in memory is the same as for [body](#method.body).
+    ///If body exceeds it, whole body is still written to file, but no bytes are returned.
+    pub fn body_tee(&mut self, file: fs::File) -> impl Future<Output=Result<(fs::File, Option<bytes::Bytes>), extractor::BodyReadError>> {
+        #[cfg(debug_assertions)]
+        {
+            let meta = file.metadata().expect("To be able to get metadata");
+            debug_assert!(!meta.permissions().readonly(), "File is read-only");
+        }
+
+        let limit = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
+
+        extractor::tee_impl(file, body, decompress, limit)
+    }
+
+    ///Reads Response's body within `timeout`, using provided extractor.
+    ///
+    ///Extractor receives [Progress](../../extractor/struct.Progress.html), which should be passed to one of `*_notify` methods.
+    ///On timeout, returns error with response's status, headers and number of bytes received so far.
+    ///
+    ///## Usage
+    ///
+    ///```rust, no_run
+    ///use yukikaze::{matsu, client};
+    ///
+    ///async fn example(mut response: client::Response) {
+    ///    let timeout = core::time::Duration::from_secs(5);
+    ///    match matsu!(response.timed(timeout, |response, progress| response.text_notify(progress))) {
+    ///        Ok(text) => println!("Body: {:?}", text),
+    ///        Err(error) => println!("Received only {} bytes", error.read),
+    ///    }
+    ///}
+    ///```
+    pub async fn timed<T, F, R>(&mut self, timeout: core::time::Duration, extract: F) -> Result<T, errors::BodyTimeout>
+        where F: FnOnce(&mut Self, extractor::Progress) -> R, R: Future<Output=T>
+    {
+        let progress = extractor::Progress::new();
+        let content_len = self.content_len();
+        let ongoing = extract(self, progress.clone());
+
+        match matsu!(async_timer::timed(ongoing, timeout)) {
+            Ok(result) => Ok(result),
+            Err(_) => Err(errors::BodyTimeout {
+                status: self.status(),
+                headers: self.headers().clone(),
+                read: progress.get(),
+                content_len,
+                timeout,
+            }),
+        }
     }
 
     ///Extracts Response's body as raw bytes.
     pub fn body_notify<N: extractor::Notifier>(&mut self, notify: N) -> impl Future<Output=Result<bytes::Bytes, extractor::BodyReadError>> {
-        let (encoding, buffer_size, body) = self.extract_body();
+        let buffer_size = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
 
-        extractor::raw_bytes_notify(body, encoding, buffer_size, notify)
+        extractor::raw_bytes_notify_impl(body, decompress, buffer_size, notify)
     }
 
     ///Extracts Response's body as text
     pub fn text_notify<N: extractor::Notifier>(&mut self, notify: N) -> impl Future<Output=Result<String, extractor::BodyReadError>> {
-        let (encoding, buffer_size, body) = self.extract_body();
+        let buffer_size = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
 
         #[cfg(feature = "encoding")]
         {
             let charset = self.charset_encoding().unwrap_or(encoding_rs::UTF_8);
-            extractor::text_charset_notify(body, encoding, buffer_size, charset, notify)
+            extractor::text_charset_notify_impl(body, decompress, buffer_size, charset, notify)
         }
 
         #[cfg(not(feature = "encoding"))]
         {
-            extractor::text_notify(body, encoding, buffer_size, notify)
+            extractor::text_notify_impl(body, decompress, buffer_size, notify)
         }
     }
 
     ///Extracts Response's body as JSON
     pub fn json_notify<N: extractor::Notifier, J: serde::de::DeserializeOwned>(&mut self, notify: N) -> impl Future<Output=Result<J, extractor::BodyReadError>> {
-        let (encoding, buffer_size, body) = self.extract_body();
+        let buffer_size = self.body_limit();
+        let (decompress, body) = self.extract_sized_body();
 
         #[cfg(feature = "encoding")]
         {
             let charset = self.charset_encoding().unwrap_or(encoding_rs::UTF_8);
-            extractor::json_charset_notify(body, encoding, buffer_size, charset, notify)
+            extractor::json_charset_notify_impl(body, decompress, buffer_size, charset, notify)
         }
 
         #[cfg(not(feature = "encoding"))]
         {
-            extractor::json_notify(body, encoding, buffer_size, notify)
+            extractor::json_notify_impl(body, decompress, buffer_size, notify)
         }
     }
 
@@ -331,21 +965,24 @@ impl Response {
             debug_assert!(!meta.permissions().readonly(), "File is read-only");
         }
 
-        let (encoding, _, body) = self.extract_body();
+        let (decompress, body) = self.extract_sized_body();
 
-        extractor::file_notify(file, body, encoding, notify)
+        extractor::file_notify_impl(file, body, decompress, notify)
     }
 
 
     ///Finalize upgrade procedure, initiated from request, if any.
-    pub async fn upgrade<U: upgrade::Upgrade>(self, _: U) -> Result<Result<(Self, hyper::upgrade::Upgraded), hyper::Error>, U::VerifyError> {
-        if let Err(error) = U::verify_response(self.status(), self.inner.headers(), self.inner.extensions()) {
-            return Err(error);
-        }
+    ///
+    ///Response is verified by the same upgrader, that has been used to prepare request.
+    ///On success returns response, outcome of verification and upgraded stream.
+    pub async fn upgrade<U: upgrade::Upgrade>(mut self, upgrade: &U) -> Result<Result<(Self, U::Output, upgrade::Upgraded), hyper::Error>, U::VerifyError> {
+        let output = upgrade.verify_response(self.status(), self.inner.headers(), self.inner.extensions())?;
 
+        //Stream keeps request in flight, instead of response
+        let in_flight = self.inner.extensions_mut().remove::<crate::client::shutdown::InFlight>();
         let (head, body) = self.inner.into_parts();
         Ok(match matsu!(upgrade::upgrade_response(head, body.on_upgrade())) {
-            Ok((hyper, body)) => Ok((Self::new(hyper), body)),
+            Ok((hyper, body)) => Ok((Self::new(hyper), output, upgrade::Upgraded::new(body, in_flight))),
             Err(err) => Err(err),
         })
     }
@@ -0,0 +1,70 @@
+//!Tracking of in-flight requests for graceful shutdown.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+#[derive(Default)]
+pub(crate) struct Lifecycle {
+    closed: AtomicBool,
+    in_flight: AtomicUsize,
+}
+
+impl Lifecycle {
+    ///Registers new request, unless client is shut down.
+    pub(crate) fn start(self: &Arc<Self>) -> Option<InFlight> {
+        //Counter must be incremented before check, so that shutdown never misses request.
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlight(self.clone());
+
+        match self.closed.load(Ordering::SeqCst) {
+            true => None,
+            false => Some(guard),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    #[inline]
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    #[inline]
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+}
+
+///Request, that is in flight until response and its body are dropped.
+pub(crate) struct InFlight(Arc<Lifecycle>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lifecycle;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn should_reject_after_close() {
+        let lifecycle = Arc::new(Lifecycle::default());
+
+        let guard = lifecycle.start().expect("To start request");
+        assert_eq!(lifecycle.in_flight(), 1);
+
+        lifecycle.close();
+        assert!(lifecycle.start().is_none());
+        assert_eq!(lifecycle.in_flight(), 1);
+
+        drop(guard);
+        assert_eq!(lifecycle.in_flight(), 0);
+    }
+}
@@ -0,0 +1,384 @@
+//!Multipart upload to S3 compatible storage.
+//!
+//!Upload is performed as described in [S3 API](https://docs.aws.amazon.com/AmazonS3/latest/userguide/mpuoverview.html):
+//!
+//!- Upload is initiated by `POST <object>?uploads`, which returns upload's id;
+//!- Each part is sent as `PUT <object>?partNumber=<n>&uploadId=<id>`;
+//!- Upload is completed by `POST <object>?uploadId=<id>` with list of parts' ETags.
+//!
+//!On failure, upload is aborted by `DELETE <object>?uploadId=<id>`, so that storage discards uploaded parts.
+//!
+//!Requests are not signed, so credentials should be either part of pre-signed URL or
+//!added by [Config::finalize_request](../config/trait.Config.html#method.finalize_request).
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::{matsu, client};
+//!use yukikaze::client::upload;
+//!
+//!async fn backup<R: tokio::io::AsyncRead + Unpin>(archive: R) {
+//!    let client = client::Client::default();
+//!
+//!    let result = matsu!(upload::multipart_s3(&client, "https://bucket.s3.amazonaws.com/backup.tar", archive)).expect("To upload");
+//!    println!("Uploaded {} bytes in {} parts", result.size, result.parts.len());
+//!}
+//!```
+
+use core::{fmt, time};
+use core::future::Future;
+use core::task::Poll;
+use std::error::Error;
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+
+use super::{config, request, response, Client};
+use crate::extractor::BodyReadError;
+use crate::header;
+
+///Maximum number of parts in single upload.
+pub const MAX_PARTS: u32 = 10_000;
+
+const ERROR_BODY_LIMIT: usize = 1024;
+
+#[derive(Debug, Clone)]
+///Options of multipart upload.
+pub struct S3Options {
+    ///Size of each part, except the last one.
+    ///
+    ///S3 requires parts to be at least 5MiB. Defaults to 8MiB.
+    pub part_size: usize,
+    ///Maximum number of parts uploaded concurrently. Defaults to 4.
+    pub parallelism: usize,
+    ///Number of times to retry part, which upload failed due to network error, timeout or server error. Defaults to 3.
+    pub retries: usize,
+    ///Delay before each retry. Defaults to 500ms.
+    pub retry_delay: time::Duration,
+}
+
+impl Default for S3Options {
+    fn default() -> Self {
+        Self {
+            part_size: 8 * 1024 * 1024,
+            parallelism: 4,
+            retries: 3,
+            retry_delay: time::Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+///Uploaded part.
+pub struct Part {
+    ///Part's number, starting from 1.
+    pub number: u32,
+    ///Part's `ETag`, as it is returned by storage.
+    pub etag: String,
+    ///Part's size.
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+///Completed multipart upload.
+pub struct S3Upload {
+    ///Upload's id.
+    pub upload_id: String,
+    ///Uploaded parts, ordered by number.
+    pub parts: Vec<Part>,
+    ///Total size of uploaded object.
+    pub size: u64,
+    ///`ETag` of object, if storage returns it.
+    pub etag: Option<String>,
+}
+
+#[derive(Debug)]
+///Describes failure of multipart upload.
+pub enum UploadError {
+    ///Failed to perform request.
+    Call(super::errors::CallError),
+    ///Failed to read response's body.
+    Body(BodyReadError),
+    ///Failed to read data to upload.
+    Io(io::Error),
+    ///Response to initiation has no `UploadId`.
+    MissingUploadId,
+    ///Response to part's upload has no `ETag`.
+    MissingEtag(u32),
+    ///Data requires more than [MAX_PARTS](constant.MAX_PARTS.html) parts.
+    TooManyParts,
+    ///Completion is rejected with error in body, despite successful status.
+    Rejected(String),
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UploadError::Call(error) => fmt::Display::fmt(error, f),
+            UploadError::Body(error) => fmt::Display::fmt(error, f),
+            UploadError::Io(error) => write!(f, "Failed to read data: {}", error),
+            UploadError::MissingUploadId => f.write_str("Storage returned no UploadId"),
+            UploadError::MissingEtag(number) => write!(f, "Storage returned no ETag for part {}", number),
+            UploadError::TooManyParts => write!(f, "Upload exceeds {} parts", MAX_PARTS),
+            UploadError::Rejected(error) => write!(f, "Storage rejected completion: {}", error),
+        }
+    }
+}
+
+impl Error for UploadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UploadError::Call(error) => Some(error),
+            UploadError::Body(error) => Some(error),
+            UploadError::Io(error) => Some(error),
+            UploadError::MissingUploadId | UploadError::MissingEtag(_) | UploadError::TooManyParts | UploadError::Rejected(_) => None,
+        }
+    }
+}
+
+impl_call_error!(UploadError);
+
+impl From<BodyReadError> for UploadError {
+    #[inline]
+    fn from(error: BodyReadError) -> Self {
+        UploadError::Body(error)
+    }
+}
+
+impl From<io::Error> for UploadError {
+    #[inline]
+    fn from(error: io::Error) -> Self {
+        UploadError::Io(error)
+    }
+}
+
+impl UploadError {
+    ///Returns whether part's upload can be retried after this error.
+    fn is_transient(&self) -> bool {
+        match self {
+            UploadError::Call(super::errors::CallError::Status(error)) => error.status.is_server_error(),
+            UploadError::Call(_) | UploadError::Body(_) => true,
+            _ => false,
+        }
+    }
+}
+
+///Returns unescaped text of first element `tag` in XML `text`.
+fn xml_value(text: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+
+    let start = text.find(&open)? + open.len();
+    let end = start + text[start..].find(&close)?;
+    let value = text[start..end].trim().replace("&quot;", "\"")
+                                       .replace("&apos;", "'")
+                                       .replace("&lt;", "<")
+                                       .replace("&gt;", ">")
+                                       .replace("&amp;", "&");
+    Some(value)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+///Sends request within [Client::timeout](../struct.Client.html#method.timeout), failing on unsuccessful status.
+async fn send<C: config::Config>(client: &Client<C>, req: request::Request) -> Result<response::Response, UploadError>
+    where <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    let ongoing = async move {
+        let response = matsu!(client.request(req))?;
+        matsu!(response.error_for_status_body(ERROR_BODY_LIMIT)).map_err(UploadError::from)
+    };
+
+    matsu!(client.with_timeout(ongoing)).unwrap_or(Err(super::errors::CallError::Timeout.into()))
+}
+
+///Reads up to `size` bytes, stopping early only at the end of `reader`.
+async fn read_part<R: AsyncRead + Unpin>(reader: &mut R, size: usize) -> io::Result<bytes::Bytes> {
+    let mut part = Vec::with_capacity(size);
+    let mut taken = reader.take(size as u64);
+    matsu!(taken.read_to_end(&mut part))?;
+    Ok(part.into())
+}
+
+async fn initiate<C: config::Config>(client: &Client<C>, uri: &str) -> Result<String, UploadError>
+    where <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    let req = request::Request::post(uri)?.append_query_pair("uploads", "").empty();
+    let mut response = matsu!(send(client, req))?;
+    let body = matsu!(response.text())?;
+    xml_value(&body, "UploadId").filter(|id| !id.is_empty()).ok_or(UploadError::MissingUploadId)
+}
+
+async fn upload_part<C: config::Config>(client: &Client<C>, uri: &str, upload_id: &str, number: u32, data: bytes::Bytes, options: &S3Options) -> Result<Part, UploadError>
+    where <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    use async_timer::Oneshot;
+
+    let mut attempt = 0;
+    loop {
+        let req = request::Request::put(uri)?.append_query_pair("partNumber", number.to_string())
+                                             .append_query_pair("uploadId", upload_id)
+                                             .body(Some(data.clone()));
+
+        let error = match matsu!(send(client, req)) {
+            Ok(response) => {
+                let etag = response.headers().get(header::ETAG).and_then(|etag| etag.to_str().ok());
+                return match etag {
+                    Some(etag) => Ok(Part {
+                        number,
+                        etag: etag.to_owned(),
+                        size: data.len(),
+                    }),
+                    None => Err(UploadError::MissingEtag(number)),
+                };
+            },
+            Err(error) => error,
+        };
+
+        if attempt >= options.retries || !error.is_transient() {
+            return Err(error);
+        }
+
+        attempt += 1;
+        if options.retry_delay != time::Duration::from_secs(0) {
+            matsu!(C::Timer::new(options.retry_delay));
+        }
+    }
+}
+
+async fn complete<C: config::Config>(client: &Client<C>, uri: &str, upload_id: &str, parts: &[Part]) -> Result<Option<String>, UploadError>
+    where <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    let mut body = String::from("<CompleteMultipartUpload>");
+    for part in parts {
+        body.push_str(&format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", part.number, escape_xml(&part.etag)));
+    }
+    body.push_str("</CompleteMultipartUpload>");
+
+    let req = request::Request::post(uri)?.append_query_pair("uploadId", upload_id)
+                                          .set_header(header::CONTENT_TYPE, "application/xml")
+                                          .body(Some(body));
+    let mut response = matsu!(send(client, req))?;
+    let body = matsu!(response.text())?;
+
+    //Storage may report failure of completion within successful response
+    match body.contains("<Error>") {
+        true => Err(UploadError::Rejected(xml_value(&body, "Message").or_else(|| xml_value(&body, "Code")).unwrap_or(body))),
+        false => Ok(xml_value(&body, "ETag")),
+    }
+}
+
+async fn abort<C: config::Config>(client: &Client<C>, uri: &str, upload_id: &str)
+    where <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    if let Ok(req) = request::Request::delete(uri) {
+        let req = req.append_query_pair("uploadId", upload_id).empty();
+        let _ = matsu!(send(client, req));
+    }
+}
+
+///Uploads data from `reader` to object at `bucket_url` by multipart upload, with default [options](struct.S3Options.html).
+///
+///See [multipart_s3_with](fn.multipart_s3_with.html).
+pub async fn multipart_s3<C: config::Config, R: AsyncRead + Unpin>(client: &Client<C>, bucket_url: &str, reader: R) -> Result<S3Upload, UploadError>
+    where <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    matsu!(multipart_s3_with(client, bucket_url, reader, &S3Options::default()))
+}
+
+///Uploads data from `reader` to object at `bucket_url` by multipart upload.
+///
+///`bucket_url` is URL of object, i.e. it includes object's key.
+///
+///Data is split into parts of [S3Options::part_size](struct.S3Options.html#structfield.part_size),
+///which are uploaded concurrently, while each part is held in memory until it is uploaded.
+///Each request is limited by [Client::timeout](../struct.Client.html#method.timeout), without following redirects.
+///
+///Empty data is uploaded as single empty part.
+///If any part fails after all retries, upload is aborted and error is returned.
+pub async fn multipart_s3_with<C: config::Config, R: AsyncRead + Unpin>(client: &Client<C>, bucket_url: &str, mut reader: R, options: &S3Options) -> Result<S3Upload, UploadError>
+    where <C::Connector as hyper::service::Service<hyper::Uri>>::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Future: Send + Unpin,
+          <C::Connector as hyper::service::Service<hyper::Uri>>::Response: AsyncRead + AsyncWrite + hyper::client::connect::Connection + Unpin + Send
+{
+    assert_ne!(options.part_size, 0, "Part size cannot be zero");
+
+    let upload_id = matsu!(initiate(client, bucket_url))?;
+    let parallelism = core::cmp::max(options.parallelism, 1);
+
+    let uploading = async {
+        let mut parts: Vec<Part> = Vec::new();
+        let mut ongoing = Vec::new();
+        let mut number = 0;
+        let mut is_eof = false;
+
+        loop {
+            while !is_eof && ongoing.len() < parallelism {
+                let data = matsu!(read_part(&mut reader, options.part_size))?;
+                is_eof = data.len() < options.part_size;
+                if data.is_empty() && number > 0 {
+                    break;
+                } else if number == MAX_PARTS {
+                    return Err(UploadError::TooManyParts);
+                }
+
+                number += 1;
+                ongoing.push(Box::pin(upload_part(client, bucket_url, &upload_id, number, data, options)));
+            }
+
+            if ongoing.is_empty() {
+                break;
+            }
+
+            let part = matsu!(core::future::poll_fn(|ctx| {
+                for idx in 0..ongoing.len() {
+                    if let Poll::Ready(result) = ongoing[idx].as_mut().poll(ctx) {
+                        drop(ongoing.swap_remove(idx));
+                        return Poll::Ready(result);
+                    }
+                }
+
+                Poll::Pending
+            }))?;
+            parts.push(part);
+        }
+
+        parts.sort_by_key(|part| part.number);
+        Ok(parts)
+    };
+
+    let parts = match matsu!(uploading) {
+        Ok(parts) => parts,
+        Err(error) => {
+            matsu!(abort(client, bucket_url, &upload_id));
+            return Err(error);
+        },
+    };
+
+    match matsu!(complete(client, bucket_url, &upload_id, &parts)) {
+        Ok(etag) => Ok(S3Upload {
+            size: parts.iter().map(|part| part.size as u64).sum(),
+            upload_id,
+            parts,
+            etag,
+        }),
+        Err(error) => {
+            matsu!(abort(client, bucket_url, &upload_id));
+            Err(error)
+        },
+    }
+}
@@ -0,0 +1,651 @@
+//!WebDAV requests and multistatus responses, as defined in [RFC4918](https://tools.ietf.org/html/rfc4918).
+//!
+//!Available only with feature `webdav`
+//!
+//!Request builders set headers of WebDAV methods, while XML bodies are created by
+//![propfind_body](fn.propfind_body.html) and [proppatch_body](fn.proppatch_body.html).
+//!
+//!Multistatus body is parsed by simple XML parser, which resolves namespaces,
+//!but ignores DTD and processing instructions.
+//!
+//!## Usage
+//!
+//!```rust, no_run
+//!use yukikaze::{matsu, client};
+//!use yukikaze::client::webdav;
+//!
+//!async fn list() {
+//!    let client = client::Client::default();
+//!
+//!    let request = webdav::propfind("https://dav.example.com/files/", webdav::Depth::One).expect("To create request");
+//!    let request = webdav::with_xml(request, webdav::propfind_body(&[(webdav::DAV, "displayname"), (webdav::DAV, "resourcetype")]));
+//!    let mut response = matsu!(client.send(request)).expect("Not timed out").expect("To get response");
+//!    assert_eq!(response.status(), http::StatusCode::MULTI_STATUS);
+//!
+//!    let body = matsu!(response.text()).expect("To read body");
+//!    let multistatus = webdav::MultiStatus::parse(&body).expect("To parse multistatus");
+//!    for entry in multistatus.responses.iter() {
+//!        println!("{} collection={}", entry.href, entry.is_collection());
+//!    }
+//!}
+//!```
+
+use core::fmt;
+use std::borrow::Cow;
+use std::error::Error as StdError;
+
+use crate::header;
+use super::request::{Builder, Request};
+
+///Namespace of WebDAV properties.
+pub const DAV: &str = "DAV:";
+
+///Content type of XML bodies.
+pub const XML_CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Value of `Depth` header.
+pub enum Depth {
+    ///Applies only to resource itself.
+    Zero,
+    ///Applies to resource and its immediate members.
+    One,
+    ///Applies to resource and all its members, recursively.
+    Infinity,
+}
+
+impl Depth {
+    #[inline]
+    ///Returns header's value.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Depth::Zero => "0",
+            Depth::One => "1",
+            Depth::Infinity => "infinity",
+        }
+    }
+}
+
+#[inline]
+fn method(name: &'static str) -> http::Method {
+    match http::Method::from_bytes(name.as_bytes()) {
+        Ok(method) => method,
+        Err(_) => unreachable!(),
+    }
+}
+
+fn destination_request<U: AsRef<str>, D: AsRef<str>>(name: &'static str, uri: U, destination: D, overwrite: bool) -> Result<Builder, http::uri::InvalidUri> {
+    let destination = destination.as_ref().parse::<http::Uri>()?;
+    let overwrite = match overwrite {
+        true => "T",
+        false => "F",
+    };
+
+    Ok(Request::new(method(name), uri)?.set_header(header::HeaderName::from_static("destination"), destination.to_string())
+                                       .set_header(header::HeaderName::from_static("overwrite"), overwrite))
+}
+
+///Creates `PROPFIND` request, which retrieves properties of resource and, depending on `depth`, its members.
+///
+///Without body server returns all properties, otherwise use [propfind_body](fn.propfind_body.html).
+pub fn propfind<U: AsRef<str>>(uri: U, depth: Depth) -> Result<Builder, http::uri::InvalidUri> {
+    Ok(Request::new(method("PROPFIND"), uri)?.set_header(header::HeaderName::from_static("depth"), depth.as_str()))
+}
+
+///Creates `PROPPATCH` request, which sets and removes properties of resource.
+///
+///Body should be created by [proppatch_body](fn.proppatch_body.html).
+pub fn proppatch<U: AsRef<str>>(uri: U) -> Result<Builder, http::uri::InvalidUri> {
+    Request::new(method("PROPPATCH"), uri)
+}
+
+///Creates `MKCOL` request, which creates collection.
+pub fn mkcol<U: AsRef<str>>(uri: U) -> Result<Builder, http::uri::InvalidUri> {
+    Request::new(method("MKCOL"), uri)
+}
+
+///Creates `COPY` request, which copies resource to `destination`.
+///
+///If `overwrite` is `false`, then server fails request when `destination` already exists.
+///
+///Collection is copied with all its members, unless `Depth: 0` is set.
+pub fn copy_to<U: AsRef<str>, D: AsRef<str>>(uri: U, destination: D, overwrite: bool) -> Result<Builder, http::uri::InvalidUri> {
+    destination_request("COPY", uri, destination, overwrite)
+}
+
+///Creates `MOVE` request, which moves resource to `destination`.
+///
+///If `overwrite` is `false`, then server fails request when `destination` already exists.
+pub fn move_to<U: AsRef<str>, D: AsRef<str>>(uri: U, destination: D, overwrite: bool) -> Result<Builder, http::uri::InvalidUri> {
+    destination_request("MOVE", uri, destination, overwrite)
+}
+
+///Creates request with XML `body`, setting `Content-Type` to [XML_CONTENT_TYPE](constant.XML_CONTENT_TYPE.html).
+pub fn with_xml(builder: Builder, body: String) -> Request {
+    builder.set_header(header::CONTENT_TYPE, XML_CONTENT_TYPE).body(Some(body))
+}
+
+///Escapes XML special characters.
+fn escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['<', '>', '&', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut result = String::with_capacity(text.len() + 8);
+    for ch in text.chars() {
+        match ch {
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '&' => result.push_str("&amp;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            ch => result.push(ch),
+        }
+    }
+    Cow::Owned(result)
+}
+
+fn push_prop(body: &mut String, namespace: &str, name: &str, value: Option<&str>) {
+    body.push('<');
+    body.push_str(name);
+    body.push_str(" xmlns=\"");
+    body.push_str(&escape(namespace));
+    match value {
+        Some(value) => {
+            body.push_str("\">");
+            body.push_str(&escape(value));
+            body.push_str("</");
+            body.push_str(name);
+            body.push('>');
+        },
+        None => body.push_str("\"/>"),
+    }
+}
+
+///Creates body of `PROPFIND` request, which asks for properties `props`, given as `(namespace, name)`.
+///
+///If `props` is empty, then asks for all properties.
+pub fn propfind_body(props: &[(&str, &str)]) -> String {
+    let mut body = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><D:propfind xmlns:D=\"DAV:\">");
+
+    match props.is_empty() {
+        true => body.push_str("<D:allprop/>"),
+        false => {
+            body.push_str("<D:prop>");
+            for (namespace, name) in props {
+                push_prop(&mut body, namespace, name, None);
+            }
+            body.push_str("</D:prop>");
+        },
+    }
+
+    body.push_str("</D:propfind>");
+    body
+}
+
+///Creates body of `PROPPATCH` request, which sets properties `set`, given as `(namespace, name, value)`,
+///and removes properties `remove`, given as `(namespace, name)`.
+pub fn proppatch_body(set: &[(&str, &str, &str)], remove: &[(&str, &str)]) -> String {
+    let mut body = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><D:propertyupdate xmlns:D=\"DAV:\">");
+
+    if !set.is_empty() {
+        body.push_str("<D:set><D:prop>");
+        for (namespace, name, value) in set {
+            push_prop(&mut body, namespace, name, Some(value));
+        }
+        body.push_str("</D:prop></D:set>");
+    }
+
+    if !remove.is_empty() {
+        body.push_str("<D:remove><D:prop>");
+        for (namespace, name) in remove {
+            push_prop(&mut body, namespace, name, None);
+        }
+        body.push_str("</D:prop></D:remove>");
+    }
+
+    body.push_str("</D:propertyupdate>");
+    body
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+///Describes failure to parse multistatus body.
+pub enum ParseError {
+    ///Body is not well-formed XML.
+    Syntax(&'static str),
+    ///Root element is not `DAV:multistatus`.
+    NotMultiStatus,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax(error) => write!(f, "Invalid XML: {}", error),
+            ParseError::NotMultiStatus => f.write_str("Root element is not DAV:multistatus"),
+        }
+    }
+}
+
+impl StdError for ParseError {
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+///XML element of property.
+pub struct Property {
+    ///Namespace of element.
+    pub namespace: String,
+    ///Local name of element.
+    pub name: String,
+    ///Text content of element, with whitespace trimmed.
+    pub value: String,
+    ///Child elements.
+    pub children: Vec<Property>,
+}
+
+impl Property {
+    #[inline]
+    ///Returns whether element has specified `namespace` and `name`.
+    pub fn is(&self, namespace: &str, name: &str) -> bool {
+        self.name == name && self.namespace == namespace
+    }
+
+    #[inline]
+    ///Returns first child element with specified `namespace` and `name`.
+    pub fn child(&self, namespace: &str, name: &str) -> Option<&Property> {
+        self.children.iter().find(|child| child.is(namespace, name))
+    }
+
+    #[inline]
+    fn dav_children<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a Property> + 'a {
+        self.children.iter().filter(move |child| child.is(DAV, name))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+///Properties with the same status.
+pub struct PropStat {
+    ///Status of properties.
+    pub status: http::StatusCode,
+    ///Properties.
+    pub props: Vec<Property>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+///Result of operation on single resource.
+pub struct Entry {
+    ///URL of resource, as it is specified by server.
+    pub href: String,
+    ///Status of operation, if it is not specified per property.
+    pub status: Option<http::StatusCode>,
+    ///Properties, grouped by status.
+    pub propstats: Vec<PropStat>,
+    ///Optional human readable description.
+    pub description: Option<String>,
+}
+
+impl Entry {
+    ///Returns property with `namespace` and `name`, if it is retrieved successfully.
+    pub fn prop(&self, namespace: &str, name: &str) -> Option<&Property> {
+        self.propstats.iter()
+                      .filter(|propstat| propstat.status.is_success())
+                      .flat_map(|propstat| propstat.props.iter())
+                      .find(|prop| prop.is(namespace, name))
+    }
+
+    #[inline]
+    ///Returns whether resource is collection, according to its `DAV:resourcetype`.
+    pub fn is_collection(&self) -> bool {
+        self.prop(DAV, "resourcetype").and_then(|prop| prop.child(DAV, "collection")).is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+///Body of `207 Multi-Status` response.
+pub struct MultiStatus {
+    ///Results per resource.
+    pub responses: Vec<Entry>,
+    ///Optional human readable description.
+    pub description: Option<String>,
+}
+
+fn parse_status(line: &str) -> Option<http::StatusCode> {
+    line.split_ascii_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok()).and_then(|code| http::StatusCode::from_u16(code).ok())
+}
+
+impl MultiStatus {
+    ///Parses multistatus XML document.
+    ///
+    ///Elements with unknown status are skipped.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let root = Parser::new(text).parse()?;
+        if !root.is(DAV, "multistatus") {
+            return Err(ParseError::NotMultiStatus);
+        }
+
+        let mut responses = Vec::new();
+        for response in root.dav_children("response") {
+            let href = match response.child(DAV, "href") {
+                Some(href) => href.value.clone(),
+                None => continue,
+            };
+
+            let mut propstats = Vec::new();
+            for propstat in response.dav_children("propstat") {
+                if let Some(status) = propstat.child(DAV, "status").and_then(|status| parse_status(&status.value)) {
+                    let props = propstat.child(DAV, "prop").map(|prop| prop.children.clone()).unwrap_or_default();
+                    propstats.push(PropStat {
+                        status,
+                        props,
+                    });
+                }
+            }
+
+            responses.push(Entry {
+                href,
+                status: response.child(DAV, "status").and_then(|status| parse_status(&status.value)),
+                propstats,
+                description: response.child(DAV, "responsedescription").map(|description| description.value.clone()),
+            });
+        }
+
+        Ok(Self {
+            responses,
+            description: root.child(DAV, "responsedescription").map(|description| description.value.clone()),
+        })
+    }
+}
+
+fn decode_entities(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let ch = match &rest[1..end] {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                entity if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16).ok().and_then(core::char::from_u32),
+                entity if entity.starts_with('#') => entity[1..].parse::<u32>().ok().and_then(core::char::from_u32),
+                _ => None,
+            };
+            ch.map(|ch| (ch, end))
+        });
+
+        match decoded {
+            Some((ch, end)) => {
+                result.push(ch);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    result.push_str(rest);
+
+    Cow::Owned(result)
+}
+
+struct Open {
+    element: Property,
+    qname: String,
+    scope_len: usize,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    //Namespace declarations in scope, as `(prefix, namespace)`
+    scope: Vec<(String, String)>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            pos: 0,
+            scope: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_past(&mut self, pattern: &str) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        match rest.find(pattern) {
+            Some(end) => {
+                self.pos += end + pattern.len();
+                Ok(&rest[..end])
+            },
+            None => Err(ParseError::Syntax("unterminated markup")),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn name(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|ch: char| ch.is_whitespace() || matches!(ch, '/' | '>' | '=')).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn resolve(&self, prefix: &str) -> String {
+        self.scope.iter().rev().find(|(declared, _)| declared == prefix).map(|(_, namespace)| namespace.clone()).unwrap_or_default()
+    }
+
+    ///Parses start tag after `<`, returning element and whether it is self-closing.
+    fn start_tag(&mut self) -> Result<(Open, bool), ParseError> {
+        let qname = self.name();
+        if qname.is_empty() {
+            return Err(ParseError::Syntax("expected element's name"));
+        }
+
+        let scope_len = self.scope.len();
+        let is_empty = loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                break true;
+            } else if rest.starts_with('>') {
+                self.pos += 1;
+                break false;
+            }
+
+            let attr = self.name();
+            if attr.is_empty() {
+                return Err(ParseError::Syntax("expected attribute"));
+            }
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(ParseError::Syntax("expected `=` after attribute"));
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+
+            let quote = match self.rest().chars().next() {
+                Some(quote) if quote == '"' || quote == '\'' => quote,
+                _ => return Err(ParseError::Syntax("expected quoted attribute value")),
+            };
+            self.pos += 1;
+            let value = match self.rest().find(quote) {
+                Some(end) => {
+                    let value = &self.rest()[..end];
+                    self.pos += end + 1;
+                    value
+                },
+                None => return Err(ParseError::Syntax("unterminated attribute value")),
+            };
+
+            if attr == "xmlns" {
+                self.scope.push((String::new(), decode_entities(value).into_owned()));
+            } else if let Some(prefix) = attr.strip_prefix("xmlns:") {
+                self.scope.push((prefix.to_owned(), decode_entities(value).into_owned()));
+            }
+        };
+
+        let (prefix, name) = match qname.find(':') {
+            Some(idx) => (&qname[..idx], &qname[idx + 1..]),
+            None => ("", qname),
+        };
+
+        let open = Open {
+            element: Property {
+                namespace: self.resolve(prefix),
+                name: name.to_owned(),
+                ..Property::default()
+            },
+            qname: qname.to_owned(),
+            scope_len,
+        };
+        Ok((open, is_empty))
+    }
+
+    fn parse(mut self) -> Result<Property, ParseError> {
+        let mut stack: Vec<Open> = Vec::new();
+        let mut root = None;
+
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                break;
+            }
+
+            let closed = if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+                None
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+                None
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += 9;
+                let text = self.skip_past("]]>")?;
+                if let Some(open) = stack.last_mut() {
+                    open.element.value.push_str(text);
+                }
+                None
+            } else if rest.starts_with("<!") {
+                self.skip_past(">")?;
+                None
+            } else if rest.starts_with("</") {
+                self.pos += 2;
+                let qname = self.skip_past(">")?.trim_end();
+                match stack.pop() {
+                    Some(open) if open.qname == qname => Some(open),
+                    _ => return Err(ParseError::Syntax("mismatched closing tag")),
+                }
+            } else if rest.starts_with('<') {
+                self.pos += 1;
+                let (open, is_empty) = self.start_tag()?;
+                match is_empty {
+                    true => Some(open),
+                    false => {
+                        stack.push(open);
+                        None
+                    },
+                }
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.pos += end;
+                match stack.last_mut() {
+                    Some(open) => open.element.value.push_str(&decode_entities(&rest[..end])),
+                    None => if !rest[..end].trim().is_empty() {
+                        return Err(ParseError::Syntax("text outside of root element"));
+                    },
+                }
+                None
+            };
+
+            if let Some(mut open) = closed {
+                self.scope.truncate(open.scope_len);
+                let trimmed = open.element.value.trim();
+                if trimmed.len() != open.element.value.len() {
+                    open.element.value = trimmed.to_owned();
+                }
+
+                match stack.last_mut() {
+                    Some(parent) => parent.element.children.push(open.element),
+                    None if root.is_none() => root = Some(open.element),
+                    None => return Err(ParseError::Syntax("multiple root elements")),
+                }
+            }
+        }
+
+        match (root, stack.is_empty()) {
+            (Some(root), true) => Ok(root),
+            (_, false) => Err(ParseError::Syntax("unexpected end of document")),
+            (None, true) => Err(ParseError::Syntax("missing root element")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MultiStatus, ParseError, DAV};
+
+    #[test]
+    fn should_parse_multistatus() {
+        const BODY: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+<!-- listing -->
+<d:multistatus xmlns:d="DAV:" xmlns:y="urn:yukikaze">
+  <d:response>
+    <d:href>/files/</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:displayname>Files &amp; more</d:displayname>
+        <d:resourcetype><d:collection/></d:resourcetype>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+    <d:propstat>
+      <d:prop><y:class/></d:prop>
+      <d:status>HTTP/1.1 404 Not Found</d:status>
+    </d:propstat>
+  </d:response>
+  <response xmlns="DAV:">
+    <href>/files/ship.txt</href>
+    <propstat>
+      <prop><resourcetype/><class xmlns="urn:yukikaze"><![CDATA[<Kagero>]]></class></prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+  </response>
+  <d:response><d:href>/files/locked</d:href><d:status>HTTP/1.1 423 Locked</d:status></d:response>
+</d:multistatus>"#;
+
+        let multistatus = MultiStatus::parse(BODY).expect("To parse multistatus");
+        assert_eq!(multistatus.responses.len(), 3);
+
+        let dir = &multistatus.responses[0];
+        assert_eq!(dir.href, "/files/");
+        assert!(dir.is_collection());
+        assert_eq!(dir.prop(DAV, "displayname").unwrap().value, "Files & more");
+        assert!(dir.prop("urn:yukikaze", "class").is_none());
+        assert_eq!(dir.propstats[1].status, http::StatusCode::NOT_FOUND);
+
+        let file = &multistatus.responses[1];
+        assert!(!file.is_collection());
+        assert_eq!(file.prop("urn:yukikaze", "class").unwrap().value, "<Kagero>");
+
+        assert_eq!(multistatus.responses[2].status, Some(http::StatusCode::LOCKED));
+
+        assert_eq!(MultiStatus::parse("<prop xmlns=\"DAV:\"/>"), Err(ParseError::NotMultiStatus));
+        assert!(MultiStatus::parse("<d:multistatus xmlns:d=\"DAV:\"><d:response></d:multistatus>").is_err());
+    }
+}
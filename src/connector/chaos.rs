@@ -0,0 +1,409 @@
+//!Fault injection for resilience testing.
+//!
+//![Chaos](struct.Chaos.html) wraps connector and injects [Faults](struct.Faults.html) into its connections,
+//!allowing to test retries, timeouts and circuit breaker of application against misbehaving server.
+//!
+//!Faults are chosen by pseudo-random generator with fixed seed, so that the same sequence of connections
+//!results in the same sequence of faults.
+//!
+//!Faults are applied per connection, hence in order to affect each request, connection pool should not
+//!keep idle connections.
+//!Injected error responses are valid only for HTTP/1.
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::client;
+//!use yukikaze::connector::chaos::{Chaos, Faults};
+//!
+//!pub struct ChaosCfg;
+//!
+//!impl client::config::Config for ChaosCfg {
+//!    type Connector = Chaos<client::config::DefaultConnector>;
+//!    type Timer = client::config::DefaultTimer;
+//!}
+//!
+//!let faults = Faults {
+//!    seed: 42,
+//!    error_rate: 0.1,
+//!    error_burst: 3,
+//!    ..Default::default()
+//!};
+//!let connector = Chaos::new(client::config::DefaultConnector::default(), faults);
+//!let client = client::builder::ClientBuilder::<ChaosCfg>::new().connector(connector).build();
+//!```
+
+use core::future::Future;
+use core::mem::MaybeUninit;
+use core::{cmp, fmt, pin, task, time};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use async_timer::Oneshot;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[derive(Debug, Clone)]
+///Describes faults to inject.
+///
+///Rates are probabilities in range `0.0..=1.0`, where `0.0` disables fault.
+pub struct Faults {
+    ///Seed of pseudo-random generator.
+    pub seed: u64,
+    ///Minimum latency, added before connecting.
+    pub min_latency: time::Duration,
+    ///Maximum latency, added before connecting.
+    ///
+    ///Latency is chosen uniformly between minimum and maximum.
+    pub max_latency: time::Duration,
+    ///Rate of failures to connect.
+    pub connect_failure_rate: f64,
+    ///Rate of connections, that are reset while reading response.
+    pub drop_rate: f64,
+    ///Rate of connections, that are closed by server before response is complete.
+    pub truncate_rate: f64,
+    ///Maximum number of bytes, that are read before connection is dropped or truncated.
+    ///
+    ///Actual number is chosen randomly up to this value.
+    pub fault_after: usize,
+    ///Rate of error responses.
+    ///
+    ///Request is not sent to server, instead response with `error_status` is returned.
+    pub error_rate: f64,
+    ///Number of consecutive connections, that receive error response, once error is injected.
+    pub error_burst: usize,
+    ///Status of error responses.
+    pub error_status: http::StatusCode,
+}
+
+impl Default for Faults {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            min_latency: time::Duration::from_secs(0),
+            max_latency: time::Duration::from_secs(0),
+            connect_failure_rate: 0.0,
+            drop_rate: 0.0,
+            truncate_rate: 0.0,
+            fault_after: 16 * 1024,
+            error_rate: 0.0,
+            error_burst: 1,
+            error_status: http::StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+///Pseudo-random generator, based on SplitMix64.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut result = self.state;
+        result = (result ^ (result >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        result = (result ^ (result >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        result ^ (result >> 31)
+    }
+
+    ///Returns `true` with probability `rate`.
+    fn chance(&mut self, rate: f64) -> bool {
+        if rate <= 0.0 {
+            return false;
+        }
+
+        let sample = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        sample < rate
+    }
+
+    ///Returns number in range `0..=max`.
+    fn up_to(&mut self, max: u64) -> u64 {
+        match max.checked_add(1) {
+            Some(range) => self.next() % range,
+            None => self.next(),
+        }
+    }
+}
+
+struct State {
+    rng: Rng,
+    burst: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    None,
+    Drop(usize),
+    Truncate(usize),
+    Status(http::StatusCode),
+}
+
+///Connector wrapper, that injects faults into connections.
+pub struct Chaos<C> {
+    inner: C,
+    faults: Arc<Faults>,
+    state: Arc<Mutex<State>>,
+}
+
+impl<C> Chaos<C> {
+    ///Creates new instance, which injects `faults` into connections of `inner`.
+    pub fn new(inner: C, faults: Faults) -> Self {
+        let state = State {
+            rng: Rng {
+                state: faults.seed,
+            },
+            burst: 0,
+        };
+
+        Self {
+            inner,
+            faults: Arc::new(faults),
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    ///Chooses latency, whether to fail connecting, and fault of connection.
+    fn choose(&self) -> (time::Duration, bool, Fault) {
+        let faults = &self.faults;
+        let mut state = self.state.lock().unwrap_or_else(|error| error.into_inner());
+
+        let latency = match faults.max_latency.checked_sub(faults.min_latency) {
+            Some(range) if range.as_millis() > 0 => faults.min_latency + time::Duration::from_millis(state.rng.up_to(range.as_millis() as u64)),
+            _ => faults.min_latency,
+        };
+
+        if state.rng.chance(faults.connect_failure_rate) {
+            return (latency, true, Fault::None);
+        }
+
+        let fault = if state.burst > 0 {
+            state.burst -= 1;
+            Fault::Status(faults.error_status)
+        } else if state.rng.chance(faults.error_rate) {
+            state.burst = faults.error_burst.saturating_sub(1);
+            Fault::Status(faults.error_status)
+        } else if state.rng.chance(faults.drop_rate) {
+            Fault::Drop(state.rng.up_to(faults.fault_after as u64) as usize)
+        } else if state.rng.chance(faults.truncate_rate) {
+            Fault::Truncate(state.rng.up_to(faults.fault_after as u64) as usize)
+        } else {
+            Fault::None
+        };
+
+        (latency, false, fault)
+    }
+}
+
+impl<C: Default> Default for Chaos<C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(C::default(), Faults::default())
+    }
+}
+
+impl<C: Clone> Clone for Chaos<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            faults: self.faults.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<C> super::Connect for Chaos<C> where C: super::Connect, C::Error: From<io::Error>, C::Future: Send + 'static {
+    #[inline]
+    fn with_proxy(mut self, proxy: Option<super::proxy::ProxyUri>) -> Self {
+        self.inner = self.inner.with_proxy(proxy);
+        self
+    }
+
+    #[inline]
+    fn connect(&mut self, dst: hyper::Uri, reporter: super::Reporter) -> Self::Future {
+        let connecting = self.inner.connect(dst, reporter);
+        self.inject(connecting)
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Chaos<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Chaos").field("inner", &self.inner)
+                               .field("faults", &self.faults)
+                               .finish()
+    }
+}
+
+impl<C> hyper::service::Service<hyper::Uri> for Chaos<C> where C: hyper::service::Service<hyper::Uri>, C::Error: From<io::Error>, C::Future: Send + 'static {
+    type Response = ChaosStream<C::Response>;
+    type Error = C::Error;
+    type Future = pin::Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, ctx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(ctx)
+    }
+
+    #[inline]
+    fn call(&mut self, dst: hyper::Uri) -> Self::Future {
+        let connecting = self.inner.call(dst);
+        self.inject(connecting)
+    }
+}
+
+impl<C> Chaos<C> where C: hyper::service::Service<hyper::Uri>, C::Error: From<io::Error>, C::Future: Send + 'static {
+    ///Injects faults into connection, that is being established by `connecting`.
+    fn inject(&self, connecting: C::Future) -> <Self as hyper::service::Service<hyper::Uri>>::Future {
+        let (latency, is_failed, fault) = self.choose();
+
+        Box::pin(async move {
+            if latency.as_millis() > 0 {
+                matsu!(async_timer::oneshot::Timer::new(latency));
+            }
+
+            if is_failed {
+                return Err(io::Error::new(io::ErrorKind::ConnectionRefused, "Chaos: connection refused").into());
+            }
+
+            let inner = matsu!(connecting)?;
+            let response = match fault {
+                Fault::Status(status) => format!("HTTP/1.1 {} {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status.as_u16(), status.canonical_reason().unwrap_or("")).into_bytes(),
+                _ => Vec::new(),
+            };
+
+            Ok(ChaosStream {
+                inner,
+                fault,
+                read: 0,
+                response,
+                is_requested: false,
+                reader: None,
+            })
+        })
+    }
+}
+
+///Connection, that is affected by [Chaos](struct.Chaos.html) connector.
+pub struct ChaosStream<S> {
+    inner: S,
+    fault: Fault,
+    ///Number of bytes read from connection.
+    read: usize,
+    ///Injected response, which is returned instead of server's one.
+    response: Vec<u8>,
+    ///Whether request is written, after which injected response becomes available.
+    is_requested: bool,
+    reader: Option<task::Waker>,
+}
+
+impl<S> ChaosStream<S> {
+    #[inline]
+    ///Returns reference to underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: hyper::client::connect::Connection> hyper::client::connect::Connection for ChaosStream<S> {
+    #[inline(always)]
+    fn connected(&self) -> hyper::client::connect::Connected {
+        self.inner.connected()
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for ChaosStream<S> {
+    #[inline(always)]
+    unsafe fn prepare_uninitialized_buffer(&self, buff: &mut [MaybeUninit<u8>]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buff)
+    }
+
+    fn poll_read(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>, buff: &mut [u8]) -> task::Poll<io::Result<usize>> {
+        let this = &mut *self;
+
+        let limit = match this.fault {
+            Fault::None => return AsyncRead::poll_read(pin::Pin::new(&mut this.inner), ctx, buff),
+            Fault::Status(_) if !this.is_requested => {
+                this.reader = Some(ctx.waker().clone());
+                return task::Poll::Pending;
+            },
+            Fault::Status(_) => {
+                let size = cmp::min(buff.len(), this.response.len() - this.read);
+                buff[..size].copy_from_slice(&this.response[this.read..this.read + size]);
+                this.read += size;
+                return task::Poll::Ready(Ok(size));
+            },
+            Fault::Drop(limit) | Fault::Truncate(limit) => limit,
+        };
+
+        if this.read >= limit {
+            return match this.fault {
+                Fault::Drop(_) => task::Poll::Ready(Err(io::Error::new(io::ErrorKind::ConnectionReset, "Chaos: connection reset"))),
+                _ => task::Poll::Ready(Ok(0)),
+            };
+        }
+
+        let size = cmp::min(buff.len(), limit - this.read);
+        match AsyncRead::poll_read(pin::Pin::new(&mut this.inner), ctx, &mut buff[..size]) {
+            task::Poll::Ready(Ok(size)) => {
+                this.read += size;
+                task::Poll::Ready(Ok(size))
+            },
+            result => result,
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for ChaosStream<S> {
+    fn poll_write(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>, buff: &[u8]) -> task::Poll<io::Result<usize>> {
+        match self.fault {
+            //Request is discarded, as server is not supposed to see it
+            Fault::Status(_) => {
+                self.is_requested = true;
+                if let Some(reader) = self.reader.take() {
+                    reader.wake();
+                }
+                task::Poll::Ready(Ok(buff.len()))
+            },
+            _ => AsyncWrite::poll_write(pin::Pin::new(&mut self.inner), ctx, buff),
+        }
+    }
+
+    #[inline(always)]
+    fn poll_flush(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(pin::Pin::new(&mut self.inner), ctx)
+    }
+
+    #[inline(always)]
+    fn poll_shutdown(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(pin::Pin::new(&mut self.inner), ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Chaos, Faults, Fault};
+
+    #[test]
+    fn should_choose_faults_deterministically() {
+        let faults = Faults {
+            seed: 7,
+            drop_rate: 0.3,
+            truncate_rate: 0.3,
+            error_rate: 0.2,
+            error_burst: 2,
+            ..Default::default()
+        };
+
+        let first = Chaos::new((), faults.clone());
+        let second = Chaos::new((), faults);
+        let first = (0..50).map(|_| first.choose()).collect::<Vec<_>>();
+        let second = (0..50).map(|_| second.choose()).collect::<Vec<_>>();
+        assert_eq!(first, second);
+
+        for window in first.windows(2) {
+            if let (Fault::Status(_), Fault::Status(_)) = (window[0].2, window[1].2) {
+                return;
+            }
+        }
+        panic!("Error burst is not injected");
+    }
+}
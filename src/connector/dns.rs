@@ -0,0 +1,205 @@
+//!DNS cache.
+//!
+//![Cache](struct.Cache.html) is owned by [HttpConnector](../struct.HttpConnector.html) and shared
+//!by all of its clones, hence by all connection pools of client, that uses connector.
+//!Connector doesn't cache lookups unless it is provided with cache.
+//!HTTPS connectors forward cache to their underlying HTTP connector.
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::client;
+//!use yukikaze::connector::dns;
+//!
+//!let cache = dns::Cache::with_max_entries(core::time::Duration::from_secs(60), 1024);
+//!let connector = client::config::DefaultConnector::default().dns_cache(Some(cache.clone()));
+//!let client = client::builder::ClientBuilder::<client::config::DefaultCfg>::new().connector(connector).build();
+//!
+//!//On network change
+//!cache.flush();
+//!```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Instant;
+use std::io;
+use core::fmt;
+use core::time::Duration;
+
+///Default limit on number of cached hosts.
+pub const DEFAULT_MAX_ENTRIES: usize = 256;
+
+struct Entry {
+    addrs: Vec<IpAddr>,
+    expires: Instant,
+}
+
+struct Inner {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+#[derive(Clone)]
+///Cache of resolved addresses.
+///
+///Clones share the same entries.
+pub struct Cache {
+    inner: Arc<Inner>,
+}
+
+impl Cache {
+    #[inline]
+    ///Creates new instance, which caches addresses for `ttl`, limited by [DEFAULT_MAX_ENTRIES](constant.DEFAULT_MAX_ENTRIES.html).
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_max_entries(ttl, DEFAULT_MAX_ENTRIES)
+    }
+
+    ///Creates new instance, which caches addresses of at most `max_entries` hosts for `ttl`.
+    ///
+    ///When limit is reached, expired entries are evicted first, then ones that would expire the soonest.
+    ///Zero `ttl` or `max_entries` disables caching.
+    pub fn with_max_entries(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                ttl,
+                max_entries,
+                entries: Mutex::new(HashMap::new()),
+            })
+        }
+    }
+
+    #[inline]
+    ///Returns duration for which resolved addresses are cached.
+    pub fn ttl(&self) -> Duration {
+        self.inner.ttl
+    }
+
+    #[inline]
+    ///Returns maximum number of cached hosts.
+    pub fn max_entries(&self) -> usize {
+        self.inner.max_entries
+    }
+
+    ///Removes all cached entries.
+    pub fn flush(&self) {
+        self.lock().clear();
+    }
+
+    ///Returns number of cached hosts, including expired ones that are not yet evicted.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    #[inline]
+    ///Returns whether cache has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.inner.entries.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn get(&self, host: &str, now: Instant) -> Option<Vec<IpAddr>> {
+        let mut cache = self.lock();
+
+        match cache.get(host) {
+            Some(entry) if entry.expires > now => Some(entry.addrs.clone()),
+            Some(_) => {
+                cache.remove(host);
+                None
+            },
+            None => None,
+        }
+    }
+
+    fn insert(&self, host: &str, addrs: Vec<IpAddr>, expires: Instant, now: Instant) {
+        let max = self.inner.max_entries;
+        if max == 0 || addrs.is_empty() {
+            return;
+        }
+
+        let mut cache = self.lock();
+
+        if cache.len() >= max && !cache.contains_key(host) {
+            cache.retain(|_, entry| entry.expires > now);
+
+            while cache.len() >= max {
+                let oldest = match cache.iter().min_by_key(|(_, entry)| entry.expires) {
+                    Some((oldest, _)) => oldest.clone(),
+                    None => break,
+                };
+                cache.remove(&oldest);
+            }
+        }
+
+        cache.insert(host.to_owned(), Entry {
+            addrs,
+            expires,
+        });
+    }
+
+    async fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let ttl = self.inner.ttl;
+        if ttl.as_millis() == 0 {
+            return matsu!(tokio::net::lookup_host((host, port))).map(|addrs| addrs.collect());
+        }
+
+        let now = Instant::now();
+        if let Some(addrs) = self.get(host, now) {
+            return Ok(addrs.into_iter().map(|addr| SocketAddr::new(addr, port)).collect());
+        }
+
+        let addrs: Vec<SocketAddr> = matsu!(tokio::net::lookup_host((host, port)))?.collect();
+        self.insert(host, addrs.iter().map(SocketAddr::ip).collect(), now + ttl, now);
+
+        Ok(addrs)
+    }
+}
+
+impl fmt::Debug for Cache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Cache").field("ttl", &self.inner.ttl)
+                               .field("max_entries", &self.inner.max_entries)
+                               .field("len", &self.len())
+                               .finish()
+    }
+}
+
+///Resolves host, using cache if it is provided.
+pub(crate) async fn resolve(cache: Option<&Cache>, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+    match cache {
+        Some(cache) => matsu!(cache.resolve(host, port)),
+        None => matsu!(tokio::net::lookup_host((host, port))).map(|addrs| addrs.collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cache;
+
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn should_evict_soonest_to_expire() {
+        let now = Instant::now();
+        let addr = vec!["127.0.0.1".parse().unwrap()];
+        let cache = Cache::with_max_entries(Duration::from_secs(60), 2);
+
+        cache.insert("dns-test-1", addr.clone(), now + Duration::from_secs(10), now);
+        cache.insert("dns-test-2", addr.clone(), now + Duration::from_secs(5), now);
+        cache.insert("dns-test-3", addr.clone(), now + Duration::from_secs(20), now);
+
+        assert!(cache.get("dns-test-1", now).is_some());
+        assert!(cache.get("dns-test-2", now).is_none());
+        assert!(cache.get("dns-test-3", now).is_some());
+        assert!(cache.get("dns-test-3", now + Duration::from_secs(30)).is_none());
+        assert_eq!(cache.len(), 1);
+
+        cache.clone().flush();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+//!Connection lifecycle events.
+//!
+//!Events are delivered to [Config::on_connection_event](../../client/config/trait.Config.html#method.on_connection_event),
+//!if [Config::instrument_connections](../../client/config/trait.Config.html#method.instrument_connections) is enabled.
+//!Connectors report events, that only they can observe (e.g. TLS handshake), via [Reporter](../struct.Reporter.html),
+//!which is no-op unless connector is invoked by client.
+
+use std::net::SocketAddr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Event of connection's lifecycle.
+///
+///Host is identified by `<scheme>://<authority>`, same as in [PoolStats](../stats/struct.PoolStats.html).
+pub enum ConnectionEvent<'a> {
+    ///Connector is invoked to open new connection.
+    ConnectStart {
+        ///Host to connect to.
+        host: &'a str,
+    },
+    ///Connection is ready to be used.
+    Connected {
+        ///Host of connection.
+        host: &'a str,
+        ///Remote address, if known to connector.
+        addr: Option<SocketAddr>,
+        ///Whether existing connection is used by another request.
+        ///
+        ///Re-use is reported once response's head is received.
+        reused: bool,
+    },
+    ///TLS handshake is finished.
+    TlsHandshakeDone {
+        ///Host of connection.
+        host: &'a str,
+        ///Protocol, negotiated via ALPN.
+        protocol: Option<&'a [u8]>,
+    },
+    ///Connector failed to open connection.
+    ConnectFailed {
+        ///Host to connect to.
+        host: &'a str,
+    },
+    ///Connection is closed.
+    ConnectionClosed {
+        ///Host of connection.
+        host: &'a str,
+        ///Remote address, if known to connector.
+        addr: Option<SocketAddr>,
+    },
+}
+
+///Receiver of connection events.
+pub type Listener = fn(&ConnectionEvent<'_>);
+
+///Listener, that ignores events.
+pub(crate) fn ignore(_: &ConnectionEvent<'_>) {
+}
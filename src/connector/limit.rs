@@ -0,0 +1,202 @@
+//!Limit on number of connections per host.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use core::future::Future;
+use core::{pin, task};
+
+#[derive(Default)]
+struct Host {
+    open: usize,
+    next_id: u64,
+    waiters: VecDeque<(u64, task::Waker)>,
+}
+
+///Asynchronous semaphore per host, which permits are held by open connections.
+pub(crate) struct Limits {
+    max: usize,
+    hosts: Mutex<HashMap<Arc<str>, Host>>,
+}
+
+impl Limits {
+    pub(crate) fn new(max: usize) -> Self {
+        assert_ne!(max, 0, "Connection limit cannot be zero");
+
+        Self {
+            max,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, HashMap<Arc<str>, Host>> {
+        self.hosts.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    ///Waits until connection to `host` can be opened.
+    pub(crate) fn acquire(self: &Arc<Self>, host: Arc<str>) -> Acquire {
+        Acquire {
+            limits: self.clone(),
+            host,
+            id: None,
+            is_done: false,
+        }
+    }
+
+    fn release(&self, host: &Arc<str>) {
+        let mut hosts = self.lock();
+        let is_unused = match hosts.get_mut(host) {
+            Some(state) => {
+                state.open = state.open.saturating_sub(1);
+                if let Some((_, waker)) = state.waiters.pop_front() {
+                    waker.wake();
+                }
+                state.open == 0 && state.waiters.is_empty()
+            },
+            None => false,
+        };
+
+        if is_unused {
+            hosts.remove(host);
+        }
+    }
+}
+
+///Future of [Limits::acquire](struct.Limits.html#method.acquire).
+pub(crate) struct Acquire {
+    limits: Arc<Limits>,
+    host: Arc<str>,
+    id: Option<u64>,
+    is_done: bool,
+}
+
+impl Future for Acquire {
+    type Output = Permit;
+
+    fn poll(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        let mut hosts = this.limits.lock();
+        let state = hosts.entry(this.host.clone()).or_default();
+
+        if state.open < this.limits.max {
+            state.open += 1;
+            if let Some(id) = this.id.take() {
+                state.waiters.retain(|(waiter, _)| *waiter != id);
+            }
+            this.is_done = true;
+            drop(hosts);
+
+            return task::Poll::Ready(Permit {
+                limits: this.limits.clone(),
+                host: this.host.clone(),
+            });
+        }
+
+        let id = match this.id {
+            Some(id) => id,
+            None => {
+                let id = state.next_id;
+                state.next_id = state.next_id.wrapping_add(1);
+                this.id = Some(id);
+                id
+            },
+        };
+
+        match state.waiters.iter_mut().find(|(waiter, _)| *waiter == id) {
+            Some((_, waker)) => if !waker.will_wake(ctx.waker()) {
+                *waker = ctx.waker().clone();
+            },
+            None => state.waiters.push_back((id, ctx.waker().clone())),
+        }
+
+        task::Poll::Pending
+    }
+}
+
+impl Drop for Acquire {
+    fn drop(&mut self) {
+        if self.is_done {
+            return;
+        }
+
+        let id = match self.id {
+            Some(id) => id,
+            None => return,
+        };
+
+        let mut hosts = self.limits.lock();
+        if let Some(state) = hosts.get_mut(&self.host) {
+            let len = state.waiters.len();
+            state.waiters.retain(|(waiter, _)| *waiter != id);
+
+            //If it has been woken already, then wake-up is passed to the next waiter
+            if len == state.waiters.len() && state.open < self.limits.max {
+                if let Some((_, waker)) = state.waiters.pop_front() {
+                    waker.wake();
+                }
+            }
+
+            if state.open == 0 && state.waiters.is_empty() {
+                hosts.remove(&self.host);
+            }
+        }
+    }
+}
+
+///Permit to keep connection open, which is released on drop.
+pub(crate) struct Permit {
+    limits: Arc<Limits>,
+    host: Arc<str>,
+}
+
+impl Drop for Permit {
+    #[inline]
+    fn drop(&mut self) {
+        self.limits.release(&self.host);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Limits;
+
+    use std::sync::Arc;
+    use core::future::Future;
+    use core::{pin, task};
+
+    fn noop_waker() -> task::Waker {
+        fn clone(_: *const ()) -> task::RawWaker {
+            task::RawWaker::new(core::ptr::null(), &VTABLE)
+        }
+        fn noop(_: *const ()) {
+        }
+        static VTABLE: task::RawWakerVTable = task::RawWakerVTable::new(clone, noop, noop, noop);
+
+        unsafe { task::Waker::from_raw(clone(core::ptr::null())) }
+    }
+
+    #[test]
+    fn should_limit_connections_per_host() {
+        let waker = noop_waker();
+        let mut ctx = task::Context::from_waker(&waker);
+        let limits = Arc::new(Limits::new(2));
+        let host: Arc<str> = "http://example.com".into();
+
+        let mut first = limits.acquire(host.clone());
+        let mut second = limits.acquire(host.clone());
+        let mut third = limits.acquire(host.clone());
+        let mut other = limits.acquire("http://other.com".into());
+
+        let first = match pin::Pin::new(&mut first).poll(&mut ctx) {
+            task::Poll::Ready(permit) => permit,
+            task::Poll::Pending => panic!("First connection should be permitted"),
+        };
+        let second = pin::Pin::new(&mut second).poll(&mut ctx);
+        assert!(second.is_ready());
+        assert!(pin::Pin::new(&mut third).poll(&mut ctx).is_pending());
+        assert!(pin::Pin::new(&mut other).poll(&mut ctx).is_ready());
+
+        drop(first);
+        assert!(pin::Pin::new(&mut third).poll(&mut ctx).is_ready());
+    }
+}
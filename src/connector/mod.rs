@@ -2,14 +2,163 @@
 
 #[cfg(feature = "rustls-on")]
 pub mod rustls;
+pub mod dns;
+pub mod chaos;
+pub mod events;
+pub mod proxy;
+mod limit;
+pub mod stats;
+pub mod timings;
 
 use std::io;
+use std::error::Error;
+use std::sync::Arc;
 use core::{task, pin, fmt};
 use core::future::Future;
 
-async fn connect_tcp(dst: hyper::Uri) -> io::Result<tokio::net::TcpStream> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Describes stage of connection establishment.
+pub enum ConnectStage {
+    ///Resolution of host name.
+    Dns,
+    ///Establishing TCP connection.
+    Tcp,
+    ///Establishing tunnel via proxy.
+    Proxy,
+    ///Performing TLS handshake.
+    Tls,
+}
+
+#[derive(Debug)]
+///Connector's error, which remembers at which stage connection failed.
+///
+///Connectors return it wrapped into `io::Error`, use [from_io](#method.from_io) to retrieve it.
+pub struct ConnectError {
+    stage: ConnectStage,
+    inner: io::Error,
+}
+
+impl ConnectError {
+    #[inline]
+    ///Creates new instance.
+    pub fn new(stage: ConnectStage, inner: io::Error) -> Self {
+        Self {
+            stage,
+            inner,
+        }
+    }
+
+    #[inline]
+    ///Returns stage at which connection failed.
+    pub fn stage(&self) -> ConnectStage {
+        self.stage
+    }
+
+    #[inline]
+    ///Returns reference to underlying IO error.
+    pub fn io(&self) -> &io::Error {
+        &self.inner
+    }
+
+    #[inline]
+    ///Retrieves `ConnectError` out of `io::Error`, if it is wrapped within.
+    pub fn from_io(error: &io::Error) -> Option<&Self> {
+        error.get_ref().and_then(|error| error.downcast_ref::<Self>())
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.stage {
+            ConnectStage::Dns => write!(f, "Unable to resolve host: {}", self.inner),
+            ConnectStage::Tcp => write!(f, "Unable to connect: {}", self.inner),
+            ConnectStage::Proxy => write!(f, "Unable to establish tunnel via proxy: {}", self.inner),
+            ConnectStage::Tls => write!(f, "TLS handshake failed: {}", self.inner),
+        }
+    }
+}
+
+impl Error for ConnectError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.inner)
+    }
+}
+
+impl From<ConnectError> for io::Error {
+    #[inline]
+    fn from(error: ConnectError) -> Self {
+        io::Error::new(error.inner.kind(), error)
+    }
+}
+
+#[derive(Clone, Default)]
+///Reports progress of connection establishment to client.
+///
+///Client passes it to [Connect::connect](trait.Connect.html#method.connect), when it opens new connection.
+///Reporting is no-op, unless client collects respective information.
+pub struct Reporter {
+    timings: Option<timings::SharedState>,
+    events: Option<(events::Listener, Arc<str>)>,
+}
+
+impl Reporter {
+    #[inline]
+    pub(crate) fn new(timings: Option<timings::SharedState>, events: events::Listener, host: Arc<str>) -> Self {
+        Self {
+            timings,
+            events: Some((events, host)),
+        }
+    }
+
+    ///Records stage of connection establishment.
+    pub fn record<F: FnOnce(&mut timings::ConnectTimings)>(&self, cb: F) {
+        if let Some(state) = self.timings.as_ref() {
+            cb(&mut timings::lock(state).connect);
+        }
+    }
+
+    ///Reports that TLS handshake is finished, negotiating `protocol` via ALPN.
+    pub fn tls_handshake_done(&self, protocol: Option<&[u8]>) {
+        if let Some((listener, host)) = self.events.as_ref() {
+            listener(&events::ConnectionEvent::TlsHandshakeDone {
+                host,
+                protocol,
+            });
+        }
+    }
+}
+
+impl fmt::Debug for Reporter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Reporter").field("timings", &self.timings.is_some())
+                                  .field("host", &self.events.as_ref().map(|(_, host)| host))
+                                  .finish()
+    }
+}
+
+///Connector, that can be used by client.
+///
+///Client configures copy of connector for each of its connection pools.
+pub trait Connect: hyper::service::Service<hyper::Uri> + Clone {
+    ///Returns connector, that reaches destinations via `proxy`.
+    ///
+    ///For `https` destinations, connector must establish tunnel via [proxy::tunnel](proxy/fn.tunnel.html).
+    ///Otherwise it must connect to proxy itself, as client sends requests to it in absolute-form.
+    fn with_proxy(self, proxy: Option<proxy::ProxyUri>) -> Self;
+
+    #[inline]
+    ///Connects to `dst`, reporting progress via `reporter`.
+    ///
+    ///By default connector is invoked as service, without reporting anything.
+    fn connect(&mut self, dst: hyper::Uri, reporter: Reporter) -> Self::Future {
+        let _ = reporter;
+        self.call(dst)
+    }
+}
+
+async fn connect_tcp(dst: hyper::Uri, proxy: Option<proxy::ProxyUri>, dns: Option<dns::Cache>, reporter: Reporter) -> io::Result<tokio::net::TcpStream> {
     let host = match dst.host() {
-        Some(host) => host,
+        Some(host) => crate::utils::host_to_ascii(host),
         None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "No host specified")),
     };
 
@@ -21,15 +170,86 @@ async fn connect_tcp(dst: hyper::Uri) -> io::Result<tokio::net::TcpStream> {
         }
     };
 
-    match matsu!(tokio::net::TcpStream::connect((host, port))) {
-        Ok(io) => return Ok(io),
-        Err(_) => Err(io::Error::new(io::ErrorKind::NotFound, "Unable to connect")),
+    match proxy {
+        //Plain HTTP requests are sent to proxy as they are
+        Some(proxy) if dst.scheme_str() != Some("https") => matsu!(connect_addr(proxy.host(), proxy.port(), dns.as_ref(), &reporter)),
+        Some(proxy) => {
+            let mut io = matsu!(connect_addr(proxy.host(), proxy.port(), dns.as_ref(), &reporter))?;
+            let result = match matsu!(proxy::tunnel(&mut io, &host, port, &proxy)) {
+                Err(ref error) if matches!(proxy::ProxyAuthError::from_io(error), Some(proxy::ProxyAuthError::ConnectionClosed)) => {
+                    io = matsu!(connect_addr(proxy.host(), proxy.port(), dns.as_ref(), &reporter))?;
+                    matsu!(proxy::tunnel_authorized(&mut io, &host, port, &proxy))
+                },
+                result => result,
+            };
+
+            match result {
+                Ok(()) => Ok(io),
+                Err(error) => Err(ConnectError::new(ConnectStage::Proxy, error).into()),
+            }
+        },
+        None => matsu!(connect_addr(&host, port, dns.as_ref(), &reporter)),
     }
 }
 
-#[derive(Clone, Copy, Default)]
+async fn connect_addr(host: &str, port: u16, dns: Option<&dns::Cache>, reporter: &Reporter) -> io::Result<tokio::net::TcpStream> {
+    reporter.record(|timings| timings.resolve_start = Some(std::time::Instant::now()));
+    let addrs = match matsu!(dns::resolve(dns, host, port)) {
+        Ok(addrs) => addrs,
+        Err(error) => return Err(ConnectError::new(ConnectStage::Dns, error).into()),
+    };
+    reporter.record(|timings| timings.resolve_end = Some(std::time::Instant::now()));
+
+    let mut last_error = None;
+    for addr in addrs {
+        match matsu!(tokio::net::TcpStream::connect(addr)) {
+            Ok(io) => {
+                reporter.record(|timings| timings.connected = Some(std::time::Instant::now()));
+                return Ok(io);
+            },
+            Err(error) => last_error = Some(error),
+        }
+    }
+
+    let error = match last_error {
+        Some(error) => ConnectError::new(ConnectStage::Tcp, error),
+        None => ConnectError::new(ConnectStage::Dns, io::Error::new(io::ErrorKind::NotFound, "Host has no address")),
+    };
+
+    Err(error.into())
+}
+
+#[derive(Clone, Default)]
 ///Plain HTTP Connector
 pub struct HttpConnector {
+    proxy: Option<proxy::ProxyUri>,
+    dns: Option<dns::Cache>,
+}
+
+impl HttpConnector {
+    #[inline]
+    ///Sets cache of DNS lookups, which is shared by clones of connector.
+    ///
+    ///By default, hosts are resolved on each connection.
+    pub fn dns_cache(mut self, cache: Option<dns::Cache>) -> Self {
+        self.dns = cache;
+        self
+    }
+}
+
+impl Connect for HttpConnector {
+    #[inline]
+    fn with_proxy(mut self, proxy: Option<proxy::ProxyUri>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    #[inline]
+    fn connect(&mut self, dst: hyper::Uri, reporter: Reporter) -> Self::Future {
+        //TODO: remove uncessary allocations
+        //      Most likely need to work-around Unpin requirement
+        Box::pin(connect_tcp(dst, self.proxy.clone(), self.dns.clone(), reporter))
+    }
 }
 
 impl hyper::service::Service<hyper::Uri> for HttpConnector {
@@ -44,9 +264,7 @@ impl hyper::service::Service<hyper::Uri> for HttpConnector {
 
     #[inline(always)]
     fn call(&mut self, dst: hyper::Uri) -> Self::Future {
-        //TODO: remove uncessary allocations
-        //      Most likely need to work-around Unpin requirement
-        Box::pin(connect_tcp(dst))
+        self.connect(dst, Reporter::default())
     }
 }
 
@@ -3,7 +3,7 @@
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_rustls::client::TlsStream;
 
-use super::super::{HttpConnector};
+use super::super::{HttpConnector, Connect, ConnectError, ConnectStage, Reporter, dns, proxy};
 use crate::utils;
 
 use std::io;
@@ -63,10 +63,10 @@ impl<T> From<TlsStream<T>> for HttpsStream<T> {
     }
 }
 
-impl<T> Into<TlsStream<T>> for HttpsStream<T> {
+impl<T> From<HttpsStream<T>> for TlsStream<T> {
     #[inline(always)]
-    fn into(self) -> TlsStream<T> {
-        self.inner
+    fn from(val: HttpsStream<T>) -> Self {
+        val.inner
     }
 }
 
@@ -75,7 +75,7 @@ pub enum MaybeHttpsStream<T> {
     /// A stream over plain text.
     Http(T),
     /// A stream protected with TLS.
-    Https(TlsStream<T>),
+    Https(Box<TlsStream<T>>),
 }
 
 impl hyper::client::connect::Connection for MaybeHttpsStream<tokio::net::TcpStream> {
@@ -155,44 +155,80 @@ impl Default for HttpsConnector {
     }
 }
 
+impl HttpsConnector {
+    #[inline]
+    ///Sets cache of DNS lookups on underlying HTTP connector.
+    ///
+    ///See [HttpConnector::dns_cache](../../struct.HttpConnector.html#method.dns_cache).
+    ///
+    ///## Usage
+    ///
+    ///```rust
+    ///use yukikaze::client;
+    ///use yukikaze::connector::dns;
+    ///
+    ///let cache = dns::Cache::new(core::time::Duration::from_secs(60));
+    ///let connector = client::config::DefaultConnector::default().dns_cache(Some(cache));
+    ///let client = client::builder::ClientBuilder::<client::config::DefaultCfg>::new().connector(connector).build();
+    ///```
+    pub fn dns_cache(mut self, cache: Option<dns::Cache>) -> Self {
+        self.http = self.http.dns_cache(cache);
+        self
+    }
+}
+
 impl fmt::Debug for HttpsConnector {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.pad("HttpsConnector")
     }
 }
 
-impl hyper::service::Service<hyper::Uri> for HttpsConnector {
-    type Response = MaybeHttpsStream<<HttpConnector as hyper::service::Service<hyper::Uri>>::Response>;
-    type Error = io::Error;
-    type Future = utils::fut::Either<MaybeHttpsConnecting<<HttpConnector as hyper::service::Service<hyper::Uri>>::Future>, MaybeHttpConnecting<<HttpConnector as hyper::service::Service<hyper::Uri>>::Future>>;
-
-    #[inline(always)]
-    fn poll_ready(&mut self, ctx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.http.poll_ready(ctx).map_err(Into::into)
+impl Connect for HttpsConnector {
+    #[inline]
+    fn with_proxy(mut self, proxy: Option<proxy::ProxyUri>) -> Self {
+        self.http = self.http.with_proxy(proxy);
+        self
     }
 
-    fn call(&mut self, dst: hyper::Uri) -> Self::Future {
+    fn connect(&mut self, dst: hyper::Uri, reporter: Reporter) -> Self::Future {
         let is_https = dst.scheme().unwrap().as_str() == "https";
 
         match is_https {
             true => {
-                let state = HttpsOnlyConnectingState::Conneting(self.http.call(dst.clone()));
+                let state = HttpsOnlyConnectingState::Conneting(self.http.connect(dst.clone(), reporter.clone()));
 
                 let fut = HttpsOnlyConnecting {
                     dst,
                     config: self.config.clone(),
+                    reporter,
                     state,
                 };
 
                 utils::fut::Either::Left(MaybeHttpsConnecting(fut))
             },
             false => {
-                utils::fut::Either::Right(MaybeHttpConnecting(self.http.call(dst)))
+                utils::fut::Either::Right(MaybeHttpConnecting(self.http.connect(dst, reporter)))
             }
         }
     }
 }
 
+impl hyper::service::Service<hyper::Uri> for HttpsConnector {
+    type Response = MaybeHttpsStream<<HttpConnector as hyper::service::Service<hyper::Uri>>::Response>;
+    type Error = io::Error;
+    type Future = utils::fut::Either<MaybeHttpsConnecting<<HttpConnector as hyper::service::Service<hyper::Uri>>::Future>, MaybeHttpConnecting<<HttpConnector as hyper::service::Service<hyper::Uri>>::Future>>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, ctx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(ctx).map_err(Into::into)
+    }
+
+    #[inline]
+    fn call(&mut self, dst: hyper::Uri) -> Self::Future {
+        self.connect(dst, Reporter::default())
+    }
+}
+
 #[derive(Clone)]
 ///HTTPs only connect based on Rustls.
 ///
@@ -216,12 +252,42 @@ impl Default for HttpsOnlyConnector {
     }
 }
 
+impl HttpsOnlyConnector {
+    #[inline]
+    ///Sets cache of DNS lookups on underlying HTTP connector.
+    ///
+    ///See [HttpConnector::dns_cache](../../struct.HttpConnector.html#method.dns_cache).
+    pub fn dns_cache(mut self, cache: Option<dns::Cache>) -> Self {
+        self.http = self.http.dns_cache(cache);
+        self
+    }
+}
+
 impl fmt::Debug for HttpsOnlyConnector {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.pad("HttpsOnlyConnector")
     }
 }
 
+impl Connect for HttpsOnlyConnector {
+    #[inline]
+    fn with_proxy(mut self, proxy: Option<proxy::ProxyUri>) -> Self {
+        self.http = self.http.with_proxy(proxy);
+        self
+    }
+
+    fn connect(&mut self, dst: hyper::Uri, reporter: Reporter) -> Self::Future {
+        let state = HttpsOnlyConnectingState::Conneting(self.http.connect(dst.clone(), reporter.clone()));
+
+        HttpsOnlyConnecting {
+            dst,
+            config: self.config.clone(),
+            reporter,
+            state,
+        }
+    }
+}
+
 impl hyper::service::Service<hyper::Uri> for HttpsOnlyConnector {
     type Response = HttpsStream<<HttpConnector as hyper::service::Service<hyper::Uri>>::Response>;
     type Error = io::Error;
@@ -232,26 +298,22 @@ impl hyper::service::Service<hyper::Uri> for HttpsOnlyConnector {
         self.http.poll_ready(ctx)
     }
 
+    #[inline]
     fn call(&mut self, dst: hyper::Uri) -> Self::Future {
-        let state = HttpsOnlyConnectingState::Conneting(self.http.call(dst.clone()));
-
-        HttpsOnlyConnecting {
-            dst,
-            config: self.config.clone(),
-            state,
-        }
+        self.connect(dst, Reporter::default())
     }
 }
 
 enum HttpsOnlyConnectingState<T> {
     Conneting(T),
-    Tls(tokio_rustls::Connect<tokio::net::TcpStream>),
+    Tls(Box<tokio_rustls::Connect<tokio::net::TcpStream>>),
 }
 
 ///Ongoing HTTPS only connect
 pub struct HttpsOnlyConnecting<T> {
     dst: hyper::Uri,
     config: Arc<tokio_rustls::rustls::ClientConfig>,
+    reporter: Reporter,
     state: HttpsOnlyConnectingState<T>,
 }
 
@@ -267,21 +329,30 @@ impl<F: Unpin + Future<Output = io::Result<<HttpConnector as hyper::service::Ser
                 HttpsOnlyConnectingState::Conneting(ref mut connecting) => match Future::poll(unsafe { Pin::new_unchecked(connecting) }, ctx) {
                     Poll::Pending => return Poll::Pending,
                     Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
-                    Poll::Ready(Ok(tcp)) => match DNSNameRef::try_from_ascii_str(self.dst.host().unwrap()) {
+                    //Name must be the same as the one used for DNS lookup
+                    Poll::Ready(Ok(tcp)) => match DNSNameRef::try_from_ascii_str(&crate::utils::host_to_ascii(self.dst.host().unwrap())) {
                         Ok(dns_name) => {
                             let cfg = self.config.clone();
                             let connector = tokio_rustls::TlsConnector::from(cfg);
-                            HttpsOnlyConnectingState::Tls(connector.connect(dns_name, tcp))
+                            HttpsOnlyConnectingState::Tls(Box::new(connector.connect(dns_name, tcp)))
                         },
-                        Err(_) => return Poll::Ready(Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid DNS name"))),
+                        Err(_) => {
+                            let error = io::Error::new(io::ErrorKind::InvalidInput, "invalid DNS name");
+                            return Poll::Ready(Err(ConnectError::new(ConnectStage::Tls, error).into()));
+                        }
                     }
                 },
-                HttpsOnlyConnectingState::Tls(ref mut connecting) => match Future::poll(unsafe { Pin::new_unchecked(connecting) }, ctx) {
+                HttpsOnlyConnectingState::Tls(ref mut connecting) => match Future::poll(Pin::new(connecting), ctx) {
                     Poll::Pending => return Poll::Pending,
-                    Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
-                    Poll::Ready(Ok(tls)) => match tls.get_ref().1.get_alpn_protocol() {
-                        Some(b"h2") => return Poll::Ready(Ok(tls.into())),
-                        _ => return Poll::Ready(Ok(tls.into())),
+                    Poll::Ready(Err(error)) => return Poll::Ready(Err(ConnectError::new(ConnectStage::Tls, error).into())),
+                    Poll::Ready(Ok(tls)) => {
+                        self.reporter.record(|timings| timings.tls_handshake = Some(std::time::Instant::now()));
+                        self.reporter.tls_handshake_done(tls.get_ref().1.get_alpn_protocol());
+
+                        match tls.get_ref().1.get_alpn_protocol() {
+                            Some(b"h2") => return Poll::Ready(Ok(tls.into())),
+                            _ => return Poll::Ready(Ok(tls.into())),
+                        }
                     }
                 }
             }
@@ -297,7 +368,7 @@ impl<F: Unpin + Future<Output = io::Result<<HttpConnector as hyper::service::Ser
 
     fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
         let inner = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
-        Future::poll(inner, ctx).map(|res| res.map(|tls| MaybeHttpsStream::Https(tls.into())))
+        Future::poll(inner, ctx).map(|res| res.map(|tls| MaybeHttpsStream::Https(Box::new(tls.into()))))
     }
 }
 
@@ -309,6 +380,6 @@ impl<F: Unpin + Future<Output = io::Result<<HttpConnector as hyper::service::Ser
 
     fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
         let inner = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
-        Future::poll(inner, ctx).map(|res| res.map(|tcp| MaybeHttpsStream::Http(tcp)))
+        Future::poll(inner, ctx).map(|res| res.map(MaybeHttpsStream::Http))
     }
 }
@@ -0,0 +1,434 @@
+//!Connection statistics.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::io;
+use core::{task, pin, fmt};
+use core::future::Future;
+use core::mem::MaybeUninit;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use super::{events, limit, timings};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+///Statistics of connections to single host.
+pub struct HostStats {
+    ///Number of requests sent.
+    pub requests: usize,
+    ///Number of opened connections.
+    pub opened: usize,
+    ///Number of requests, that re-used existing connection.
+    pub reused: usize,
+    ///Number of currently open connections.
+    pub alive: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+///Statistics of client's connection pool.
+pub struct PoolStats {
+    ///Number of requests sent.
+    pub requests: usize,
+    ///Number of opened connections.
+    pub opened: usize,
+    ///Number of requests, that re-used existing connection.
+    pub reused: usize,
+    ///Number of currently open connections.
+    pub alive: usize,
+    ///Number of open connections, that are not used by ongoing requests.
+    ///
+    ///It is approximation, as connection is considered busy only until response's head is received.
+    pub idle: usize,
+    ///Statistics per host, identified by `<scheme>://<authority>`
+    pub hosts: HashMap<String, HostStats>,
+}
+
+///Returns key of host, used in statistics.
+pub(crate) fn host_key(uri: &hyper::Uri) -> String {
+    match (uri.scheme_str(), uri.authority()) {
+        (Some(scheme), Some(authority)) => format!("{}://{}", scheme, authority),
+        (None, Some(authority)) => authority.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Registry {
+    //Whether statistics are collected
+    is_enabled: bool,
+    hosts: Mutex<HashMap<String, HostStats>>,
+    in_flight: AtomicUsize,
+    limits: Option<Arc<limit::Limits>>,
+}
+
+impl Registry {
+    ///Creates registry, that limits number of connections per host, if `max_per_host` is specified.
+    ///
+    ///Statistics are collected only if `is_enabled` is true.
+    pub(crate) fn new(is_enabled: bool, max_per_host: Option<usize>) -> Self {
+        Self {
+            is_enabled,
+            limits: max_per_host.map(|max| Arc::new(limit::Limits::new(max))),
+            ..Self::default()
+        }
+    }
+
+    fn with_host<F: FnOnce(&mut HostStats)>(&self, host: &str, cb: F) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|error| error.into_inner());
+
+        match hosts.get_mut(host) {
+            Some(stats) => cb(stats),
+            None => {
+                let mut stats = HostStats::default();
+                cb(&mut stats);
+                hosts.insert(host.to_owned(), stats);
+            }
+        }
+    }
+
+    #[inline]
+    ///Returns whether connections need to be instrumented.
+    fn is_instrumented(&self) -> bool {
+        self.is_enabled || self.limits.is_some()
+    }
+
+    ///Registers request, which is in flight until guard is dropped.
+    pub(crate) fn on_request(self: &Arc<Self>, uri: &hyper::Uri) -> Option<RequestGuard> {
+        if !self.is_enabled {
+            return None;
+        }
+
+        self.with_host(&host_key(uri), |stats| stats.requests += 1);
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+
+        Some(RequestGuard {
+            registry: self.clone()
+        })
+    }
+
+    fn on_open(&self, host: &str) {
+        if self.is_enabled {
+            self.with_host(host, |stats| {
+                stats.opened += 1;
+                stats.alive += 1;
+            });
+        }
+    }
+
+    fn on_reuse(&self, host: &str) {
+        if self.is_enabled {
+            self.with_host(host, |stats| stats.reused += 1);
+        }
+    }
+
+    fn on_close(&self, host: &str) {
+        if self.is_enabled {
+            self.with_host(host, |stats| stats.alive = stats.alive.saturating_sub(1));
+        }
+    }
+
+    pub(crate) fn stats(&self) -> PoolStats {
+        let hosts = self.hosts.lock().unwrap_or_else(|error| error.into_inner());
+        let mut result = PoolStats::default();
+
+        for (host, stats) in hosts.iter() {
+            let stats = *stats;
+
+            result.requests += stats.requests;
+            result.opened += stats.opened;
+            result.reused += stats.reused;
+            result.alive += stats.alive;
+            result.hosts.insert(host.clone(), stats);
+        }
+
+        result.idle = result.alive.saturating_sub(self.in_flight.load(Ordering::Acquire));
+
+        result
+    }
+}
+
+pub(crate) struct RequestGuard {
+    registry: Arc<Registry>,
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.registry.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[derive(Clone)]
+///Connection's timings state, that is stored in response's extensions by hyper.
+pub(crate) struct ConnectionTimings(pub(crate) timings::SharedState);
+
+#[derive(Clone)]
+///Connection's identity, that is stored in response's extensions by hyper.
+pub(crate) struct ConnectionInfo {
+    host: Arc<str>,
+    addr: Option<std::net::SocketAddr>,
+    uses: Arc<AtomicUsize>,
+    registry: Arc<Registry>,
+    events: events::Listener,
+}
+
+impl ConnectionInfo {
+    ///Registers response received over connection, accounting it as re-used, if it has been used before.
+    pub(crate) fn on_response(&self) {
+        if self.uses.fetch_add(1, Ordering::AcqRel) > 0 {
+            self.registry.on_reuse(&self.host);
+            (self.events)(&events::ConnectionEvent::Connected {
+                host: &self.host,
+                addr: self.addr,
+                reused: true,
+            });
+        }
+    }
+}
+
+///Connector wrapper, that collects statistics of connections.
+///
+///Connections are instrumented only if it is required by client's config, otherwise they are
+///established as it is.
+pub struct Instrumented<C> {
+    inner: C,
+    registry: Arc<Registry>,
+    timings: bool,
+    //Whether connections lead to proxy, which expects requests in absolute-form
+    is_proxied: bool,
+    events: events::Listener,
+}
+
+impl<C> Instrumented<C> {
+    pub(crate) fn new(inner: C, registry: Arc<Registry>, timings: bool, is_proxied: bool, events: events::Listener) -> Self {
+        Self {
+            inner,
+            registry,
+            timings,
+            is_proxied,
+            events,
+        }
+    }
+}
+
+impl<C: Clone> Clone for Instrumented<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            registry: self.registry.clone(),
+            timings: self.timings,
+            is_proxied: self.is_proxied,
+            events: self.events,
+        }
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Instrumented<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+enum ConnectingState<F, S, E> {
+    Plain(F, bool),
+    Instrumented(pin::Pin<Box<dyn Future<Output = Result<InstrumentedStream<S>, E>> + Send>>),
+}
+
+///Future of connection, that is established by [Instrumented](struct.Instrumented.html) connector.
+pub struct Connecting<F, S, E>(ConnectingState<F, S, E>);
+
+impl<F: Future<Output = Result<S, E>> + Unpin, S, E> Future for Connecting<F, S, E> {
+    type Output = Result<InstrumentedStream<S>, E>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        match &mut self.0 {
+            ConnectingState::Plain(connecting, is_proxied) => {
+                let is_proxied = *is_proxied;
+                Future::poll(pin::Pin::new(connecting), ctx).map(|result| result.map(|inner| InstrumentedStream {
+                    inner,
+                    timings: None,
+                    info: None,
+                    is_proxied,
+                    _permit: None,
+                }))
+            },
+            ConnectingState::Instrumented(connecting) => Future::poll(connecting.as_mut(), ctx),
+        }
+    }
+}
+
+impl<C> hyper::service::Service<hyper::Uri> for Instrumented<C> where C: super::Connect, C::Response: hyper::client::connect::Connection, C::Future: Send + Unpin + 'static {
+    type Response = InstrumentedStream<C::Response>;
+    type Error = C::Error;
+    type Future = Connecting<C::Future, C::Response, C::Error>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, ctx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(ctx)
+    }
+
+    fn call(&mut self, dst: hyper::Uri) -> Self::Future {
+        if !self.timings && !self.registry.is_instrumented() {
+            return Connecting(ConnectingState::Plain(self.inner.connect(dst, super::Reporter::default()), self.is_proxied));
+        }
+
+        let host = host_key(&dst);
+        let registry = self.registry.clone();
+        let events = self.events;
+        let timings = match self.timings {
+            true => Some(timings::SharedState::default()),
+            false => None,
+        };
+
+        let start = std::time::Instant::now();
+        events(&events::ConnectionEvent::ConnectStart {
+            host: &host,
+        });
+        let host: Arc<str> = host.into();
+        let permit = registry.limits.as_ref().map(|limits| limits.acquire(host.clone()));
+
+        let connecting = self.inner.connect(dst, super::Reporter::new(timings.clone(), events, host.clone()));
+        let is_proxied = self.is_proxied;
+
+        Connecting(ConnectingState::Instrumented(Box::pin(async move {
+            //Connection is established only once host has free slot, which is held until connection is closed.
+            let permit = match permit {
+                Some(permit) => Some(matsu!(permit)),
+                None => None,
+            };
+
+            if let Some(timings) = timings.as_ref() {
+                timings::lock(timings).connect.start = Some(start);
+            }
+
+            let inner = match matsu!(connecting) {
+                Ok(inner) => inner,
+                Err(error) => {
+                    events(&events::ConnectionEvent::ConnectFailed {
+                        host: &host,
+                    });
+                    return Err(error);
+                }
+            };
+            registry.on_open(&host);
+
+            if let Some(timings) = timings.as_ref() {
+                timings::lock(timings).connect.end = Some(std::time::Instant::now());
+            }
+
+            let mut extras = http::Extensions::new();
+            hyper::client::connect::Connection::connected(&inner).get_extras(&mut extras);
+            let info = ConnectionInfo {
+                host,
+                addr: extras.get::<hyper::client::connect::HttpInfo>().map(|info| info.remote_addr()),
+                uses: Arc::new(AtomicUsize::new(0)),
+                registry,
+                events,
+            };
+            events(&events::ConnectionEvent::Connected {
+                host: &info.host,
+                addr: info.addr,
+                reused: false,
+            });
+
+            Ok(InstrumentedStream {
+                inner,
+                timings,
+                info: Some(info),
+                is_proxied,
+                _permit: permit,
+            })
+        })))
+    }
+}
+
+///Connection, that is tracked by [Instrumented](struct.Instrumented.html) connector.
+pub struct InstrumentedStream<S> {
+    inner: S,
+    timings: Option<timings::SharedState>,
+    info: Option<ConnectionInfo>,
+    is_proxied: bool,
+    _permit: Option<limit::Permit>,
+}
+
+impl<S> InstrumentedStream<S> {
+    #[inline]
+    ///Returns reference to underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S> Drop for InstrumentedStream<S> {
+    fn drop(&mut self) {
+        if let Some(info) = self.info.as_ref() {
+            info.registry.on_close(&info.host);
+            (info.events)(&events::ConnectionEvent::ConnectionClosed {
+                host: &info.host,
+                addr: info.addr,
+            });
+        }
+    }
+}
+
+impl<S: hyper::client::connect::Connection> hyper::client::connect::Connection for InstrumentedStream<S> {
+    #[inline(always)]
+    fn connected(&self) -> hyper::client::connect::Connected {
+        let connected = self.inner.connected();
+        let connected = match self.info.as_ref() {
+            Some(info) => connected.extra(info.clone()),
+            None => connected,
+        };
+        let connected = match self.is_proxied {
+            true => connected.proxy(true),
+            false => connected,
+        };
+        match self.timings.as_ref() {
+            Some(timings) => connected.extra(ConnectionTimings(timings.clone())),
+            None => connected,
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for InstrumentedStream<S> {
+    #[inline(always)]
+    unsafe fn prepare_uninitialized_buffer(&self, buff: &mut [MaybeUninit<u8>]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buff)
+    }
+
+    #[inline(always)]
+    fn poll_read(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>, buff: &mut [u8]) -> task::Poll<io::Result<usize>> {
+        let result = AsyncRead::poll_read(pin::Pin::new(&mut self.inner), ctx, buff);
+
+        if let (Some(timings), task::Poll::Ready(Ok(size))) = (self.timings.as_ref(), &result) {
+            if *size > 0 {
+                timings::lock(timings).on_read();
+            }
+        }
+
+        result
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for InstrumentedStream<S> {
+    #[inline(always)]
+    fn poll_write(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>, buff: &[u8]) -> task::Poll<io::Result<usize>> {
+        let result = AsyncWrite::poll_write(pin::Pin::new(&mut self.inner), ctx, buff);
+
+        if let (Some(timings), task::Poll::Ready(Ok(_))) = (self.timings.as_ref(), &result) {
+            timings::lock(timings).on_write();
+        }
+
+        result
+    }
+
+    #[inline(always)]
+    fn poll_flush(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(pin::Pin::new(&mut self.inner), ctx)
+    }
+
+    #[inline(always)]
+    fn poll_shutdown(mut self: pin::Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(pin::Pin::new(&mut self.inner), ctx)
+    }
+}
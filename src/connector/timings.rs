@@ -0,0 +1,134 @@
+//!Connection timings.
+//!
+//!Connectors report stages of connection establishment via [Reporter::record](../struct.Reporter.html#method.record),
+//!which is no-op unless client collects timings.
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, Default)]
+///Timestamps of connection establishment.
+pub struct ConnectTimings {
+    ///Connector is invoked.
+    pub start: Option<Instant>,
+    ///Host name resolution started.
+    pub resolve_start: Option<Instant>,
+    ///Host name is resolved.
+    pub resolve_end: Option<Instant>,
+    ///TCP connection is established.
+    pub connected: Option<Instant>,
+    ///TLS handshake is finished.
+    pub tls_handshake: Option<Instant>,
+    ///Connection is ready to be used.
+    pub end: Option<Instant>,
+}
+
+#[derive(Default)]
+pub(crate) struct ConnectionState {
+    pub(crate) connect: ConnectTimings,
+    pub(crate) last_write: Option<Instant>,
+    pub(crate) first_read: Option<Instant>,
+    pub(crate) last_read: Option<Instant>,
+}
+
+impl ConnectionState {
+    pub(crate) fn on_write(&mut self) {
+        self.last_write = Some(Instant::now());
+        self.first_read = None;
+    }
+
+    pub(crate) fn on_read(&mut self) {
+        let now = Instant::now();
+        self.last_read = Some(now);
+        if self.first_read.is_none() {
+            self.first_read = Some(now);
+        }
+    }
+}
+
+pub(crate) type SharedState = Arc<Mutex<ConnectionState>>;
+
+#[inline]
+pub(crate) fn lock(state: &SharedState) -> std::sync::MutexGuard<'_, ConnectionState> {
+    state.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+#[derive(Clone)]
+///Timings of request.
+///
+///Available in response's extensions, when [Config::collect_timings](../../client/config/trait.Config.html#method.collect_timings) is enabled.
+///
+///Timestamps of connection's IO are taken from the connection, hence it is precise only as long
+///as connection is not used by other request.
+pub struct Timings {
+    ///Request is started.
+    pub start: Instant,
+    ///Timings of connection establishment.
+    ///
+    ///`None` if request re-used existing connection.
+    pub connect: Option<ConnectTimings>,
+    ///Request is written into connection.
+    pub request_written: Option<Instant>,
+    ///First byte of response is received.
+    pub first_byte: Option<Instant>,
+    ///Response's head is received.
+    pub headers: Instant,
+    pub(crate) state: SharedState,
+}
+
+impl Timings {
+    pub(crate) fn new(start: Instant, state: SharedState) -> Self {
+        let headers = Instant::now();
+        let guard = lock(&state);
+
+        let connect = match guard.connect.start {
+            Some(connect_start) if connect_start >= start => Some(guard.connect),
+            _ => None,
+        };
+        let request_written = guard.last_write;
+        let first_byte = guard.first_read;
+        drop(guard);
+
+        Self {
+            start,
+            connect,
+            request_written,
+            first_byte,
+            headers,
+            state,
+        }
+    }
+
+    #[inline]
+    ///Returns time to first byte, since request is started.
+    pub fn ttfb(&self) -> Option<core::time::Duration> {
+        self.first_byte.map(|first_byte| first_byte.duration_since(self.start))
+    }
+
+    #[inline]
+    ///Returns time, when last byte of response has been received.
+    ///
+    ///It is meaningful only after body is read.
+    pub fn last_byte(&self) -> Option<Instant> {
+        lock(&self.state).last_read
+    }
+
+    #[inline]
+    ///Returns total duration of request, as long as body has been read.
+    pub fn total(&self) -> Option<core::time::Duration> {
+        self.last_byte().map(|last_byte| last_byte.duration_since(self.start))
+    }
+}
+
+impl fmt::Debug for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Timings").field("start", &self.start)
+                                 .field("connect", &self.connect)
+                                 .field("request_written", &self.request_written)
+                                 .field("first_byte", &self.first_byte)
+                                 .field("headers", &self.headers)
+                                 .field("last_byte", &self.last_byte())
+                                 .finish()
+    }
+}
@@ -1,9 +1,10 @@
 use core::marker::Unpin;
 use core::cmp;
 use std::io::{self, Write};
-use std::fs::File;
+use std::fs::{self, File};
+use std::path::Path;
 
-use super::BodyReadError;
+use super::{BodyReadError, Decompress};
 use crate::header::ContentEncoding;
 
 use http_body::Body as HttpBody;
@@ -17,111 +18,163 @@ use super::Notifier;
 
 const BUFFER_SIZE: usize = 4096;
 
+///Maximum capacity, that is allocated upfront, when body is read into memory.
+///
+///Buffer starts with capacity of body's limit (e.g. `Content-Length`), but not above this cap,
+///so that server cannot cause huge allocation just by declaring big length.
+///Then it grows geometrically as data arrives, without exceeding limit.
+pub const DEFAULT_BUFFER_CAP: usize = 64 * 1024;
+
 #[inline(always)]
 fn calculate_buffer_size(limit: Option<usize>) -> (usize, usize) {
+    calculate_capped_buffer_size(limit, DEFAULT_BUFFER_CAP)
+}
+
+#[inline(always)]
+fn calculate_capped_buffer_size(limit: Option<usize>, cap: usize) -> (usize, usize) {
     match limit {
-        Some(limit) => (limit, cmp::min(BUFFER_SIZE, limit)),
-        None => (BUFFER_SIZE, BUFFER_SIZE)
+        Some(limit) => (limit, cmp::min(cap, limit)),
+        None => (BUFFER_SIZE, cmp::min(cap, BUFFER_SIZE))
     }
 }
 
+///Appends `chunk` to `buffer`, doubling its capacity when needed, but not beyond `limit`, unless chunk requires it.
+fn extend_buffer(buffer: &mut Vec<u8>, chunk: &[u8], limit: usize) {
+    let required = buffer.len().saturating_add(chunk.len());
+    if required > buffer.capacity() {
+        let target = cmp::max(required, cmp::min(buffer.capacity().saturating_mul(2), limit));
+        buffer.reserve_exact(target - buffer.len());
+    }
+    buffer.extend_from_slice(chunk);
+}
+
 #[cfg(feature = "compu")]
 macro_rules! impl_compu_bytes {
-    ($decoder:expr, $body:expr, $limit:expr) => {
-        use compu::decoder::DecoderResult;
+    ($decoder:expr, $decompress:expr, $body:expr, $limit:expr) => {
+        impl_compu_bytes!($decoder, $decompress, $body, $limit, crate::extractor::Noop)
+    };
+    ($decoder:expr, $decompress:expr, $body:expr, $limit:expr, $notify:expr) => {
+        use crate::extractor::inflate::Status;
 
-        let mut decoder = compu::decompressor::memory::Decompressor::new($decoder);
+        let mut decoder = $decompress.inflater($decoder);
+        let mut output = Vec::with_capacity(cmp::min($limit, BUFFER_SIZE));
 
         while let Some(chunk) = matsu!($body.data()) {
             let chunk = chunk.map(Into::into).map_err(Into::into)?;
 
-            match decoder.push(&chunk) {
-                DecoderResult::Finished => break,
-                DecoderResult::NeedInput => (),
-                result => return Err(BodyReadError::CompuError(result)),
+            crate::extractor::Notifier::send(&mut $notify, chunk.len());
+            if !crate::extractor::Notifier::should_continue(&mut $notify) {
+                return Err(BodyReadError::Cancelled);
+            }
+
+            //Output is limited within chunk, so that small chunk cannot expand beyond limit.
+            let (_, status) = decoder.push(&chunk, &mut output, $limit.saturating_add(1))?;
+            if output.len() > $limit {
+                return Err(BodyReadError::Overflow(output.into()))
             }
 
-            if $limit < decoder.output().len() {
-                return Err(BodyReadError::Overflow(decoder.take().into()))
+            match status {
+                Status::Finished => break,
+                _ => (),
             }
         }
 
-        match decoder.decoder().is_finished() {
-            true => return Ok(decoder.take().into()),
+        match decoder.is_finished() {
+            true => return Ok(output),
             false => return Err(BodyReadError::IncompleteDecompression),
         }
+    }
+}
+#[cfg(feature = "compu")]
+macro_rules! impl_compu_file {
+    ($decoder:expr, $decompress:expr, $body:expr, $file:expr) => {
+        impl_compu_file!($decoder, $decompress, $body, $file, crate::extractor::Noop)
     };
-    ($decoder:expr, $body:expr, $limit:expr, $notify:expr) => {
-        use compu::decoder::DecoderResult;
-
-        let mut decoder = compu::decompressor::memory::Decompressor::new($decoder);
+    ($decoder:expr, $decompress:expr, $body:expr, $file:expr, $notify:expr) => {
+        let mut decoder = $decompress.inflater($decoder);
+        let mut buffer = Vec::new();
+        let writer = $file;
 
         while let Some(chunk) = matsu!($body.data()) {
             let chunk = chunk.map(Into::into).map_err(Into::into)?;
 
-            $notify.send(chunk.len());
-
-            match decoder.push(&chunk) {
-                DecoderResult::Finished => break,
-                DecoderResult::NeedInput => (),
-                result => return Err(BodyReadError::CompuError(result)),
+            crate::extractor::Notifier::send(&mut $notify, chunk.len());
+            if !crate::extractor::Notifier::should_continue(&mut $notify) {
+                return Err(BodyReadError::Cancelled);
             }
 
-            if $limit < decoder.output().len() {
-                return Err(BodyReadError::Overflow(decoder.take().into()))
+            if decoder.push_all(&chunk, &mut buffer, |data| std::io::Write::write_all(writer, data).map_err(Into::into))? {
+                break;
             }
         }
 
-        match decoder.decoder().is_finished() {
-            true => return Ok(decoder.take().into()),
+        match decoder.is_finished() {
+            true => (),
             false => return Err(BodyReadError::IncompleteDecompression),
         }
     }
 }
+
 #[cfg(feature = "compu")]
-macro_rules! impl_compu_file {
-    ($decoder:expr, $body:expr, $file:expr) => {
-        use compu::decoder::DecoderResult;
+macro_rules! impl_compu_tee {
+    ($decoder:expr, $decompress:expr, $body:expr, $file:expr, $limit:expr) => {{
+        use crate::extractor::inflate::Status;
 
-        let mut decoder = compu::decompressor::write::Decompressor::new($decoder, $file);
+        let mut decoder = $decompress.inflater($decoder);
+        let mut output = Vec::with_capacity(cmp::min($limit, BUFFER_SIZE));
+        let mut overflow = false;
+        let mut finished = false;
 
         while let Some(chunk) = matsu!($body.data()) {
             let chunk = chunk.map(Into::into).map_err(Into::into)?;
+            let mut input = &chunk[..];
 
-            match decoder.push(&chunk)? {
-                (DecoderResult::Finished, _) => break,
-                (DecoderResult::NeedInput, _) => (),
-                (result, _) => return Err(BodyReadError::CompuError(result)),
-            }
-        }
+            //Once memory copy exceeds limit, output is only used as scratch buffer for file.
+            loop {
+                let max_len = match overflow {
+                    true => {
+                        output.clear();
+                        BUFFER_SIZE
+                    },
+                    false => $limit.saturating_add(1),
+                };
 
-        match decoder.decoder().is_finished() {
-            true => (),
-            false => return Err(BodyReadError::IncompleteDecompression),
-        }
-    };
-    ($decoder:expr, $body:expr, $file:expr, $notify:expr) => {
-        use compu::decoder::DecoderResult;
+                let written = output.len();
+                let (consumed, status) = decoder.push(input, &mut output, max_len)?;
+                input = &input[consumed..];
 
-        let mut decoder = compu::decompressor::write::Decompressor::new($decoder, $file);
+                if let Err(error) = $file.write_all(&output[written..]) {
+                    return Err(BodyReadError::FileError($file.into_inner().expect("To get File"), error));
+                }
 
-        while let Some(chunk) = matsu!($body.data()) {
-            let chunk = chunk.map(Into::into).map_err(Into::into)?;
+                if !overflow && $limit < output.len() {
+                    overflow = true;
+                    output = Vec::with_capacity(BUFFER_SIZE);
+                }
 
-            $notify.send(chunk.len());
+                match status {
+                    Status::Full => continue,
+                    Status::NeedInput => break,
+                    Status::Finished => {
+                        finished = true;
+                        break;
+                    }
+                }
+            }
 
-            match decoder.push(&chunk)? {
-                (DecoderResult::Finished, _) => break,
-                (DecoderResult::NeedInput, _) => (),
-                (result, _) => return Err(BodyReadError::CompuError(result)),
+            if finished {
+                break;
             }
         }
 
-        match decoder.decoder().is_finished() {
-            true => decoder.take(),
+        match decoder.is_finished() {
+            true => match overflow {
+                true => None,
+                false => Some(bytes::Bytes::from(output)),
+            },
             false => return Err(BodyReadError::IncompleteDecompression),
         }
-    }
+    }}
 }
 
 ///Extracts body as bytes from `Stream`
@@ -131,41 +184,165 @@ macro_rules! impl_compu_file {
 ///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
 ///- `encoding` - Specifies encoding to use.
 ///- `limit` - Specifies limit on body size, if not specified uses default 4kb
-pub async fn raw_bytes<S, I, E>(mut body: S, encoding: ContentEncoding, limit: Option<usize>) -> Result<bytes::Bytes, BodyReadError>
+pub async fn raw_bytes<S, I, E>(body: S, encoding: ContentEncoding, limit: Option<usize>) -> Result<bytes::Bytes, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
 {
-    let (limit, buffer_size) = calculate_buffer_size(limit);
+    matsu!(raw_bytes_capped(body, encoding, limit, DEFAULT_BUFFER_CAP))
+}
+
+///Extracts body as bytes from `Stream`, allocating at most `cap` bytes upfront.
+///
+///See [DEFAULT_BUFFER_CAP](constant.DEFAULT_BUFFER_CAP.html), which is used by [raw_bytes](fn.raw_bytes.html).
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
+///- `encoding` - Specifies encoding to use.
+///- `limit` - Specifies limit on body size, if not specified uses default 4kb
+///- `cap` - Specifies maximum initial capacity of buffer.
+pub async fn raw_bytes_capped<S, I, E>(body: S, encoding: ContentEncoding, limit: Option<usize>, cap: usize) -> Result<bytes::Bytes, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    matsu!(raw_bytes_capped_impl(body, encoding.into(), limit, cap))
+}
 
-    match encoding {
+///Implements [raw_bytes_capped](fn.raw_bytes_capped.html) with specified decompression.
+pub(crate) async fn raw_bytes_capped_impl<S, I, E>(body: S, decompress: Decompress, limit: Option<usize>, cap: usize) -> Result<bytes::Bytes, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    matsu!(raw_vec_capped(body, decompress, limit, cap)).map(Into::into)
+}
+
+///Extracts body into `Vec`, which can be converted into `String` without copying.
+async fn raw_vec_capped<S, I, E>(mut body: S, decompress: Decompress, limit: Option<usize>, cap: usize) -> Result<Vec<u8>, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    let (limit, buffer_size) = calculate_capped_buffer_size(limit, cap);
+
+    match decompress.encoding {
         #[cfg(feature = "compu")]
         ContentEncoding::Brotli => {
-            impl_compu_bytes!(compu::decoder::brotli::BrotliDecoder::default(), body, limit);
+            impl_compu_bytes!(compu::decoder::brotli::BrotliDecoder::default(), decompress, body, limit);
         },
         #[cfg(feature = "compu")]
         ContentEncoding::Gzip => {
             let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Gzip);
-            impl_compu_bytes!(compu::decoder::zlib::ZlibDecoder::new(&options), body, limit);
+            impl_compu_bytes!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, limit);
         },
         #[cfg(feature = "compu")]
         ContentEncoding::Deflate => {
             let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Zlib);
-            impl_compu_bytes!(compu::decoder::zlib::ZlibDecoder::new(&options), body, limit);
+            impl_compu_bytes!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, limit);
         },
         _ => {
-            let mut buffer = bytes::BytesMut::with_capacity(buffer_size);
+            let mut buffer = Vec::with_capacity(buffer_size);
 
             while let Some(chunk) = matsu!(body.data()) {
                 let chunk = chunk.map(Into::into).map_err(Into::into)?;
 
-                buffer.extend_from_slice(&chunk[..]);
+                extend_buffer(&mut buffer, &chunk[..], limit);
                 if buffer.len() > limit {
-                    return Err(BodyReadError::Overflow(buffer.freeze()));
+                    return Err(BodyReadError::Overflow(buffer.into()));
                 }
             }
 
-            Ok(buffer.freeze())
+            Ok(buffer)
+        }
+    }
+}
+
+///Extracts body as chunks, in which it is received, without coalescing them into contiguous buffer.
+///
+///Body is read as it is, without decompression.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
+///- `limit` - Specifies limit on body size, if not specified uses default 4kb
+///
+///On overflow, error contains chunks read so far, joined together.
+pub async fn raw_chunks<S, I, E>(mut body: S, limit: Option<usize>) -> Result<Vec<bytes::Bytes>, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    let (limit, _) = calculate_buffer_size(limit);
+    let mut chunks = Vec::new();
+    let mut len = 0usize;
+
+    while let Some(chunk) = matsu!(body.data()) {
+        let chunk: bytes::Bytes = chunk.map(Into::into).map_err(Into::into)?;
+
+        len = len.saturating_add(chunk.len());
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+
+        if len > limit {
+            return Err(BodyReadError::Overflow(chunks.concat().into()));
+        }
+    }
+
+    Ok(chunks)
+}
+
+///Partially read body, that hit limit.
+///
+///Contains already read data and the rest of body, allowing to continue reading it.
+pub struct OverflowWithRest<S> {
+    ///Data read so far, it may exceed limit by size of last chunk.
+    pub data: bytes::Bytes,
+    ///Remaining body.
+    pub rest: S,
+}
+
+impl<S> core::fmt::Debug for OverflowWithRest<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "OverflowWithRest {{ data: {} bytes }}", self.data.len())
+    }
+}
+
+impl<S, I, E> OverflowWithRest<S> where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError> {
+    ///Writes already read data and the rest of body to file.
+    pub async fn file(self, file: File) -> Result<File, BodyReadError> {
+        let mut file = file;
+
+        if let Err(error) = file.write_all(&self.data[..]) {
+            return Err(BodyReadError::FileError(file, error));
+        }
+
+        matsu!(self::file(file, self.rest, ContentEncoding::Identity))
+    }
+}
+
+///Extracts body as bytes from `Stream`, returning the rest of body if limit is hit.
+///
+///Body is read as it is, without decompression, so that the rest of it
+///can be processed together with already read data.
+///To decompress both, use [Response::body_or_rest](../client/response/struct.Response.html#method.body_or_rest),
+///which yields decompressed body.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read.
+///- `limit` - Specifies limit on body size, if not specified uses default 4kb
+pub async fn raw_bytes_or_rest<S, I, E>(mut body: S, limit: Option<usize>) -> Result<Result<bytes::Bytes, OverflowWithRest<S>>, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    let (limit, buffer_size) = calculate_buffer_size(limit);
+    let mut buffer = Vec::with_capacity(buffer_size);
+
+    while let Some(chunk) = matsu!(body.data()) {
+        let chunk = chunk.map(Into::into).map_err(Into::into)?;
+
+        extend_buffer(&mut buffer, &chunk[..], limit);
+        if buffer.len() > limit {
+            return Ok(Err(OverflowWithRest {
+                data: buffer.into(),
+                rest: body,
+            }));
         }
     }
+
+    Ok(Ok(buffer.into()))
 }
 
 ///Extracts body as text from `Stream`
@@ -178,9 +355,16 @@ pub async fn raw_bytes<S, I, E>(mut body: S, encoding: ContentEncoding, limit: O
 pub async fn text<S, I, E>(body: S, encoding: ContentEncoding, limit: Option<usize>) -> Result<String, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
 {
-    let bytes = matsu!(raw_bytes(body, encoding, limit))?;
+    matsu!(text_impl(body, encoding.into(), limit))
+}
 
-    String::from_utf8(bytes.to_vec()).map_err(|error| error.into())
+///Implements [text](fn.text.html) with specified decompression.
+pub(crate) async fn text_impl<S, I, E>(body: S, decompress: Decompress, limit: Option<usize>) -> Result<String, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    let bytes = matsu!(raw_vec_capped(body, decompress, limit, DEFAULT_BUFFER_CAP))?;
+
+    String::from_utf8(bytes).map_err(|error| error.into())
 }
 
 #[cfg(feature = "encoding")]
@@ -192,14 +376,65 @@ pub async fn text<S, I, E>(body: S, encoding: ContentEncoding, limit: Option<usi
 ///- `encoding` - Specifies content's encoding to use.
 ///- `limit` - Specifies limit on body size, if not specified uses default 4kb
 ///- `charset` - Specifies charset to use, if omitted assumes `UTF-8`. Available only with feature `encoding`
+///
+///Text is decoded as data arrives, so body is not buffered before conversion.
+///Limit is applied to decompressed body, and on overflow, error contains text decoded so far.
 pub async fn text_charset<S, I, E>(body: S, encoding: ContentEncoding, limit: Option<usize>, charset: &'static Encoding) -> Result<String, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
 {
-    let bytes = matsu!(raw_bytes(body, encoding, limit))?;
+    matsu!(text_charset_impl(body, encoding.into(), limit, charset))
+}
 
-    match charset.decode(&bytes) {
-        (result, _, false) => Ok(result.into_owned()),
-        (_, _, true) => Err(BodyReadError::EncodingError)
+#[cfg(feature = "encoding")]
+///Implements [text_charset](fn.text_charset.html) with specified decompression.
+pub(crate) async fn text_charset_impl<S, I, E>(body: S, decompress: Decompress, limit: Option<usize>, charset: &'static Encoding) -> Result<String, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    let (limit, buffer_size) = calculate_buffer_size(limit);
+
+    let mut body = super::SizedBody::new(body, &decompress);
+    let mut decoder = charset.new_decoder();
+    let mut result = String::with_capacity(buffer_size);
+    let mut read = 0;
+    let mut is_last = false;
+
+    while !is_last {
+        let chunk = match matsu!(body.data()) {
+            Some(chunk) => chunk?,
+            None => {
+                is_last = true;
+                bytes::Bytes::new()
+            },
+        };
+
+        read += chunk.len();
+        decode_charset(&mut decoder, &chunk, &mut result, is_last)?;
+
+        if read > limit {
+            return Err(BodyReadError::Overflow(result.into()));
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(feature = "encoding")]
+///Decodes chunk of text, appending it to `output`.
+fn decode_charset(decoder: &mut encoding_rs::Decoder, mut input: &[u8], output: &mut String, is_last: bool) -> Result<(), BodyReadError> {
+    use encoding_rs::DecoderResult;
+
+    loop {
+        let len = decoder.max_utf8_buffer_length_without_replacement(input.len()).unwrap_or(input.len());
+        output.reserve(len);
+
+        let (result, read) = decoder.decode_to_string_without_replacement(input, output, is_last);
+        input = &input[read..];
+
+        match result {
+            DecoderResult::InputEmpty => return Ok(()),
+            DecoderResult::OutputFull => continue,
+            DecoderResult::Malformed(_, _) => return Err(BodyReadError::EncodingError),
+        }
     }
 }
 
@@ -213,9 +448,16 @@ pub async fn text_charset<S, I, E>(body: S, encoding: ContentEncoding, limit: Op
 pub async fn json<S, I, E, J>(body: S, encoding: ContentEncoding, limit: Option<usize>) -> Result<J, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned
 {
-    let bytes = matsu!(raw_bytes(body, encoding, limit))?;
+    matsu!(json_impl(body, encoding.into(), limit))
+}
+
+///Implements [json](fn.json.html) with specified decompression.
+pub(crate) async fn json_impl<S, I, E, J>(body: S, decompress: Decompress, limit: Option<usize>) -> Result<J, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned
+{
+    let bytes = matsu!(raw_bytes_capped_impl(body, decompress, limit, DEFAULT_BUFFER_CAP))?;
 
-    serde_json::from_slice(&bytes).map_err(BodyReadError::from)
+    super::json_from_slice(&bytes)
 }
 
 #[cfg(feature = "encoding")]
@@ -230,12 +472,103 @@ pub async fn json<S, I, E, J>(body: S, encoding: ContentEncoding, limit: Option<
 pub async fn json_charset<S, I, E, J>(body: S, encoding: ContentEncoding, limit: Option<usize>, charset: &'static Encoding) -> Result<J, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned
 {
-    let bytes = matsu!(raw_bytes(body, encoding, limit))?;
+    matsu!(json_charset_impl(body, encoding.into(), limit, charset))
+}
 
-    match charset.decode(&bytes) {
-        (result, _, false) => serde_json::from_str(&result).map_err(BodyReadError::from),
+#[cfg(feature = "encoding")]
+///Implements [json_charset](fn.json_charset.html) with specified decompression.
+pub(crate) async fn json_charset_impl<S, I, E, J>(body: S, decompress: Decompress, limit: Option<usize>, charset: &'static Encoding) -> Result<J, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned
+{
+    let text = matsu!(text_charset_impl(body, decompress, limit, charset))?;
+
+    super::json_from_slice(text.as_bytes())
+}
+
+#[cfg(feature = "nested_form")]
+///Max depth of nested form.
+const FORM_MAX_DEPTH: usize = 5;
+
+#[cfg(feature = "nested_form")]
+///Extracts body as nested form from `Stream`
+///
+///Both plain and percent-encoded brackets are accepted.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
+///- `encoding` - Specifies content's encoding to use.
+///- `limit` - Specifies limit on body size, if not specified uses default 4kb
+pub async fn form_nested<S, I, E, F>(body: S, encoding: ContentEncoding, limit: Option<usize>) -> Result<F, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, F: serde::de::DeserializeOwned
+{
+    matsu!(form_nested_impl(body, encoding.into(), limit))
+}
+
+#[cfg(feature = "nested_form")]
+///Implements [form_nested](fn.form_nested.html) with specified decompression.
+pub(crate) async fn form_nested_impl<S, I, E, F>(body: S, decompress: Decompress, limit: Option<usize>) -> Result<F, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, F: serde::de::DeserializeOwned
+{
+    let bytes = matsu!(raw_bytes_capped_impl(body, decompress, limit, DEFAULT_BUFFER_CAP))?;
+
+    serde_qs::Config::new(FORM_MAX_DEPTH, false).deserialize_bytes(&bytes).map_err(BodyReadError::from)
+}
+
+///Extracts body as JSON from `Stream`, returning raw bytes together with result of parsing.
+///
+///Fails only if unable to read body.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
+///- `encoding` - Specifies content's encoding to use.
+///- `limit` - Specifies limit on body size, if not specified uses default 4kb
+pub async fn json_with_raw<S, I, E, J>(body: S, encoding: ContentEncoding, limit: Option<usize>) -> Result<(Result<J, BodyReadError>, bytes::Bytes), BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned
+{
+    matsu!(json_with_raw_impl(body, encoding.into(), limit))
+}
+
+///Implements [json_with_raw](fn.json_with_raw.html) with specified decompression.
+pub(crate) async fn json_with_raw_impl<S, I, E, J>(body: S, decompress: Decompress, limit: Option<usize>) -> Result<(Result<J, BodyReadError>, bytes::Bytes), BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned
+{
+    let bytes = matsu!(raw_bytes_capped_impl(body, decompress, limit, DEFAULT_BUFFER_CAP))?;
+
+    Ok((super::json_from_slice(&bytes), bytes))
+}
+
+#[cfg(feature = "encoding")]
+///Extracts body as JSON from `Stream`, returning raw bytes together with result of parsing.
+///
+///Fails only if unable to read body.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
+///- `encoding` - Specifies content's encoding to use.
+///- `limit` - Specifies limit on body size, if not specified uses default 4kb
+///- `charset` - Specifies charset to use, if omitted assumes `UTF-8`. Available only with feature `encoding`
+pub async fn json_charset_with_raw<S, I, E, J>(body: S, encoding: ContentEncoding, limit: Option<usize>, charset: &'static Encoding) -> Result<(Result<J, BodyReadError>, bytes::Bytes), BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned
+{
+    matsu!(json_charset_with_raw_impl(body, encoding.into(), limit, charset))
+}
+
+#[cfg(feature = "encoding")]
+///Implements [json_charset_with_raw](fn.json_charset_with_raw.html) with specified decompression.
+pub(crate) async fn json_charset_with_raw_impl<S, I, E, J>(body: S, decompress: Decompress, limit: Option<usize>, charset: &'static Encoding) -> Result<(Result<J, BodyReadError>, bytes::Bytes), BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned
+{
+    let bytes = matsu!(raw_bytes_capped_impl(body, decompress, limit, DEFAULT_BUFFER_CAP))?;
+
+    let result = match charset.decode(&bytes) {
+        (result, _, false) => super::json_from_slice(result.as_bytes()),
         (_, _, true) => Err(BodyReadError::EncodingError)
-    }
+    };
+
+    Ok((result, bytes))
 }
 
 ///Extracts body as bytes from `Stream` and write it to file
@@ -245,25 +578,32 @@ pub async fn json_charset<S, I, E, J>(body: S, encoding: ContentEncoding, limit:
 ///- `file` - Into which to write
 ///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
 ///- `encoding` - Specifies encoding to use.
-pub async fn file<S, I, E>(file: File, mut body: S, encoding: ContentEncoding) -> Result<File, BodyReadError>
+pub async fn file<S, I, E>(file: File, body: S, encoding: ContentEncoding) -> Result<File, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    matsu!(file_impl(file, body, encoding.into()))
+}
+
+///Implements [file](fn.file.html) with specified decompression.
+pub(crate) async fn file_impl<S, I, E>(file: File, mut body: S, decompress: Decompress) -> Result<File, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
 {
     let mut file = io::BufWriter::new(file);
 
-    match encoding {
+    match decompress.encoding {
         #[cfg(feature = "compu")]
         ContentEncoding::Brotli => {
-            impl_compu_file!(compu::decoder::brotli::BrotliDecoder::default(), body, &mut file);
+            impl_compu_file!(compu::decoder::brotli::BrotliDecoder::default(), decompress, body, &mut file);
         },
         #[cfg(feature = "compu")]
         ContentEncoding::Gzip => {
             let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Gzip);
-            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), body, &mut file);
+            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, &mut file);
         },
         #[cfg(feature = "compu")]
         ContentEncoding::Deflate => {
             let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Zlib);
-            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), body, &mut file);
+            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, &mut file);
         },
         _ => while let Some(chunk) = matsu!(body.data()) {
             let chunk = chunk.map(Into::into).map_err(Into::into)?;
@@ -283,6 +623,313 @@ pub async fn file<S, I, E>(file: File, mut body: S, encoding: ContentEncoding) -
     }
 }
 
+#[cfg(feature = "encoding")]
+///Extracts body from `Stream` and writes it to file, transcoding text from `charset` to UTF-8.
+///
+///Text is decoded as it arrives, so body is never stored in memory as whole.
+///
+///Params:
+///
+///- `file` - Into which to write
+///- `body` - Stream of data chunks to read.
+///- `encoding` - Specifies content's encoding to use.
+///- `charset` - Specifies charset of text. If it is `UTF-8`, body is written as it is. Available only with feature `encoding`
+pub async fn file_charset<S, I, E>(file: File, body: S, encoding: ContentEncoding, charset: &'static Encoding) -> Result<File, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    matsu!(file_charset_impl(file, body, encoding.into(), charset))
+}
+
+#[cfg(feature = "encoding")]
+///Implements [file_charset](fn.file_charset.html) with specified decompression.
+pub(crate) async fn file_charset_impl<S, I, E>(file: File, body: S, decompress: Decompress, charset: &'static Encoding) -> Result<File, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    use encoding_rs::DecoderResult;
+
+    if charset == encoding_rs::UTF_8 {
+        return matsu!(file_impl(file, body, decompress));
+    }
+
+    let mut body = super::SizedBody::new(body, &decompress);
+    let mut decoder = charset.new_decoder();
+    let mut file = io::BufWriter::new(file);
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    let mut is_last = false;
+
+    while !is_last {
+        let chunk = match matsu!(body.data()) {
+            Some(chunk) => chunk?,
+            None => {
+                is_last = true;
+                bytes::Bytes::new()
+            },
+        };
+
+        let mut input = &chunk[..];
+        loop {
+            let (result, read, written) = decoder.decode_to_utf8_without_replacement(input, &mut buffer, is_last);
+            input = &input[read..];
+
+            if let Err(error) = file.write_all(&buffer[..written]) {
+                return Err(BodyReadError::FileError(file.into_inner().expect("To get File"), error));
+            }
+
+            match result {
+                DecoderResult::InputEmpty => break,
+                DecoderResult::OutputFull => continue,
+                DecoderResult::Malformed(_, _) => return Err(BodyReadError::EncodingError),
+            }
+        }
+    }
+
+    let mut file = file.into_inner().expect("To get File out of BufWriter");
+    match file.flush() {
+        Ok(_) => Ok(file),
+        Err(error) => Err(BodyReadError::FileError(file, error))
+    }
+}
+
+///Extracts body as bytes from `Stream` and atomically writes it to file at `path`.
+///
+///Body is written into temporary file `<path>.part`, which replaces `path` only after
+///whole body is written and synced to disk. On error temporary file is removed, leaving `path` intact.
+///
+///Params:
+///
+///- `path` - Path to file into which to write.
+///- `body` - Stream of data chunks to read.
+///- `encoding` - Specifies encoding to use.
+pub async fn file_atomic<S, I, E>(path: &Path, body: S, encoding: ContentEncoding) -> Result<File, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    matsu!(file_atomic_impl(path, body, encoding.into()))
+}
+
+///Implements [file_atomic](fn.file_atomic.html) with specified decompression.
+pub(crate) async fn file_atomic_impl<S, I, E>(path: &Path, body: S, decompress: Decompress) -> Result<File, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    let part = crate::utils::part_path(path);
+    let output = File::create(&part)?;
+
+    let result = match matsu!(file_impl(output, body, decompress)) {
+        Ok(file) => match file.sync_all().and_then(|_| fs::rename(&part, path)) {
+            Ok(_) => Ok(file),
+            Err(error) => Err(BodyReadError::FileError(file, error)),
+        },
+        Err(error) => Err(error),
+    };
+
+    if result.is_err() {
+        let _ = fs::remove_file(&part);
+    }
+
+    result
+}
+
+///Body, that is stored either in memory or in temporary file.
+pub enum Spooled {
+    ///Body fits within threshold.
+    Memory(bytes::Bytes),
+    ///Body exceeds threshold, file is positioned at the beginning.
+    File(File),
+}
+
+impl core::fmt::Debug for Spooled {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Spooled::Memory(bytes) => write!(f, "Spooled::Memory({} bytes)", bytes.len()),
+            Spooled::File(file) => write!(f, "Spooled::File({:?})", file),
+        }
+    }
+}
+
+///Creates temporary file, that is removed once handle is closed.
+///
+///On platforms, that cannot remove open files, it is left within temporary directory.
+fn temp_file() -> io::Result<File> {
+    let path = std::env::temp_dir().join(format!("yukikaze-{}.spool", crate::utils::uuid_v4()));
+    let file = fs::OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+    let _ = fs::remove_file(&path);
+    Ok(file)
+}
+
+///Writer, that switches from memory to file, when threshold is exceeded.
+enum Spool {
+    Memory(bytes::BytesMut, usize),
+    File(io::BufWriter<File>),
+}
+
+impl Spool {
+    fn finish(self) -> Result<Spooled, BodyReadError> {
+        use std::io::Seek;
+
+        match self {
+            Spool::Memory(buffer, _) => Ok(Spooled::Memory(buffer.freeze())),
+            Spool::File(file) => {
+                let mut file = file.into_inner().map_err(|error| error.into_error())?;
+                file.seek(io::SeekFrom::Start(0))?;
+                Ok(Spooled::File(file))
+            }
+        }
+    }
+}
+
+impl Write for Spool {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_all(buf).map(|_| buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Spool::Memory(buffer, threshold) => match buffer.len() + buf.len() > *threshold {
+                true => {
+                    let mut file = io::BufWriter::new(temp_file()?);
+                    file.write_all(&buffer[..])?;
+                    file.write_all(buf)?;
+                    *self = Spool::File(file);
+                    Ok(())
+                },
+                false => {
+                    buffer.extend_from_slice(buf);
+                    Ok(())
+                },
+            },
+            Spool::File(file) => file.write_all(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Spool::Memory(..) => Ok(()),
+            Spool::File(file) => file.flush(),
+        }
+    }
+}
+
+///Extracts body from `Stream`, keeping it in memory unless it exceeds threshold.
+///
+///Once size of (decompressed) body exceeds `threshold`, it is spilled into temporary file,
+///which is removed when handle is closed.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read.
+///- `encoding` - Specifies encoding to use.
+///- `threshold` - Specifies max size of body to keep in memory.
+pub async fn spooled<S, I, E>(body: S, encoding: ContentEncoding, threshold: usize) -> Result<Spooled, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    matsu!(spooled_impl(body, encoding.into(), threshold))
+}
+
+///Implements [spooled](fn.spooled.html) with specified decompression.
+pub(crate) async fn spooled_impl<S, I, E>(mut body: S, decompress: Decompress, threshold: usize) -> Result<Spooled, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    let mut spool = Spool::Memory(bytes::BytesMut::with_capacity(cmp::min(BUFFER_SIZE, threshold)), threshold);
+
+    match decompress.encoding {
+        #[cfg(feature = "compu")]
+        ContentEncoding::Brotli => {
+            impl_compu_file!(compu::decoder::brotli::BrotliDecoder::default(), decompress, body, &mut spool);
+        },
+        #[cfg(feature = "compu")]
+        ContentEncoding::Gzip => {
+            let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Gzip);
+            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, &mut spool);
+        },
+        #[cfg(feature = "compu")]
+        ContentEncoding::Deflate => {
+            let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Zlib);
+            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, &mut spool);
+        },
+        _ => while let Some(chunk) = matsu!(body.data()) {
+            let chunk = chunk.map(Into::into).map_err(Into::into)?;
+            spool.write_all(&chunk[..])?;
+        }
+    };
+
+    spool.finish()
+}
+
+///Extracts body as bytes from `Stream`, while writing it to file.
+///
+///Decompressed content is written to file, and at the same time accumulated in memory,
+///allowing to use both without fetching content again.
+///
+///Once body exceeds `limit`, its in-memory copy is dropped, but body is still written to file completely.
+///In this case returns `None` instead of bytes.
+///
+///Params:
+///
+///- `file` - Into which to write
+///- `body` - Stream of data chunks to read. It is always exhausted completely.
+///- `encoding` - Specifies encoding to use.
+///- `limit` - Specifies limit on size of body in memory, if not specified uses default 4kb
+pub async fn tee<S, I, E>(file: File, body: S, encoding: ContentEncoding, limit: Option<usize>) -> Result<(File, Option<bytes::Bytes>), BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    matsu!(tee_impl(file, body, encoding.into(), limit))
+}
+
+///Implements [tee](fn.tee.html) with specified decompression.
+pub(crate) async fn tee_impl<S, I, E>(file: File, mut body: S, decompress: Decompress, limit: Option<usize>) -> Result<(File, Option<bytes::Bytes>), BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    let (limit, buffer_size) = calculate_buffer_size(limit);
+    let mut file = io::BufWriter::new(file);
+
+    let bytes = match decompress.encoding {
+        #[cfg(feature = "compu")]
+        ContentEncoding::Brotli => {
+            impl_compu_tee!(compu::decoder::brotli::BrotliDecoder::default(), decompress, body, file, limit)
+        },
+        #[cfg(feature = "compu")]
+        ContentEncoding::Gzip => {
+            let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Gzip);
+            impl_compu_tee!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, file, limit)
+        },
+        #[cfg(feature = "compu")]
+        ContentEncoding::Deflate => {
+            let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Zlib);
+            impl_compu_tee!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, file, limit)
+        },
+        _ => {
+            let mut buffer = Some(Vec::with_capacity(buffer_size));
+
+            while let Some(chunk) = matsu!(body.data()) {
+                let chunk = chunk.map(Into::into).map_err(Into::into)?;
+
+                if let Err(error) = file.write_all(&chunk[..]) {
+                    return Err(BodyReadError::FileError(file.into_inner().expect("To get File"), error));
+                }
+
+                let overflow = match buffer.as_mut() {
+                    Some(memory) if memory.len().saturating_add(chunk.len()) <= limit => {
+                        extend_buffer(memory, &chunk[..], limit);
+                        false
+                    },
+                    _ => true,
+                };
+
+                if overflow {
+                    buffer = None;
+                }
+            }
+
+            buffer.map(bytes::Bytes::from)
+        }
+    };
+
+    let mut file = file.into_inner().expect("To get File out of BufWriter");
+    match file.flush() {
+        Ok(_) => Ok((file, bytes)),
+        Err(error) => Err(BodyReadError::FileError(file, error))
+    }
+}
+
 //Notify
 
 ///Extracts body as bytes from `Stream`
@@ -292,40 +939,57 @@ pub async fn file<S, I, E>(file: File, mut body: S, encoding: ContentEncoding) -
 ///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
 ///- `encoding` - Specifies encoding to use.
 ///- `limit` - Specifies limit on body size, if not specified uses default 4kb
-pub async fn raw_bytes_notify<S, I, E, N: Notifier>(mut body: S, encoding: ContentEncoding, limit: Option<usize>, mut notify: N) -> Result<bytes::Bytes, BodyReadError>
+pub async fn raw_bytes_notify<S, I, E, N: Notifier>(body: S, encoding: ContentEncoding, limit: Option<usize>, notify: N) -> Result<bytes::Bytes, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    matsu!(raw_bytes_notify_impl(body, encoding.into(), limit, notify))
+}
+
+///Implements [raw_bytes_notify](fn.raw_bytes_notify.html) with specified decompression.
+pub(crate) async fn raw_bytes_notify_impl<S, I, E, N: Notifier>(body: S, decompress: Decompress, limit: Option<usize>, notify: N) -> Result<bytes::Bytes, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    matsu!(raw_vec_notify(body, decompress, limit, notify)).map(Into::into)
+}
+
+///Extracts body into `Vec`, notifying about progress.
+async fn raw_vec_notify<S, I, E, N: Notifier>(mut body: S, decompress: Decompress, limit: Option<usize>, mut notify: N) -> Result<Vec<u8>, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
 {
     let (limit, buffer_size) = calculate_buffer_size(limit);
 
-    match encoding {
+    match decompress.encoding {
         #[cfg(feature = "compu")]
         ContentEncoding::Brotli => {
-            impl_compu_bytes!(compu::decoder::brotli::BrotliDecoder::default(), body, limit);
+            impl_compu_bytes!(compu::decoder::brotli::BrotliDecoder::default(), decompress, body, limit, notify);
         },
         #[cfg(feature = "compu")]
         ContentEncoding::Gzip => {
             let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Gzip);
-            impl_compu_bytes!(compu::decoder::zlib::ZlibDecoder::new(&options), body, limit);
+            impl_compu_bytes!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, limit, notify);
         },
         #[cfg(feature = "compu")]
         ContentEncoding::Deflate => {
             let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Zlib);
-            impl_compu_bytes!(compu::decoder::zlib::ZlibDecoder::new(&options), body, limit);
+            impl_compu_bytes!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, limit, notify);
         },
         _ => {
-            let mut buffer = bytes::BytesMut::with_capacity(buffer_size);
+            let mut buffer = Vec::with_capacity(buffer_size);
 
             while let Some(chunk) = matsu!(body.data()) {
                 let chunk = chunk.map(Into::into).map_err(Into::into)?;
 
-                buffer.extend_from_slice(&chunk[..]);
+                extend_buffer(&mut buffer, &chunk[..], limit);
                 notify.send(chunk.len());
+                if !notify.should_continue() {
+                    return Err(BodyReadError::Cancelled);
+                }
                 if buffer.len() > limit {
-                    return Err(BodyReadError::Overflow(buffer.freeze()));
+                    return Err(BodyReadError::Overflow(buffer.into()));
                 }
             }
 
-            Ok(buffer.freeze())
+            Ok(buffer)
         }
     }
 }
@@ -340,9 +1004,16 @@ pub async fn raw_bytes_notify<S, I, E, N: Notifier>(mut body: S, encoding: Conte
 pub async fn text_notify<S, I, E, N: Notifier>(body: S, encoding: ContentEncoding, limit: Option<usize>, notify: N) -> Result<String, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
 {
-    let bytes = matsu!(raw_bytes_notify(body, encoding, limit, notify))?;
+    matsu!(text_notify_impl(body, encoding.into(), limit, notify))
+}
+
+///Implements [text_notify](fn.text_notify.html) with specified decompression.
+pub(crate) async fn text_notify_impl<S, I, E, N: Notifier>(body: S, decompress: Decompress, limit: Option<usize>, notify: N) -> Result<String, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    let bytes = matsu!(raw_vec_notify(body, decompress, limit, notify))?;
 
-    String::from_utf8(bytes.to_vec()).map_err(|error| error.into())
+    String::from_utf8(bytes).map_err(|error| error.into())
 }
 
 #[cfg(feature = "encoding")]
@@ -357,7 +1028,15 @@ pub async fn text_notify<S, I, E, N: Notifier>(body: S, encoding: ContentEncodin
 pub async fn text_charset_notify<S, I, E, N>(body: S, encoding: ContentEncoding, limit: Option<usize>, charset: &'static Encoding, notify: N) -> Result<String, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, N: Notifier
 {
-    let bytes = matsu!(raw_bytes_notify(body, encoding, limit, notify))?;
+    matsu!(text_charset_notify_impl(body, encoding.into(), limit, charset, notify))
+}
+
+#[cfg(feature = "encoding")]
+///Implements [text_charset_notify](fn.text_charset_notify.html) with specified decompression.
+pub(crate) async fn text_charset_notify_impl<S, I, E, N>(body: S, decompress: Decompress, limit: Option<usize>, charset: &'static Encoding, notify: N) -> Result<String, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, N: Notifier
+{
+    let bytes = matsu!(raw_bytes_notify_impl(body, decompress, limit, notify))?;
 
     match charset.decode(&bytes) {
         (result, _, false) => Ok(result.into_owned()),
@@ -375,9 +1054,16 @@ pub async fn text_charset_notify<S, I, E, N>(body: S, encoding: ContentEncoding,
 pub async fn json_notify<S, I, E, N, J>(body: S, encoding: ContentEncoding, limit: Option<usize>, notify: N) -> Result<J, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned, N: Notifier
 {
-    let bytes = matsu!(raw_bytes_notify(body, encoding, limit, notify))?;
+    matsu!(json_notify_impl(body, encoding.into(), limit, notify))
+}
+
+///Implements [json_notify](fn.json_notify.html) with specified decompression.
+pub(crate) async fn json_notify_impl<S, I, E, N, J>(body: S, decompress: Decompress, limit: Option<usize>, notify: N) -> Result<J, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned, N: Notifier
+{
+    let bytes = matsu!(raw_bytes_notify_impl(body, decompress, limit, notify))?;
 
-    serde_json::from_slice(&bytes).map_err(BodyReadError::from)
+    super::json_from_slice(&bytes)
 }
 
 #[cfg(feature = "encoding")]
@@ -392,10 +1078,18 @@ pub async fn json_notify<S, I, E, N, J>(body: S, encoding: ContentEncoding, limi
 pub async fn json_charset_notify<S, I, E, N, J>(body: S, encoding: ContentEncoding, limit: Option<usize>, charset: &'static Encoding, notify: N) -> Result<J, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned, N: Notifier
 {
-    let bytes = matsu!(raw_bytes_notify(body, encoding, limit, notify))?;
+    matsu!(json_charset_notify_impl(body, encoding.into(), limit, charset, notify))
+}
+
+#[cfg(feature = "encoding")]
+///Implements [json_charset_notify](fn.json_charset_notify.html) with specified decompression.
+pub(crate) async fn json_charset_notify_impl<S, I, E, N, J>(body: S, decompress: Decompress, limit: Option<usize>, charset: &'static Encoding, notify: N) -> Result<J, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, J: serde::de::DeserializeOwned, N: Notifier
+{
+    let bytes = matsu!(raw_bytes_notify_impl(body, decompress, limit, notify))?;
 
     match charset.decode(&bytes) {
-        (result, _, false) => serde_json::from_str(&result).map_err(BodyReadError::from),
+        (result, _, false) => super::json_from_slice(result.as_bytes()),
         (_, _, true) => Err(BodyReadError::EncodingError)
     }
 }
@@ -407,25 +1101,34 @@ pub async fn json_charset_notify<S, I, E, N, J>(body: S, encoding: ContentEncodi
 ///- `file` - Into which to write
 ///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
 ///- `encoding` - Specifies encoding to use.
-pub async fn file_notify<S, I, E, N: Notifier>(file: File, mut body: S, encoding: ContentEncoding, mut notify: N) -> Result<File, BodyReadError>
+///
+///If reading is cancelled by notifier, file is left with content written so far.
+pub async fn file_notify<S, I, E, N: Notifier>(file: File, body: S, encoding: ContentEncoding, notify: N) -> Result<File, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
+{
+    matsu!(file_notify_impl(file, body, encoding.into(), notify))
+}
+
+///Implements [file_notify](fn.file_notify.html) with specified decompression.
+pub(crate) async fn file_notify_impl<S, I, E, N: Notifier>(file: File, mut body: S, decompress: Decompress, mut notify: N) -> Result<File, BodyReadError>
     where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>
 {
     let mut file = io::BufWriter::new(file);
 
-    match encoding {
+    match decompress.encoding {
         #[cfg(feature = "compu")]
         ContentEncoding::Brotli => {
-            impl_compu_file!(compu::decoder::brotli::BrotliDecoder::default(), body, &mut file, notify);
+            impl_compu_file!(compu::decoder::brotli::BrotliDecoder::default(), decompress, body, &mut file, notify);
         },
         #[cfg(feature = "compu")]
         ContentEncoding::Gzip => {
             let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Gzip);
-            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), body, &mut file, notify);
+            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, &mut file, notify);
         },
         #[cfg(feature = "compu")]
         ContentEncoding::Deflate => {
             let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Zlib);
-            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), body, &mut file, notify);
+            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, &mut file, notify);
         },
         _ => while let Some(chunk) = matsu!(body.data()) {
             let chunk = chunk.map(Into::into).map_err(Into::into)?;
@@ -435,6 +1138,10 @@ pub async fn file_notify<S, I, E, N: Notifier>(file: File, mut body: S, encoding
                 //TODO: consider how to get File without stumbling into error
                 Err(error) => return Err(BodyReadError::FileError(file.into_inner().expect("To get File"), error)),
             }
+
+            if !notify.should_continue() {
+                return Err(BodyReadError::Cancelled);
+            }
         }
     };
 
@@ -444,3 +1151,27 @@ pub async fn file_notify<S, I, E, N: Notifier>(file: File, mut body: S, encoding
         Err(error) => Err(BodyReadError::FileError(file, error))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{calculate_capped_buffer_size, extend_buffer, DEFAULT_BUFFER_CAP};
+
+    #[test]
+    fn should_grow_buffer_within_limit() {
+        assert_eq!(calculate_capped_buffer_size(Some(2 * 1024 * 1024 * 1024), DEFAULT_BUFFER_CAP), (2 * 1024 * 1024 * 1024, DEFAULT_BUFFER_CAP));
+        assert_eq!(calculate_capped_buffer_size(Some(100), DEFAULT_BUFFER_CAP), (100, 100));
+        assert_eq!(calculate_capped_buffer_size(None, 16), (4096, 16));
+
+        let limit = 100;
+        let mut buffer = Vec::with_capacity(16);
+        extend_buffer(&mut buffer, &[1; 20], limit);
+        assert_eq!(buffer.capacity(), 32);
+        extend_buffer(&mut buffer, &[2; 20], limit);
+        assert_eq!(buffer.capacity(), 64);
+        extend_buffer(&mut buffer, &[3; 30], limit);
+        assert_eq!(buffer.capacity(), limit);
+        extend_buffer(&mut buffer, &[4; 40], limit);
+        assert_eq!(buffer.len(), 110);
+        assert_eq!(buffer.capacity(), 110);
+    }
+}
@@ -0,0 +1,411 @@
+use core::pin::Pin;
+use core::task;
+use core::fmt;
+
+use http_body::Body as HttpBody;
+
+use super::{BodyReadError, Decompress};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+///Digest algorithm
+pub enum Algorithm {
+    ///MD5, supported only to verify legacy checksums (e.g. `Content-MD5`).
+    Md5,
+    ///SHA-1, supported only to verify legacy checksums.
+    Sha1,
+    ///SHA-256
+    Sha256,
+    ///SHA-384
+    Sha384,
+    ///SHA-512
+    Sha512,
+}
+
+impl Algorithm {
+    #[inline]
+    fn ring(self) -> Option<&'static ring::digest::Algorithm> {
+        match self {
+            Algorithm::Md5 => None,
+            Algorithm::Sha1 => Some(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY),
+            Algorithm::Sha256 => Some(&ring::digest::SHA256),
+            Algorithm::Sha384 => Some(&ring::digest::SHA384),
+            Algorithm::Sha512 => Some(&ring::digest::SHA512),
+        }
+    }
+
+    #[inline]
+    ///Returns length of digest in bytes.
+    pub fn output_len(self) -> usize {
+        match self.ring() {
+            Some(algorithm) => algorithm.output_len,
+            None => 16,
+        }
+    }
+
+    ///Returns name of algorithm, as used by `Digest` header.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha1 => "SHA",
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Sha384 => "SHA-384",
+            Algorithm::Sha512 => "SHA-512",
+        }
+    }
+
+    fn from_digest_name(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("MD5") {
+            Some(Algorithm::Md5)
+        } else if name.eq_ignore_ascii_case("SHA") {
+            Some(Algorithm::Sha1)
+        } else if name.eq_ignore_ascii_case("SHA-256") {
+            Some(Algorithm::Sha256)
+        } else if name.eq_ignore_ascii_case("SHA-384") {
+            Some(Algorithm::Sha384)
+        } else if name.eq_ignore_ascii_case("SHA-512") {
+            Some(Algorithm::Sha512)
+        } else {
+            None
+        }
+    }
+}
+
+///Digest computation in progress.
+enum Context {
+    Ring(ring::digest::Context),
+    Md5(md5::Md5),
+}
+
+impl Context {
+    fn new(algorithm: Algorithm) -> Self {
+        match algorithm.ring() {
+            Some(algorithm) => Context::Ring(ring::digest::Context::new(algorithm)),
+            None => Context::Md5(md5::Digest::new()),
+        }
+    }
+
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Context::Ring(context) => context.update(data),
+            Context::Md5(context) => md5::Digest::update(context, data),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            Context::Ring(context) => context.finish().as_ref().to_vec(),
+            Context::Md5(context) => md5::Digest::finalize(context).to_vec(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+///Expected checksum of body.
+pub struct Checksum {
+    algorithm: Algorithm,
+    expected: Vec<u8>,
+}
+
+impl Checksum {
+    #[inline]
+    ///Creates new instance with raw digest value.
+    pub fn new(algorithm: Algorithm, expected: Vec<u8>) -> Self {
+        Self {
+            algorithm,
+            expected,
+        }
+    }
+
+    ///Creates new instance from hex encoded digest.
+    ///
+    ///Returns `None` if value is not valid hex.
+    pub fn from_hex(algorithm: Algorithm, expected: &str) -> Option<Self> {
+        data_encoding::HEXLOWER_PERMISSIVE.decode(expected.trim().as_bytes()).ok().map(|expected| Self::new(algorithm, expected))
+    }
+
+    ///Creates new instance from value of `Digest` header.
+    ///
+    ///When header contains multiple digests, the strongest supported one is picked.
+    ///
+    ///Returns `None` if there is no supported digest.
+    pub fn from_digest_header(value: &str) -> Option<Self> {
+        let mut result: Option<Self> = None;
+
+        for digest in value.split(',') {
+            let mut parts = digest.trim().splitn(2, '=');
+            let algorithm = match parts.next().and_then(Algorithm::from_digest_name) {
+                Some(algorithm) => algorithm,
+                None => continue,
+            };
+            let expected = match parts.next().and_then(|value| data_encoding::BASE64.decode(value.trim().as_bytes()).ok()) {
+                Some(expected) => expected,
+                None => continue,
+            };
+
+            match result {
+                Some(ref current) if current.algorithm.output_len() >= algorithm.output_len() => (),
+                _ => result = Some(Self::new(algorithm, expected)),
+            }
+        }
+
+        result
+    }
+
+    ///Creates new instance from value of `Content-MD5` header.
+    ///
+    ///Returns `None` if value is not valid base64 encoded MD5 digest.
+    pub fn from_content_md5(value: &str) -> Option<Self> {
+        match data_encoding::BASE64.decode(value.trim().as_bytes()) {
+            Ok(expected) if expected.len() == Algorithm::Md5.output_len() => Some(Self::new(Algorithm::Md5, expected)),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    ///Returns algorithm.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    #[inline]
+    ///Returns expected digest.
+    pub fn expected(&self) -> &[u8] {
+        &self.expected
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.algorithm.as_str(), data_encoding::BASE64.encode(&self.expected))
+    }
+}
+
+///Body wrapper, that computes digest of data passing through it.
+///
+///Digest is computed over data as it is received, i.e. before decompression,
+///which is what `Digest` header describes.
+pub struct ChecksumBody<S> {
+    body: S,
+    context: Context,
+}
+
+impl<S> ChecksumBody<S> {
+    #[inline]
+    ///Creates new instance
+    pub fn new(body: S, algorithm: Algorithm) -> Self {
+        Self {
+            body,
+            context: Context::new(algorithm),
+        }
+    }
+
+    ///Finishes computation, and compares digest against expected checksum.
+    pub fn verify(self, checksum: &Checksum) -> Result<(), BodyReadError> {
+        let digest = self.context.finish();
+
+        match digest == checksum.expected() {
+            true => Ok(()),
+            false => Err(BodyReadError::ChecksumMismatch(digest)),
+        }
+    }
+}
+
+impl<S, I, E> HttpBody for ChecksumBody<S> where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError> {
+    type Data = bytes::Bytes;
+    type Error = BodyReadError;
+
+    fn poll_data(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = &mut *self;
+
+        match Pin::new(&mut this.body).poll_data(ctx) {
+            task::Poll::Ready(Some(Ok(chunk))) => {
+                let chunk = chunk.into();
+                this.context.update(&chunk);
+                task::Poll::Ready(Some(Ok(chunk)))
+            },
+            task::Poll::Ready(Some(Err(error))) => task::Poll::Ready(Some(Err(error.into()))),
+            task::Poll::Ready(None) => task::Poll::Ready(None),
+            task::Poll::Pending => task::Poll::Pending,
+        }
+    }
+
+    #[inline]
+    fn poll_trailers(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.body).poll_trailers(ctx).map_err(Into::into)
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.body.size_hint()
+    }
+}
+
+async fn drain<S: HttpBody<Error=BodyReadError> + Unpin>(mut body: S) -> Result<(), BodyReadError> {
+    while let Some(chunk) = matsu!(body.data()) {
+        chunk?;
+    }
+
+    Ok(())
+}
+
+///Extracts body as bytes from `Stream`, verifying its checksum.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
+///- `encoding` - Specifies encoding to use.
+///- `limit` - Specifies limit on body size, if not specified uses default 4kb
+///- `checksum` - Expected checksum of received body.
+pub async fn raw_bytes_checksum<S, I, E>(body: S, encoding: crate::header::ContentEncoding, limit: Option<usize>, checksum: Checksum) -> Result<bytes::Bytes, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    matsu!(raw_bytes_checksum_impl(body, encoding.into(), limit, checksum))
+}
+
+///Implements [raw_bytes_checksum](fn.raw_bytes_checksum.html) with specified decompression.
+pub(crate) async fn raw_bytes_checksum_impl<S, I, E>(body: S, decompress: Decompress, limit: Option<usize>, checksum: Checksum) -> Result<bytes::Bytes, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    let mut body = ChecksumBody::new(body, checksum.algorithm());
+    let bytes = matsu!(super::raw_bytes_capped_impl(&mut body, decompress, limit, super::DEFAULT_BUFFER_CAP))?;
+    matsu!(drain(&mut body))?;
+    body.verify(&checksum)?;
+
+    Ok(bytes)
+}
+
+///Extracts body as bytes from `Stream` and write it to file, verifying its checksum.
+///
+///Params:
+///
+///- `file` - Into which to write
+///- `body` - Stream of data chunks to read.
+///- `encoding` - Specifies encoding to use.
+///- `checksum` - Expected checksum of received body.
+///
+///Note that on mismatch, file is not removed and its content should be discarded by user.
+pub async fn file_checksum<S, I, E>(file: std::fs::File, body: S, encoding: crate::header::ContentEncoding, checksum: Checksum) -> Result<std::fs::File, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    matsu!(file_checksum_impl(file, body, encoding.into(), checksum))
+}
+
+///Implements [file_checksum](fn.file_checksum.html) with specified decompression.
+pub(crate) async fn file_checksum_impl<S, I, E>(file: std::fs::File, body: S, decompress: Decompress, checksum: Checksum) -> Result<std::fs::File, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    let mut body = ChecksumBody::new(body, checksum.algorithm());
+    let file = matsu!(super::file_impl(file, &mut body, decompress))?;
+    matsu!(drain(&mut body))?;
+    body.verify(&checksum)?;
+
+    Ok(file)
+}
+
+///Writer, that only feeds data into digest.
+struct DigestWriter(Context);
+
+impl std::io::Write for DigestWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+///Computes digest of body from `Stream`, without storing it.
+///
+///Unlike [ChecksumBody](struct.ChecksumBody.html), digest is computed over decompressed body.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read.
+///- `encoding` - Specifies encoding to use.
+///- `algorithm` - Digest algorithm.
+///
+///Returns digest as lower case hex string.
+pub async fn digest<S, I, E>(body: S, encoding: crate::header::ContentEncoding, algorithm: Algorithm) -> Result<String, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    matsu!(digest_impl(body, encoding.into(), algorithm))
+}
+
+///Implements [digest](fn.digest.html) with specified decompression.
+pub(crate) async fn digest_impl<S, I, E>(mut body: S, decompress: Decompress, algorithm: Algorithm) -> Result<String, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    #[cfg(feature = "compu")]
+    use crate::header::ContentEncoding;
+    #[cfg(feature = "compu")]
+    use compu::decoder::Decoder;
+
+    let mut writer = DigestWriter(Context::new(algorithm));
+
+    match decompress.encoding {
+        #[cfg(feature = "compu")]
+        ContentEncoding::Brotli => {
+            impl_compu_file!(compu::decoder::brotli::BrotliDecoder::default(), decompress, body, &mut writer);
+        },
+        #[cfg(feature = "compu")]
+        ContentEncoding::Gzip => {
+            let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Gzip);
+            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, &mut writer);
+        },
+        #[cfg(feature = "compu")]
+        ContentEncoding::Deflate => {
+            let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Zlib);
+            impl_compu_file!(compu::decoder::zlib::ZlibDecoder::new(&options), decompress, body, &mut writer);
+        },
+        _ => while let Some(chunk) = matsu!(body.data()) {
+            let chunk = chunk.map(Into::into).map_err(Into::into)?;
+            writer.0.update(&chunk);
+        }
+    };
+
+    Ok(data_encoding::HEXLOWER.encode(&writer.0.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Algorithm, Checksum};
+
+    #[test]
+    fn compute_digest() {
+        let mut rt = tokio::runtime::Builder::new().basic_scheduler().build().expect("To create runtime");
+        let body = hyper::Body::from("");
+
+        let digest = rt.block_on(super::digest(body, crate::header::ContentEncoding::Identity, Algorithm::Sha256)).expect("To compute digest");
+        assert_eq!(digest, "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+
+        let body = hyper::Body::from("");
+        let digest = rt.block_on(super::digest(body, crate::header::ContentEncoding::Identity, Algorithm::Md5)).expect("To compute digest");
+        assert_eq!(digest, "d41d8cd98f00b204e9800998ecf8427e");
+    }
+
+    #[test]
+    fn parse_digest_header() {
+        let checksum = Checksum::from_digest_header("MD5=HUXZLQLMuI/KZ5KDcJPcOA==, sha-256=47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=").expect("To parse");
+        assert_eq!(checksum.algorithm(), Algorithm::Sha256);
+        assert_eq!(checksum.to_string(), "SHA-256=47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=");
+
+        let checksum = Checksum::from_hex(Algorithm::Sha256, "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855").expect("To parse");
+        assert_eq!(checksum, Checksum::from_digest_header("SHA-256=47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=").unwrap());
+
+        let checksum = Checksum::from_digest_header("MD5=HUXZLQLMuI/KZ5KDcJPcOA==").expect("To parse");
+        assert_eq!(checksum.algorithm(), Algorithm::Md5);
+        assert_eq!(Some(checksum), Checksum::from_content_md5("HUXZLQLMuI/KZ5KDcJPcOA=="));
+        assert!(Checksum::from_content_md5("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=").is_none());
+        assert!(Checksum::from_digest_header("UNIXsum=30637").is_none());
+    }
+}
@@ -1,5 +1,7 @@
 //!Response extractors
 
+use core::fmt;
+
 use crate::header;
 
 ///Cookie extractor.
@@ -9,13 +11,20 @@ use crate::header;
 ///you can use `Cookie::into_owned()`
 pub struct CookieIter<'a> {
     iter: header::ValueIter<'a, header::HeaderValue>,
+    encoding: header::CookieEncoding,
 }
 
 impl<'a> CookieIter<'a> {
-    ///Creates new instance from `http::header::ValueIter`
+    ///Creates new instance from `http::header::ValueIter`, which cookies are percent-encoded.
     pub fn new(iter: header::ValueIter<'a, header::HeaderValue>) -> Self {
+        Self::with_encoding(iter, header::CookieEncoding::Percent)
+    }
+
+    ///Creates new instance from `http::header::ValueIter`, using specified cookie encoding.
+    pub fn with_encoding(iter: header::ValueIter<'a, header::HeaderValue>, encoding: header::CookieEncoding) -> Self {
         Self {
-            iter
+            iter,
+            encoding,
         }
     }
 }
@@ -28,12 +37,107 @@ impl<'a> Iterator for CookieIter<'a> {
         use percent_encoding::percent_decode;
 
         if let Some(cook) = self.iter.by_ref().next() {
-            let cook = percent_decode(cook.as_bytes());
-            let cook = cook.decode_utf8().map_err(|error| cookie::ParseError::Utf8Error(error))
-                                         .and_then(|cook| cookie::Cookie::parse(cook));
+            let cook = match self.encoding {
+                header::CookieEncoding::Percent => percent_decode(cook.as_bytes()).decode_utf8(),
+                header::CookieEncoding::Raw => core::str::from_utf8(cook.as_bytes()).map(Into::into),
+            };
+            let cook = cook.map_err(cookie::ParseError::Utf8Error)
+                           .and_then(cookie::Cookie::parse);
             Some(cook)
         } else {
             None
         }
     }
 }
+
+#[derive(Debug)]
+///Cookie, that cannot be parsed by [LenientCookieIter](struct.LenientCookieIter.html).
+pub struct InvalidCookie<'a> {
+    ///Untouched value of `Set-Cookie` header.
+    pub value: &'a header::HeaderValue,
+    ///Error of parsing.
+    pub error: cookie::ParseError,
+}
+
+impl fmt::Display for InvalidCookie<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid cookie {:?}: {}", self.value, self.error)
+    }
+}
+
+impl std::error::Error for InvalidCookie<'_> {}
+
+///Cookie extractor, that tolerates invalid cookies.
+///
+///Unlike [CookieIter](struct.CookieIter.html), if cookie cannot be decoded, it is parsed as it is.
+///Cookies, that still cannot be parsed, are returned as error together with their raw value,
+///while iteration continues with the rest of them.
+pub struct LenientCookieIter<'a> {
+    iter: header::ValueIter<'a, header::HeaderValue>,
+    encoding: header::CookieEncoding,
+}
+
+impl<'a> LenientCookieIter<'a> {
+    ///Creates new instance from `http::header::ValueIter`, using specified cookie encoding.
+    pub fn new(iter: header::ValueIter<'a, header::HeaderValue>, encoding: header::CookieEncoding) -> Self {
+        Self {
+            iter,
+            encoding,
+        }
+    }
+}
+
+impl<'a> Iterator for LenientCookieIter<'a> {
+    type Item = Result<cookie::Cookie<'a>, InvalidCookie<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use percent_encoding::percent_decode;
+
+        let value = self.iter.next()?;
+        let raw = core::str::from_utf8(value.as_bytes());
+
+        let decoded = match self.encoding {
+            header::CookieEncoding::Percent => percent_decode(value.as_bytes()).decode_utf8().ok(),
+            header::CookieEncoding::Raw => None,
+        };
+
+        let result = match (decoded, raw) {
+            (Some(decoded), raw) => match cookie::Cookie::parse(decoded) {
+                Ok(cook) => Ok(cook),
+                Err(error) => match raw {
+                    Ok(raw) => cookie::Cookie::parse(raw).map_err(|_| error),
+                    Err(_) => Err(error),
+                },
+            },
+            (None, Ok(raw)) => cookie::Cookie::parse(raw),
+            (None, Err(error)) => Err(cookie::ParseError::Utf8Error(error)),
+        };
+
+        Some(result.map_err(|error| InvalidCookie {
+            value,
+            error,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LenientCookieIter;
+    use crate::header;
+
+    #[test]
+    fn should_skip_invalid_cookies() {
+        let mut headers = header::HeaderMap::new();
+        headers.append(header::SET_COOKIE, header::HeaderValue::from_static("session=1; Path=/"));
+        headers.append(header::SET_COOKIE, header::HeaderValue::from_static("=no-name"));
+        headers.append(header::SET_COOKIE, header::HeaderValue::from_bytes(b"vendor=%FF%FE").unwrap());
+        headers.append(header::SET_COOKIE, header::HeaderValue::from_static("token=x%3Dy"));
+
+        let mut iter = LenientCookieIter::new(headers.get_all(header::SET_COOKIE).iter(), header::CookieEncoding::Percent);
+        assert_eq!(iter.next().unwrap().unwrap().value(), "1");
+        assert_eq!(iter.next().unwrap().unwrap_err().value, "=no-name");
+        assert_eq!(iter.next().unwrap().unwrap().value(), "%FF%FE");
+        assert_eq!(iter.next().unwrap().unwrap().value(), "x=y");
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,358 @@
+//!Extraction of page's metadata from HTML.
+//!
+//!Available only with feature `html`
+//!
+//!Document is scanned by simple pull parser until end of `<head>`, so malformed HTML is tolerated.
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::extractor::html;
+//!
+//!const PAGE: &str = r#"<html><head>
+//!<title>Yukikaze &amp; friends</title>
+//!<meta name="description" content="Lucky ship">
+//!<meta property="og:image" content="https://example.com/yukikaze.png">
+//!<link rel="canonical" href="https://example.com/yukikaze">
+//!</head><body></body></html>"#;
+//!
+//!let meta = html::Meta::parse(PAGE);
+//!assert_eq!(meta.title.as_ref().unwrap(), "Yukikaze & friends");
+//!assert_eq!(meta.description.as_ref().unwrap(), "Lucky ship");
+//!assert_eq!(meta.canonical.as_ref().unwrap(), "https://example.com/yukikaze");
+//!assert_eq!(meta.open_graph("image"), Some("https://example.com/yukikaze.png"));
+//!```
+
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+///Metadata of HTML page, commonly used to create its preview.
+pub struct Meta {
+    ///Content of `<title>`.
+    pub title: Option<String>,
+    ///Content of `<meta name="description">`.
+    pub description: Option<String>,
+    ///URL of `<link rel="canonical">`.
+    pub canonical: Option<String>,
+    ///Open Graph properties, i.e. `<meta property="og:*">`, in order of appearance.
+    ///
+    ///Property names are stored without `og:` prefix.
+    pub open_graph: Vec<(String, String)>,
+}
+
+impl Meta {
+    ///Parses metadata out of HTML text.
+    ///
+    ///If there are multiple occurrences of the same element, the first one is used.
+    pub fn parse(text: &str) -> Self {
+        let mut result = Self::default();
+        let mut parser = Parser::new(text);
+
+        while let Some(tag) = parser.next_tag() {
+            match tag.name.as_str() {
+                "title" if result.title.is_none() => {
+                    let title = collapse_whitespace(&decode_entities(parser.raw_text("title")));
+                    result.title = Some(title);
+                },
+                "meta" => {
+                    let content = match tag.attr("content") {
+                        Some(content) => content,
+                        None => continue,
+                    };
+
+                    if let Some(property) = tag.attr("property").or_else(|| tag.attr("name")) {
+                        if property.eq_ignore_ascii_case("description") {
+                            if result.description.is_none() {
+                                result.description = Some(content.to_owned());
+                            }
+                        } else if property.len() > 3 && property[..3].eq_ignore_ascii_case("og:") {
+                            result.open_graph.push((property[3..].to_owned(), content.to_owned()));
+                        }
+                    }
+                },
+                "link" if result.canonical.is_none() => {
+                    let is_canonical = tag.attr("rel").map(|rel| rel.split_ascii_whitespace().any(|rel| rel.eq_ignore_ascii_case("canonical"))).unwrap_or(false);
+                    if is_canonical {
+                        result.canonical = tag.attr("href").map(|href| href.trim().to_owned());
+                    }
+                },
+                "script" | "style" => {
+                    parser.raw_text(&tag.name);
+                },
+                "/head" | "body" => break,
+                _ => (),
+            }
+        }
+
+        result
+    }
+
+    ///Returns first value of Open Graph property, specified without `og:` prefix.
+    pub fn open_graph(&self, name: &str) -> Option<&str> {
+        self.open_graph.iter().find(|(property, _)| property.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+}
+
+struct Tag<'a> {
+    //Lower case name, prefixed with `/` for closing tag.
+    name: String,
+    attrs: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl<'a> Tag<'a> {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(attr, _)| attr.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_ref())
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            pos: 0,
+        }
+    }
+
+    #[inline]
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_until(&mut self, pattern: &str) {
+        self.pos = match self.rest().find(pattern) {
+            Some(idx) => self.pos + idx + pattern.len(),
+            None => self.text.len(),
+        };
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, pred: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|ch| !pred(ch)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    ///Returns text until closing tag `name`, moving after it.
+    fn raw_text(&mut self, name: &str) -> &'a str {
+        let rest = self.rest();
+        let bytes = rest.as_bytes();
+        let mut idx = 0;
+
+        while let Some(offset) = rest[idx..].find("</") {
+            let start = idx + offset;
+            let end = start + 2 + name.len();
+            if end <= rest.len() && bytes[start+2..end].eq_ignore_ascii_case(name.as_bytes()) {
+                self.pos += start;
+                self.skip_until(">");
+                return &rest[..start];
+            }
+            idx = start + 2;
+        }
+
+        self.pos = self.text.len();
+        rest
+    }
+
+    fn next_tag(&mut self) -> Option<Tag<'a>> {
+        loop {
+            self.skip_until("<");
+            let rest = self.rest();
+
+            if rest.is_empty() {
+                return None;
+            } else if rest.starts_with("!--") {
+                self.skip_until("-->");
+                continue;
+            } else if rest.starts_with('!') || rest.starts_with('?') {
+                self.skip_until(">");
+                continue;
+            }
+
+            let is_closing = rest.starts_with('/');
+            if is_closing {
+                self.pos += 1;
+            }
+
+            let name = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == ':');
+            if name.is_empty() {
+                continue;
+            }
+
+            let mut tag = Tag {
+                name: match is_closing {
+                    true => format!("/{}", name.to_ascii_lowercase()),
+                    false => name.to_ascii_lowercase(),
+                },
+                attrs: Vec::new(),
+            };
+
+            loop {
+                self.skip_whitespace();
+                let rest = self.rest();
+
+                if rest.is_empty() {
+                    return Some(tag);
+                } else if rest.starts_with('>') {
+                    self.pos += 1;
+                    return Some(tag);
+                } else if rest.starts_with('/') {
+                    self.pos += 1;
+                    continue;
+                }
+
+                let attr = self.take_while(|ch| !ch.is_whitespace() && ch != '=' && ch != '>' && ch != '/');
+                if attr.is_empty() {
+                    //Stray character, skip it to make progress.
+                    self.pos += rest.chars().next().map(char::len_utf8).unwrap_or(1);
+                    continue;
+                }
+
+                self.skip_whitespace();
+                let value = match self.rest().starts_with('=') {
+                    true => {
+                        self.pos += 1;
+                        self.skip_whitespace();
+                        self.attr_value()
+                    },
+                    false => "",
+                };
+
+                tag.attrs.push((attr, decode_entities(value)));
+            }
+        }
+    }
+
+    fn attr_value(&mut self) -> &'a str {
+        let rest = self.rest();
+
+        match rest.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                let value = &rest[1..];
+                match value.find(quote) {
+                    Some(end) => {
+                        self.pos += end + 2;
+                        &value[..end]
+                    },
+                    None => {
+                        self.pos = self.text.len();
+                        value
+                    },
+                }
+            },
+            _ => self.take_while(|ch| !ch.is_whitespace() && ch != '>'),
+        }
+    }
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let code = match entity.strip_prefix('#') {
+                Some(hex) if hex.starts_with('x') || hex.starts_with('X') => u32::from_str_radix(&hex[1..], 16).ok()?,
+                Some(dec) => dec.parse().ok()?,
+                None => return None,
+            };
+
+            core::char::from_u32(code)
+        }
+    }
+}
+
+///Decodes character references, leaving unknown ones as they are.
+fn decode_entities(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest[1..].find(';').filter(|end| *end <= 10).and_then(|end| decode_entity(&rest[1..end+1]).map(|ch| (ch, end + 2)));
+        match decoded {
+            Some((ch, len)) => {
+                result.push(ch);
+                rest = &rest[len..];
+            },
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    result.push_str(rest);
+
+    Cow::Owned(result)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+
+    for part in text.split_whitespace() {
+        if !result.is_empty() {
+            result.push(' ');
+        }
+        result.push_str(part);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Meta;
+
+    #[test]
+    fn should_parse_meta() {
+        const PAGE: &str = r#"<!DOCTYPE html>
+<HTML><HEAD>
+<!-- <title>Commented</title> -->
+<script>var title = "</tit" + "le>";</script>
+<TITLE>
+    Yukikaze &#x2014; lucky   ship
+</TITLE>
+<meta charset=utf-8>
+<meta name=Description content='Destroyer of &quot;Kagero&quot; class'>
+<meta property="og:title" content="Yukikaze"/>
+<meta property="og:image" content="/first.png"><meta property="og:image" content="/second.png">
+<link rel="alternate canonical" href=" https://example.com/yukikaze ">
+</head>
+<body><meta name="description" content="Ignored"></body>
+</HTML>"#;
+
+        let meta = Meta::parse(PAGE);
+        assert_eq!(meta.title.as_deref(), Some("Yukikaze \u{2014} lucky ship"));
+        assert_eq!(meta.description.as_deref(), Some("Destroyer of \"Kagero\" class"));
+        assert_eq!(meta.canonical.as_deref(), Some("https://example.com/yukikaze"));
+        assert_eq!(meta.open_graph("title"), Some("Yukikaze"));
+        assert_eq!(meta.open_graph("image"), Some("/first.png"));
+        assert_eq!(meta.open_graph.len(), 3);
+    }
+
+    #[test]
+    fn should_tolerate_malformed_html() {
+        assert_eq!(Meta::parse(""), Meta::default());
+        assert_eq!(Meta::parse("<title>Unclosed").title.as_deref(), Some("Unclosed"));
+        assert_eq!(Meta::parse("<meta name=\"description\" content=\"a &unknown; & b").description.as_deref(), Some("a &unknown; & b"));
+        assert_eq!(Meta::parse("< <> <=x> <title>Ok</title>").title.as_deref(), Some("Ok"));
+    }
+}
@@ -0,0 +1,168 @@
+use compu::decoder::{Decoder, DecoderResult};
+
+use super::{BodyReadError, BodySize, RATIO_THRESHOLD};
+
+///Maximum number of bytes, that is decompressed in one step.
+const STEP: usize = 16 * 1024;
+
+///Outcome of decompression step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    ///Decompression is finished.
+    Finished,
+    ///Input is consumed.
+    NeedInput,
+    ///Output reached its maximum length.
+    Full,
+}
+
+///Decompressor, that produces output incrementally and protects from decompression bombs.
+pub(crate) struct Inflater<D> {
+    decoder: D,
+    max_ratio: Option<u32>,
+    size: Option<BodySize>,
+    received: u64,
+    produced: u64,
+}
+
+impl<D: Decoder> Inflater<D> {
+    #[inline]
+    pub(crate) fn new(decoder: D, max_ratio: Option<u32>) -> Self {
+        Self {
+            decoder,
+            max_ratio,
+            size: None,
+            received: 0,
+            produced: 0,
+        }
+    }
+
+    #[inline]
+    ///Sets size, that accounts decompressed bytes.
+    pub(crate) fn size(mut self, size: Option<BodySize>) -> Self {
+        self.size = size;
+        self
+    }
+
+    #[inline]
+    pub(crate) fn is_finished(&self) -> bool {
+        self.decoder.is_finished()
+    }
+
+    fn check_ratio(&self) -> Result<(), BodyReadError> {
+        match self.max_ratio {
+            Some(ratio) if self.produced > RATIO_THRESHOLD && self.produced > self.received.saturating_mul(ratio as u64) => Err(BodyReadError::DecompressionBomb),
+            _ => Ok(()),
+        }
+    }
+
+    ///Decompresses `input` into `output`, until input is consumed or output's length reaches `max_len`.
+    ///
+    ///Returns number of consumed bytes of input, which must be passed again, if output is full.
+    pub(crate) fn push(&mut self, input: &[u8], output: &mut Vec<u8>, max_len: usize) -> Result<(usize, Status), BodyReadError> {
+        let mut consumed = 0;
+
+        loop {
+            let offset = output.len();
+            if offset >= max_len {
+                return Ok((consumed, Status::Full));
+            }
+
+            let step = core::cmp::min(STEP, max_len - offset);
+            output.resize(offset + step, 0);
+
+            let (remaining_input, remaining_output, result) = self.decoder.decode(&input[consumed..], &mut output[offset..]);
+            let new_consumed = input.len() - remaining_input;
+            self.received += (new_consumed - consumed) as u64;
+            consumed = new_consumed;
+
+            output.truncate(offset + step - remaining_output);
+            self.produced += (step - remaining_output) as u64;
+            if let Some(size) = self.size.as_ref() {
+                size.add_decoded(step - remaining_output);
+            }
+            self.check_ratio()?;
+
+            match result {
+                DecoderResult::NeedOutput => continue,
+                DecoderResult::NeedInput => return Ok((consumed, Status::NeedInput)),
+                DecoderResult::Finished => return Ok((consumed, Status::Finished)),
+                result => return Err(BodyReadError::CompuError(result)),
+            }
+        }
+    }
+
+    ///Decompresses whole `input`, passing output to `sink` in small pieces.
+    ///
+    ///Returns whether decompression is finished.
+    pub(crate) fn push_all<F: FnMut(&[u8]) -> Result<(), BodyReadError>>(&mut self, mut input: &[u8], buffer: &mut Vec<u8>, mut sink: F) -> Result<bool, BodyReadError> {
+        loop {
+            buffer.clear();
+            let (consumed, status) = self.push(input, buffer, STEP)?;
+            input = &input[consumed..];
+            sink(buffer)?;
+
+            match status {
+                Status::Full => continue,
+                Status::NeedInput => return Ok(false),
+                Status::Finished => return Ok(true),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Inflater, Status};
+    use crate::extractor::{BodyReadError, RATIO_THRESHOLD};
+
+    use compu::encoder::Encoder;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let options = compu::encoder::zlib::ZlibOptions::default().mode(compu::encoder::zlib::ZlibMode::Gzip);
+        let mut encoder = compu::compressor::memory::Compressor::new(compu::encoder::zlib::ZlibEncoder::new(&options));
+        encoder.push(data, compu::encoder::EncoderOp::Finish);
+        encoder.take()
+    }
+
+    fn decoder() -> compu::decoder::zlib::ZlibDecoder {
+        use compu::decoder::Decoder;
+
+        let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Gzip);
+        compu::decoder::zlib::ZlibDecoder::new(&options)
+    }
+
+    #[test]
+    fn should_limit_output_within_chunk() {
+        let compressed = gzip(&[b'y'; 256 * 1024]);
+
+        let mut inflater = Inflater::new(decoder(), None);
+        let mut output = Vec::new();
+        let (consumed, status) = inflater.push(&compressed, &mut output, 1000).expect("To decompress");
+        assert_eq!(status, Status::Full);
+        assert_eq!(output.len(), 1000);
+
+        let (_, status) = inflater.push(&compressed[consumed..], &mut output, usize::MAX).expect("To decompress");
+        assert_eq!(status, Status::Finished);
+        assert_eq!(output.len(), 256 * 1024);
+    }
+
+    #[test]
+    fn should_detect_decompression_bomb() {
+        let compressed = gzip(&vec![0; 2 * RATIO_THRESHOLD as usize]);
+
+        let mut inflater = Inflater::new(decoder(), Some(10));
+        let mut buffer = Vec::new();
+        let mut produced = 0;
+        match inflater.push_all(&compressed, &mut buffer, |data| {
+            produced += data.len();
+            Ok(())
+        }) {
+            Err(BodyReadError::DecompressionBomb) => assert!(produced <= RATIO_THRESHOLD as usize + super::STEP),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        let mut inflater = Inflater::new(decoder(), None);
+        assert!(inflater.push_all(&compressed, &mut buffer, |_| Ok(())).expect("To decompress"));
+    }
+}
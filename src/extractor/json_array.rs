@@ -0,0 +1,266 @@
+use core::future::Future;
+use core::marker::{PhantomData, Unpin};
+use core::pin::Pin;
+use core::task;
+
+use http_body::Body as HttpBody;
+
+use super::{BodyReadError, SizedBody};
+use crate::header::ContentEncoding;
+
+///Default limit on size of single array's element.
+pub const ELEMENT_LIMIT: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    //Expects `[`
+    Start,
+    //Expects element, or `]` if array is empty.
+    Element(bool),
+    //Expects `,` or `]`
+    Delimiter,
+    Done,
+}
+
+enum Step<T> {
+    Item(T),
+    NeedInput,
+    Done,
+}
+
+#[inline]
+fn syntax_error(msg: &str) -> BodyReadError {
+    BodyReadError::from(<serde_json::Error as serde::de::Error>::custom(msg))
+}
+
+///Stream of elements of top-level JSON array.
+///
+///Elements are deserialized as soon as they are received, so that only one element is kept in memory.
+///Reading stops on first error.
+///
+///Created by [json_array_stream](fn.json_array_stream.html) or [Response::json_array_stream](../client/response/struct.Response.html#method.json_array_stream).
+pub struct JsonArrayStream<S, T> {
+    body: SizedBody<S>,
+    buffer: Vec<u8>,
+    pos: usize,
+    limit: usize,
+    state: State,
+    is_eof: bool,
+    _element: PhantomData<fn() -> T>,
+}
+
+impl<S, T> JsonArrayStream<S, T> {
+    #[inline]
+    pub(crate) fn new(body: SizedBody<S>, limit: Option<usize>) -> Self {
+        Self {
+            body,
+            buffer: Vec::new(),
+            pos: 0,
+            limit: limit.unwrap_or(ELEMENT_LIMIT),
+            state: State::Start,
+            is_eof: false,
+            _element: PhantomData,
+        }
+    }
+
+    #[inline]
+    ///Returns whether array is read completely or reading failed.
+    pub fn is_finished(&self) -> bool {
+        self.state == State::Done
+    }
+
+    fn skip_whitespace(&mut self) -> Option<u8> {
+        while let Some(byte) = self.buffer.get(self.pos) {
+            match byte {
+                b' ' | b'\n' | b'\r' | b'\t' => self.pos += 1,
+                byte => return Some(*byte),
+            }
+        }
+
+        None
+    }
+}
+
+impl<S, T: serde::de::DeserializeOwned> JsonArrayStream<S, T> {
+    fn parse_next(&mut self) -> Result<Step<T>, BodyReadError> {
+        loop {
+            let byte = match (self.state, self.skip_whitespace()) {
+                (State::Done, _) => return Ok(Step::Done),
+                (_, Some(byte)) => byte,
+                (_, None) => return match self.is_eof {
+                    true => Err(syntax_error("EOF while parsing JSON array")),
+                    false => Ok(Step::NeedInput),
+                },
+            };
+
+            match self.state {
+                State::Start => match byte {
+                    b'[' => {
+                        self.pos += 1;
+                        self.state = State::Element(true);
+                    },
+                    _ => return Err(syntax_error("expected JSON array")),
+                },
+                State::Element(true) if byte == b']' => {
+                    self.pos += 1;
+                    self.state = State::Done;
+                },
+                State::Element(_) => {
+                    let input = &self.buffer[self.pos..];
+                    let mut elements = serde_json::Deserializer::from_slice(input).into_iter::<T>();
+
+                    return match elements.next() {
+                        //Scalar at the end of input may be incomplete, so it is accepted only once delimiter is received.
+                        Some(Ok(_)) if !self.is_eof && elements.byte_offset() == input.len() => Ok(Step::NeedInput),
+                        Some(Ok(element)) => {
+                            self.pos += elements.byte_offset();
+                            self.state = State::Delimiter;
+                            Ok(Step::Item(element))
+                        },
+                        Some(Err(ref error)) if error.is_eof() && !self.is_eof => Ok(Step::NeedInput),
+                        Some(Err(error)) => Err(error.into()),
+                        None => unreach!(),
+                    }
+                },
+                State::Delimiter => {
+                    self.pos += 1;
+                    match byte {
+                        b',' => self.state = State::Element(false),
+                        b']' => self.state = State::Done,
+                        _ => return Err(syntax_error("expected `,` or `]`")),
+                    }
+                },
+                State::Done => unreach!(),
+            }
+        }
+    }
+}
+
+impl<S, I, E, T> JsonArrayStream<S, T> where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, T: serde::de::DeserializeOwned {
+    fn poll_element(&mut self, ctx: &mut task::Context<'_>) -> task::Poll<Option<Result<T, BodyReadError>>> {
+        loop {
+            match self.parse_next() {
+                Ok(Step::Item(element)) => return task::Poll::Ready(Some(Ok(element))),
+                Ok(Step::Done) => return task::Poll::Ready(None),
+                Ok(Step::NeedInput) => (),
+                Err(error) => {
+                    self.state = State::Done;
+                    return task::Poll::Ready(Some(Err(error)));
+                },
+            }
+
+            //Drop consumed input, so that buffer contains only incomplete element.
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+
+            match Pin::new(&mut self.body).poll_data(ctx) {
+                task::Poll::Ready(Some(Ok(chunk))) => {
+                    self.buffer.extend_from_slice(&chunk);
+                    if self.buffer.len() > self.limit {
+                        self.state = State::Done;
+                        let buffer = std::mem::take(&mut self.buffer);
+                        return task::Poll::Ready(Some(Err(BodyReadError::Overflow(buffer.into()))));
+                    }
+                },
+                task::Poll::Ready(Some(Err(error))) => {
+                    self.state = State::Done;
+                    return task::Poll::Ready(Some(Err(error)));
+                },
+                task::Poll::Ready(None) => self.is_eof = true,
+                task::Poll::Pending => return task::Poll::Pending,
+            }
+        }
+    }
+
+    #[inline]
+    ///Reads next element, returning `None` once array ends.
+    pub async fn next(&mut self) -> Option<Result<T, BodyReadError>> {
+        matsu!(Next(self))
+    }
+}
+
+impl<S, I, E, T> futures_core::Stream for JsonArrayStream<S, T> where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, T: serde::de::DeserializeOwned {
+    type Item = Result<T, BodyReadError>;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Option<Self::Item>> {
+        self.get_mut().poll_element(ctx)
+    }
+}
+
+impl<S, T> core::fmt::Debug for JsonArrayStream<S, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("JsonArrayStream").field("buffered", &(self.buffer.len() - self.pos)).field("limit", &self.limit).finish()
+    }
+}
+
+struct Next<'a, S, T>(&'a mut JsonArrayStream<S, T>);
+
+impl<'a, S, I, E, T> Future for Next<'a, S, T> where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>, T: serde::de::DeserializeOwned {
+    type Output = Option<Result<T, BodyReadError>>;
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        self.0.poll_element(ctx)
+    }
+}
+
+///Extracts elements of top-level JSON array from `Stream` one by one.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read.
+///- `encoding` - Specifies content's encoding to use.
+///- `limit` - Specifies limit on size of single element, if not specified uses [ELEMENT_LIMIT](constant.ELEMENT_LIMIT.html)
+pub fn json_array_stream<S, T>(body: S, encoding: ContentEncoding, limit: Option<usize>) -> JsonArrayStream<S, T> {
+    JsonArrayStream::new(SizedBody::new(body, &encoding.into()), limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_array_stream;
+    use crate::extractor::BodyReadError;
+    use crate::header::ContentEncoding;
+
+    async fn collect(chunks: &[&'static str], limit: Option<usize>) -> Vec<Result<serde_json::Value, BodyReadError>> {
+        let (mut sender, body) = hyper::Body::channel();
+        let chunks = chunks.to_vec();
+        tokio::spawn(async move {
+            for chunk in chunks {
+                sender.send_data(chunk.into()).await.expect("To send chunk");
+            }
+        });
+
+        let mut stream = json_array_stream(body, ContentEncoding::Identity, limit);
+        let mut result = Vec::new();
+        while let Some(element) = stream.next().await {
+            result.push(element);
+        }
+        assert!(stream.is_finished());
+        result
+    }
+
+    #[tokio::test]
+    async fn should_parse_split_elements() {
+        let result = collect(&[" [", "1", "2, {\"a\"", ": [1, 2]} , \"x", "]\" ", ", null,tr", "ue]"], None).await;
+        let result = result.into_iter().map(|element| element.expect("To parse element")).collect::<Vec<_>>();
+        assert_eq!(result, [serde_json::json!(12), serde_json::json!({"a": [1, 2]}), serde_json::json!("x]"), serde_json::json!(null), serde_json::json!(true)]);
+
+        assert!(collect(&["[ ", "]"], None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_fail_on_invalid_array() {
+        let result = collect(&["{}"], None).await;
+        assert!(matches!(result.as_slice(), [Err(BodyReadError::JsonError(_))]));
+
+        let result = collect(&["[1, 2"], None).await;
+        assert!(matches!(result.as_slice(), [Ok(_), Ok(_), Err(BodyReadError::JsonError(_))]));
+
+        let result = collect(&["[1 2]"], None).await;
+        assert!(matches!(result.as_slice(), [Ok(_), Err(BodyReadError::JsonError(_))]));
+
+        let result = collect(&["[\"short\", \"", "loooooooooooooong\"]"], Some(16)).await;
+        assert!(matches!(result.as_slice(), [Ok(_), Err(BodyReadError::Overflow(_))]));
+    }
+}
@@ -0,0 +1,352 @@
+use core::marker::Unpin;
+
+use http_body::Body as HttpBody;
+
+use super::{BodyReadError, Decompress, SizedBody, ELEMENT_LIMIT};
+use crate::header::ContentEncoding;
+
+#[inline]
+fn syntax_error(msg: &str) -> BodyReadError {
+    BodyReadError::from(<serde_json::Error as serde::de::Error>::custom(msg))
+}
+
+///Parses [RFC6901](https://tools.ietf.org/html/rfc6901) JSON pointer into reference tokens.
+fn parse_pointer(pointer: &str) -> Result<Vec<String>, BodyReadError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    } else if !pointer.starts_with('/') {
+        return Err(syntax_error("JSON pointer must start with `/`"));
+    }
+
+    Ok(pointer[1..].split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+enum Frame {
+    Object(Option<String>),
+    Array(usize),
+}
+
+impl Frame {
+    fn matches(&self, token: &str) -> bool {
+        match self {
+            Frame::Object(Some(key)) => key == token,
+            Frame::Object(None) => false,
+            Frame::Array(index) => token.parse::<usize>().map(|token| token == *index).unwrap_or(false) && (token == "0" || !token.starts_with('0')),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    //Value or `]` of empty array.
+    ValueOrEnd,
+    //Key or `}` of empty object.
+    KeyOrEnd,
+    Key,
+    Colon,
+    CommaOrEnd,
+    //Trailing whitespace after top-level value.
+    Eof,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lex {
+    None,
+    String {
+        is_key: bool,
+        is_escaped: bool,
+    },
+    Scalar,
+}
+
+///Resumable scanner, that looks for value by JSON pointer without deserializing document.
+struct Scanner {
+    tokens: Vec<String>,
+    frames: Vec<Frame>,
+    expect: Expect,
+    lex: Lex,
+    pos: usize,
+    key_start: Option<usize>,
+    capture: Option<usize>,
+}
+
+enum Step {
+    Found(usize, usize),
+    NotFound,
+    NeedInput,
+}
+
+impl Scanner {
+    fn new(tokens: Vec<String>) -> Self {
+        Self {
+            tokens,
+            frames: Vec::new(),
+            expect: Expect::Value,
+            lex: Lex::None,
+            pos: 0,
+            key_start: None,
+            capture: None,
+        }
+    }
+
+    #[inline]
+    fn is_target(&self) -> bool {
+        self.frames.len() == self.tokens.len() && self.frames.iter().zip(self.tokens.iter()).all(|(frame, token)| frame.matches(token))
+    }
+
+    ///Handles end of value at `end`, returning range of target value, if it is complete.
+    fn on_value_end(&mut self, end: usize) -> Option<(usize, usize)> {
+        self.expect = match self.frames.is_empty() {
+            true => Expect::Eof,
+            false => Expect::CommaOrEnd,
+        };
+
+        match self.capture {
+            Some(start) if self.frames.len() == self.tokens.len() => Some((start, end)),
+            _ => None,
+        }
+    }
+
+    fn on_close(&mut self, is_object: bool) -> Result<Option<(usize, usize)>, BodyReadError> {
+        match (self.frames.pop(), is_object) {
+            (Some(Frame::Object(_)), true) | (Some(Frame::Array(_)), false) => (),
+            _ => return Err(syntax_error("mismatched closing bracket")),
+        }
+
+        self.pos += 1;
+        Ok(self.on_value_end(self.pos))
+    }
+
+    fn scan(&mut self, buffer: &[u8], is_eof: bool) -> Result<Step, BodyReadError> {
+        while let Some(byte) = buffer.get(self.pos).copied() {
+            match self.lex {
+                Lex::String { is_key, is_escaped } => {
+                    self.pos += 1;
+                    match byte {
+                        _ if is_escaped => self.lex = Lex::String { is_key, is_escaped: false },
+                        b'\\' => self.lex = Lex::String { is_key, is_escaped: true },
+                        b'"' => {
+                            self.lex = Lex::None;
+                            match is_key {
+                                true => {
+                                    let start = self.key_start.take().unwrap_or(0);
+                                    let key = serde_json::from_slice::<String>(&buffer[start..self.pos])?;
+                                    if let Some(Frame::Object(current)) = self.frames.last_mut() {
+                                        *current = Some(key);
+                                    }
+                                    self.expect = Expect::Colon;
+                                },
+                                false => if let Some(found) = self.on_value_end(self.pos) {
+                                    return Ok(Step::Found(found.0, found.1));
+                                },
+                            }
+                        },
+                        _ => (),
+                    }
+                    continue;
+                },
+                Lex::Scalar => match byte {
+                    b',' | b'}' | b']' | b' ' | b'\n' | b'\r' | b'\t' => {
+                        self.lex = Lex::None;
+                        if let Some(found) = self.on_value_end(self.pos) {
+                            return Ok(Step::Found(found.0, found.1));
+                        }
+                    },
+                    _ => {
+                        self.pos += 1;
+                        continue;
+                    },
+                },
+                Lex::None => (),
+            }
+
+            if let b' ' | b'\n' | b'\r' | b'\t' = byte {
+                self.pos += 1;
+                continue;
+            }
+
+            match self.expect {
+                Expect::ValueOrEnd if byte == b']' => if let Some(found) = self.on_close(false)? {
+                    return Ok(Step::Found(found.0, found.1));
+                },
+                Expect::Value | Expect::ValueOrEnd => {
+                    if self.is_target() {
+                        self.capture = Some(self.pos);
+                    }
+
+                    self.pos += 1;
+                    match byte {
+                        b'{' => {
+                            self.frames.push(Frame::Object(None));
+                            self.expect = Expect::KeyOrEnd;
+                        },
+                        b'[' => {
+                            self.frames.push(Frame::Array(0));
+                            self.expect = Expect::ValueOrEnd;
+                        },
+                        b'"' => self.lex = Lex::String { is_key: false, is_escaped: false },
+                        b'-' | b'0'..=b'9' | b't' | b'f' | b'n' => self.lex = Lex::Scalar,
+                        _ => return Err(syntax_error("expected JSON value")),
+                    }
+                },
+                Expect::KeyOrEnd if byte == b'}' => if let Some(found) = self.on_close(true)? {
+                    return Ok(Step::Found(found.0, found.1));
+                },
+                Expect::KeyOrEnd | Expect::Key => match byte {
+                    b'"' => {
+                        self.key_start = Some(self.pos);
+                        self.pos += 1;
+                        self.lex = Lex::String { is_key: true, is_escaped: false };
+                    },
+                    _ => return Err(syntax_error("expected object's key")),
+                },
+                Expect::Colon => match byte {
+                    b':' => {
+                        self.pos += 1;
+                        self.expect = Expect::Value;
+                    },
+                    _ => return Err(syntax_error("expected `:`")),
+                },
+                Expect::CommaOrEnd => match byte {
+                    b',' => {
+                        self.pos += 1;
+                        match self.frames.last_mut() {
+                            Some(Frame::Object(key)) => {
+                                *key = None;
+                                self.expect = Expect::Key;
+                            },
+                            Some(Frame::Array(index)) => {
+                                *index += 1;
+                                self.expect = Expect::Value;
+                            },
+                            None => return Err(syntax_error("unexpected `,`")),
+                        }
+                    },
+                    b'}' | b']' => if let Some(found) = self.on_close(byte == b'}')? {
+                        return Ok(Step::Found(found.0, found.1));
+                    },
+                    _ => return Err(syntax_error("expected `,` or closing bracket")),
+                },
+                Expect::Eof => return Err(syntax_error("trailing characters after JSON value")),
+            }
+        }
+
+        if !is_eof {
+            return Ok(Step::NeedInput);
+        }
+
+        match (self.lex, self.expect) {
+            (Lex::Scalar, _) => match self.on_value_end(self.pos) {
+                Some(found) => Ok(Step::Found(found.0, found.1)),
+                None => Ok(Step::NotFound),
+            },
+            (Lex::None, Expect::Eof) => Ok(Step::NotFound),
+            _ => Err(syntax_error("EOF while parsing JSON")),
+        }
+    }
+
+    ///Returns offset, before which input is no longer needed.
+    #[inline]
+    fn consumed(&self) -> usize {
+        self.capture.or(self.key_start).unwrap_or(self.pos)
+    }
+
+    fn shift(&mut self, offset: usize) {
+        self.pos -= offset;
+        self.key_start = self.key_start.map(|start| start - offset);
+        self.capture = self.capture.map(|start| start - offset);
+    }
+}
+
+///Extracts raw JSON value, located by [RFC6901](https://tools.ietf.org/html/rfc6901) JSON `pointer`, from `Stream`.
+///
+///Document is scanned without deserialization and reading stops as soon as value is complete,
+///so only part of body up to the value is read. Input before value is discarded as it is scanned.
+///Document is validated only as much as it is necessary to locate value.
+///
+///Returns `None`, if document has no such value.
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read.
+///- `encoding` - Specifies content's encoding to use.
+///- `pointer` - JSON pointer, e.g. `/items/0/name`. Empty pointer refers to whole document.
+///- `limit` - Specifies limit on size of value, if not specified uses [ELEMENT_LIMIT](constant.ELEMENT_LIMIT.html)
+pub async fn json_pointer<S, I, E>(body: S, encoding: ContentEncoding, pointer: &str, limit: Option<usize>) -> Result<Option<bytes::Bytes>, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    matsu!(json_pointer_impl(body, encoding.into(), pointer, limit))
+}
+
+///Implements [json_pointer](fn.json_pointer.html) with specified decompression.
+pub(crate) async fn json_pointer_impl<S, I, E>(body: S, decompress: Decompress, pointer: &str, limit: Option<usize>) -> Result<Option<bytes::Bytes>, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    let limit = limit.unwrap_or(ELEMENT_LIMIT);
+    let mut scanner = Scanner::new(parse_pointer(pointer)?);
+    let mut body = SizedBody::new(body, &decompress);
+    let mut buffer = Vec::new();
+    let mut is_eof = false;
+
+    loop {
+        match scanner.scan(&buffer, is_eof)? {
+            Step::Found(start, end) => {
+                buffer.truncate(end);
+                return Ok(Some(bytes::Bytes::from(buffer).slice(start..)));
+            },
+            Step::NotFound => return Ok(None),
+            Step::NeedInput => (),
+        }
+
+        let consumed = scanner.consumed();
+        buffer.drain(..consumed);
+        scanner.shift(consumed);
+
+        match matsu!(body.data()) {
+            Some(chunk) => {
+                buffer.extend_from_slice(&chunk?);
+                if buffer.len() > limit {
+                    return Err(BodyReadError::Overflow(buffer.into()));
+                }
+            },
+            None => is_eof = true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_pointer;
+    use crate::header::ContentEncoding;
+
+    async fn find(chunks: &[&'static str], pointer: &str) -> Option<String> {
+        let (mut sender, body) = hyper::Body::channel();
+        let chunks = chunks.to_vec();
+        tokio::spawn(async move {
+            for chunk in chunks {
+                sender.send_data(chunk.into()).await.expect("To send chunk");
+            }
+        });
+
+        let value = json_pointer(body, ContentEncoding::Identity, pointer, None).await.expect("To scan JSON");
+        value.map(|value| String::from_utf8(value.to_vec()).expect("UTF-8"))
+    }
+
+    #[tokio::test]
+    async fn should_find_json_pointer() {
+        let doc = ["{\"ships\": [{\"name\": \"Yuki", "kaze\", \"class\": {\"a/b\": 1, \"~\": [true]}}, 5", "0], \"count\": 2}"];
+
+        assert_eq!(find(&doc, "/ships/0/name").await.as_deref(), Some("\"Yukikaze\""));
+        assert_eq!(find(&doc, "/ships/0/class").await.as_deref(), Some("{\"a/b\": 1, \"~\": [true]}"));
+        assert_eq!(find(&doc, "/ships/0/class/a~1b").await.as_deref(), Some("1"));
+        assert_eq!(find(&doc, "/ships/0/class/~0/0").await.as_deref(), Some("true"));
+        assert_eq!(find(&doc, "/ships/1").await.as_deref(), Some("50"));
+        assert_eq!(find(&doc, "/count").await.as_deref(), Some("2"));
+        assert_eq!(find(&doc, "/ships/2").await, None);
+        assert_eq!(find(&doc, "/ships/01").await, None);
+        assert_eq!(find(&doc, "/missing").await, None);
+        assert_eq!(find(&[" 42 "], "").await.as_deref(), Some("42"));
+        assert_eq!(find(&["{\"k\\\"ey\": [], \"next\": {}}"], "/k\"ey").await.as_deref(), Some("[]"));
+    }
+}
@@ -9,11 +9,43 @@ use core::fmt;
 
 mod notify;
 mod cookie;
+#[macro_use]
 mod body;
+#[cfg(feature = "checksum")]
+mod checksum;
+mod size;
+mod json_array;
+mod json_pointer;
+mod multipart;
+mod sniff;
+#[cfg(feature = "compu")]
+mod inflate;
+#[cfg(feature = "html")]
+pub mod html;
 
-pub use self::cookie::CookieIter;
-pub use notify::{Notifier, Noop};
+pub use self::cookie::{CookieIter, LenientCookieIter, InvalidCookie};
+pub use notify::{Notifier, Noop, Progress, Deadline};
 pub use body::{*};
+pub use size::{BodySize, SizedBody};
+pub(crate) use size::Decompress;
+pub use json_array::{JsonArrayStream, json_array_stream, ELEMENT_LIMIT};
+pub use json_pointer::json_pointer;
+pub(crate) use json_pointer::json_pointer_impl;
+pub use multipart::{Multipart, MultipartError, Part};
+pub use sniff::{Sniffed, sniff_encoding, sniff_mime, raw_bytes_sniffed, SNIFF_LEN};
+pub(crate) use sniff::raw_bytes_sniffed_impl;
+pub(crate) use multipart::boundary as multipart_boundary;
+#[cfg(feature = "checksum")]
+pub use checksum::{Algorithm, Checksum, ChecksumBody, raw_bytes_checksum, file_checksum, digest};
+#[cfg(feature = "checksum")]
+pub(crate) use checksum::{raw_bytes_checksum_impl, file_checksum_impl, digest_impl};
+
+///Default limit on ratio of decompressed size to compressed size.
+pub const DEFAULT_MAX_RATIO: u32 = 1000;
+///Decompressed size, after which ratio is verified.
+///
+///Small bodies often have high ratio, while being harmless.
+pub const RATIO_THRESHOLD: u64 = 1024 * 1024;
 
 #[derive(Debug)]
 ///Describes possible errors when reading body.
@@ -23,18 +55,36 @@ pub enum BodyReadError {
     ///Unable to decode body as UTF-8
     EncodingError,
     ///Json serialization error.
-    JsonError(serde_json::error::Error),
+    JsonError(JsonError),
+    #[cfg(feature = "nested_form")]
+    ///Nested form deserialization error.
+    FormError(serde_qs::Error),
     #[cfg(feature = "compu")]
     ///Error happened during decompression.
     CompuError(compu::decoder::DecoderResult),
     ///Failed to decompress content as it is not complete.
     IncompleteDecompression,
+    ///Ratio of decompressed size to compressed size exceeds limit.
+    ///
+    ///See [DEFAULT_MAX_RATIO](constant.DEFAULT_MAX_RATIO.html).
+    DecompressionBomb,
+    ///Body ends before `Content-Length` is received, contains expected and received length.
+    TruncatedBody(u64, u64),
+    ///Body exceeds `Content-Length`, contains expected length and length received so far.
+    ExcessBody(u64, u64),
     ///Error happened when writing to file.
     FileError(fs::File, io::Error),
     ///Some IO Error during reading
     ///
     ///Convertion from `io::Error` creates this  variant
     ReadError(io::Error),
+    #[cfg(feature = "checksum")]
+    ///Digest of body doesn't match expected checksum, contains actual digest.
+    ChecksumMismatch(Vec<u8>),
+    ///Reading is aborted by [Notifier](trait.Notifier.html).
+    Cancelled,
+    ///Unable to parse multipart body.
+    MultipartError(MultipartError),
     ///Hyper's error.
     ///
     ///Disabled when `client` feature is not enabled
@@ -47,20 +97,131 @@ impl fmt::Display for BodyReadError {
             BodyReadError::Overflow(_) => f.write_str("Read limit is reached. Aborted reading."),
             BodyReadError::EncodingError => f.write_str("Unable to decode content into UTF-8"),
             BodyReadError::JsonError(err) => write!(f, "Failed to extract JSON. Error: {}", err),
+            #[cfg(feature = "nested_form")]
+            BodyReadError::FormError(err) => write!(f, "Failed to extract form. Error: {}", err),
             #[cfg(feature = "compu")]
             BodyReadError::CompuError(err) => write!(f, "Failed to decompress content. Error: {:?}", err),
             BodyReadError::IncompleteDecompression => f.write_str("Failed to decompress content as it is not complete"),
+            BodyReadError::DecompressionBomb => f.write_str("Decompression ratio exceeds limit. Aborted reading."),
+            BodyReadError::TruncatedBody(expected, received) => write!(f, "Body is truncated: expected {} bytes, but received {}", expected, received),
+            BodyReadError::ExcessBody(expected, received) => write!(f, "Body exceeds Content-Length: expected {} bytes, but received {}", expected, received),
             BodyReadError::FileError(_, err) => write!(f, "Error file writing response into file. Error: {}", err),
             BodyReadError::ReadError(err) => write!(f, "IO Error while reading: {}", err),
+            #[cfg(feature = "checksum")]
+            BodyReadError::ChecksumMismatch(_) => f.write_str("Body's checksum doesn't match"),
+            BodyReadError::Cancelled => f.write_str("Reading is cancelled"),
+            BodyReadError::MultipartError(err) => write!(f, "Failed to extract multipart. Error: {}", err),
             BodyReadError::Hyper(err) => write!(f, "Failed to read due to HTTP error: {}", err),
         }
     }
 }
 
+#[derive(Debug)]
+///Describes JSON deserialization error.
+///
+///With feature `json_path`, it also contains path to field, that failed to deserialize, and beginning of body.
+pub struct JsonError {
+    ///Path to failing field, e.g. `data.items[1].name`
+    ///
+    ///Available only with feature `json_path`.
+    pub path: Option<String>,
+    ///Beginning of body, truncated to `JsonError::SNIPPET_LEN` bytes.
+    ///
+    ///Available only with feature `json_path`.
+    pub snippet: Option<String>,
+    ///Original error.
+    pub inner: serde_json::error::Error,
+}
+
+impl JsonError {
+    ///Max length of body's snippet.
+    pub const SNIPPET_LEN: usize = 256;
+}
+
+impl From<serde_json::error::Error> for JsonError {
+    #[inline]
+    fn from(inner: serde_json::error::Error) -> Self {
+        Self {
+            path: None,
+            snippet: None,
+            inner,
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.inner, f)?;
+        if let Some(path) = self.path.as_ref() {
+            write!(f, " at '{}'", path)?;
+        }
+        if let Some(snippet) = self.snippet.as_ref() {
+            write!(f, ". Body: {}", snippet)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for JsonError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.inner)
+    }
+}
+
+#[cfg(feature = "json_path")]
+pub(crate) fn json_from_slice<J: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<J, BodyReadError> {
+    let mut de = serde_json::Deserializer::from_slice(bytes);
+
+    let snippet = || match bytes.len() > JsonError::SNIPPET_LEN {
+        true => format!("{}...", String::from_utf8_lossy(&bytes[..JsonError::SNIPPET_LEN])),
+        false => String::from_utf8_lossy(bytes).into_owned(),
+    };
+
+    let result = match serde_path_to_error::deserialize(&mut de) {
+        Ok(result) => result,
+        Err(error) => return Err(BodyReadError::JsonError(JsonError {
+            path: Some(error.path().to_string()),
+            snippet: Some(snippet()),
+            inner: error.into_inner(),
+        })),
+    };
+
+    match de.end() {
+        Ok(()) => Ok(result),
+        Err(error) => Err(BodyReadError::JsonError(JsonError {
+            path: Some(".".to_owned()),
+            snippet: Some(snippet()),
+            inner: error,
+        })),
+    }
+}
+
+#[cfg(not(feature = "json_path"))]
+#[inline(always)]
+pub(crate) fn json_from_slice<J: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<J, BodyReadError> {
+    serde_json::from_slice(bytes).map_err(BodyReadError::from)
+}
+
 impl From<serde_json::error::Error> for BodyReadError {
     #[inline]
     fn from(error: serde_json::error::Error) -> Self {
-        BodyReadError::JsonError(error)
+        BodyReadError::JsonError(error.into())
+    }
+}
+
+#[cfg(feature = "nested_form")]
+impl From<serde_qs::Error> for BodyReadError {
+    #[inline]
+    fn from(error: serde_qs::Error) -> Self {
+        BodyReadError::FormError(error)
+    }
+}
+
+impl From<MultipartError> for BodyReadError {
+    #[inline]
+    fn from(error: MultipartError) -> Self {
+        BodyReadError::MultipartError(error)
     }
 }
 
@@ -86,3 +247,23 @@ impl From<hyper::Error> for BodyReadError {
 }
 
 impl Error for BodyReadError {}
+
+#[cfg(all(test, feature = "json_path"))]
+mod tests {
+    use super::{json_from_slice, BodyReadError};
+
+    use std::collections::HashMap;
+
+    #[test]
+    fn json_error_should_contain_path() {
+        let body = br#"{"items": ["first", 2]}"#;
+
+        match json_from_slice::<HashMap<String, Vec<String>>>(body) {
+            Err(BodyReadError::JsonError(error)) => {
+                assert_eq!(error.path.as_deref(), Some("items[1]"));
+                assert_eq!(error.snippet.as_deref().map(str::as_bytes), Some(&body[..]));
+            },
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use core::fmt;
+use core::str::FromStr;
+use std::error::Error;
+
+use crate::header;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Describes failure to parse multipart body.
+pub enum MultipartError {
+    ///`Content-Type` is not `multipart/*` or has no boundary.
+    NotMultipart,
+    ///Body has no delimiter of first part.
+    MissingDelimiter,
+    ///Part's headers are malformed.
+    InvalidHeaders,
+    ///Body ends before close delimiter.
+    Incomplete,
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MultipartError::NotMultipart => f.write_str("Content is not multipart or has no boundary"),
+            MultipartError::MissingDelimiter => f.write_str("Multipart body has no parts"),
+            MultipartError::InvalidHeaders => f.write_str("Multipart body has part with invalid headers"),
+            MultipartError::Incomplete => f.write_str("Multipart body is not complete"),
+        }
+    }
+}
+
+impl Error for MultipartError {}
+
+#[derive(Debug, Clone)]
+///Part of multipart body.
+pub struct Part {
+    headers: http::HeaderMap,
+    body: bytes::Bytes,
+}
+
+impl Part {
+    #[inline]
+    ///Returns part's headers.
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+
+    #[inline]
+    ///Returns part's body.
+    pub fn body(&self) -> &bytes::Bytes {
+        &self.body
+    }
+
+    #[inline]
+    ///Consumes self, returning part's body.
+    pub fn into_body(self) -> bytes::Bytes {
+        self.body
+    }
+
+    #[inline]
+    ///Returns part's body as UTF-8 text, if it is valid.
+    pub fn text(&self) -> Option<&str> {
+        core::str::from_utf8(&self.body).ok()
+    }
+
+    #[inline]
+    ///Retrieves `Content-Type` as Mime, if any valid is present.
+    pub fn mime(&self) -> Option<mime::Mime> {
+        self.headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()).and_then(|value| value.parse().ok())
+    }
+
+    #[inline]
+    ///Retrieves `Content-Disposition`, if valid one is present.
+    pub fn content_disposition(&self) -> Option<header::ContentDisposition> {
+        self.headers.get(header::CONTENT_DISPOSITION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| header::ContentDisposition::from_str(value).ok())
+    }
+
+    ///Returns name of form's field.
+    pub fn name(&self) -> Option<String> {
+        match self.content_disposition() {
+            Some(header::ContentDisposition::FormData(name, _)) => name,
+            _ => None,
+        }
+    }
+
+    ///Returns name of file, percent decoded if necessary.
+    pub fn filename(&self) -> Option<String> {
+        match self.content_disposition() {
+            Some(header::ContentDisposition::FormData(_, filename)) => filename.into_name(),
+            Some(header::ContentDisposition::Attachment(filename)) => filename.into_name(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+///Parsed multipart body, such as `multipart/form-data`.
+pub struct Multipart {
+    parts: Vec<Part>,
+}
+
+impl Multipart {
+    ///Parses `body`, which parts are separated by `boundary`.
+    ///
+    ///Preamble and epilogue are ignored. Parts' bodies reference `body`, without copying.
+    pub fn parse(body: bytes::Bytes, boundary: &str) -> Result<Self, MultipartError> {
+        let delimiter = format!("--{}", boundary);
+        let delimiter = delimiter.as_bytes();
+
+        let mut pos = match body.starts_with(delimiter) {
+            true => 0,
+            false => match find(&body, 0, &[b"\r\n", delimiter]) {
+                Some(pos) => pos + 2,
+                None => return Err(MultipartError::MissingDelimiter),
+            },
+        };
+        let mut parts = Vec::new();
+
+        loop {
+            pos += delimiter.len();
+            if body[pos..].starts_with(b"--") {
+                break;
+            }
+
+            //Delimiter may be followed by transport padding
+            pos = match find(&body, pos, &[b"\r\n"]) {
+                Some(end) if body[pos..end].iter().all(|byte| *byte == b' ' || *byte == b'\t') => end + 2,
+                Some(_) => return Err(MultipartError::MissingDelimiter),
+                None => return Err(MultipartError::Incomplete),
+            };
+
+            //Delimiter line may be repeated between parts (e.g. by request's `multipart::Form`)
+            if body[pos..].starts_with(delimiter) {
+                continue;
+            }
+
+            let headers_end = match body[pos..].starts_with(b"\r\n") {
+                true => pos,
+                false => match find(&body, pos, &[b"\r\n\r\n"]) {
+                    Some(end) => end + 2,
+                    None => return Err(MultipartError::Incomplete),
+                },
+            };
+            let headers = parse_headers(&body[pos..headers_end])?;
+
+            let body_start = headers_end + 2;
+            let body_end = match find(&body, body_start - 2, &[b"\r\n", delimiter]) {
+                Some(end) => end,
+                None => return Err(MultipartError::Incomplete),
+            };
+
+            parts.push(Part {
+                headers,
+                body: match body_end > body_start {
+                    true => body.slice(body_start..body_end),
+                    false => bytes::Bytes::new(),
+                },
+            });
+            pos = body_end + 2;
+        }
+
+        Ok(Self {
+            parts,
+        })
+    }
+
+    #[inline]
+    ///Returns iterator over parts.
+    pub fn parts(&self) -> core::slice::Iter<'_, Part> {
+        self.parts.iter()
+    }
+
+    #[inline]
+    ///Returns first part of form's field with `name`.
+    pub fn get(&self, name: &str) -> Option<&Part> {
+        self.parts.iter().find(|part| part.name().as_deref() == Some(name))
+    }
+
+    #[inline]
+    ///Returns number of parts.
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    #[inline]
+    ///Returns whether there are no parts.
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    #[inline]
+    ///Consumes self, returning parts.
+    pub fn into_parts(self) -> Vec<Part> {
+        self.parts
+    }
+}
+
+impl IntoIterator for Multipart {
+    type Item = Part;
+    type IntoIter = std::vec::IntoIter<Part>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.parts.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Multipart {
+    type Item = &'a Part;
+    type IntoIter = core::slice::Iter<'a, Part>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.parts.iter()
+    }
+}
+
+///Retrieves boundary out of multipart's `Content-Type`.
+pub(crate) fn boundary(content_type: Option<&mime::Mime>) -> Result<String, MultipartError> {
+    match content_type {
+        Some(mime) if mime.type_() == mime::MULTIPART => match mime.get_param(mime::BOUNDARY) {
+            Some(boundary) if !boundary.as_str().is_empty() => Ok(boundary.as_str().to_owned()),
+            _ => Err(MultipartError::NotMultipart),
+        },
+        _ => Err(MultipartError::NotMultipart),
+    }
+}
+
+///Finds position of concatenated `needle` in `haystack`, starting from `from`.
+fn find(haystack: &[u8], from: usize, needle: &[&[u8]]) -> Option<usize> {
+    let len = needle.iter().map(|part| part.len()).sum::<usize>();
+    if haystack.len() < len {
+        return None;
+    }
+
+    (from..=haystack.len() - len).find(|&idx| {
+        let mut pos = idx;
+        needle.iter().all(|part| {
+            let is_match = haystack[pos..].starts_with(part);
+            pos += part.len();
+            is_match
+        })
+    })
+}
+
+fn parse_headers(text: &[u8]) -> Result<http::HeaderMap, MultipartError> {
+    let mut headers = http::HeaderMap::new();
+
+    for line in text.split(|byte| *byte == b'\n') {
+        let line = match line.last() {
+            Some(b'\r') => &line[..line.len() - 1],
+            _ => line,
+        };
+        if line.is_empty() {
+            continue;
+        }
+
+        let sep = match line.iter().position(|byte| *byte == b':') {
+            Some(sep) => sep,
+            None => return Err(MultipartError::InvalidHeaders),
+        };
+
+        let name = match header::HeaderName::from_bytes(&line[..sep]) {
+            Ok(name) => name,
+            Err(_) => return Err(MultipartError::InvalidHeaders),
+        };
+        let value = match header::HeaderValue::from_bytes(trim(&line[sep + 1..])) {
+            Ok(value) => value,
+            Err(_) => return Err(MultipartError::InvalidHeaders),
+        };
+
+        headers.append(name, value);
+    }
+
+    Ok(headers)
+}
+
+fn trim(mut value: &[u8]) -> &[u8] {
+    while let Some(b' ') | Some(b'\t') = value.first() {
+        value = &value[1..];
+    }
+    while let Some(b' ') | Some(b'\t') = value.last() {
+        value = &value[..value.len() - 1];
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Multipart, MultipartError};
+
+    #[test]
+    fn should_parse_form_data() {
+        let body = "preamble\r\n--XyZ\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nYukikaze\r\n--XyZ  \r\n\
+                    Content-Disposition: form-data; name=\"file\"; filename*=utf-8''%E9%9B%AA%E9%A2%A8.txt\r\nContent-Type: text/plain\r\n\r\nline\r\n\r\n--XyZ\r\n\
+                    \r\n\r\n--XyZ--\r\nepilogue";
+        let form = Multipart::parse(body.into(), "XyZ").expect("To parse");
+        assert_eq!(form.len(), 3);
+
+        let title = form.get("title").expect("To have title");
+        assert_eq!(title.text(), Some("Yukikaze"));
+        assert_eq!(title.filename(), None);
+
+        let file = form.get("file").expect("To have file");
+        assert_eq!(file.filename().as_deref(), Some("雪風.txt"));
+        assert_eq!(file.mime(), Some(mime::TEXT_PLAIN));
+        assert_eq!(file.body(), "line\r\n");
+
+        let empty = form.parts().nth(2).expect("To have empty part");
+        assert!(empty.headers().is_empty());
+        assert!(empty.body().is_empty());
+    }
+
+    #[test]
+    fn should_fail_on_invalid_body() {
+        assert_eq!(Multipart::parse("no parts".into(), "XyZ").unwrap_err(), MultipartError::MissingDelimiter);
+        assert_eq!(Multipart::parse("--XyZ\r\nName: value\r\n\r\ndata".into(), "XyZ").unwrap_err(), MultipartError::Incomplete);
+        assert_eq!(Multipart::parse("--XyZ\r\nbroken\r\n\r\ndata\r\n--XyZ--".into(), "XyZ").unwrap_err(), MultipartError::InvalidHeaders);
+    }
+}
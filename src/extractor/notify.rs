@@ -9,13 +9,29 @@
 //!The trait that describes how to send notification.
 //!User may use already existing impls or create own `Notifier`
 //!
+//!## Cancellation
+//!
+//!After each chunk, extractor asks [Notifier::should_continue](trait.Notifier.html#method.should_continue)
+//!and stops reading with `BodyReadError::Cancelled` once it returns `false`.
+//!It allows to abort download, e.g. on user's request or with [Deadline](struct.Deadline.html).
 
 use std::sync::mpsc as std_mpsc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time;
 
 ///Describes Body download progress
 pub trait Notifier {
     ///Sends data over Notifier.
     fn send(&mut self, num: usize);
+
+    #[inline]
+    ///Returns whether reading should continue.
+    ///
+    ///Called after each `send`, by default always `true`.
+    fn should_continue(&mut self) -> bool {
+        true
+    }
 }
 
 ///Noop Notifier.
@@ -34,3 +50,99 @@ impl Notifier for std_mpsc::Sender<usize> {
         let _ = std_mpsc::Sender::send(self, num);
     }
 }
+
+#[derive(Debug, Default)]
+struct ProgressState {
+    received: AtomicUsize,
+    is_cancelled: AtomicBool,
+}
+
+#[derive(Debug, Clone, Default)]
+///Notifier, that accumulates number of received bytes.
+///
+///Clones share the same counter, allowing to observe progress while body is being read.
+///Reading can be aborted by calling [cancel](#method.cancel) on any of clones.
+pub struct Progress(Arc<ProgressState>);
+
+impl Progress {
+    #[inline]
+    ///Creates new instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    ///Returns number of bytes received so far.
+    pub fn get(&self) -> usize {
+        self.0.received.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    ///Requests to stop reading body.
+    pub fn cancel(&self) {
+        self.0.is_cancelled.store(true, Ordering::Release);
+    }
+
+    #[inline]
+    ///Returns whether reading is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_cancelled.load(Ordering::Acquire)
+    }
+}
+
+impl Notifier for Progress {
+    #[inline]
+    fn send(&mut self, num: usize) {
+        self.0.received.fetch_add(num, Ordering::AcqRel);
+    }
+
+    #[inline]
+    fn should_continue(&mut self) -> bool {
+        !self.is_cancelled()
+    }
+}
+
+#[derive(Debug, Clone)]
+///Notifier, that stops reading once deadline is reached.
+///
+///Unlike timeout, deadline is checked only when chunk arrives, so it aborts slow, but still
+///progressing download, while partial data is reported to inner notifier.
+pub struct Deadline<N> {
+    inner: N,
+    deadline: time::Instant,
+}
+
+impl<N: Notifier> Deadline<N> {
+    #[inline]
+    ///Creates new instance, that stops reading at `deadline`.
+    pub fn new(inner: N, deadline: time::Instant) -> Self {
+        Self {
+            inner,
+            deadline,
+        }
+    }
+
+    #[inline]
+    ///Creates new instance, that stops reading after `duration` from now.
+    pub fn after(inner: N, duration: time::Duration) -> Self {
+        Self::new(inner, time::Instant::now() + duration)
+    }
+
+    #[inline]
+    ///Returns inner notifier.
+    pub fn into_inner(self) -> N {
+        self.inner
+    }
+}
+
+impl<N: Notifier> Notifier for Deadline<N> {
+    #[inline]
+    fn send(&mut self, num: usize) {
+        self.inner.send(num)
+    }
+
+    #[inline]
+    fn should_continue(&mut self) -> bool {
+        self.inner.should_continue() && time::Instant::now() < self.deadline
+    }
+}
@@ -0,0 +1,337 @@
+use core::pin::Pin;
+use core::task;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use http_body::Body as HttpBody;
+
+use super::BodyReadError;
+use crate::header::ContentEncoding;
+
+#[cfg(feature = "compu")]
+use compu::decoder::Decoder;
+#[cfg(feature = "compu")]
+use super::inflate::{Inflater, Status};
+
+#[derive(Debug, Default)]
+struct Counters {
+    sent: AtomicU64,
+    received: AtomicU64,
+    decoded: AtomicU64,
+    #[cfg(feature = "metrics")]
+    received_total: Option<prometheus::IntCounter>,
+}
+
+#[derive(Debug, Clone, Default)]
+///Number of body bytes, transferred within single exchange.
+///
+///Client stores it in response's extensions, and it is updated as body is being read by
+///[Response](../client/response/struct.Response.html) extractors.
+///Clones share the same counters, so it is possible to observe it while body is being read.
+///
+///Body, that is taken via `extract_body`, is not accounted.
+pub struct BodySize(Arc<Counters>);
+
+impl BodySize {
+    #[inline]
+    ///Creates new instance with all counters set to zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "metrics")]
+    #[inline]
+    ///Creates new instance, that also adds received bytes to `received_total`.
+    pub(crate) fn with_received_total(received_total: prometheus::IntCounter) -> Self {
+        Self(Arc::new(Counters {
+            received_total: Some(received_total),
+            ..Counters::default()
+        }))
+    }
+
+    #[inline]
+    ///Returns number of bytes in request's body.
+    pub fn sent(&self) -> u64 {
+        self.0.sent.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    ///Returns number of response's body bytes, received so far as they are transferred, i.e. compressed.
+    pub fn received(&self) -> u64 {
+        self.0.received.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    ///Returns number of response's body bytes, read so far after decompression.
+    ///
+    ///If body is not compressed, it is the same as `received`.
+    pub fn decoded(&self) -> u64 {
+        self.0.decoded.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn set_sent(&self, num: u64) {
+        self.0.sent.store(num, Ordering::Release);
+    }
+
+    #[inline]
+    fn add_received(&self, num: usize) {
+        self.0.received.fetch_add(num as u64, Ordering::AcqRel);
+        #[cfg(feature = "metrics")]
+        if let Some(received_total) = self.0.received_total.as_ref() {
+            received_total.inc_by(num as u64);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn add_decoded(&self, num: usize) {
+        self.0.decoded.fetch_add(num as u64, Ordering::AcqRel);
+    }
+}
+
+///Decompression, that is performed by extractors.
+#[derive(Clone)]
+#[cfg_attr(not(feature = "compu"), allow(dead_code))]
+pub(crate) struct Decompress {
+    pub(crate) encoding: ContentEncoding,
+    ///Limit on ratio of decompressed size to compressed size.
+    pub(crate) max_ratio: Option<u32>,
+    ///Accounts decompressed bytes.
+    pub(crate) size: Option<BodySize>,
+}
+
+impl From<ContentEncoding> for Decompress {
+    #[inline]
+    fn from(encoding: ContentEncoding) -> Self {
+        Self {
+            encoding,
+            max_ratio: Some(super::DEFAULT_MAX_RATIO),
+            size: None,
+        }
+    }
+}
+
+#[cfg(feature = "compu")]
+impl Decompress {
+    #[inline]
+    pub(crate) fn inflater<D: Decoder>(&self, decoder: D) -> Inflater<D> {
+        Inflater::new(decoder, self.max_ratio).size(self.size.clone())
+    }
+}
+
+///Maximum size of decompressed chunk, that is yielded at once.
+#[cfg(feature = "compu")]
+const MAX_CHUNK: usize = 64 * 1024;
+
+enum Decoding {
+    Identity,
+    #[cfg(feature = "compu")]
+    Brotli(Box<Inflater<compu::decoder::brotli::BrotliDecoder>>),
+    #[cfg(feature = "compu")]
+    Zlib(Box<Inflater<compu::decoder::zlib::ZlibDecoder>>),
+}
+
+impl Decoding {
+    #[cfg_attr(not(feature = "compu"), allow(unused_variables))]
+    fn new(decompress: &Decompress) -> Self {
+        match decompress.encoding {
+            #[cfg(feature = "compu")]
+            ContentEncoding::Brotli => Decoding::Brotli(Box::new(decompress.inflater(compu::decoder::brotli::BrotliDecoder::default()))),
+            #[cfg(feature = "compu")]
+            ContentEncoding::Gzip => {
+                let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Gzip);
+                Decoding::Zlib(Box::new(decompress.inflater(compu::decoder::zlib::ZlibDecoder::new(&options))))
+            },
+            #[cfg(feature = "compu")]
+            ContentEncoding::Deflate => {
+                let options = compu::decoder::zlib::ZlibOptions::default().mode(compu::decoder::zlib::ZlibMode::Zlib);
+                Decoding::Zlib(Box::new(decompress.inflater(compu::decoder::zlib::ZlibDecoder::new(&options))))
+            },
+            _ => Decoding::Identity,
+        }
+    }
+
+    #[inline]
+    fn is_identity(&self) -> bool {
+        matches!(self, Decoding::Identity)
+    }
+
+    fn is_finished(&self) -> bool {
+        match self {
+            Decoding::Identity => true,
+            #[cfg(feature = "compu")]
+            Decoding::Brotli(decoder) => decoder.is_finished(),
+            #[cfg(feature = "compu")]
+            Decoding::Zlib(decoder) => decoder.is_finished(),
+        }
+    }
+
+    ///Returns decompressed chunk and whether decompression is finished.
+    ///
+    ///Output is limited by `MAX_CHUNK`, and if it is reached, remaining input is put back.
+    fn push(&mut self, input: &mut Option<bytes::Bytes>) -> Result<(bytes::Bytes, bool), BodyReadError> {
+        match self {
+            Decoding::Identity => Ok((input.take().unwrap_or_default(), false)),
+            #[cfg(feature = "compu")]
+            Decoding::Brotli(decoder) => decode(decoder, input),
+            #[cfg(feature = "compu")]
+            Decoding::Zlib(decoder) => decode(decoder, input),
+        }
+    }
+}
+
+#[cfg(feature = "compu")]
+fn decode<D: Decoder>(decoder: &mut Inflater<D>, input: &mut Option<bytes::Bytes>) -> Result<(bytes::Bytes, bool), BodyReadError> {
+    use bytes::Buf;
+
+    let mut data = input.take().unwrap_or_default();
+    let mut output = Vec::new();
+    let (consumed, status) = decoder.push(&data, &mut output, MAX_CHUNK)?;
+
+    data.advance(consumed);
+    if status == Status::Full {
+        //Decoder may still have buffered output, even if input is consumed.
+        *input = Some(data);
+    }
+
+    Ok((output.into(), status == Status::Finished))
+}
+
+///Body, that accounts its size in [BodySize](struct.BodySize.html).
+///
+///It is passed to extractors by [Response](../client/response/struct.Response.html), which decompress it as usual,
+///and is returned by those, that allow to continue reading body, in which case it is decompressed already.
+pub struct SizedBody<S> {
+    body: S,
+    size: Option<BodySize>,
+    decoding: Decoding,
+    //Compressed input, that is not yet decompressed.
+    pending: Option<bytes::Bytes>,
+    //Length, that body must have, if it is verified.
+    expected_len: Option<u64>,
+    received: u64,
+    //Whether received chunks are accounted as decompressed content too.
+    is_decoded: bool,
+    is_done: bool,
+}
+
+impl<S> SizedBody<S> {
+    #[inline]
+    ///Creates body, that yields decompressed data, without accounting its size.
+    pub(crate) fn new(body: S, decompress: &Decompress) -> Self {
+        Self::raw(body, decompress.encoding, None).decompress(decompress)
+    }
+
+    #[inline]
+    ///Creates body, that yields data as it is received.
+    ///
+    ///Decompressed size is accounted only if body cannot be decompressed,
+    ///otherwise it is up to decompression.
+    pub(crate) fn raw(body: S, encoding: ContentEncoding, size: Option<BodySize>) -> Self {
+        Self {
+            body,
+            size,
+            is_decoded: !encoding.can_decompress(),
+            decoding: Decoding::Identity,
+            pending: None,
+            expected_len: None,
+            received: 0,
+            is_done: false,
+        }
+    }
+
+    #[inline]
+    ///Sets decompression of received data.
+    pub(crate) fn decompress(mut self, decompress: &Decompress) -> Self {
+        self.decoding = Decoding::new(decompress);
+        self
+    }
+
+    #[inline]
+    ///Sets length, that body is verified against, as it is received (i.e. before decompression).
+    pub(crate) fn expected_len(mut self, len: Option<u64>) -> Self {
+        self.expected_len = len;
+        self
+    }
+}
+
+impl<S, I, E> HttpBody for SizedBody<S> where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError> {
+    type Data = bytes::Bytes;
+    type Error = BodyReadError;
+
+    fn poll_data(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = &mut *self;
+
+        loop {
+            if this.is_done {
+                return task::Poll::Ready(None);
+            }
+
+            if this.pending.is_none() {
+                let chunk = match Pin::new(&mut this.body).poll_data(ctx) {
+                    task::Poll::Ready(Some(Ok(chunk))) => chunk.into(),
+                    task::Poll::Ready(Some(Err(error))) => return task::Poll::Ready(Some(Err(error.into()))),
+                    task::Poll::Ready(None) => {
+                        this.is_done = true;
+                        if let Some(expected) = this.expected_len {
+                            if this.received < expected {
+                                return task::Poll::Ready(Some(Err(BodyReadError::TruncatedBody(expected, this.received))));
+                            }
+                        }
+                        return match this.decoding.is_finished() {
+                            true => task::Poll::Ready(None),
+                            false => task::Poll::Ready(Some(Err(BodyReadError::IncompleteDecompression))),
+                        }
+                    },
+                    task::Poll::Pending => return task::Poll::Pending,
+                };
+
+                if let Some(size) = this.size.as_ref() {
+                    size.add_received(chunk.len());
+                    if this.is_decoded {
+                        size.add_decoded(chunk.len());
+                    }
+                }
+                this.received += chunk.len() as u64;
+                if let Some(expected) = this.expected_len {
+                    if this.received > expected {
+                        this.is_done = true;
+                        return task::Poll::Ready(Some(Err(BodyReadError::ExcessBody(expected, this.received))));
+                    }
+                }
+                this.pending = Some(chunk);
+            }
+
+            let (chunk, is_finished) = match this.decoding.push(&mut this.pending) {
+                Ok(result) => result,
+                Err(error) => {
+                    this.is_done = true;
+                    return task::Poll::Ready(Some(Err(error)));
+                }
+            };
+            this.is_done = is_finished;
+
+            if !chunk.is_empty() {
+                return task::Poll::Ready(Some(Ok(chunk)));
+            }
+        }
+    }
+
+    #[inline]
+    fn poll_trailers(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.body).poll_trailers(ctx).map_err(Into::into)
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.is_done || (self.decoding.is_identity() && self.pending.is_none() && self.body.is_end_stream())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        match self.decoding.is_identity() {
+            true => self.body.size_hint(),
+            false => http_body::SizeHint::default(),
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use core::marker::Unpin;
+use core::pin::Pin;
+use core::task;
+
+use http_body::Body as HttpBody;
+
+use super::{BodyReadError, Decompress};
+use crate::header::ContentEncoding;
+
+#[derive(Debug, Clone)]
+///Body, read by [raw_bytes_sniffed](fn.raw_bytes_sniffed.html).
+pub struct Sniffed {
+    ///Decoded body.
+    pub data: bytes::Bytes,
+    ///Encoding, detected by magic bytes, if body is decoded as result of sniffing.
+    pub sniffed: Option<ContentEncoding>,
+}
+
+impl Sniffed {
+    #[inline]
+    ///Returns whether encoding is detected by sniffing.
+    pub fn is_sniffed(&self) -> bool {
+        self.sniffed.is_some()
+    }
+}
+
+///Detects compression of `data` by its magic prefix.
+///
+///Recognizes gzip (`1f 8b 08`) and zlib (`78` with valid header checksum), which is used by `deflate` encoding.
+///Raw brotli stream has no magic prefix, hence it is never detected.
+///
+///Returns `None` if prefix is not recognized or `data` is too short.
+pub fn sniff_encoding(data: &[u8]) -> Option<ContentEncoding> {
+    match data {
+        [0x1f, 0x8b, 0x08, ..] => Some(ContentEncoding::Gzip),
+        //CMF specifies deflate with window up to 32kb, and FLG has no preset dictionary and makes CMF*256 + FLG multiple of 31
+        [cmf, flg, ..] if cmf & 0x0f == 8 && cmf >> 4 <= 7 && flg & 0x20 == 0 && ((*cmf as u16) << 8 | *flg as u16).is_multiple_of(31) => Some(ContentEncoding::Deflate),
+        _ => None,
+    }
+}
+
+///Number of bytes, that are examined by [sniff_mime](fn.sniff_mime.html).
+pub const SNIFF_LEN: usize = 1445;
+
+///Tags, which indicate HTML document, when followed by space or `>`.
+const HTML_TAGS: &[&[u8]] = &[
+    b"<!DOCTYPE HTML", b"<HTML", b"<HEAD", b"<SCRIPT", b"<IFRAME", b"<H1", b"<DIV", b"<FONT",
+    b"<TABLE", b"<A", b"<STYLE", b"<TITLE", b"<B", b"<BODY", b"<BR", b"<P", b"<!--",
+];
+
+#[inline]
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\t' | b'\n' | 0x0c | b'\r' | b' ')
+}
+
+#[inline]
+fn is_binary(byte: u8) -> bool {
+    matches!(byte, 0x00..=0x08 | 0x0b | 0x0e..=0x1a | 0x1c..=0x1f)
+}
+
+fn skip_whitespace(data: &[u8]) -> &[u8] {
+    let start = data.iter().position(|byte| !is_whitespace(*byte)).unwrap_or(data.len());
+    &data[start..]
+}
+
+fn is_html(data: &[u8]) -> bool {
+    HTML_TAGS.iter().any(|tag| match data.get(tag.len()) {
+        Some(b' ') | Some(b'>') => data[..tag.len()].eq_ignore_ascii_case(tag),
+        _ => false,
+    })
+}
+
+///Checks whether text looks like JSON object or array.
+fn is_json(data: &[u8]) -> bool {
+    match data.split_first() {
+        Some((b'{', rest)) => matches!(skip_whitespace(rest).first(), Some(b'"') | Some(b'}')),
+        Some((b'[', rest)) => match skip_whitespace(rest).first() {
+            Some(byte) => matches!(byte, b'{' | b'[' | b'"' | b']' | b'-' | b'0'..=b'9' | b't' | b'f' | b'n'),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+///Detects MIME type of `data` by its content.
+///
+///Implements subset of [WHATWG](https://mimesniff.spec.whatwg.org/) sniffing algorithm:
+///
+///- Images (`gif`, `png`, `jpeg`, `webp`, `bmp` and `ico`), `pdf`, `zip` and `gzip` by magic numbers;
+///- HTML and XML by leading tags;
+///- JSON by leading object or array, which is not part of WHATWG algorithm;
+///- Otherwise `text/plain`, unless data contains binary bytes, which results in `application/octet-stream`.
+///
+///Only first [SNIFF_LEN](constant.SNIFF_LEN.html) bytes are examined.
+///Returns `None` if `data` is empty.
+pub fn sniff_mime(data: &[u8]) -> Option<mime::Mime> {
+    let data = &data[..core::cmp::min(data.len(), SNIFF_LEN)];
+
+    let mime = match data {
+        [] => return None,
+        [b'G', b'I', b'F', b'8', b'7' ..= b'9', b'a', ..] => mime::IMAGE_GIF,
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => mime::IMAGE_PNG,
+        [0xff, 0xd8, 0xff, ..] => mime::IMAGE_JPEG,
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', b'V', b'P', ..] => "image/webp".parse().expect("Valid mime"),
+        [b'B', b'M', ..] => mime::IMAGE_BMP,
+        [0x00, 0x00, 0x01, 0x00, ..] => "image/x-icon".parse().expect("Valid mime"),
+        [b'%', b'P', b'D', b'F', b'-', ..] => mime::APPLICATION_PDF,
+        [b'P', b'K', 0x03, 0x04, ..] => "application/zip".parse().expect("Valid mime"),
+        [0x1f, 0x8b, 0x08, ..] => "application/x-gzip".parse().expect("Valid mime"),
+        [0xfe, 0xff, ..] | [0xff, 0xfe, ..] => mime::TEXT_PLAIN,
+        _ => {
+            let text = match data {
+                [0xef, 0xbb, 0xbf, rest @ ..] => rest,
+                _ => data,
+            };
+            let text = skip_whitespace(text);
+
+            if is_html(text) {
+                mime::TEXT_HTML
+            } else if text.starts_with(b"<?xml") {
+                mime::TEXT_XML
+            } else if is_json(text) {
+                mime::APPLICATION_JSON
+            } else if data.iter().any(|byte| is_binary(*byte)) {
+                mime::APPLICATION_OCTET_STREAM
+            } else {
+                mime::TEXT_PLAIN
+            }
+        }
+    };
+
+    Some(mime)
+}
+
+///Body, that yields already read prefix before the rest of it.
+struct Prefixed<S> {
+    prefix: Option<bytes::Bytes>,
+    body: S,
+}
+
+impl<S, I, E> HttpBody for Prefixed<S> where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError> {
+    type Data = bytes::Bytes;
+    type Error = BodyReadError;
+
+    fn poll_data(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Option<Result<Self::Data, Self::Error>>> {
+        if let Some(prefix) = self.prefix.take() {
+            return task::Poll::Ready(Some(Ok(prefix)));
+        }
+
+        match Pin::new(&mut self.body).poll_data(ctx) {
+            task::Poll::Ready(Some(Ok(chunk))) => task::Poll::Ready(Some(Ok(chunk.into()))),
+            task::Poll::Ready(Some(Err(error))) => task::Poll::Ready(Some(Err(error.into()))),
+            task::Poll::Ready(None) => task::Poll::Ready(None),
+            task::Poll::Pending => task::Poll::Pending,
+        }
+    }
+
+    fn poll_trailers(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.body).poll_trailers(ctx).map_err(Into::into)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.prefix.is_none() && self.body.is_end_stream()
+    }
+}
+
+///Extracts body as bytes from `Stream`, detecting compression by magic bytes.
+///
+///Some servers send compressed body without `Content-Encoding`.
+///When `encoding` is `Identity`, first bytes of body are checked by [sniff_encoding](fn.sniff_encoding.html)
+///and body is decompressed accordingly, if it is possible with enabled features.
+///Otherwise it behaves as [raw_bytes](fn.raw_bytes.html).
+///
+///Params:
+///
+///- `body` - Stream of data chunks to read. If limit is hit, body is not exhausted completely.
+///- `encoding` - Specifies encoding to use.
+///- `limit` - Specifies limit on body size, if not specified uses default 4kb
+pub async fn raw_bytes_sniffed<S, I, E>(body: S, encoding: ContentEncoding, limit: Option<usize>) -> Result<Sniffed, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    matsu!(raw_bytes_sniffed_impl(body, encoding.into(), limit))
+}
+
+///Implements [raw_bytes_sniffed](fn.raw_bytes_sniffed.html) with specified decompression.
+pub(crate) async fn raw_bytes_sniffed_impl<S, I, E>(mut body: S, decompress: Decompress, limit: Option<usize>) -> Result<Sniffed, BodyReadError>
+    where S: HttpBody<Data=I, Error=E> + Unpin, I: Into<bytes::Bytes> + bytes::Buf, E: Into<BodyReadError>,
+{
+    if decompress.encoding.is_compression() {
+        return Ok(Sniffed {
+            data: matsu!(super::raw_bytes_capped_impl(body, decompress, limit, super::DEFAULT_BUFFER_CAP))?,
+            sniffed: None,
+        });
+    }
+
+    //Magic prefix may be split between chunks, so read until it is complete.
+    let mut prefix = bytes::BytesMut::new();
+    while prefix.len() < 3 {
+        match matsu!(body.data()) {
+            Some(chunk) => {
+                let chunk: bytes::Bytes = chunk.map(Into::into).map_err(Into::into)?;
+                prefix.extend_from_slice(&chunk[..]);
+            },
+            None => break,
+        }
+    }
+
+    let sniffed = sniff_encoding(&prefix).filter(ContentEncoding::can_decompress);
+    let body = Prefixed {
+        prefix: Some(prefix.freeze()),
+        body,
+    };
+
+    //Encoding is not declared, so received body is accounted as decompressed already.
+    let decompress = Decompress {
+        encoding: sniffed.unwrap_or(ContentEncoding::Identity),
+        size: None,
+        ..decompress
+    };
+
+    Ok(Sniffed {
+        data: matsu!(super::raw_bytes_capped_impl(body, decompress, limit, super::DEFAULT_BUFFER_CAP))?,
+        sniffed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sniff_encoding, sniff_mime};
+    use crate::header::ContentEncoding;
+
+    #[test]
+    fn should_sniff_encoding() {
+        assert_eq!(sniff_encoding(&[0x1f, 0x8b, 0x08, 0x00]), Some(ContentEncoding::Gzip));
+        assert_eq!(sniff_encoding(&[0x78, 0x9c, 0x4b]), Some(ContentEncoding::Deflate));
+        assert_eq!(sniff_encoding(&[0x78, 0x01]), Some(ContentEncoding::Deflate));
+        assert_eq!(sniff_encoding(b"x-y"), None);
+        assert_eq!(sniff_encoding(b"x y"), None);
+        assert_eq!(sniff_encoding(b"{\"json\": true}"), None);
+        assert_eq!(sniff_encoding(&[0x1f]), None);
+    }
+
+    #[test]
+    fn should_sniff_mime() {
+        assert_eq!(sniff_mime(b""), None);
+        assert_eq!(sniff_mime(b"GIF89a..."), Some(mime::IMAGE_GIF));
+        assert_eq!(sniff_mime(b"\x89PNG\r\n\x1a\n...."), Some(mime::IMAGE_PNG));
+        assert_eq!(sniff_mime(b"\xff\xd8\xff\xe0"), Some(mime::IMAGE_JPEG));
+        assert_eq!(sniff_mime(b"RIFF\x00\x00\x00\x00WEBPVP8 ").map(|mime| mime.to_string()), Some("image/webp".to_owned()));
+        assert_eq!(sniff_mime(b"%PDF-1.4"), Some(mime::APPLICATION_PDF));
+        assert_eq!(sniff_mime(b"  \n<!doctype html>"), Some(mime::TEXT_HTML));
+        assert_eq!(sniff_mime(b"<p>text</p>"), Some(mime::TEXT_HTML));
+        assert_eq!(sniff_mime(b"<pre>text</pre>"), Some(mime::TEXT_PLAIN));
+        assert_eq!(sniff_mime(b"<?xml version=\"1.0\"?>"), Some(mime::TEXT_XML));
+        assert_eq!(sniff_mime(b"\xef\xbb\xbf{\"name\": \"Yukikaze\"}"), Some(mime::APPLICATION_JSON));
+        assert_eq!(sniff_mime(b" [1, 2]"), Some(mime::APPLICATION_JSON));
+        assert_eq!(sniff_mime(b"{not json"), Some(mime::TEXT_PLAIN));
+        assert_eq!(sniff_mime(b"plain\x00binary"), Some(mime::APPLICATION_OCTET_STREAM));
+    }
+}
@@ -0,0 +1,194 @@
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+use std::error::Error;
+
+use percent_encoding::percent_decode_str;
+
+///Default freshness of alternative service, when `ma` parameter is omitted.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(86400);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+///Alternative service, advertised by server.
+pub struct AltService {
+    ///ALPN protocol identifier (e.g. `h2` or `h3`)
+    pub protocol: String,
+    ///Authority of alternative service.
+    ///
+    ///Host may be empty, in which case it is the same as origin's.
+    pub authority: String,
+    ///Duration for which alternative service is considered fresh.
+    pub max_age: Duration,
+    ///Whether alternative service should be kept on network changes.
+    pub persist: bool,
+}
+
+impl AltService {
+    ///Returns host of alternative service, if it is specified.
+    pub fn host(&self) -> Option<&str> {
+        match self.authority.rfind(':') {
+            Some(0) | None => None,
+            Some(idx) => Some(&self.authority[..idx]),
+        }
+    }
+
+    ///Returns port of alternative service.
+    pub fn port(&self) -> Option<u16> {
+        self.authority.rfind(':').and_then(|idx| self.authority[idx+1..].parse().ok())
+    }
+}
+
+impl fmt::Display for AltService {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}=\"{}\"; ma={}", self.protocol, self.authority, self.max_age.as_secs())?;
+        if self.persist {
+            f.write_str("; persist=1")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+///An `Alt-Svc` header, defined in [RFC7838](https://tools.ietf.org/html/rfc7838).
+///
+///Advertises alternative services, through which the same resources can be reached.
+pub enum AltSvc {
+    ///All alternative services of origin are invalidated.
+    Clear,
+    ///List of alternative services.
+    Services(Vec<AltService>),
+}
+
+#[derive(Debug)]
+///Error parsing `Alt-Svc` header.
+pub enum ParseError {
+    ///Alternative service misses protocol identifier.
+    MissingProtocol,
+    ///Alternative service's authority is missing or not quoted.
+    InvalidAuthority,
+    ///Parameter `ma` is not valid number of seconds.
+    InvalidMaxAge,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingProtocol => f.write_str("Alternative service has no protocol identifier"),
+            ParseError::InvalidAuthority => f.write_str("Alternative service's authority should be quoted string"),
+            ParseError::InvalidMaxAge => f.write_str("Parameter ma should be number of seconds"),
+        }
+    }
+}
+
+impl Error for ParseError {
+}
+
+impl FromStr for AltSvc {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        if text.eq_ignore_ascii_case("clear") {
+            return Ok(AltSvc::Clear);
+        }
+
+        let mut services = Vec::new();
+
+        for service in text.split(',').map(|service| service.trim()).filter(|service| !service.is_empty()) {
+            let mut params = service.split(';').map(|param| param.trim());
+
+            let (protocol, authority) = match params.next().and_then(|alt| alt.find('=').map(|idx| (&alt[..idx], &alt[idx+1..]))) {
+                Some((protocol, authority)) => (protocol.trim(), authority.trim()),
+                None => return Err(ParseError::MissingProtocol),
+            };
+
+            if protocol.is_empty() {
+                return Err(ParseError::MissingProtocol);
+            } else if authority.len() < 2 || !authority.starts_with('"') || !authority.ends_with('"') {
+                return Err(ParseError::InvalidAuthority);
+            }
+
+            let mut alt = AltService {
+                protocol: percent_decode_str(protocol).decode_utf8_lossy().into_owned(),
+                authority: authority[1..authority.len()-1].to_owned(),
+                max_age: DEFAULT_MAX_AGE,
+                persist: false,
+            };
+
+            for param in params {
+                let mut parts = param.splitn(2, '=');
+                let name = parts.next().unwrap_or("").trim();
+                let value = parts.next().unwrap_or("").trim().trim_matches('"');
+
+                if name.eq_ignore_ascii_case("ma") {
+                    alt.max_age = match value.parse() {
+                        Ok(secs) => Duration::from_secs(secs),
+                        Err(_) => return Err(ParseError::InvalidMaxAge),
+                    };
+                } else if name.eq_ignore_ascii_case("persist") {
+                    alt.persist = value == "1";
+                }
+                //Unknown parameters are ignored as per RFC
+            }
+
+            services.push(alt);
+        }
+
+        Ok(AltSvc::Services(services))
+    }
+}
+
+impl fmt::Display for AltSvc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AltSvc::Clear => f.write_str("clear"),
+            AltSvc::Services(services) => {
+                let mut services = services.iter();
+
+                if let Some(service) = services.next() {
+                    fmt::Display::fmt(service, f)?;
+                }
+                for service in services {
+                    write!(f, ", {}", service)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FromStr, AltSvc, Duration};
+
+    #[test]
+    fn parse_alt_svc() {
+        assert_eq!(AltSvc::from_str(" Clear ").expect("To parse"), AltSvc::Clear);
+
+        let services = match AltSvc::from_str("h3=\":443\"; ma=2592000; persist=1, h2=\"alt.example.com:8443\"; foo=bar").expect("To parse") {
+            AltSvc::Services(services) => services,
+            AltSvc::Clear => panic!("Unexpected clear"),
+        };
+
+        assert_eq!(services.len(), 2);
+        assert_eq!(services[0].protocol, "h3");
+        assert_eq!(services[0].host(), None);
+        assert_eq!(services[0].port(), Some(443));
+        assert_eq!(services[0].max_age, Duration::from_secs(2592000));
+        assert!(services[0].persist);
+
+        assert_eq!(services[1].protocol, "h2");
+        assert_eq!(services[1].host(), Some("alt.example.com"));
+        assert_eq!(services[1].port(), Some(8443));
+        assert_eq!(services[1].max_age, Duration::from_secs(86400));
+        assert!(!services[1].persist);
+
+        assert_eq!(services[1].to_string(), "h2=\"alt.example.com:8443\"; ma=86400");
+
+        assert!(AltSvc::from_str("h2=alt.example.com").is_err());
+        assert!(AltSvc::from_str("h2=\":443\"; ma=soon").is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+///Authentication challenge of `WWW-Authenticate` or `Proxy-Authenticate`, defined in [RFC7235](https://tools.ietf.org/html/rfc7235).
+///
+///Single header may contain multiple challenges, use [parse_all](#method.parse_all) to retrieve them.
+pub struct AuthChallenge {
+    ///Authentication scheme (e.g. `Basic`).
+    pub scheme: String,
+    ///Single token, that is used by some schemes instead of parameters.
+    pub token68: Option<String>,
+    ///Parameters, with quoted values being unescaped.
+    pub params: Vec<(String, String)>,
+}
+
+fn is_tchar(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(ch)
+}
+
+fn is_token68_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "-._~+/".contains(ch)
+}
+
+///Splits leading token off `text`.
+fn token(text: &str) -> Option<(&str, &str)> {
+    let end = text.find(|ch| !is_tchar(ch)).unwrap_or(text.len());
+    match end {
+        0 => None,
+        end => Some((&text[..end], &text[end..])),
+    }
+}
+
+///Splits leading `token68` off `text`, if it is the only value of challenge.
+fn token68(text: &str) -> Option<(&str, &str)> {
+    let end = text.find(|ch| !is_token68_char(ch)).unwrap_or(text.len());
+    if end == 0 {
+        return None;
+    }
+    let end = end + text[end..].find(|ch| ch != '=').unwrap_or(text.len() - end);
+
+    match text[end..].trim_start() {
+        rest if rest.is_empty() || rest.starts_with(',') => Some((&text[..end], rest)),
+        _ => None,
+    }
+}
+
+///Splits leading quoted string off `text`, unescaping it.
+fn quoted(text: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1);
+
+    while let Some((idx, ch)) = chars.next() {
+        match ch {
+            '"' => return Some((value, &text[idx + 1..])),
+            '\\' => value.push(chars.next()?.1),
+            ch => value.push(ch),
+        }
+    }
+
+    None
+}
+
+///Splits leading `name=value` parameter off `text`.
+fn param(text: &str) -> Option<(String, String, &str)> {
+    let (name, rest) = token(text)?;
+    let rest = rest.trim_start();
+    if !rest.starts_with('=') {
+        return None;
+    }
+    let rest = rest[1..].trim_start();
+
+    let (value, rest) = match rest.starts_with('"') {
+        true => quoted(rest)?,
+        false => token(rest).map(|(value, rest)| (value.to_owned(), rest))?,
+    };
+
+    Some((name.to_owned(), value, rest))
+}
+
+impl AuthChallenge {
+    ///Parses all challenges within header's value.
+    ///
+    ///Parsing stops at first malformed challenge, returning challenges before it.
+    pub fn parse_all(text: &str) -> Vec<Self> {
+        let mut result = Vec::new();
+        let mut rest = text;
+
+        loop {
+            rest = rest.trim_start_matches(|ch: char| ch == ',' || ch.is_ascii_whitespace());
+            let (scheme, after) = match token(rest) {
+                Some(scheme) => scheme,
+                None => break,
+            };
+            rest = after;
+
+            let mut challenge = Self {
+                scheme: scheme.to_owned(),
+                token68: None,
+                params: Vec::new(),
+            };
+
+            if rest.starts_with(' ') {
+                let value = rest.trim_start();
+                if let Some((token, after)) = token68(value) {
+                    challenge.token68 = Some(token.to_owned());
+                    rest = after;
+                } else {
+                    //Parameters are separated by commas, same as challenges, so parameter ends challenge only if it is not followed by `=`
+                    let mut next = value;
+                    while let Some((name, value, after)) = param(next) {
+                        challenge.params.push((name, value));
+                        rest = after;
+                        next = after.trim_start_matches(|ch: char| ch == ',' || ch.is_ascii_whitespace());
+                    }
+
+                    if challenge.params.is_empty() && !value.is_empty() && !value.starts_with(',') {
+                        break;
+                    }
+                }
+            }
+
+            result.push(challenge);
+        }
+
+        result
+    }
+
+    ///Parses all challenges within headers' values.
+    pub fn from_headers<'a, I: IntoIterator<Item=&'a http::HeaderValue>>(values: I) -> Vec<Self> {
+        values.into_iter()
+              .filter_map(|value| value.to_str().ok())
+              .flat_map(Self::parse_all)
+              .collect()
+    }
+
+    #[inline]
+    ///Returns whether challenge is of `scheme`, ignoring case.
+    pub fn is_scheme(&self, scheme: &str) -> bool {
+        self.scheme.eq_ignore_ascii_case(scheme)
+    }
+
+    #[inline]
+    ///Returns value of parameter `name`, ignoring case of name.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    #[inline]
+    ///Returns protection space, if specified.
+    pub fn realm(&self) -> Option<&str> {
+        self.param("realm")
+    }
+}
+
+impl fmt::Display for AuthChallenge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.scheme)?;
+
+        if let Some(token68) = self.token68.as_ref() {
+            return write!(f, " {}", token68);
+        }
+
+        for (idx, (name, value)) in self.params.iter().enumerate() {
+            let sep = match idx {
+                0 => " ",
+                _ => ", ",
+            };
+            write!(f, "{}{}=\"", sep, name)?;
+            for ch in value.chars() {
+                if ch == '"' || ch == '\\' {
+                    f.write_str("\\")?;
+                }
+                write!(f, "{}", ch)?;
+            }
+            f.write_str("\"")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthChallenge;
+
+    #[test]
+    fn should_parse_challenges() {
+        let challenges = AuthChallenge::parse_all("Newauth realm=\"apps\", type=1, title=\"Login to \\\"apps\\\"\", Basic realm=\"simple\", Negotiate abc+/==, Bearer");
+        assert_eq!(challenges.len(), 4);
+
+        assert!(challenges[0].is_scheme("newauth"));
+        assert_eq!(challenges[0].realm(), Some("apps"));
+        assert_eq!(challenges[0].param("TYPE"), Some("1"));
+        assert_eq!(challenges[0].param("title"), Some("Login to \"apps\""));
+
+        assert!(challenges[1].is_scheme("Basic"));
+        assert_eq!(challenges[1].realm(), Some("simple"));
+
+        assert!(challenges[2].is_scheme("Negotiate"));
+        assert_eq!(challenges[2].token68.as_deref(), Some("abc+/=="));
+
+        assert!(challenges[3].is_scheme("Bearer"));
+        assert!(challenges[3].params.is_empty());
+
+        assert_eq!(challenges[0].to_string(), "Newauth realm=\"apps\", type=\"1\", title=\"Login to \\\"apps\\\"\"");
+        assert!(AuthChallenge::parse_all("").is_empty());
+        assert_eq!(AuthChallenge::parse_all("Bearer, Basic bad value, Digest").len(), 1);
+    }
+}
@@ -58,10 +58,7 @@ impl Filename {
     #[inline]
     ///Returns whether filename is of extended type.
     pub fn is_extended(&self) -> bool {
-        match self {
-            Filename::Extended(_, _) => true,
-            _ => false
-        }
+        matches!(self, Filename::Extended(_, _))
     }
 
     ///Returns file name, percent decoded if necessary.
@@ -71,7 +68,33 @@ impl Filename {
         match self {
             Filename::Name(None) => None,
             Filename::Name(Some(ref name)) => Some(name.as_str().into()),
-            Filename::Extended(_, name) => Some(percent_decode_str(&name).decode_utf8_lossy()),
+            Filename::Extended(_, name) => Some(percent_decode_str(name).decode_utf8_lossy()),
+        }
+    }
+
+    ///Returns file name, that is safe to use as name of file on current platform.
+    ///
+    ///Name, sent by server, can contain path separators, `..` or control characters.
+    ///Directory components and control characters are stripped. On Windows, characters `<>:"|?*`
+    ///and trailing dots are removed as well, and reserved device names (e.g. `CON`) are prefixed with `_`.
+    ///
+    ///Returns `None` if there is no name, or nothing is left after sanitization.
+    pub fn sanitized(&self) -> Option<String> {
+        self.name().and_then(|name| sanitize(&name))
+    }
+
+    ///Returns sanitized file name as `OsString`.
+    ///
+    ///Same as [sanitized](#method.sanitized), but on Unix extended name, that is not valid UTF-8,
+    ///is preserved as it is, instead of replacing invalid sequences.
+    pub fn file_name_os(&self) -> Option<std::ffi::OsString> {
+        match self {
+            #[cfg(unix)]
+            Filename::Extended(_, name) => match percent_decode_str(name).decode_utf8() {
+                Ok(name) => sanitize(&name).map(Into::into),
+                Err(_) => sanitize_bytes(&percent_decode_str(name).collect::<Vec<_>>()),
+            },
+            _ => self.sanitized().map(Into::into),
         }
     }
 
@@ -87,6 +110,60 @@ impl Filename {
     }
 }
 
+impl Default for Filename {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(windows)]
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+///Strips `name` of directory components and characters, that are not allowed in file name.
+///
+///Returns `None` if nothing is left, or name refers to directory itself.
+fn sanitize(name: &str) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    #[cfg(not(windows))]
+    let name = name.chars().filter(|ch| !ch.is_control()).collect::<String>();
+    #[cfg(windows)]
+    let name = name.chars().filter(|ch| !ch.is_control() && !"<>:\"|?*".contains(*ch)).collect::<String>();
+    let name = name.trim();
+    //Windows silently drops trailing dots and spaces
+    #[cfg(windows)]
+    let name = name.trim_end_matches(|ch| ch == '.' || ch == ' ');
+
+    match name {
+        "" | "." | ".." => None,
+        #[cfg(windows)]
+        name if RESERVED_NAMES.iter().any(|reserved| name.split('.').next().map(|stem| stem.trim_end().eq_ignore_ascii_case(reserved)).unwrap_or(false)) => Some(format!("_{}", name)),
+        name => Some(name.to_owned()),
+    }
+}
+
+#[cfg(unix)]
+///Same as `sanitize`, but for name, that is not valid UTF-8.
+fn sanitize_bytes(name: &[u8]) -> Option<std::ffi::OsString> {
+    use std::os::unix::ffi::OsStringExt;
+
+    let name = name.rsplit(|byte| *byte == b'/' || *byte == b'\\').next().unwrap_or(name);
+    let mut name = name.iter().cloned().filter(|byte| !byte.is_ascii_control()).collect::<Vec<_>>();
+    while let Some(b' ') = name.last() {
+        name.pop();
+    }
+    let start = name.iter().position(|byte| *byte != b' ').unwrap_or(name.len());
+
+    match &name[start..] {
+        b"" | b"." | b".." => None,
+        _ => Some(std::ffi::OsString::from_vec(name.split_off(start))),
+    }
+}
+
 #[derive(Debug)]
 /// A `Content-Disposition` header, defined in [RFC6266](https://tools.ietf.org/html/rfc6266).
 ///
@@ -110,7 +187,7 @@ pub enum ContentDisposition {
 
 fn split_into_two(text: &str, sep: char) -> (&str, &str) {
     match text.find(sep) {
-        Some(end) => (&text[..end].trim_end(), &text[end+1..].trim_start()),
+        Some(end) => (text[..end].trim_end(), text[end+1..].trim_start()),
         None => (text, ""),
     }
 }
@@ -143,10 +220,10 @@ pub enum ParseError {
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            &ParseError::InvalidDispositionType => f.write_str("Specified disposition type is not valid. Should be inline, attachment or form-data"),
-            &ParseError::UnknownAttachmentParam => f.write_str("Form-data parameter is invalid. Allowed: filename[*]"),
-            &ParseError::UnknownFormParam => f.write_str("Form-data parameter is invalid. Allowed: name, filename[*]"),
+        match *self {
+            ParseError::InvalidDispositionType => f.write_str("Specified disposition type is not valid. Should be inline, attachment or form-data"),
+            ParseError::UnknownAttachmentParam => f.write_str("Form-data parameter is invalid. Allowed: filename[*]"),
+            ParseError::UnknownFormParam => f.write_str("Form-data parameter is invalid. Allowed: name, filename[*]"),
         }
     }
 }
@@ -173,7 +250,7 @@ impl FromStr for ContentDisposition {
             for arg in arg.split(';').map(|arg| arg.trim()) {
                 let (name, value) = split_into_two(arg, '=');
 
-                if value.len() == 0 {
+                if value.is_empty() {
                     continue;
                 }
 
@@ -185,7 +262,7 @@ impl FromStr for ContentDisposition {
                 if prefix.eq_ignore_ascii_case("filename") {
                     let value = value.trim_matches('"');
 
-                    if let Some(_) = name.rfind('*') {
+                    if name.rfind('*').is_some() {
                         file_name = parse_file_ext!(value);
                         break;
                     } else {
@@ -204,7 +281,7 @@ impl FromStr for ContentDisposition {
             for arg in arg.split(';').map(|arg| arg.trim()) {
                 let (name, value) = split_into_two(arg, '=');
 
-                if value.len() == 0 {
+                if value.is_empty() {
                     continue;
                 }
 
@@ -220,7 +297,7 @@ impl FromStr for ContentDisposition {
                 if prefix.eq_ignore_ascii_case("filename") {
                     let value = value.trim_matches('"');
 
-                    if let Some(_) = name.rfind('*') {
+                    if name.rfind('*').is_some() {
                         file_name = parse_file_ext!(value);
                     } else if !file_name.is_extended() {
                         file_name = Filename::Name(Some(value.to_owned()));
@@ -280,21 +357,46 @@ mod tests {
 
     #[test]
     fn parse_file_name_extended_ascii() {
-        const INPUT: &'static str = "rori.mp4";
+        const INPUT: &str = "rori.mp4";
         let file_name = Filename::with_encoded_name(INPUT.into());
         assert!(!file_name.is_extended());
     }
 
     #[test]
     fn parse_file_name_extended_non_ascii() {
-        const INPUT: &'static str = "ロリへんたい.mp4";
+        const INPUT: &str = "ロリへんたい.mp4";
         let file_name = Filename::with_encoded_name(INPUT.into());
         assert!(file_name.is_extended());
     }
 
+    #[test]
+    fn should_sanitize_file_name() {
+        assert_eq!(Filename::with_name("../../etc/passwd".to_owned()).sanitized().as_deref(), Some("passwd"));
+        assert_eq!(Filename::with_name("..\\..\\boot.ini".to_owned()).sanitized().as_deref(), Some("boot.ini"));
+        assert_eq!(Filename::with_name(" report\u{0}\n.txt ".to_owned()).sanitized().as_deref(), Some("report.txt"));
+        assert_eq!(Filename::with_name("dir/..".to_owned()).sanitized(), None);
+        assert_eq!(Filename::with_name("/".to_owned()).sanitized(), None);
+        assert_eq!(Filename::new().sanitized(), None);
+        assert_eq!(Filename::with_extended(None, "%2E%2E%2Fsecret%0A.txt".to_owned()).sanitized().as_deref(), Some("secret.txt"));
+
+        #[cfg(windows)]
+        {
+            assert_eq!(Filename::with_name("a<b>:c|d?.txt.".to_owned()).sanitized().as_deref(), Some("abcd.txt"));
+            assert_eq!(Filename::with_name("con.txt".to_owned()).sanitized().as_deref(), Some("_con.txt"));
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+
+            let name = Filename::with_extended(None, "..%2Fbad%FF%0A.bin".to_owned()).file_name_os().expect("To have name");
+            assert_eq!(name.as_bytes(), b"bad\xFF.bin");
+        }
+    }
+
     #[test]
     fn parse_inline_disp() {
-        const INPUT: &'static str = "inline";
+        const INPUT: &str = "inline";
 
         let result = ContentDisposition::from_str(INPUT).expect("To have inline Disposition");
 
@@ -309,7 +411,7 @@ mod tests {
 
     #[test]
     fn parse_attach_disp_wo_filename() {
-        const INPUT: &'static str = "attachment; filename";
+        const INPUT: &str = "attachment; filename";
 
         let result = ContentDisposition::from_str(INPUT).expect("To have attachment Disposition");
 
@@ -330,7 +432,7 @@ mod tests {
 
     #[test]
     fn parse_attach_disp_w_filename() {
-        const INPUT: &'static str = "attachment; filename=\"lolka.jpg\";filename=\"lolka2.jpg\"";
+        const INPUT: &str = "attachment; filename=\"lolka.jpg\";filename=\"lolka2.jpg\"";
 
         let result = ContentDisposition::from_str(INPUT).expect("To have attachment Disposition");
 
@@ -351,8 +453,8 @@ mod tests {
 
     #[test]
     fn parse_attach_disp_w_filename_ext() {
-        const EXPECT_INPUT: &'static str = "attachment; filename*=utf-8'en'%C2%A3%20and%20%E2%82%AC%20rates";
-        const INPUT: &'static str = "attachment;\t filename*=UTF-8'en'%C2%A3%20and%20%E2%82%AC%20rates";
+        const EXPECT_INPUT: &str = "attachment; filename*=utf-8'en'%C2%A3%20and%20%E2%82%AC%20rates";
+        const INPUT: &str = "attachment;\t filename*=UTF-8'en'%C2%A3%20and%20%E2%82%AC%20rates";
 
         let result = ContentDisposition::from_str(INPUT).expect("To have attachment Disposition");
 
@@ -374,8 +476,8 @@ mod tests {
 
     #[test]
     fn parse_form_data() {
-        const EXPECT_INPUT: &'static str = "form-data; name=\"lolka\"; filename=\"lolka.jpg\"";
-        const INPUT: &'static str = "form-data;\t name=\"lolka\";filename=\"lolka.jpg\"";
+        const EXPECT_INPUT: &str = "form-data; name=\"lolka\"; filename=\"lolka.jpg\"";
+        const INPUT: &str = "form-data;\t name=\"lolka\";filename=\"lolka.jpg\"";
 
         let result = ContentDisposition::from_str(INPUT).expect("To have form-data Disposition");
 
@@ -397,7 +499,7 @@ mod tests {
 
     #[test]
     fn parse_form_data_wo_params() {
-        const INPUT: &'static str = "form-data";
+        const INPUT: &str = "form-data";
 
         let result = ContentDisposition::from_str(INPUT).expect("To have form-data Disposition");
 
@@ -419,7 +521,7 @@ mod tests {
 
     #[test]
     fn parse_form_data_wo_name() {
-        const INPUT: &'static str = "form-data; filename=\"lolka.jpg\"";
+        const INPUT: &str = "form-data; filename=\"lolka.jpg\"";
 
         let result = ContentDisposition::from_str(INPUT).expect("To have form-data Disposition");
 
@@ -441,7 +543,7 @@ mod tests {
 
     #[test]
     fn parse_form_data_wo_filename() {
-        const INPUT: &'static str = "form-data; name=\"lolka\"";
+        const INPUT: &str = "form-data; name=\"lolka\"";
 
         let result = ContentDisposition::from_str(INPUT).expect("To have form-data Disposition");
 
@@ -1,4 +1,9 @@
-#[derive(Debug)]
+use core::fmt;
+use std::io::Write;
+
+use crate::utils;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 ///`Content-Encoding` header
 pub enum ContentEncoding {
     ///Indicates that no compression is taken place.
@@ -11,13 +16,15 @@ pub enum ContentEncoding {
     Brotli
 }
 
+#[cfg(feature = "compu")]
+const DECOMPRESSABLE: &[ContentEncoding] = &[ContentEncoding::Brotli, ContentEncoding::Gzip, ContentEncoding::Deflate];
+#[cfg(not(feature = "compu"))]
+const DECOMPRESSABLE: &[ContentEncoding] = &[];
+
 impl ContentEncoding {
     ///Returns whether encoding indicates compression or not
     pub fn is_compression(&self) -> bool {
-        match *self {
-            ContentEncoding::Identity => false,
-            _ => true,
-        }
+        !matches!(*self, ContentEncoding::Identity)
     }
 
     ///Returns whether Yukikaze-sama can decompress.
@@ -38,6 +45,14 @@ impl ContentEncoding {
         }
     }
 
+    #[inline]
+    ///Returns all compressions that Yukikaze-sama can decompress, in order of preference.
+    ///
+    ///Based on enabled features.
+    pub fn decompressable() -> &'static [ContentEncoding] {
+        DECOMPRESSABLE
+    }
+
     ///Returns textual representation.
     pub fn as_str(&self) -> &'static str {
         match *self {
@@ -59,3 +74,121 @@ impl<'a> From<&'a str> for ContentEncoding {
         }
     }
 }
+
+#[derive(Debug, Clone, Default)]
+///`Accept-Encoding` header
+///
+///Quality is specified in thousandths, i.e. `500` means `q=0.5`.
+pub struct AcceptEncoding {
+    encodings: Vec<(ContentEncoding, u16)>,
+}
+
+impl AcceptEncoding {
+    ///Maximum quality value, corresponds to `q=1`
+    pub const MAX_QUALITY: u16 = 1000;
+
+    #[inline]
+    ///Creates empty list of encodings.
+    pub fn new() -> Self {
+        Self {
+            encodings: Vec::new(),
+        }
+    }
+
+    ///Creates list of all encodings that Yukikaze-sama can decompress.
+    pub fn decompressable() -> Self {
+        let mut result = Self::new();
+        for encoding in ContentEncoding::decompressable() {
+            result = result.add(*encoding, Self::MAX_QUALITY);
+        }
+        result
+    }
+
+    ///Adds encoding with specified quality.
+    ///
+    ///If encoding is already present, then its quality is replaced.
+    ///Quality above `MAX_QUALITY` is treated as `MAX_QUALITY`.
+    pub fn add(mut self, encoding: ContentEncoding, quality: u16) -> Self {
+        let quality = core::cmp::min(quality, Self::MAX_QUALITY);
+
+        match self.encodings.iter_mut().find(|(existing, _)| *existing == encoding) {
+            Some(existing) => existing.1 = quality,
+            None => self.encodings.push((encoding, quality)),
+        }
+
+        self
+    }
+
+    #[inline]
+    ///Forbids server to send uncompressed content, by adding `identity;q=0`
+    pub fn no_identity(self) -> Self {
+        self.add(ContentEncoding::Identity, 0)
+    }
+
+    #[inline]
+    ///Returns whether list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.encodings.is_empty()
+    }
+
+    #[inline]
+    ///Returns iterator over encodings and their quality.
+    pub fn iter(&self) -> impl Iterator<Item=&(ContentEncoding, u16)> {
+        self.encodings.iter()
+    }
+
+    ///Creates header value.
+    pub fn to_header_value(&self) -> http::header::HeaderValue {
+        let mut buffer = utils::BytesWriter::with_smol_capacity();
+        let _ = write!(&mut buffer, "{}", self);
+        unsafe { http::header::HeaderValue::from_maybe_shared_unchecked(buffer.freeze()) }
+    }
+}
+
+impl fmt::Display for AcceptEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, (encoding, quality)) in self.encodings.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+
+            f.write_str(encoding.as_str())?;
+
+            match *quality {
+                AcceptEncoding::MAX_QUALITY => (),
+                0 => f.write_str(";q=0")?,
+                quality => {
+                    let fraction = format!("{:03}", quality);
+                    write!(f, ";q=0.{}", fraction.trim_end_matches('0'))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AcceptEncoding, ContentEncoding};
+
+    #[test]
+    fn format_accept_encoding_with_quality() {
+        let accept = AcceptEncoding::new().add(ContentEncoding::Gzip, 1000)
+                                          .add(ContentEncoding::Brotli, 800)
+                                          .add(ContentEncoding::Deflate, 25)
+                                          .no_identity();
+
+        assert_eq!(accept.to_string(), "gzip, br;q=0.8, deflate;q=0.025, identity;q=0");
+    }
+
+    #[test]
+    fn accept_encoding_replace_quality() {
+        let accept = AcceptEncoding::new().add(ContentEncoding::Gzip, 1000)
+                                          .add(ContentEncoding::Gzip, 5000)
+                                          .add(ContentEncoding::Brotli, 0)
+                                          .add(ContentEncoding::Brotli, 500);
+
+        assert_eq!(accept.to_string(), "gzip, br;q=0.5");
+    }
+}
@@ -0,0 +1,20 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Encoding of cookie's name and value within `Cookie` and `Set-Cookie` headers.
+///
+///Default is `Percent`.
+pub enum CookieEncoding {
+    ///Percent-encoded on write and percent-decoded on read.
+    Percent,
+    ///Written and read as it is.
+    ///
+    ///Cookies with characters, that cannot be sent as it is (e.g. `;` or control characters),
+    ///are still percent-encoded on write.
+    Raw,
+}
+
+impl Default for CookieEncoding {
+    #[inline]
+    fn default() -> Self {
+        CookieEncoding::Percent
+    }
+}
@@ -0,0 +1,83 @@
+///Iterator over elements of comma-separated header's value.
+///
+///Created by [split_list](fn.split_list.html).
+pub struct ListIter<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for ListIter<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+
+            let mut is_quoted = false;
+            let mut is_escaped = false;
+            let mut comment_depth = 0usize;
+            let mut end = self.rest.len();
+
+            for (idx, ch) in self.rest.char_indices() {
+                if is_escaped {
+                    is_escaped = false;
+                    continue;
+                }
+
+                match ch {
+                    '\\' if is_quoted || comment_depth > 0 => is_escaped = true,
+                    '"' if comment_depth == 0 => is_quoted = !is_quoted,
+                    '(' if !is_quoted => comment_depth += 1,
+                    ')' if !is_quoted => comment_depth = comment_depth.saturating_sub(1),
+                    ',' if !is_quoted && comment_depth == 0 => {
+                        end = idx;
+                        break;
+                    },
+                    _ => (),
+                }
+            }
+
+            let element = self.rest[..end].trim();
+            self.rest = match end < self.rest.len() {
+                true => &self.rest[end + 1..],
+                false => "",
+            };
+
+            //Empty elements are allowed by RFC7230 and must be ignored
+            if !element.is_empty() {
+                return Some(element);
+            }
+        }
+    }
+}
+
+#[inline]
+///Splits header's value into elements of list, as defined in [RFC7230](https://tools.ietf.org/html/rfc7230#section-7).
+///
+///Commas within quoted strings and comments (e.g. in `Via`) do not separate elements.
+///Elements are trimmed and empty ones are skipped.
+pub fn split_list(value: &str) -> ListIter<'_> {
+    ListIter {
+        rest: value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_list;
+
+    #[test]
+    fn should_split_list() {
+        let elements = split_list("Accept-Encoding, , Origin ,User-Agent").collect::<Vec<_>>();
+        assert_eq!(elements, ["Accept-Encoding", "Origin", "User-Agent"]);
+
+        let elements = split_list("W/\"a,b\", \"c\\\",d\"").collect::<Vec<_>>();
+        assert_eq!(elements, ["W/\"a,b\"", "\"c\\\",d\""]);
+
+        let elements = split_list("1.0 fred, 1.1 p.example.net (Apache/1.1, (nested, comment))").collect::<Vec<_>>();
+        assert_eq!(elements, ["1.0 fred", "1.1 p.example.net (Apache/1.1, (nested, comment))"]);
+
+        assert_eq!(split_list(" , ,").count(), 0);
+    }
+}
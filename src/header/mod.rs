@@ -4,6 +4,16 @@ pub use http::header::*;
 
 mod content_encoding;
 mod content_disposition;
+mod alt_svc;
+mod trace_context;
+mod cookie_encoding;
+mod auth_challenge;
+mod list;
 
-pub use self::content_encoding::ContentEncoding;
+pub use self::content_encoding::{ContentEncoding, AcceptEncoding};
 pub use self::content_disposition::{Filename, ContentDisposition};
+pub use self::alt_svc::{AltSvc, AltService, ParseError as AltSvcParseError};
+pub use self::cookie_encoding::CookieEncoding;
+pub use self::auth_challenge::AuthChallenge;
+pub use self::list::{split_list, ListIter};
+pub use self::trace_context::{TraceContext, ParseError as TraceContextParseError, TRACEPARENT, TRACESTATE};
@@ -0,0 +1,208 @@
+use core::fmt;
+use core::str::FromStr;
+use std::error::Error;
+
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+
+///Name of `traceparent` header.
+pub const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+///Name of `tracestate` header.
+pub const TRACESTATE: HeaderName = HeaderName::from_static("tracestate");
+
+const FLAG_SAMPLED: u8 = 0x01;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+///Trace context, defined in [W3C Trace Context](https://www.w3.org/TR/trace-context/).
+///
+///Sent via `traceparent` and `tracestate` headers to propagate distributed trace.
+pub struct TraceContext {
+    ///Identifier of the whole trace.
+    pub trace_id: [u8; 16],
+    ///Identifier of the caller's span.
+    pub parent_id: [u8; 8],
+    ///Trace flags.
+    pub flags: u8,
+    ///Vendor specific `tracestate`, if any.
+    pub state: Option<String>,
+}
+
+impl TraceContext {
+    ///Starts new sampled trace with random identifiers.
+    pub fn new() -> Self {
+        let mut trace_id = [0u8; 16];
+        crate::utils::random_bytes(&mut trace_id);
+
+        let mut result = Self {
+            trace_id,
+            parent_id: [0u8; 8],
+            flags: FLAG_SAMPLED,
+            state: None,
+        };
+        crate::utils::random_bytes(&mut result.parent_id);
+        result
+    }
+
+    ///Creates context of child span within the same trace.
+    pub fn child(&self) -> Self {
+        let mut result = self.clone();
+        crate::utils::random_bytes(&mut result.parent_id);
+        result
+    }
+
+    #[inline]
+    ///Returns whether caller records trace.
+    pub fn is_sampled(&self) -> bool {
+        self.flags & FLAG_SAMPLED == FLAG_SAMPLED
+    }
+
+    ///Retrieves context from headers.
+    ///
+    ///Returns `None` if `traceparent` is missing or invalid.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let mut result = headers.get(TRACEPARENT).and_then(|value| value.to_str().ok()).and_then(|value| value.parse::<Self>().ok())?;
+
+        let mut state = String::new();
+        for value in headers.get_all(TRACESTATE).iter().filter_map(|value| value.to_str().ok()) {
+            if !state.is_empty() {
+                state.push(',');
+            }
+            state.push_str(value);
+        }
+        if !state.is_empty() {
+            result.state = Some(state);
+        }
+
+        Some(result)
+    }
+
+    ///Sets `traceparent` and `tracestate` headers, replacing previous values.
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        match HeaderValue::from_maybe_shared(bytes::Bytes::from(self.to_string())) {
+            Ok(value) => headers.insert(TRACEPARENT, value),
+            Err(_) => unreach!(),
+        };
+
+        headers.remove(TRACESTATE);
+        if let Some(state) = self.state.as_ref() {
+            if let Ok(value) = HeaderValue::from_str(state) {
+                headers.insert(TRACESTATE, value);
+            }
+        }
+    }
+}
+
+impl Default for TraceContext {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+///Error parsing `traceparent` header.
+pub enum ParseError {
+    ///Version is not supported.
+    UnsupportedVersion,
+    ///Header is malformed.
+    Malformed,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnsupportedVersion => f.write_str("Unsupported version of traceparent"),
+            ParseError::Malformed => f.write_str("Malformed traceparent. Expected <version>-<trace-id>-<parent-id>-<flags>"),
+        }
+    }
+}
+
+impl Error for ParseError {
+}
+
+fn decode_hex(text: &str, out: &mut [u8]) -> Result<(), ParseError> {
+    //Only lower case is valid as per specification
+    if text.len() != out.len() * 2 || text.bytes().any(|byte| byte.is_ascii_uppercase()) {
+        return Err(ParseError::Malformed);
+    }
+
+    match data_encoding::HEXLOWER.decode_mut(text.as_bytes(), out) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ParseError::Malformed),
+    }
+}
+
+impl FromStr for TraceContext {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.trim().split('-');
+
+        let mut version = [0u8; 1];
+        decode_hex(parts.next().unwrap_or(""), &mut version)?;
+        if version[0] == 0xff {
+            return Err(ParseError::UnsupportedVersion);
+        }
+
+        let mut trace_id = [0u8; 16];
+        decode_hex(parts.next().unwrap_or(""), &mut trace_id)?;
+        let mut parent_id = [0u8; 8];
+        decode_hex(parts.next().unwrap_or(""), &mut parent_id)?;
+        let mut flags = [0u8; 1];
+        decode_hex(parts.next().unwrap_or(""), &mut flags)?;
+
+        //Version 00 has exactly 4 parts, while future versions may append more.
+        if (version[0] == 0 && parts.next().is_some()) || trace_id.iter().all(|byte| *byte == 0) || parent_id.iter().all(|byte| *byte == 0) {
+            return Err(ParseError::Malformed);
+        }
+
+        Ok(Self {
+            trace_id,
+            parent_id,
+            flags: flags[0],
+            state: None,
+        })
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "00-{}-{}-{:02x}", data_encoding::HEXLOWER.encode(&self.trace_id), data_encoding::HEXLOWER.encode(&self.parent_id), self.flags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TraceContext, FromStr, TRACEPARENT, TRACESTATE};
+
+    #[test]
+    fn parse_traceparent() {
+        const INPUT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        let ctx = TraceContext::from_str(INPUT).expect("To parse");
+        assert!(ctx.is_sampled());
+        assert_eq!(ctx.parent_id, [0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7]);
+        assert_eq!(ctx.to_string(), INPUT);
+
+        let child = ctx.child();
+        assert_eq!(child.trace_id, ctx.trace_id);
+        assert_ne!(child.parent_id, ctx.parent_id);
+
+        assert!(TraceContext::from_str("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01").is_err());
+        assert!(TraceContext::from_str("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_err());
+        assert!(TraceContext::from_str("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_err());
+        assert!(TraceContext::from_str("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7").is_err());
+    }
+
+    #[test]
+    fn apply_trace_context() {
+        let mut ctx = TraceContext::new();
+        ctx.state = Some("vendor=value".to_owned());
+
+        let mut headers = http::HeaderMap::new();
+        ctx.apply(&mut headers);
+        assert_eq!(headers.get(TRACESTATE).unwrap(), "vendor=value");
+        assert_eq!(headers.get(TRACEPARENT).unwrap().len(), 55);
+
+        assert_eq!(TraceContext::from_headers(&headers), Some(ctx));
+    }
+}
@@ -6,6 +6,7 @@
 //!
 //!- [Client](client/index.html)
 //!- [Runtime](rt/index.html)
+//!- [Blocking client](blocking/index.html)
 //!
 //!## Features
 //!
@@ -22,6 +23,17 @@
 //!- `rustls` - Enables use of `rustls` for default SSL implementation. By default `on`.
 //!- `compu` - Enables compression support. By default `on`.
 //!- `encoding` - Enables `encoding` crate support. Default `off`.
+//!- `typed_headers` - Enables support of typed headers from `headers` crate. Default `off`.
+//!- `blocking` - Enables blocking client. Default `off`.
+//!- `checksum` - Enables verification of body's checksum. Default `off`.
+//!- `json_path` - Enables path to failing field in JSON errors. Default `off`.
+//!- `log` - Enables logging of requests via `log` crate. Default `off`.
+//!- `metrics` - Enables collection of request metrics into Prometheus registry. Default `off`.
+//!- `idna` - Enables conversion of international domain names into punycode. Default `off`.
+//!- `nested_form` - Enables forms with nested structures and arrays (e.g. `a[b]=1&tags[]=x`). Default `off`.
+//!- `html` - Enables extraction of title, description and Open Graph tags from HTML. Default `off`.
+//!- `test_server` - Enables local HTTP server for offline tests. Default `off`.
+//!- `webdav` - Enables WebDAV requests and multistatus responses. Default `off`.
 //!- `websocket` - Enables Websocket Upgrade mechanism. Default `off`. Enables `carry_extensions` when `on`.
 //!- `carry_extensions` - Carries `http::Extensions` from request to resolved `Response`. Default `off`.
 //!
@@ -52,17 +64,21 @@
 
 #![warn(missing_docs)]
 #![doc(html_logo_url = "https://gitlab.com/Douman/yukikaze/raw/master/Yukikaze.png", html_favicon_url = "https://gitlab.com/Douman/yukikaze/raw/master/Yukikaze.png")]
-#![cfg_attr(feature = "cargo-clippy", allow(clippy::style))]
 
 #[macro_use]
 pub mod utils;
 pub mod header;
 pub mod extractor;
+pub mod serde_ext;
 pub mod upgrade;
 pub mod connector;
 pub mod client;
 #[macro_use]
 pub mod rt;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "test_server")]
+pub mod test;
 
 pub extern crate mime;
 pub extern crate bytes;
@@ -74,6 +90,8 @@ pub extern crate async_timer;
 pub extern crate encoding_rs;
 #[cfg(feature = "compu")]
 pub extern crate compu;
+#[cfg(feature = "typed_headers")]
+pub extern crate headers;
 pub extern crate hyper;
 pub extern crate etag;
 pub extern crate cookie;
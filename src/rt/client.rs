@@ -5,6 +5,9 @@
 //!The macro defines global client in current scope, alongside companion `Request` wrapper and `GlobalRequest` trait.
 //!Refer to macro documentation for details.
 //!
+//!Note that [Client](../../client/struct.Client.html) is cheap to clone, so when it is possible to pass it around
+//!(e.g. within application's state), its clones can be used instead of global client.
+//!
 //!## Usage
 //!
 //!```rust,no_run
@@ -30,6 +33,15 @@
 //!
 //!use generated::{GlobalRequest};
 //!
+//!fn init() {
+//!    let mut headers = yukikaze::http::HeaderMap::new();
+//!    headers.insert(yukikaze::header::AUTHORIZATION, yukikaze::header::HeaderValue::from_static("Bearer token"));
+//!
+//!    let client = yukikaze::client::Client::new().with_default_headers(headers);
+//!    //Must be done before first use of global client
+//!    generated::GLOBAL_CLIENT.set(client).expect("Global client is not initialized yet");
+//!}
+//!
 //!async fn google() {
 //!    let res = Request::get("https://google.com").expect("To create get request")
 //!                                                .empty()
@@ -40,6 +52,22 @@
 //!    assert!(result.is_success());
 //!}
 //!```
+//!
+//!## Shutdown
+//!
+//!Global client is never dropped, so on restart of service it should be shut down explicitly via
+//![Client::shutdown](../../client/struct.Client.html#method.shutdown), which waits for in-flight requests:
+//!
+//!```rust,no_run
+//!yukikaze::declare_global_client!();
+//!
+//!async fn stop() {
+//!    let deadline = std::time::Instant::now() + core::time::Duration::from_secs(5);
+//!    if !yukikaze::matsu!(GLOBAL_CLIENT.shutdown(deadline)) {
+//!        eprintln!("Some requests are not completed in time");
+//!    }
+//!}
+//!```
 
 #[macro_export]
 ///Declares global client for use.
@@ -49,7 +77,8 @@
 ///
 ///Creates following:
 ///
-///- `GLOBAL_CLIENT` which is wrapper struct that initializes client on first `deref`
+///- `GLOBAL_CLIENT` which is wrapper struct that initializes client on first `deref`.
+///  Alternatively you can initialize it with your own instance via `GLOBAL_CLIENT.set(client)`
 ///- `Request` which uses `GLOBAL_CLIENT` and wraps `yukikaze::client::Request`
 ///- Creates and defines trait `GlobalRequest` for generated `Request`.
 ///
@@ -63,8 +92,9 @@ macro_rules! declare_global_client {
     };
     ($config:ty) => {
         ///Wrapper over client, allowing to store it as a static variable, that performs
-        ///initialization on first `deref`
+        ///initialization on first `deref`, unless it is initialized via `set`
         pub struct InitWrapper {
+            init: std::sync::Once,
             inner: core::cell::UnsafeCell<core::mem::MaybeUninit::<$crate::client::Client::<$config>>>,
         }
 
@@ -73,9 +103,40 @@ macro_rules! declare_global_client {
             #[doc(hidden)]
             pub const fn new() -> Self {
                 Self {
+                    init: std::sync::Once::new(),
                     inner: core::cell::UnsafeCell::new(core::mem::MaybeUninit::uninit()),
                 }
             }
+
+            ///Initializes global client with provided instance.
+            ///
+            ///Must be called before first use of global client.
+            ///If client is already initialized, returns provided instance back as error.
+            #[allow(clippy::result_large_err)]
+            pub fn set(&self, client: $crate::client::Client::<$config>) -> Result<(), $crate::client::Client::<$config>> {
+                let mut client = Some(client);
+                let write_ptr = unsafe {
+                    (*self.inner.get()).as_mut_ptr()
+                };
+
+                self.init.call_once(|| unsafe {
+                    match client.take() {
+                        Some(client) => core::ptr::write(write_ptr, client),
+                        None => $crate::unreach!(),
+                    }
+                });
+
+                match client {
+                    Some(client) => Err(client),
+                    None => Ok(()),
+                }
+            }
+
+            #[inline]
+            ///Returns whether global client is already initialized.
+            pub fn is_init(&self) -> bool {
+                self.init.is_completed()
+            }
         }
 
         unsafe impl core::marker::Sync for InitWrapper {}
@@ -83,12 +144,11 @@ macro_rules! declare_global_client {
         impl core::ops::Deref for InitWrapper {
             type Target = $crate::client::Client::<$config>;
             fn deref(&self) -> &Self::Target {
-                static INIT: std::sync::Once = std::sync::Once::new();
                 let write_ptr = unsafe {
                     (*self.inner.get()).as_mut_ptr()
                 };
 
-                INIT.call_once(|| unsafe {
+                self.init.call_once(|| unsafe {
                     core::ptr::write(write_ptr, $crate::client::Client::<$config>::new())
                 });
 
@@ -152,7 +212,7 @@ macro_rules! declare_global_client {
             ///On timeout error it returns `async_timer::Expired` as `Error`
             ///`Expired` implements `Future` that can be used to re-spawn ongoing request again.
             ///
-            ///If request resolves in time returns `Result<response::Response, hyper::Error>` as `Ok`
+            ///If request resolves in time returns `Result<response::Response, Error>` as `Ok`
             ///variant.
             pub fn send(self) -> impl core::future::Future<Output=Result<RequestResult, $crate::async_timer::Expired<impl core::future::Future<Output=RequestResult>, impl $crate::async_timer::Oneshot>>> {
                 GLOBAL_CLIENT.send(self.0)
@@ -164,12 +224,18 @@ macro_rules! declare_global_client {
             ///On timeout error it returns `async_timer::Expired` as `Error`
             ///`Expired` implements `Future` that can be used to re-spawn ongoing request again.
             ///
-            ///If request resolves in time returns `Result<response::Response, hyper::Error>` as `Ok`
+            ///If request resolves in time returns `Result<response::Response, Error>` as `Ok`
             ///variant.
             pub fn send_redirect(self) -> impl core::future::Future<Output=Result<RequestResult, $crate::async_timer::Expired<impl core::future::Future<Output=RequestResult> + 'static, impl $crate::async_timer::Oneshot>>> {
                 GLOBAL_CLIENT.send_redirect(self.0)
             }
 
+            #[inline(always)]
+            ///Sends request and returns response, re-spawning it on expiration until `budget` is spent.
+            pub fn send_with_budget(self, budget: core::time::Duration) -> impl core::future::Future<Output=Result<$crate::client::Response, $crate::client::errors::BudgetError>> {
+                GLOBAL_CLIENT.send_with_budget(self.0, budget)
+            }
+
             #[inline(always)]
             ///Sends request and returns response, while handling redirects.
             pub fn redirect_request(self) -> impl core::future::Future<Output=RequestResult> {
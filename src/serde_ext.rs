@@ -0,0 +1,372 @@
+//!Serde helpers for values, that have no natural representation in query or form.
+//!
+//!Wrappers serialize into plain strings or integers, so they can be used with
+//![Builder::query](../client/request/struct.Builder.html#method.query) and
+//![Builder::form](../client/request/struct.Builder.html#method.form), as well as deserialized back.
+//!
+//!- [HttpDate](struct.HttpDate.html) - Timestamp as IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`;
+//!- [Rfc3339](struct.Rfc3339.html) - Timestamp as RFC 3339 in UTC, e.g. `1994-11-06T08:49:37Z`;
+//!- [UnixTime](struct.UnixTime.html) - Timestamp as number of seconds since Unix epoch;
+//!- [CommaSeparated](struct.CommaSeparated.html) - Sequence as comma-joined values, e.g. `a,b,c`;
+//!- [Base64](struct.Base64.html) - Bytes as standard base64 with padding.
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::serde_ext::{CommaSeparated, Rfc3339, Base64};
+//!use yukikaze::client::Request;
+//!
+//!use std::time::{Duration, UNIX_EPOCH};
+//!
+//!#[derive(serde::Serialize)]
+//!struct Search {
+//!    tags: CommaSeparated<&'static str>,
+//!    since: Rfc3339,
+//!    cursor: Base64,
+//!}
+//!
+//!let search = Search {
+//!    tags: CommaSeparated(vec!["rust", "http"]),
+//!    since: Rfc3339(UNIX_EPOCH + Duration::from_secs(784111777)),
+//!    cursor: Base64(b"page:2".to_vec()),
+//!};
+//!
+//!let request = Request::get("https://example.com/search").expect("To create request").query(&search).empty();
+//!assert_eq!(request.uri().query(), Some("tags=rust%2Chttp&since=1994-11-06T08%3A49%3A37Z&cursor=cGFnZToy"));
+//!```
+
+use core::fmt;
+use core::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::Error as _;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///Timestamp, that is serialized as HTTP date (IMF-fixdate).
+///
+///Sub-second precision is discarded.
+pub struct HttpDate(pub SystemTime);
+
+impl From<SystemTime> for HttpDate {
+    #[inline]
+    fn from(time: SystemTime) -> Self {
+        Self(time)
+    }
+}
+
+impl serde::Serialize for HttpDate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&httpdate::HttpDate::from(self.0))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for HttpDate {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        httpdate::parse_http_date(&text).map(HttpDate).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///Timestamp, that is serialized as RFC 3339 date-time in UTC.
+///
+///Fraction of second is written only if it is not zero.
+///When deserializing, any offset is accepted and converted to UTC.
+pub struct Rfc3339(pub SystemTime);
+
+impl From<SystemTime> for Rfc3339 {
+    #[inline]
+    fn from(time: SystemTime) -> Self {
+        Self(time)
+    }
+}
+
+impl fmt::Display for Rfc3339 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (secs, nanos) = to_unix(self.0);
+        let days = secs.div_euclid(86400);
+        let secs = secs.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+
+        write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", year, month, day, secs / 3600, secs / 60 % 60, secs % 60)?;
+        if nanos > 0 {
+            let fraction = format!("{:09}", nanos);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        f.write_str("Z")
+    }
+}
+
+impl FromStr for Rfc3339 {
+    type Err = InvalidTimestamp;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        fn number(text: &str) -> Result<i64, InvalidTimestamp> {
+            match text.bytes().all(|byte| byte.is_ascii_digit()) {
+                true => text.parse().map_err(|_| InvalidTimestamp),
+                false => Err(InvalidTimestamp),
+            }
+        }
+
+        let bytes = text.as_bytes();
+        if bytes.len() < 20 || !text.is_char_boundary(19) || bytes[4] != b'-' || bytes[7] != b'-' || bytes[13] != b':' || bytes[16] != b':' {
+            return Err(InvalidTimestamp);
+        }
+        match bytes[10] {
+            b'T' | b't' | b' ' => (),
+            _ => return Err(InvalidTimestamp),
+        }
+
+        let (year, month, day) = (number(&text[..4])?, number(&text[5..7])?, number(&text[8..10])?);
+        let (hour, minute, second) = (number(&text[11..13])?, number(&text[14..16])?, number(&text[17..19])?);
+        //Leap second is clamped to the last second of minute.
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 60 {
+            return Err(InvalidTimestamp);
+        }
+
+        let mut rest = &text[19..];
+        let mut nanos = 0;
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let len = fraction.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(fraction.len());
+            if len == 0 {
+                return Err(InvalidTimestamp);
+            }
+            for (idx, digit) in fraction[..len].bytes().take(9).enumerate() {
+                nanos += (digit - b'0') as u32 * 10u32.pow(8 - idx as u32);
+            }
+            rest = &fraction[len..];
+        }
+
+        let offset = match rest {
+            "Z" | "z" => 0,
+            offset if offset.len() == 6 && offset.is_char_boundary(1) && offset.as_bytes()[3] == b':' => {
+                let (hours, minutes) = (number(&offset[1..3])?, number(&offset[4..6])?);
+                if hours > 23 || minutes > 59 {
+                    return Err(InvalidTimestamp);
+                }
+                match offset.as_bytes()[0] {
+                    b'+' => hours * 3600 + minutes * 60,
+                    b'-' => -(hours * 3600 + minutes * 60),
+                    _ => return Err(InvalidTimestamp),
+                }
+            },
+            _ => return Err(InvalidTimestamp),
+        };
+
+        let secs = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + core::cmp::min(second, 59) - offset;
+        Ok(Self(from_unix(secs, nanos)))
+    }
+}
+
+impl serde::Serialize for Rfc3339 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Rfc3339 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        text.parse().map_err(D::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///Timestamp, that is serialized as number of seconds since Unix epoch.
+///
+///Sub-second precision is discarded.
+pub struct UnixTime(pub SystemTime);
+
+impl From<SystemTime> for UnixTime {
+    #[inline]
+    fn from(time: SystemTime) -> Self {
+        Self(time)
+    }
+}
+
+impl serde::Serialize for UnixTime {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(to_unix(self.0).0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for UnixTime {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        i64::deserialize(deserializer).map(|secs| UnixTime(from_unix(secs, 0)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+///Sequence, that is serialized as single string of comma-joined values.
+///
+///Values are written using `Display` and read using `FromStr`, hence they must not contain comma.
+///Empty string is deserialized as empty sequence.
+pub struct CommaSeparated<T>(pub Vec<T>);
+
+impl<T> From<Vec<T>> for CommaSeparated<T> {
+    #[inline]
+    fn from(values: Vec<T>) -> Self {
+        Self(values)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for CommaSeparated<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, value) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(",")?;
+            }
+            fmt::Display::fmt(value, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: fmt::Display> serde::Serialize for CommaSeparated<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, T: FromStr> serde::Deserialize<'de> for CommaSeparated<T> where T::Err: fmt::Display {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        if text.is_empty() {
+            return Ok(Self(Vec::new()));
+        }
+
+        text.split(',').map(|value| value.parse().map_err(D::Error::custom)).collect::<Result<_, _>>().map(CommaSeparated)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+///Bytes, that are serialized as standard base64 string with padding.
+pub struct Base64<T=Vec<u8>>(pub T);
+
+impl<T: AsRef<[u8]>> serde::Serialize for Base64<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&data_encoding::BASE64.encode(self.0.as_ref()))
+    }
+}
+
+impl<'de, T: From<Vec<u8>>> serde::Deserialize<'de> for Base64<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        data_encoding::BASE64.decode(text.as_bytes()).map(|bytes| Base64(bytes.into())).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Describes failure to parse timestamp.
+pub struct InvalidTimestamp;
+
+impl fmt::Display for InvalidTimestamp {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Invalid RFC 3339 timestamp")
+    }
+}
+
+impl std::error::Error for InvalidTimestamp {}
+
+///Returns seconds since Unix epoch, rounded down, and nanoseconds within second.
+fn to_unix(time: SystemTime) -> (i64, u32) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+        Err(error) => {
+            let before = error.duration();
+            match before.subsec_nanos() {
+                0 => (-(before.as_secs() as i64), 0),
+                nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+            }
+        }
+    }
+}
+
+fn from_unix(secs: i64, nanos: u32) -> SystemTime {
+    match secs >= 0 {
+        true => UNIX_EPOCH + Duration::new(secs as u64, nanos),
+        false => UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()) + Duration::from_nanos(nanos as u64),
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+//Algorithms from http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+
+    (yoe + era * 400 + if month <= 2 { 1 } else { 0 }, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Query {
+        date: HttpDate,
+        since: Rfc3339,
+        until: UnixTime,
+        ids: CommaSeparated<u32>,
+        token: Base64,
+    }
+
+    #[test]
+    fn should_roundtrip_query() {
+        let query = Query {
+            date: HttpDate(UNIX_EPOCH + Duration::from_secs(784111777)),
+            since: Rfc3339(UNIX_EPOCH + Duration::new(951782400, 250_000_000)),
+            until: UnixTime(UNIX_EPOCH + Duration::from_secs(1000)),
+            ids: CommaSeparated(vec![1, 20, 300]),
+            token: Base64(vec![0xfb, 0xff]),
+        };
+
+        let text = serde_urlencoded::to_string(&query).expect("To serialize");
+        assert_eq!(text, "date=Sun%2C+06+Nov+1994+08%3A49%3A37+GMT&since=2000-02-29T00%3A00%3A00.25Z&until=1000&ids=1%2C20%2C300&token=%2B%2F8%3D");
+        assert_eq!(serde_urlencoded::from_str::<Query>(&text).expect("To deserialize"), query);
+    }
+
+    #[test]
+    fn should_parse_rfc3339() {
+        let expected = UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!("1994-11-06T08:49:37Z".parse::<Rfc3339>().unwrap().0, expected);
+        assert_eq!("1994-11-06t10:19:37.000+01:30".parse::<Rfc3339>().unwrap().0, expected);
+        assert_eq!("1969-12-31T23:59:59.5Z".parse::<Rfc3339>().unwrap().0, UNIX_EPOCH - Duration::from_millis(500));
+        assert_eq!(Rfc3339(UNIX_EPOCH - Duration::from_millis(500)).to_string(), "1969-12-31T23:59:59.5Z");
+
+        assert!("1994-11-06T08:49:37".parse::<Rfc3339>().is_err());
+        assert!("1994-02-29T08:49:37Z".parse::<Rfc3339>().is_err());
+        assert!("1994-11-06T08:49:37.Z".parse::<Rfc3339>().is_err());
+        assert!("1994-11-06T08:49:37+1:00".parse::<Rfc3339>().is_err());
+    }
+}
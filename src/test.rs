@@ -0,0 +1,201 @@
+//!Local HTTP server for tests.
+//!
+//!Allows to run integration tests offline, by registering routes with responses
+//!and sending requests to server's local URL.
+//!
+//!Server runs on `tokio` runtime and is stopped once dropped.
+//!
+//!Available only with feature `test_server`
+//!
+//!## Usage
+//!
+//!```rust
+//!use yukikaze::{matsu, client, test};
+//!
+//!#[tokio::main(basic_scheduler)]
+//!async fn main() {
+//!    let server = test::Server::new();
+//!    server.route(http::Method::GET, "/ship", |_| test::response(http::StatusCode::OK, "Yukikaze"));
+//!
+//!    let client = client::Client::default();
+//!    let mut response = matsu!(client.get(server.url("/ship"))).expect("To get response");
+//!    assert_eq!(matsu!(response.text()).expect("To read body"), "Yukikaze");
+//!    assert_eq!(server.hits(http::Method::GET, "/ship"), 1);
+//!}
+//!```
+
+use core::time;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_timer::Oneshot;
+
+///Request, received by server, with body read into memory.
+pub type Request = http::Request<bytes::Bytes>;
+///Response, returned by route's handler.
+pub type Response = http::Response<bytes::Bytes>;
+
+type Handler = Arc<dyn Fn(&Request) -> Response + Send + Sync>;
+
+#[derive(Clone)]
+struct Route {
+    handler: Handler,
+    delay: time::Duration,
+    hits: usize,
+}
+
+#[derive(Default)]
+struct Routes {
+    inner: Mutex<HashMap<(http::Method, String), Route>>,
+    connections: AtomicUsize,
+}
+
+impl Routes {
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, HashMap<(http::Method, String), Route>> {
+        self.inner.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    ///Returns route, registering its hit.
+    fn hit(&self, method: &http::Method, path: &str) -> Option<Route> {
+        let mut routes = self.lock();
+        routes.get_mut(&(method.clone(), path.to_owned())).map(|route| {
+            route.hits += 1;
+            route.clone()
+        })
+    }
+}
+
+///Creates response with `status` and `body`.
+pub fn response<B: Into<bytes::Bytes>>(status: http::StatusCode, body: B) -> Response {
+    let mut response = http::Response::new(body.into());
+    *response.status_mut() = status;
+    response
+}
+
+async fn handle(routes: Arc<Routes>, request: hyper::Request<hyper::Body>) -> Result<hyper::Response<hyper::Body>, hyper::Error> {
+    let (parts, body) = request.into_parts();
+    let body = matsu!(hyper::body::to_bytes(body))?;
+    let request = http::Request::from_parts(parts, body);
+
+    let response = match routes.hit(request.method(), request.uri().path()) {
+        Some(route) => {
+            if route.delay != time::Duration::from_secs(0) {
+                matsu!(async_timer::oneshot::Timer::new(route.delay));
+            }
+            (route.handler)(&request)
+        },
+        None => response(http::StatusCode::NOT_FOUND, "No such route"),
+    };
+
+    let (parts, body) = response.into_parts();
+    Ok(hyper::Response::from_parts(parts, hyper::Body::from(body)))
+}
+
+///Local HTTP server, that responds on registered routes.
+///
+///Requests to unknown routes are responded with `404 Not Found`.
+pub struct Server {
+    addr: SocketAddr,
+    routes: Arc<Routes>,
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl Server {
+    ///Starts server on random local port.
+    ///
+    ///Must be called within `tokio` runtime.
+    pub fn new() -> Self {
+        use hyper::service::{make_service_fn, service_fn};
+
+        let routes = Arc::new(Routes::default());
+        let service_routes = routes.clone();
+        let service = make_service_fn(move |_| {
+            let routes = service_routes.clone();
+            routes.connections.fetch_add(1, Ordering::SeqCst);
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |request| handle(routes.clone(), request)))
+            }
+        });
+
+        let (shutdown, stop) = tokio::sync::oneshot::channel::<()>();
+        let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(service);
+        let addr = server.local_addr();
+        let server = server.with_graceful_shutdown(async move {
+            let _ = matsu!(stop);
+        });
+        tokio::spawn(server);
+
+        Self {
+            addr,
+            routes,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    #[inline]
+    ///Returns server's address.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    #[inline]
+    ///Returns server's base URL, in form `http://<addr>`.
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    #[inline]
+    ///Returns URL of `path` on server.
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    ///Registers route, replacing existing one with the same method and path.
+    ///
+    ///`path` is matched exactly, without query.
+    pub fn route<F: Fn(&Request) -> Response + Send + Sync + 'static>(&self, method: http::Method, path: &str, handler: F) -> &Self {
+        self.route_delayed(method, path, time::Duration::from_secs(0), handler)
+    }
+
+    ///Registers route, which responds only after `delay`.
+    ///
+    ///Useful to test timeouts.
+    pub fn route_delayed<F: Fn(&Request) -> Response + Send + Sync + 'static>(&self, method: http::Method, path: &str, delay: time::Duration, handler: F) -> &Self {
+        let route = Route {
+            handler: Arc::new(handler),
+            delay,
+            hits: 0,
+        };
+        self.routes.lock().insert((method, path.to_owned()), route);
+        self
+    }
+
+    ///Returns number of requests, received by route.
+    pub fn hits(&self, method: http::Method, path: &str) -> usize {
+        self.routes.lock().get(&(method, path.to_owned())).map(|route| route.hits).unwrap_or(0)
+    }
+
+    #[inline]
+    ///Returns number of connections, accepted by server.
+    pub fn connections(&self) -> usize {
+        self.routes.connections.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for Server {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
@@ -1,4 +1,83 @@
 //! Upgrade extension for client side
+//!
+//! ## Custom protocol
+//!
+//! Besides [websocket](websocket/index.html), any protocol can be negotiated via `Upgrade` header
+//! by implementing [Upgrade](trait.Upgrade.html) trait.
+//!
+//!```rust
+//!use yukikaze::{matsu, client, upgrade};
+//!
+//!use core::fmt;
+//!
+//!#[derive(Debug)]
+//!enum ChatUpgradeError {
+//!    Status(http::StatusCode),
+//!    Protocol,
+//!}
+//!
+//!impl fmt::Display for ChatUpgradeError {
+//!    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+//!        match self {
+//!            ChatUpgradeError::Status(status) => write!(f, "Unexpected status {}", status),
+//!            ChatUpgradeError::Protocol => f.write_str("Server switched to unexpected protocol"),
+//!        }
+//!    }
+//!}
+//!
+//!///Upgrades connection to `chat/<version>` protocol.
+//!struct ChatUpgrade {
+//!    version: u8,
+//!}
+//!
+//!impl ChatUpgrade {
+//!    fn protocol(&self) -> String {
+//!        format!("chat/{}", self.version)
+//!    }
+//!}
+//!
+//!impl upgrade::Upgrade for ChatUpgrade {
+//!    type Output = ();
+//!    type VerifyError = ChatUpgradeError;
+//!
+//!    fn prepare_request(&self, headers: &mut http::HeaderMap, _: &mut http::Extensions) {
+//!        headers.insert(http::header::CONNECTION, http::header::HeaderValue::from_static(upgrade::CONNECTION_TYPE));
+//!        headers.insert(http::header::UPGRADE, self.protocol().parse().expect("Valid header value"));
+//!    }
+//!
+//!    fn verify_response(&self, status: http::StatusCode, headers: &http::HeaderMap, _: &http::Extensions) -> Result<(), Self::VerifyError> {
+//!        if status != http::StatusCode::SWITCHING_PROTOCOLS {
+//!            return Err(ChatUpgradeError::Status(status));
+//!        }
+//!
+//!        match headers.get(http::header::UPGRADE) {
+//!            Some(protocol) if protocol == self.protocol().as_str() => Ok(()),
+//!            _ => Err(ChatUpgradeError::Protocol),
+//!        }
+//!    }
+//!}
+//!
+//!async fn start_chat() -> upgrade::Upgraded {
+//!    let chat = ChatUpgrade {
+//!        version: 2,
+//!    };
+//!
+//!    let client = client::Client::default();
+//!    let request = client::Request::get("http://localhost:8080/chat").expect("To create request").upgrade(&chat);
+//!    let response = matsu!(client.request(request)).expect("To get response");
+//!
+//!    let (_, _, upgraded) = matsu!(response.upgrade(&chat)).expect("To verify upgrade").expect("To finish upgrade");
+//!    upgraded
+//!}
+//!```
+
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncWrite};
 
 ///Connection's header value for upgrade
 pub const CONNECTION_TYPE: &str = "Upgrade";
@@ -6,20 +85,85 @@ pub const CONNECTION_TYPE: &str = "Upgrade";
 #[cfg(feature = "websocket")]
 pub mod websocket;
 #[cfg(feature = "websocket")]
-pub use self::websocket::{WebsocketUpgradeOpts, WebsocketUpgrade};
+pub use self::websocket::{WebsocketUpgradeOpts, WebsocketUpgrade, WebsocketHandshake};
 
 ///Describes upgrade protocol
+///
+///Upgrader is passed by reference both when preparing request and when verifying response,
+///so it can carry options of upgrade (e.g. requested sub-protocols) and check response against them.
+///Any state, generated per request (e.g. random key), should be stored in request's extensions,
+///which are available in response when `carry_extensions` feature is enabled.
 pub trait Upgrade {
-    ///Result of upgrading
+    ///Outcome of successful verification (e.g. negotiated parameters).
+    type Output;
+    ///Error of verifying response.
     type VerifyError;
-    ///Upgrade options.
-    type Options;
 
     ///Prepares Request for upgrade
-    fn prepare_request(headers: &mut http::HeaderMap, extensions: &mut http::Extensions, options: Self::Options);
+    fn prepare_request(&self, headers: &mut http::HeaderMap, extensions: &mut http::Extensions);
+
+    ///Verifies that Response confirms upgrade, returning negotiated outcome.
+    fn verify_response(&self, status: http::StatusCode, headers: &http::HeaderMap, extensions: &http::Extensions) -> Result<Self::Output, Self::VerifyError>;
+}
+
+///Upgraded connection.
+///
+///Request, that initiated upgrade, remains in flight while stream is alive, so that
+///[Client::shutdown](../client/struct.Client.html#method.shutdown) waits for it.
+pub struct Upgraded {
+    inner: hyper::upgrade::Upgraded,
+    _in_flight: Option<crate::client::shutdown::InFlight>,
+}
+
+impl Upgraded {
+    #[inline]
+    pub(crate) fn new(inner: hyper::upgrade::Upgraded, in_flight: Option<crate::client::shutdown::InFlight>) -> Self {
+        Self {
+            inner,
+            _in_flight: in_flight,
+        }
+    }
+
+    #[inline]
+    ///Returns underlying hyper's stream, after which request is no longer considered in flight.
+    pub fn into_inner(self) -> hyper::upgrade::Upgraded {
+        self.inner
+    }
+}
+
+impl fmt::Debug for Upgraded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+impl AsyncRead for Upgraded {
+    #[inline]
+    unsafe fn prepare_uninitialized_buffer(&self, buff: &mut [MaybeUninit<u8>]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buff)
+    }
+
+    #[inline]
+    fn poll_read(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buff: &mut [u8]) -> Poll<io::Result<usize>> {
+        AsyncRead::poll_read(Pin::new(&mut self.inner), ctx, buff)
+    }
+}
+
+impl AsyncWrite for Upgraded {
+    #[inline]
+    fn poll_write(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buff: &[u8]) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(Pin::new(&mut self.inner), ctx, buff)
+    }
+
+    #[inline]
+    fn poll_flush(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(Pin::new(&mut self.inner), ctx)
+    }
 
-    ///Upgrades Response
-    fn verify_response(status: http::StatusCode, headers: &http::HeaderMap, extensions: &http::Extensions) -> Result<(), Self::VerifyError>;
+    #[inline]
+    fn poll_shutdown(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(Pin::new(&mut self.inner), ctx)
+    }
 }
 
 pub(crate) type UpgradeRes = Result<(http::Response<hyper::Body>, hyper::upgrade::Upgraded), hyper::Error>;
@@ -5,11 +5,10 @@
 //!```rust
 //!use yukikaze::{matsu, client};
 //!
-//!async fn do_ws_handshaske() -> hyper::upgrade::Upgraded {
-//!   const WS_TEST: &str = "http://echo.websocket.org/?encoding=text";
-//!
-//!   let request = client::request::Request::get(WS_TEST).expect("Error with request!")
-//!                                                       .upgrade(yukikaze::upgrade::WebsocketUpgrade, None);
+//!async fn do_ws_handshaske(uri: &str) -> yukikaze::upgrade::Upgraded {
+//!   let websocket = yukikaze::upgrade::WebsocketUpgrade::new();
+//!   let request = client::request::Request::get(uri).expect("Error with request!")
+//!                                                       .upgrade(&websocket);
 //!
 //!   let client = client::Client::default();
 //!
@@ -18,13 +17,26 @@
 //!   let response = result.expect("To get without error");
 //!   assert!(response.is_upgrade());
 //!
-//!   let upgrade = matsu!(response.upgrade(yukikaze::upgrade::WebsocketUpgrade));
-//!   let (response, upgraded) = upgrade.expect("To validate upgrade").expect("To finish upgrade");
+//!   let upgrade = matsu!(response.upgrade(&websocket));
+//!   let (response, _, upgraded) = upgrade.expect("To validate upgrade").expect("To finish upgrade");
 //!   assert!(response.is_upgrade());
 //!
 //!   upgraded
 //!}
 //!```
+//!
+//!Or simply using [Client::websocket](../../client/struct.Client.html#method.websocket)
+//!
+//!```rust
+//!use yukikaze::{matsu, client};
+//!
+//!async fn open_websocket(uri: &str) -> yukikaze::upgrade::Upgraded {
+//!   let client = client::Client::default();
+//!
+//!   let (_, _, upgraded) = matsu!(client.websocket(uri, None)).expect("To open websocket");
+//!   upgraded
+//!}
+//!```
 
 use core::fmt;
 use core::ops::Deref;
@@ -55,6 +67,12 @@ pub enum WebsocketUpgradeError {
     MissingChallenge,
     ///Sec-Websocket-Accept has invalid challenge.
     InvalidChallenge,
+    ///Server selected sub-protocol, that has not been requested.
+    InvalidProtocol,
+    ///Sec-Websocket-Key is missing in response's extensions.
+    ///
+    ///Happens when request has not been prepared by `WebsocketUpgrade` or `carry_extensions` feature is disabled.
+    MissingKey,
 }
 
 impl fmt::Display for WebsocketUpgradeError {
@@ -65,6 +83,8 @@ impl fmt::Display for WebsocketUpgradeError {
             WebsocketUpgradeError::InvalidConnectionHeader => f.write_str("Invalid Connection Header"),
             WebsocketUpgradeError::MissingChallenge => f.write_str("Sec-Websocket-Accept header is missing"),
             WebsocketUpgradeError::InvalidChallenge => f.write_str("Sec-Websocket-Accept has invalid challenge"),
+            WebsocketUpgradeError::InvalidProtocol => f.write_str("Sec-WebSocket-Protocol is not one of requested protocols"),
+            WebsocketUpgradeError::MissingKey => f.write_str("Missing websocket Sec-Key. Did you start upgrade?"),
         }
     }
 }
@@ -103,32 +123,76 @@ impl SecKey {
     }
 }
 
+#[derive(Debug, Clone)]
 ///Options for `WebsocketUpgrade`
 pub struct WebsocketUpgradeOpts {
     ///Specifies value of header `Sec-WebSocket-Protocol`
+    ///
+    ///Multiple protocols are separated by comma in order of preference.
     pub protocols: &'static str
 }
 
 impl WebsocketUpgradeOpts {
+    #[inline]
+    ///Returns whether `protocol` is among requested ones.
+    pub fn is_requested(&self, protocol: &str) -> bool {
+        self.protocols.split(',').map(str::trim).any(|requested| requested == protocol)
+    }
+
     #[inline(always)]
-    fn apply(self, headers: &mut http::HeaderMap) {
-        match headers.entry(http::header::SEC_WEBSOCKET_PROTOCOL) {
-            http::header::Entry::Vacant(entry) => {
-                entry.insert(http::header::HeaderValue::from_static(self.protocols));
-            },
-            _ => (),
+    fn apply(&self, headers: &mut http::HeaderMap) {
+        if let http::header::Entry::Vacant(entry) = headers.entry(http::header::SEC_WEBSOCKET_PROTOCOL) {
+            entry.insert(http::header::HeaderValue::from_static(self.protocols));
         }
     }
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+///Parameters, negotiated during websocket handshake.
+pub struct WebsocketHandshake {
+    ///Sub-protocol, selected by server, if any.
+    pub protocol: Option<String>,
+    ///Extensions, accepted by server, as they are specified in `Sec-WebSocket-Extensions`.
+    ///
+    ///Each entry contains extension's name with its parameters (e.g. `permessage-deflate; client_max_window_bits`).
+    pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
 ///Websocket upgrade method
-pub struct WebsocketUpgrade;
+pub struct WebsocketUpgrade {
+    opts: Option<WebsocketUpgradeOpts>,
+}
+
+impl WebsocketUpgrade {
+    #[inline]
+    ///Creates new instance without options.
+    pub const fn new() -> Self {
+        Self {
+            opts: None,
+        }
+    }
+
+    #[inline]
+    ///Creates new instance with specified options.
+    pub const fn with_opts(opts: WebsocketUpgradeOpts) -> Self {
+        Self {
+            opts: Some(opts),
+        }
+    }
+
+    #[inline]
+    ///Returns options of upgrade, if any.
+    pub fn opts(&self) -> Option<&WebsocketUpgradeOpts> {
+        self.opts.as_ref()
+    }
+}
 
 impl super::Upgrade for WebsocketUpgrade {
+    type Output = WebsocketHandshake;
     type VerifyError = WebsocketUpgradeError;
-    type Options = Option<WebsocketUpgradeOpts>;
 
-    fn prepare_request(headers: &mut http::HeaderMap, extensions: &mut http::Extensions, options: Self::Options) {
+    fn prepare_request(&self, headers: &mut http::HeaderMap, extensions: &mut http::Extensions) {
         use ring::rand::SecureRandom;
 
         let mut sec_key: [u8; 16] = [2, 3, 99, 255, 243, 125, 17, 29, 93, 105, 201, 152, 145, 192, 200, 221];
@@ -149,29 +213,23 @@ impl super::Upgrade for WebsocketUpgrade {
 
         let key = unsafe { http::header::HeaderValue::from_maybe_shared_unchecked(key) };
 
-        match headers.entry(http::header::CONNECTION) {
-            http::header::Entry::Vacant(entry) => {
-                entry.insert(http::header::HeaderValue::from_static(CONNECTION_TYPE));
-            },
-            _ => (),
+        if let http::header::Entry::Vacant(entry) = headers.entry(http::header::CONNECTION) {
+            entry.insert(http::header::HeaderValue::from_static(CONNECTION_TYPE));
         }
 
-        match headers.entry(http::header::UPGRADE) {
-            http::header::Entry::Vacant(entry) => {
-                entry.insert(http::header::HeaderValue::from_static(UPGRADE_NAME));
-            },
-            _ => (),
+        if let http::header::Entry::Vacant(entry) = headers.entry(http::header::UPGRADE) {
+            entry.insert(http::header::HeaderValue::from_static(UPGRADE_NAME));
         }
 
         let _ = headers.insert(http::header::SEC_WEBSOCKET_VERSION, utils::content_len_value(WEBSOCKET_VERSION as u64));
         let _ = headers.insert(http::header::SEC_WEBSOCKET_KEY, key);
 
-        if let Some(options) = options {
-            options.apply(headers);
+        if let Some(opts) = self.opts.as_ref() {
+            opts.apply(headers);
         }
     }
 
-    fn verify_response(status: http::StatusCode, headers: &http::HeaderMap, extensions: &http::Extensions) -> Result<(), Self::VerifyError> {
+    fn verify_response(&self, status: http::StatusCode, headers: &http::HeaderMap, extensions: &http::Extensions) -> Result<Self::Output, Self::VerifyError> {
         if status != http::StatusCode::SWITCHING_PROTOCOLS {
             return Err(status.into());
         }
@@ -192,9 +250,72 @@ impl super::Upgrade for WebsocketUpgrade {
                 },
                 None => return Err(WebsocketUpgradeError::MissingChallenge)
             },
-            None => panic!("Missing websocket Sec-Key. Did you start upgrade?")
+            None => return Err(WebsocketUpgradeError::MissingKey)
+        }
+
+        let mut result = WebsocketHandshake::default();
+
+        if let Some(protocol) = headers.get(http::header::SEC_WEBSOCKET_PROTOCOL) {
+            let protocol = protocol.to_str().map_err(|_| WebsocketUpgradeError::InvalidProtocol)?.trim();
+            match self.opts.as_ref() {
+                Some(opts) if opts.is_requested(protocol) => result.protocol = Some(protocol.to_owned()),
+                _ => return Err(WebsocketUpgradeError::InvalidProtocol),
+            }
+        }
+
+        for value in headers.get_all(http::header::SEC_WEBSOCKET_EXTENSIONS).iter().filter_map(|value| value.to_str().ok()) {
+            result.extensions.extend(value.split(',').map(str::trim).filter(|ext| !ext.is_empty()).map(str::to_owned));
         }
 
-        Ok(())
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WebsocketUpgrade, WebsocketUpgradeOpts, WebsocketUpgradeError};
+    use crate::upgrade::Upgrade;
+
+    #[test]
+    fn should_verify_websocket_response() {
+        let websocket = WebsocketUpgrade::with_opts(WebsocketUpgradeOpts {
+            protocols: "chat, superchat-v2",
+        });
+
+        let mut headers = http::HeaderMap::new();
+        let mut extensions = http::Extensions::new();
+        websocket.prepare_request(&mut headers, &mut extensions);
+        assert_eq!(headers.get(http::header::SEC_WEBSOCKET_PROTOCOL).unwrap(), "chat, superchat-v2");
+
+        let key = extensions.get::<super::SecKey>().expect("To have key");
+        let mut ctx = ring::digest::Context::new(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY);
+        ctx.update(key);
+        ctx.update(super::GUID.as_bytes());
+        let challenge = data_encoding::BASE64.encode(ctx.finish().as_ref());
+
+        let mut response = http::HeaderMap::new();
+        response.insert(http::header::UPGRADE, http::header::HeaderValue::from_static("websocket"));
+        response.insert(http::header::CONNECTION, http::header::HeaderValue::from_static("upgrade"));
+        response.insert(http::header::SEC_WEBSOCKET_ACCEPT, challenge.parse().unwrap());
+
+        let handshake = websocket.verify_response(http::StatusCode::SWITCHING_PROTOCOLS, &response, &extensions).expect("To verify");
+        assert_eq!(handshake.protocol, None);
+        assert!(handshake.extensions.is_empty());
+
+        response.insert(http::header::SEC_WEBSOCKET_PROTOCOL, http::header::HeaderValue::from_static("superchat"));
+        match websocket.verify_response(http::StatusCode::SWITCHING_PROTOCOLS, &response, &extensions) {
+            Err(WebsocketUpgradeError::InvalidProtocol) => (),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        response.insert(http::header::SEC_WEBSOCKET_PROTOCOL, http::header::HeaderValue::from_static("chat"));
+        response.insert(http::header::SEC_WEBSOCKET_EXTENSIONS, http::header::HeaderValue::from_static("permessage-deflate; client_max_window_bits, x-custom"));
+        let handshake = websocket.verify_response(http::StatusCode::SWITCHING_PROTOCOLS, &response, &extensions).expect("To verify");
+        assert_eq!(handshake.protocol.as_deref(), Some("chat"));
+        assert_eq!(handshake.extensions, ["permessage-deflate; client_max_window_bits", "x-custom"]);
+        match websocket.verify_response(http::StatusCode::SWITCHING_PROTOCOLS, &response, &http::Extensions::new()) {
+            Err(WebsocketUpgradeError::MissingKey) => (),
+            result => panic!("Unexpected result: {:?}", result),
+        }
     }
 }
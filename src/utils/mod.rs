@@ -1,6 +1,7 @@
 //!Yukikaze-sama utilities.
 use core::mem;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
 const DEFAULT_CAPACITY: usize = 4096;
 const SMOL_CAPCITY: usize = 64;
@@ -53,6 +54,13 @@ impl<T> OptionExt<T> for Option<T> {
     }
 }
 
+///Returns path of temporary file, used while atomically writing into `path`.
+pub(crate) fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
 ///Convenience wrapper over `bytes::BytesMut`
 ///
 ///Provides `io::Write` that automatically resizes.
@@ -99,6 +107,12 @@ impl BytesWriter {
         self.buf.len()
     }
 
+    #[inline]
+    ///Returns whether buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
     #[inline]
     ///Splits off, the same as `bytes::BytesMut::split_off`
     pub fn split_off(&mut self, at: usize) -> Self {
@@ -114,6 +128,13 @@ impl BytesWriter {
     }
 }
 
+impl Default for BytesWriter {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl io::Write for BytesWriter {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.buf.extend_from_slice(buf);
@@ -136,3 +157,177 @@ pub fn content_len_value(len: u64) -> crate::http::header::HeaderValue {
     let _ = write!(&mut res, "{}", len);
     unsafe { crate::http::header::HeaderValue::from_maybe_shared_unchecked(res.freeze()) }
 }
+
+#[inline(always)]
+fn is_header_value_byte(byte: u8) -> bool {
+    byte == b'\t' || (byte >= 0x20 && byte != 0x7f)
+}
+
+///Writer of header's value, that ensures it is valid.
+///
+///Header's value must not contain control characters, other than horizontal tab,
+///as `CR` and `LF` would allow to inject headers.
+///Since values are often formatted from user's data (e.g. file names),
+///writer either rejects or percent-encodes such bytes on completion.
+pub struct HeaderValueWriter {
+    buf: BytesWriter,
+}
+
+impl HeaderValueWriter {
+    #[inline]
+    ///Creates new instance with smol capacity 64
+    pub fn new() -> Self {
+        Self {
+            buf: BytesWriter::with_smol_capacity(),
+        }
+    }
+
+    ///Creates header's value out of written data, percent-encoding invalid bytes.
+    pub fn finish(mut self) -> crate::http::header::HeaderValue {
+        let data = self.buf.freeze();
+
+        let data = match data.iter().all(|byte| is_header_value_byte(*byte)) {
+            true => data,
+            false => {
+                let mut encoded = BytesWriter::with_capacity(data.len() + 8);
+                for byte in data.iter() {
+                    let _ = match is_header_value_byte(*byte) {
+                        true => encoded.write_all(&[*byte]),
+                        false => write!(&mut encoded, "%{:02X}", byte),
+                    };
+                }
+                encoded.freeze()
+            }
+        };
+
+        //Every byte is verified above
+        unsafe { crate::http::header::HeaderValue::from_maybe_shared_unchecked(data) }
+    }
+
+    #[inline]
+    ///Creates header's value out of written data, failing if it contains invalid bytes.
+    pub fn try_finish(mut self) -> Result<crate::http::header::HeaderValue, crate::http::header::InvalidHeaderValue> {
+        crate::http::header::HeaderValue::from_maybe_shared(self.buf.freeze())
+    }
+}
+
+impl Default for HeaderValueWriter {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl io::Write for HeaderValueWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.write(buf)
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.buf.write_all(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+///Fills buffer with random bytes.
+///
+///Randomness is taken from std's randomly seeded hasher, mixed with time and counter,
+///which is sufficient to generate unique identifiers, but not for cryptographic purposes.
+pub(crate) fn random_bytes(bytes: &mut [u8]) {
+    use core::hash::{BuildHasher, Hasher};
+    use std::collections::hash_map::RandomState;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|time| time.as_nanos() as u64).unwrap_or(0);
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    for (idx, chunk) in bytes.chunks_mut(8).enumerate() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(time);
+        hasher.write_u64(counter);
+        hasher.write_usize(idx);
+        let random = hasher.finish().to_le_bytes();
+        chunk.copy_from_slice(&random[..chunk.len()]);
+    }
+}
+
+///Generates random UUID (version 4) in its textual form.
+///
+///See `random_bytes` for source of randomness, which makes it unsuitable for secrets.
+pub(crate) fn uuid_v4() -> String {
+    let mut bytes = [0u8; 16];
+    random_bytes(&mut bytes);
+
+    //Version 4
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    //Variant RFC 4122
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = data_encoding::HEXLOWER.encode(&bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+///Converts international domain name into its ASCII form (punycode), as it is used by DNS and TLS.
+///
+///ASCII host is returned as it is. Conversion requires feature `idna`, otherwise
+///host is always returned as it is, and invalid name is left to be rejected by DNS or TLS.
+pub fn host_to_ascii(host: &str) -> std::borrow::Cow<'_, str> {
+    #[cfg(feature = "idna")]
+    {
+        if !host.is_ascii() {
+            if let Ok(host) = idna::domain_to_ascii(host) {
+                return host.into();
+            }
+        }
+    }
+
+    host.into()
+}
+
+#[cfg(feature = "idna")]
+///Converts host of textual `uri` via [host_to_ascii](fn.host_to_ascii.html), so that it can be parsed.
+pub(crate) fn uri_to_ascii(uri: &str) -> std::borrow::Cow<'_, str> {
+    if uri.is_ascii() {
+        return uri.into();
+    }
+
+    let start = match uri.find("://") {
+        Some(idx) => idx + 3,
+        None => 0,
+    };
+    let end = uri[start..].find(['/', '?', '#']).map(|end| start + end).unwrap_or(uri.len());
+    let authority = &uri[start..end];
+
+    let host_start = authority.rfind('@').map(|idx| start + idx + 1).unwrap_or(start);
+    let host_end = match uri[host_start..end].rfind(':') {
+        Some(idx) if uri[host_start + idx + 1..end].bytes().all(|byte| byte.is_ascii_digit()) => host_start + idx,
+        _ => end,
+    };
+
+    match host_to_ascii(&uri[host_start..host_end]) {
+        std::borrow::Cow::Owned(host) => format!("{}{}{}", &uri[..host_start], host, &uri[host_end..]).into(),
+        std::borrow::Cow::Borrowed(_) => uri.into(),
+    }
+}
+
+#[cfg(all(test, feature = "idna"))]
+mod tests {
+    use super::{host_to_ascii, uri_to_ascii};
+
+    #[test]
+    fn should_convert_idna_host() {
+        assert_eq!(host_to_ascii("example.com"), "example.com");
+        assert_eq!(host_to_ascii("bücher.example"), "xn--bcher-kva.example");
+        assert_eq!(uri_to_ascii("https://user@Bücher.example:8443/päth?q=ü#frag"), "https://user@xn--bcher-kva.example:8443/päth?q=ü#frag");
+        assert_eq!(uri_to_ascii("http://例え.テスト"), "http://xn--r8jz45g.xn--zckzah");
+        assert_eq!(uri_to_ascii("http://example.com/päth"), "http://example.com/päth");
+    }
+}
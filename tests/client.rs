@@ -1,9 +1,95 @@
 use yukikaze::{matsu, client};
 
+use core::future::Future;
 use core::time;
 
-const BIN_URL: &'static str = "https://httpbin.org";
-const BIN_GET: &'static str = "https://httpbin.org/get";
+///Starts raw server with `handler`, for cases not covered by `test::Server`,
+///such as streamed bodies, trailers and upgrades.
+fn serve<F, R>(handler: F) -> std::net::SocketAddr
+    where F: Fn(hyper::Request<hyper::Body>) -> R + Clone + Send + 'static, R: Future<Output=Result<hyper::Response<hyper::Body>, hyper::Error>> + Send + 'static,
+{
+    use hyper::service::{make_service_fn, service_fn};
+
+    let service = make_service_fn(move |_| {
+        let handler = handler.clone();
+        async move {
+            Ok::<_, hyper::Error>(service_fn(handler))
+        }
+    });
+    let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+#[cfg(feature = "test_server")]
+///Tunnels CONNECT request to its authority, as proxy does.
+fn tunnel(req: hyper::Request<hyper::Body>) -> hyper::Response<hyper::Body> {
+    let target = req.uri().authority().expect("To have authority").to_string();
+    tokio::spawn(async move {
+        let upgraded = req.into_body().on_upgrade().await.expect("To upgrade");
+        let target = tokio::net::TcpStream::connect(target).await.expect("To connect target");
+
+        let (mut client_read, mut client_write) = tokio::io::split(upgraded);
+        let (mut target_read, mut target_write) = tokio::io::split(target);
+        tokio::select! {
+            _ = tokio::io::copy(&mut client_read, &mut target_write) => (),
+            _ = tokio::io::copy(&mut target_read, &mut client_write) => (),
+        }
+    });
+
+    hyper::Response::new(hyper::Body::empty())
+}
+
+#[cfg(feature = "test_server")]
+///Forwards request in absolute-form to its target, as proxy does.
+async fn forward(req: hyper::Request<hyper::Body>) -> Result<hyper::Response<hyper::Body>, hyper::Error> {
+    assert!(req.uri().scheme().is_some(), "Request must be in absolute-form: {}", req.uri());
+
+    let (mut parts, body) = req.into_parts();
+    parts.headers.remove(http::header::PROXY_AUTHORIZATION);
+    hyper::Client::new().request(hyper::Request::from_parts(parts, body)).await
+}
+
+#[cfg(feature = "test_server")]
+///Responds with `Yukikaze`.
+fn yukikaze(_: &yukikaze::test::Request) -> yukikaze::test::Response {
+    yukikaze::test::response(http::StatusCode::OK, "Yukikaze")
+}
+
+#[cfg(feature = "test_server")]
+///Responds with value of request's header `name`, or empty body.
+fn echo_header(name: &'static str) -> impl Fn(&yukikaze::test::Request) -> yukikaze::test::Response + Send + Sync {
+    move |request| {
+        let value = request.headers().get(name).map(|value| value.as_bytes().to_vec()).unwrap_or_default();
+        yukikaze::test::response(http::StatusCode::OK, value)
+    }
+}
+
+#[cfg(feature = "test_server")]
+///Responds with request's path and query.
+fn echo_uri(request: &yukikaze::test::Request) -> yukikaze::test::Response {
+    let uri = request.uri().path_and_query().map(|uri| uri.as_str()).unwrap_or("/").to_owned();
+    yukikaze::test::response(http::StatusCode::OK, uri)
+}
+
+#[cfg(feature = "test_server")]
+///Responds with request's body and `Content-Type`.
+fn echo_body(request: &yukikaze::test::Request) -> yukikaze::test::Response {
+    let mut response = yukikaze::test::response(http::StatusCode::OK, request.body().clone());
+    if let Some(content_type) = request.headers().get(http::header::CONTENT_TYPE) {
+        response.headers_mut().insert(http::header::CONTENT_TYPE, content_type.clone());
+    }
+    response
+}
+
+#[cfg(feature = "test_server")]
+///Creates redirect with `status` to `location`.
+fn redirect(status: http::StatusCode, location: &str) -> yukikaze::test::Response {
+    let mut response = yukikaze::test::response(status, "");
+    response.headers_mut().insert(http::header::LOCATION, http::HeaderValue::from_str(location).expect("Valid location"));
+    response
+}
 
 pub struct TimeoutCfg;
 
@@ -16,187 +102,2473 @@ impl client::config::Config for TimeoutCfg {
     }
 }
 
+#[cfg(feature = "test_server")]
 #[tokio::test]
 async fn should_time_out() {
+    let server = yukikaze::test::Server::new();
+    server.route_delayed(http::Method::GET, "/get", time::Duration::from_millis(500), |_| yukikaze::test::response(http::StatusCode::OK, "late"));
     let client = client::Client::<TimeoutCfg>::new();
 
-    let request = client::request::Request::get(BIN_GET).expect("To create get request").empty();
+    let request = client::request::Request::get(server.url("/get")).expect("To create get request").empty();
     let result = matsu!(client.send(request));
     assert!(result.is_err());
 }
 
 #[tokio::test]
-async fn should_handle_redirect() {
-    pub struct SmolRedirect;
+async fn should_classify_connect_error() {
+    let client = client::Client::default();
 
-    impl client::config::Config for SmolRedirect {
-        type Connector = client::config::DefaultConnector;
-        type Timer = client::config::DefaultTimer;
+    let request = client::Request::get("http://127.0.0.1:1").expect("To create get request").empty();
+    let result = matsu!(client.request(request));
+    match result {
+        Err(client::Error::Connect(_)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
 
-        fn max_redirect_num() -> usize {
-            3
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_should_classify_connect_error() {
+    let client = yukikaze::blocking::Client::default();
+
+    let request = client::Request::get("http://127.0.0.1:1").expect("To create get request").empty();
+    match client.send(request) {
+        Err(yukikaze::blocking::Error::Request(client::Error::Connect(_))) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn should_return_rest_of_body_on_overflow() {
+    let body = hyper::Body::from(vec![b'1'; 6000]);
+    let result = matsu!(yukikaze::extractor::raw_bytes_or_rest(body, None)).expect("To read body");
+    let overflow = result.expect_err("Should overflow");
+    assert_eq!(overflow.data.len(), 6000);
+
+    let body = hyper::Body::from(vec![b'1'; 100]);
+    let result = matsu!(yukikaze::extractor::raw_bytes_or_rest(body, None)).expect("To read body");
+    assert_eq!(result.expect("Should not overflow").len(), 100);
+}
+
+#[cfg(feature = "compu")]
+#[tokio::test]
+async fn should_return_decompressed_rest_of_body_on_overflow() {
+    use compu::encoder::{Encoder, EncoderOp};
+    use hyper::body::HttpBody;
+
+    let options = compu::encoder::zlib::ZlibOptions::default().mode(compu::encoder::zlib::ZlibMode::Gzip);
+    let mut encoder = compu::compressor::memory::Compressor::new(compu::encoder::zlib::ZlibEncoder::new(&options));
+    encoder.push(&[b'1'; 6000], EncoderOp::Finish);
+
+    let response = hyper::Response::builder().header(http::header::CONTENT_ENCODING, "gzip")
+                                              .body(hyper::Body::from(encoder.take()))
+                                              .expect("To create response");
+    let mut response = client::Response::new(response);
+
+    let result = matsu!(response.body_or_rest()).expect("To read body");
+    let mut overflow = result.expect_err("Should overflow");
+    let mut body = overflow.data.to_vec();
+    while let Some(chunk) = matsu!(overflow.rest.data()) {
+        body.extend_from_slice(&chunk.expect("To read chunk"));
+    }
+    assert_eq!(body, vec![b'1'; 6000]);
+}
+
+#[tokio::test]
+async fn should_write_file_atomically() {
+    use yukikaze::header::ContentEncoding;
+
+    let path = std::env::temp_dir().join(format!("yukikaze-atomic-{}", std::process::id()));
+    let part = std::env::temp_dir().join(format!("yukikaze-atomic-{}.part", std::process::id()));
+    std::fs::write(&path, "old").expect("To write file");
+
+    let (mut sender, body) = hyper::Body::channel();
+    sender.send_data("new".into()).await.expect("To send data");
+    sender.abort();
+    assert!(matsu!(yukikaze::extractor::file_atomic(&path, body, ContentEncoding::Identity)).is_err());
+    assert_eq!(std::fs::read_to_string(&path).expect("To read file"), "old");
+    assert!(!part.exists());
+
+    let body = hyper::Body::from("new");
+    matsu!(yukikaze::extractor::file_atomic(&path, body, ContentEncoding::Identity)).expect("To write file");
+    assert_eq!(std::fs::read_to_string(&path).expect("To read file"), "new");
+    assert!(!part.exists());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn should_spool_big_body_to_file() {
+    use std::io::Read;
+    use yukikaze::header::ContentEncoding;
+    use yukikaze::extractor::Spooled;
+
+    let body = hyper::Body::from(vec![b'1'; 100]);
+    match matsu!(yukikaze::extractor::spooled(body, ContentEncoding::Identity, 100)).expect("To read body") {
+        Spooled::Memory(bytes) => assert_eq!(bytes.len(), 100),
+        Spooled::File(_) => panic!("Body should be in memory"),
+    }
+
+    let body = hyper::Body::from(vec![b'1'; 101]);
+    match matsu!(yukikaze::extractor::spooled(body, ContentEncoding::Identity, 100)).expect("To read body") {
+        Spooled::Memory(_) => panic!("Body should be in file"),
+        Spooled::File(mut file) => {
+            let mut content = Vec::new();
+            file.read_to_end(&mut content).expect("To read file");
+            assert_eq!(content, vec![b'1'; 101]);
         }
     }
+}
 
-    const BIN_ABS_REDIRECT_2: &'static str = "http://httpbin.org/absolute-redirect/2";
-    const BIN_REL_REDIRECT_2: &'static str = "http://httpbin.org/redirect/2";
-    const BIN_ABS_REDIRECT_3: &'static str = "http://httpbin.org/absolute-redirect/3";
+#[tokio::test]
+async fn should_tee_body_into_file_beyond_limit() {
+    use std::io::{Read, Seek, SeekFrom};
+    use yukikaze::header::ContentEncoding;
 
-    let client = client::Client::<SmolRedirect>::new();
+    let path = std::env::temp_dir().join("yukikaze-tee.txt");
+    let open = || std::fs::OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path).expect("To create file");
 
-    let request = client::Request::get(BIN_ABS_REDIRECT_2).expect("To create get request").empty();
-    let result = matsu!(client.redirect_request(request));
-    let result = result.expect("To get successful response");
-    assert!(result.is_success());
+    let body = hyper::Body::from(vec![b'1'; 100]);
+    let (_, bytes) = matsu!(yukikaze::extractor::tee(open(), body, ContentEncoding::Identity, Some(100))).expect("To read body");
+    assert_eq!(bytes.expect("To have bytes in memory").len(), 100);
 
-    let request = client::Request::get(BIN_REL_REDIRECT_2).expect("To create get request").empty();
-    let result = matsu!(client.redirect_request(request));
-    let result = result.expect("To get successful response");
-    assert!(result.is_success());
+    let body = hyper::Body::from(vec![b'1'; 101]);
+    let (mut file, bytes) = matsu!(yukikaze::extractor::tee(open(), body, ContentEncoding::Identity, Some(100))).expect("To read body");
+    assert!(bytes.is_none());
 
-    let request = client::Request::get(BIN_ABS_REDIRECT_3).expect("To create get request").empty();
-    let result = matsu!(client.redirect_request(request));
-    let result = result.expect("To get successful response");
-    assert!(result.is_redirect());
+    let mut content = Vec::new();
+    file.seek(SeekFrom::Start(0)).expect("To seek file");
+    file.read_to_end(&mut content).expect("To read file");
+    assert_eq!(content, vec![b'1'; 101]);
+
+    let _ = std::fs::remove_file(&path);
 }
 
+#[cfg(feature = "test_server")]
 #[tokio::test]
-async fn make_request() {
-    let request = client::Request::get(BIN_URL).expect("To create get request")
-                                               .bearer_auth("lolka")
-                                               .basic_auth("Lolka", Some("Pass"))
-                                               .empty();
+async fn should_count_reused_connections() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", yukikaze);
+    let url = server.url("/");
 
-    {
-        assert_eq!(request.method(), http::method::Method::GET);
-        assert_eq!(request.uri(), BIN_URL);
-        assert_eq!(request.headers().len(), 1);
-        let auth = request.headers().get(http::header::AUTHORIZATION).expect("To have AUTHORIZATION header");
-        let auth = auth.to_str().expect("Convert AUTHORIZATION to str");
-        assert!(auth.starts_with("Basic "));
-        assert_eq!(auth, "Basic TG9sa2E6UGFzcw==");
+    let client = client::Client::<InstrumentedCfg>::new();
+
+    for _ in 0..2 {
+        let request = client::Request::get(&url).expect("To create get request").empty();
+        let mut response = matsu!(client.request(request)).expect("To get response");
+        assert_eq!(matsu!(response.text()).expect("To read text"), "Yukikaze");
     }
 
+    let stats = client.pool_stats();
+    assert_eq!(stats.requests, 2);
+    assert_eq!(stats.opened, 1);
+    assert_eq!(stats.reused, 1);
+    assert_eq!(stats.alive, 1);
+    assert_eq!(stats.idle, 1);
+    assert_eq!(stats.hosts.len(), 1);
+    assert_eq!(server.connections(), 1);
+
+    //Statistics are not collected by default.
     let client = client::Client::default();
+    let request = client::Request::get(&url).expect("To create get request").empty();
+    matsu!(client.request(request)).expect("To get response");
+    let stats = client.pool_stats();
+    assert_eq!(stats.requests, 0);
+    assert_eq!(stats.opened, 0);
+    assert!(stats.hosts.is_empty());
+}
 
-    let result = matsu!(client.send(request));
-    let result = result.expect("To get without timeout");
-    println!("result={:?}", result);
-    let mut result = result.expect("To get without error");
+pub struct InstrumentedCfg;
 
-    assert_eq!(result.status().as_u16(), 200);
-    assert!(!result.is_error());
-    assert!(result.is_success());
+impl client::config::Config for InstrumentedCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
 
-    let res = matsu!(result.text());
-    assert!(res.is_ok());
+    fn instrument_connections() -> bool {
+        true
+    }
 }
 
-#[cfg(feature = "websocket")]
+#[cfg(feature = "test_server")]
 #[tokio::test]
-async fn test_websocket_upgrade() {
-    const WS_TEST: &str = "http://echo.websocket.org/?encoding=text";
+async fn should_paginate() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", |request| {
+        let page = request.uri().query().and_then(|query| query.trim_start_matches("page=").parse::<usize>().ok()).unwrap_or(0);
+        yukikaze::test::response(http::StatusCode::OK, page.to_string())
+    });
+    let url = server.url("/");
 
-    let request = client::request::Request::get(WS_TEST).expect("Error with request!")
-                                                        .upgrade(yukikaze::upgrade::WebsocketUpgrade, None);
+    let client = client::Client::default();
+    let request = client::Request::get(&url).expect("To create get request").empty();
+    let mut next = 0;
+    let mut pages = client.paginate(request, |_| {
+        next += 1;
+        match next {
+            3 => None,
+            next => Some(client::Request::get(format!("{}?page={}", url, next)).expect("To create get request").empty()),
+        }
+    });
+
+    let mut expected = 0;
+    while let Some(page) = matsu!(pages.next()) {
+        let mut page = page.expect("To get page");
+        assert_eq!(matsu!(page.text()).expect("To read text"), expected.to_string());
+        expected += 1;
+    }
+
+    assert_eq!(expected, 3);
+    assert_eq!(pages.pages(), 3);
+    assert!(pages.is_finished());
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_drain_requests_on_shutdown() {
+    use std::time::{Duration, Instant};
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", yukikaze);
+    let url = server.url("/");
 
-    println!("request={:?}", request);
     let client = client::Client::default();
 
-    let result = matsu!(client.send(request));
-    let result = result.expect("To get without timeout");
-    println!("result={:?}", result);
-    let response = result.expect("To get without error");
-    assert!(response.is_upgrade());
+    let request = client::Request::get(&url).expect("To create get request").empty();
+    let response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(client.in_flight(), 1);
 
-    let upgrade = matsu!(response.upgrade(yukikaze::upgrade::WebsocketUpgrade));
-    let (response, _) = upgrade.expect("To validate upgrade").expect("To finish upgrade");
-    assert!(response.is_upgrade());
+    assert!(!matsu!(client.shutdown(Instant::now() + Duration::from_millis(20))));
+    assert!(client.is_shutdown());
+
+    drop(response);
+    assert_eq!(client.in_flight(), 0);
+
+    let request = client::Request::get(&url).expect("To create get request").empty();
+    match matsu!(client.request(request)) {
+        Err(client::Error::Shutdown) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
 }
 
-#[cfg(feature = "compu")]
+#[cfg(feature = "test_server")]
 #[tokio::test]
-async fn should_handle_compressed_bytes() {
-    let encodings = [
-        "brotli",
-        "deflate",
-        "gzip",
-        "html",
-    ];
+async fn should_propagate_ctx_through_redirect() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", |_| redirect(http::StatusCode::FOUND, "/final"));
+    server.route(http::Method::GET, "/final", yukikaze);
 
-    for encoding in encodings.iter() {
-        println!("Encoding: {}", encoding);
-        let url = format!("https://httpbin.org/{}", encoding);
-        let request = client::Request::get(url).expect("To create get request").empty();
+    #[derive(Debug, PartialEq)]
+    struct CorrelationId(u64);
 
-        let client = client::Client::default();
+    let client = client::Client::default();
+    let request = client::Request::get(server.url("/")).expect("To create get request").with_ctx(CorrelationId(42)).with_ctx("tag").empty();
+    assert_eq!(request.ctx::<CorrelationId>(), Some(&CorrelationId(42)));
 
-        let result = matsu!(client.send(request)).expect("To get without timeout");
-        println!("result={:?}", result);
-        let mut response = result.expect("To get without error");
-        assert!(response.is_success());
+    let response = matsu!(client.redirect_request(request)).expect("To get response");
+    assert!(response.is_success());
+    assert_eq!(response.ctx::<CorrelationId>(), Some(&CorrelationId(42)));
+    assert_eq!(response.ctx::<&'static str>(), Some(&"tag"));
+    assert!(response.ctx::<u64>().is_none());
+}
 
-        let result = matsu!(response.text()).expect("Read body");
-        assert!(result.contains(encoding));
-        println!("Ok");
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_download_if_changed() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", |request| match request.headers().get("if-none-match") {
+        Some(etag) if etag == "\"v1\"" => yukikaze::test::response(http::StatusCode::NOT_MODIFIED, ""),
+        _ => {
+            let mut response = yukikaze::test::response(http::StatusCode::OK, "Yukikaze");
+            response.headers_mut().insert(http::header::ETAG, http::HeaderValue::from_static("\"v1\""));
+            response
+        },
+    });
+    let url = server.url("/");
+
+    let dir = std::env::temp_dir().join(format!("yukikaze-download-{}", std::process::id()));
+    std::fs::create_dir(&dir).expect("To create dir");
+    let path = dir.join("file.txt");
+    let meta = dir.join("file.txt.meta");
+
+    let client = client::Client::default();
+    assert!(matsu!(client.download_if_changed(&url, &path, &meta)).expect("To download"));
+    assert_eq!(std::fs::read_to_string(&path).expect("To read file"), "Yukikaze");
+    assert!(!dir.join("file.txt.part").exists());
+
+    assert!(!matsu!(client.download_if_changed(&url, &path, &meta)).expect("To download"));
+
+    std::fs::remove_file(&path).expect("To remove file");
+    assert!(matsu!(client.download_if_changed(&url, &path, &meta)).expect("To download"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+pub struct GzipCfg;
+
+impl client::config::Config for GzipCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn accepted_encodings() -> yukikaze::header::AcceptEncoding {
+        use yukikaze::header::{AcceptEncoding, ContentEncoding};
+
+        AcceptEncoding::new().add(ContentEncoding::Gzip, AcceptEncoding::MAX_QUALITY).add(ContentEncoding::Brotli, 500)
     }
 }
 
-#[cfg(feature = "compu")]
+#[cfg(feature = "test_server")]
 #[tokio::test]
-async fn should_handle_compressed_file() {
-    use std::io::{Read};
+async fn should_negotiate_accept_encoding() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", echo_header("accept-encoding"));
+    let url = server.url("/");
 
-    let encodings = [
-        "brotli",
-        "deflate",
-        "gzip",
-        "html",
-    ];
+    let client = client::Client::default();
+    let request = client::Request::get(&url).expect("To create get request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let expected = yukikaze::header::AcceptEncoding::decompressable().to_string();
+    assert_eq!(matsu!(response.text()).expect("To read text"), expected);
 
-    for encoding in encodings.iter() {
-        println!("Encoding: {}", encoding);
-        let url = format!("https://httpbin.org/{}", encoding);
-        let request = client::Request::get(url).expect("To create get request").empty();
+    let client = client::Client::<GzipCfg>::new();
+    let request = client::Request::get(&url).expect("To create get request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read text"), "gzip, br;q=0.5");
 
-        let client = client::Client::default();
+    let request = client::Request::get(&url).expect("To create get request").set_header(yukikaze::header::RANGE, "bytes=0-").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read text"), "");
+}
 
-        let result = matsu!(client.send(request)).expect("To get without timeout");
-        println!("result={:?}", result);
-        let mut response = result.expect("To get without error");
-        assert!(response.is_success());
+#[tokio::test]
+async fn should_report_partial_body_on_timeout() {
+    let addr = serve(|_| async {
+        let (mut sender, body) = hyper::Body::channel();
+        sender.send_data("partial".into()).await.expect("To send data");
+        //Body is never completed
+        core::mem::forget(sender);
 
-        let file = std::fs::File::create(encoding).expect("To create file");
+        let response = hyper::Response::builder().header("content-length", "100").body(body).expect("To create response");
+        Ok::<_, hyper::Error>(response)
+    });
 
-        let file = matsu!(response.file(file)).expect("Read body");
+    let client = client::Client::default();
+    let request = client::Request::get(format!("http://{}/", addr)).expect("To create get request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
 
-        drop(file);
-        let mut file = std::fs::File::open(encoding).expect("To open file");
-        let mut result = String::new();
-        file.read_to_string(&mut result).expect("To read file");
-        assert!(result.contains(encoding));
+    let timeout = time::Duration::from_millis(50);
+    let error = matsu!(response.timed(timeout, |response, progress| response.body_notify(progress))).expect_err("Should time out");
+    assert_eq!(error.status, 200);
+    assert_eq!(error.read, 7);
+    assert_eq!(error.content_len, Some(100));
+    assert_eq!(error.headers.get("content-length").expect("To have length"), "100");
+}
 
-        let _ = std::fs::remove_file(&encoding);
+pub struct HeaderLimitCfg;
 
-        println!("Ok");
+impl client::config::Config for HeaderLimitCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn max_response_headers() -> Option<usize> {
+        Some(10)
+    }
+
+    fn max_header_size() -> Option<usize> {
+        Some(1024)
     }
 }
 
-#[cfg(feature = "encoding")]
+#[cfg(feature = "test_server")]
 #[tokio::test]
-async fn decode_non_utf8() {
-    const URI: &str = "http://seiya-saiga.com/game/kouryaku.html";
-    let request = client::Request::get(URI).expect("To create get request").empty();
+async fn should_reject_excessive_headers() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", |_| yukikaze::test::response(http::StatusCode::OK, ""));
+    server.route(http::Method::GET, "/many", |_| {
+        let mut response = yukikaze::test::response(http::StatusCode::OK, "");
+        for idx in 0..20 {
+            let name = http::header::HeaderName::from_bytes(format!("x-header-{}", idx).as_bytes()).expect("Valid header");
+            response.headers_mut().insert(name, http::HeaderValue::from_static("value"));
+        }
+        response
+    });
+    server.route(http::Method::GET, "/big", |_| {
+        let mut response = yukikaze::test::response(http::StatusCode::OK, "");
+        response.headers_mut().insert("x-big", http::HeaderValue::from_str(&"a".repeat(2048)).expect("Valid header"));
+        response
+    });
+
+    let client = client::Client::<HeaderLimitCfg>::new();
+
+    let request = client::Request::get(server.url("/")).expect("To create get request").empty();
+    let response = matsu!(client.request(request)).expect("To get response");
+    assert!(response.is_success());
+
+    let request = client::Request::get(server.url("/many")).expect("To create get request").empty();
+    match matsu!(client.request(request)) {
+        Err(client::Error::HeaderLimit { count, .. }) => assert!(count >= 20),
+        result => panic!("Unexpected result: {:?}", result.map(|response| response.status())),
+    }
+
+    let request = client::Request::get(server.url("/big")).expect("To create get request").empty();
+    match matsu!(client.request(request)) {
+        Err(client::Error::HeaderLimit { size, .. }) => assert!(size > 2048),
+        result => panic!("Unexpected result: {:?}", result.map(|response| response.status())),
+    }
+}
+
+#[tokio::test]
+async fn should_connect_tunnel() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let addr = serve(|req: hyper::Request<hyper::Body>| async move {
+        let is_allowed = req.uri().host() == Some("127.0.0.1");
+        if req.method() != hyper::Method::CONNECT || !is_allowed {
+            let mut response = hyper::Response::new(hyper::Body::empty());
+            *response.status_mut() = hyper::StatusCode::FORBIDDEN;
+            return Ok::<_, hyper::Error>(response);
+        }
+
+        tokio::spawn(async move {
+            let mut upgraded = req.into_body().on_upgrade().await.expect("To upgrade");
+            let mut buffer = [0u8; 4];
+            upgraded.read_exact(&mut buffer).await.expect("To read");
+            upgraded.write_all(&buffer).await.expect("To write");
+        });
+
+        Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::empty()))
+    });
 
     let client = client::Client::default();
 
-    let result = matsu!(client.send(request)).expect("To get without timeout");
-    println!("result={:?}", result);
-    let mut response = result.expect("To get without error");
+    let mut tunnel = matsu!(client.connect_tunnel(addr.to_string())).expect("To establish tunnel");
+    matsu!(tunnel.write_all(b"ping")).expect("To write");
+    let mut buffer = [0u8; 4];
+    matsu!(tunnel.read_exact(&mut buffer)).expect("To read");
+    assert_eq!(&buffer, b"ping");
+    //Tunnel keeps request in flight until it is closed
+    assert_eq!(client.in_flight(), 1);
+    drop(tunnel);
+    assert_eq!(client.in_flight(), 0);
+
+    match matsu!(client.connect_tunnel(format!("localhost:{}", addr.port()))) {
+        Err(client::errors::TunnelError::Call(client::errors::CallError::Status(error))) => assert_eq!(error.status, hyper::StatusCode::FORBIDDEN),
+        result => panic!("Unexpected result: {:?}", result.map(|_| ())),
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_send_via_proxy() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", yukikaze);
+    let url = server.url("/");
+
+    let forwarded = Arc::new(AtomicUsize::new(0));
+    let proxy_forwarded = forwarded.clone();
+    let proxy_addr = serve(move |req| {
+        proxy_forwarded.fetch_add(1, Ordering::SeqCst);
+        forward(req)
+    });
+    let proxy_uri = yukikaze::connector::proxy::ProxyUri::new(&proxy_addr.to_string()).expect("To parse proxy");
+
+    let client = client::Client::default();
+
+    let request = client::Request::get(&url).expect("To create get request").via_proxy(Some(proxy_uri)).empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read text"), "Yukikaze");
+    assert_eq!(forwarded.load(Ordering::SeqCst), 1);
+
+    let request = client::Request::get(&url).expect("To create get request").via_proxy(None).empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read text"), "Yukikaze");
+    assert_eq!(forwarded.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_build_client_at_runtime() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", echo_header("x-api-key"));
+
+    let mut headers = yukikaze::header::HeaderMap::new();
+    headers.insert("x-api-key", yukikaze::header::HeaderValue::from_static("secret"));
+    let client = client::Client::builder().timeout(time::Duration::from_secs(1))
+                                          .max_redirects(1)
+                                          .connector(client::config::DefaultConnector::default())
+                                          .default_headers(headers)
+                                          .build();
+    assert_eq!(client.timeout(), time::Duration::from_secs(1));
+    assert_eq!(client.max_redirects(), 1);
+
+    let request = client::Request::get(server.url("/")).expect("To create get request").empty();
+    let mut response = matsu!(client.send(request)).expect("To not time out").expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read text"), "secret");
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_share_state_between_clones() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", yukikaze);
+    let url = server.url("/");
+
+    let client = client::Client::<InstrumentedCfg>::new();
+    let clone = client.clone();
+
+    let task = tokio::spawn(async move {
+        let request = client::Request::get(&url).expect("To create get request").empty();
+        let mut response = matsu!(clone.request(request)).expect("To get response");
+        matsu!(response.text()).expect("To read text")
+    });
+    assert_eq!(matsu!(task).expect("To finish task"), "Yukikaze");
+    assert_eq!(client.pool_stats().requests, 1);
+
+    let clone = client.clone();
+    assert!(matsu!(client.shutdown(std::time::Instant::now())));
+    assert!(clone.is_shutdown());
+}
+
+#[cfg(all(feature = "compu", feature = "test_server"))]
+#[tokio::test]
+async fn should_account_body_size() {
+    use compu::encoder::{Encoder, EncoderOp};
+
+    const TEXT: &str = "Yukikaze is the luckiest ship of all";
+
+    let options = compu::encoder::zlib::ZlibOptions::default().mode(compu::encoder::zlib::ZlibMode::Gzip);
+    let mut encoder = compu::compressor::memory::Compressor::new(compu::encoder::zlib::ZlibEncoder::new(&options));
+    encoder.push(TEXT.repeat(64).as_bytes(), EncoderOp::Finish);
+    let compressed = bytes::Bytes::from(encoder.take());
+    let compressed_len = compressed.len() as u64;
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::POST, "/", move |_| {
+        let mut response = yukikaze::test::response(http::StatusCode::OK, compressed.clone());
+        response.headers_mut().insert(http::header::CONTENT_ENCODING, http::HeaderValue::from_static("gzip"));
+        response
+    });
+
+    let client = client::Client::default();
+    let request = client::Request::post(server.url("/")).expect("To create post request").body(Some("ping"));
+    let mut response = matsu!(client.request(request)).expect("To get response");
+
+    let size = response.body_size().cloned().expect("To have body size");
+    assert_eq!(size.sent(), 4);
+    assert_eq!(size.received(), 0);
+
+    let text = matsu!(response.text_limited(Some(TEXT.len() * 64))).expect("To read text");
+    assert_eq!(text, TEXT.repeat(64));
+    assert_eq!(size.received(), compressed_len);
+    assert_eq!(size.decoded(), text.len() as u64);
+    assert!(size.received() < size.decoded());
+
+    //Extractors with notification account body the same way.
+    let request = client::Request::post(server.url("/")).expect("To create post request").body(Some("ping"));
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let size = response.body_size().cloned().expect("To have body size");
+    let progress = yukikaze::extractor::Progress::new();
+
+    let path = std::env::temp_dir().join(format!("yukikaze-size-{}", std::process::id()));
+    let file = std::fs::File::create(&path).expect("To create file");
+    matsu!(response.file_notify(file, progress.clone())).expect("To read body into file");
+    let len = std::fs::metadata(&path).expect("To get metadata").len();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(len, (TEXT.len() * 64) as u64);
+    assert_eq!(progress.get() as u64, compressed_len);
+    assert_eq!(size.received(), compressed_len);
+    assert_eq!(size.decoded(), len);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_fetch_limited() {
+    let server = yukikaze::test::Server::new();
+    let ships = |_: &yukikaze::test::Request| yukikaze::test::response(http::StatusCode::OK, "Yukikaze".repeat(16));
+    server.route(http::Method::HEAD, "/", ships);
+    server.route(http::Method::GET, "/", ships);
+    let url = server.url("/");
+
+    let client = client::Client::default();
+
+    match matsu!(client.fetch_limited(&url, 64)) {
+        Err(client::errors::FetchError::TooLarge(Some(128))) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+    assert_eq!(server.hits(http::Method::GET, "/"), 0);
+
+    let (response, body) = matsu!(client.fetch_limited(&url, 128)).expect("To fetch");
     assert!(response.is_success());
-    //Pretend that it acctually sets Content-Type correctly
-    response.headers_mut().insert(yukikaze::header::CONTENT_TYPE, yukikaze::header::HeaderValue::from_static("text/html; charset=shift_jis"));
+    assert_eq!(body, "Yukikaze".repeat(16));
+    assert_eq!(server.hits(http::Method::GET, "/"), 1);
+}
 
-    let res = matsu!(response.text());
-    assert!(res.is_ok());
+#[cfg(all(feature = "encoding", feature = "test_server"))]
+#[tokio::test]
+async fn should_transcode_file_to_utf8() {
+    use std::io::Read;
+
+    let text = "Юкикадзе ".repeat(1024);
+    let (encoded, _, _) = encoding_rs::WINDOWS_1251.encode(&text);
+    let encoded = bytes::Bytes::from(encoded.into_owned());
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", move |_| {
+        let mut response = yukikaze::test::response(http::StatusCode::OK, encoded.clone());
+        response.headers_mut().insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static("text/plain; charset=windows-1251"));
+        response
+    });
+
+    let client = client::Client::default();
+    let request = client::Request::get(server.url("/")).expect("To create get request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+
+    let path = std::env::temp_dir().join("yukikaze-transcoded.txt");
+    let file = std::fs::File::create(&path).expect("To create file");
+    drop(matsu!(response.file_utf8(file)).expect("To write file"));
+
+    let mut result = String::new();
+    std::fs::File::open(&path).expect("To open file").read_to_string(&mut result).expect("To read UTF-8 file");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(result, text);
+}
+
+#[cfg(feature = "encoding")]
+#[tokio::test]
+async fn should_decode_charset_incrementally() {
+    use yukikaze::{extractor, header};
+
+    let text = "雪風は幸運艦です。".repeat(32);
+    let (encoded, _, _) = encoding_rs::SHIFT_JIS.encode(&text);
+    let encoded = bytes::Bytes::from(encoded.into_owned());
+
+    //Odd chunk size splits multi-byte characters between chunks.
+    let (mut sender, body) = hyper::Body::channel();
+    let chunks = encoded.clone();
+    tokio::spawn(async move {
+        for chunk in chunks.chunks(3) {
+            if matsu!(sender.send_data(bytes::Bytes::copy_from_slice(chunk))).is_err() {
+                break;
+            }
+        }
+    });
+
+    let result = matsu!(extractor::text_charset(body, header::ContentEncoding::Identity, Some(encoded.len()), encoding_rs::SHIFT_JIS)).expect("To decode text");
+    assert_eq!(result, text);
+
+    let body = hyper::Body::from(encoded.clone());
+    match matsu!(extractor::text_charset(body, header::ContentEncoding::Identity, Some(encoded.len() - 1), encoding_rs::SHIFT_JIS)) {
+        Err(extractor::BodyReadError::Overflow(_)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+pub struct RawCookieCfg;
+
+impl client::config::Config for RawCookieCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn cookie_encoding() -> yukikaze::header::CookieEncoding {
+        yukikaze::header::CookieEncoding::Raw
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_use_raw_cookies() {
+    use yukikaze::header::CookieEncoding;
+
+    let server = yukikaze::test::Server::new();
+    let cookie = echo_header("cookie");
+    server.route(http::Method::GET, "/", move |request| {
+        let mut response = cookie(request);
+        response.headers_mut().insert(http::header::SET_COOKIE, http::HeaderValue::from_static("token=x%3Dy"));
+        response
+    });
+    let url = server.url("/");
+
+    let client = client::Client::<RawCookieCfg>::new();
+    let request = client::Request::get(&url).expect("To create get request").add_cookie(cookie::Cookie::new("session", "a:b")).empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let cookies = response.cookies().expect("To parse cookies");
+    assert_eq!(cookies[0].value(), "x%3Dy");
+    assert_eq!(matsu!(response.text()).expect("To read text"), "session=a:b");
+
+    //Explicit encoding of request takes priority over config.
+    let request = client::Request::get(&url).expect("To create get request")
+                                            .cookie_encoding(CookieEncoding::Percent)
+                                            .add_cookie(cookie::Cookie::new("session", "a:b"))
+                                            .empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read text"), "session=a%3Ab");
+
+    let client = client::Client::default();
+    let request = client::Request::get(&url).expect("To create get request").add_cookie(cookie::Cookie::new("session", "a:b")).empty();
+    let response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(response.cookies().expect("To parse cookies")[0].value(), "x=y");
+}
+
+pub struct TimingsCfg;
+
+impl client::config::Config for TimingsCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn collect_timings() -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_collect_timings() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", yukikaze);
+    let url = server.url("/");
+
+    let client = client::Client::<TimingsCfg>::new();
+
+    let request = client::Request::get(&url).expect("To create get request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let _ = matsu!(response.text()).expect("To read text");
+
+    let timings = response.timings().expect("To have timings");
+    let connect = timings.connect.expect("To have connect timings");
+    assert!(connect.resolve_end.is_some());
+    assert!(connect.connected.is_some());
+    assert!(connect.tls_handshake.is_none());
+    assert!(timings.request_written.is_some());
+    assert!(timings.first_byte.is_some());
+    assert!(timings.total().is_some());
+
+    let request = client::Request::get(&url).expect("To create get request").empty();
+    let response = matsu!(client.request(request)).expect("To get response");
+    let timings = response.timings().expect("To have timings");
+    assert!(timings.connect.is_none());
+}
+
+#[tokio::test]
+async fn should_convert_error_status() {
+    let response = hyper::Response::builder().status(404).body(hyper::Body::from("Not found at all")).expect("To create response");
+    let response = client::Response::new(response);
+
+    let error = matsu!(response.error_for_status_body(9)).expect_err("Should be error");
+    assert_eq!(error.status, 404);
+    assert_eq!(error.body.as_ref().map(|body| &body[..]), Some(&b"Not found"[..]));
+
+    let response = hyper::Response::builder().status(503).body(hyper::Body::empty()).expect("To create response");
+    let response = client::Response::new(response);
+    assert!(response.is_internal_error());
+    assert!(!response.is_client_error());
+    assert!(response.is_status(503u16));
+    assert_eq!(response.status_class(), client::response::StatusClass::ServerError);
+    assert!(response.error_for_status().is_err());
+
+    let response = client::Response::new(hyper::Response::new(hyper::Body::empty()));
+    assert_eq!(response.status_class(), client::response::StatusClass::Success);
+    assert!(response.error_for_status().is_ok());
+}
+
+#[cfg(feature = "compu")]
+#[tokio::test]
+async fn should_decompress_error_status_body() {
+    use compu::encoder::{Encoder, EncoderOp};
+
+    let options = compu::encoder::zlib::ZlibOptions::default().mode(compu::encoder::zlib::ZlibMode::Gzip);
+    let mut encoder = compu::compressor::memory::Compressor::new(compu::encoder::zlib::ZlibEncoder::new(&options));
+    encoder.push(b"Not found at all", EncoderOp::Finish);
+
+    let response = hyper::Response::builder().status(404)
+                                             .header(http::header::CONTENT_ENCODING, "gzip")
+                                             .body(hyper::Body::from(encoder.take()))
+                                             .expect("To create response");
+    let response = client::Response::new(response);
+
+    let error = matsu!(response.error_for_status_body(9)).expect_err("Should be error");
+    assert_eq!(error.body.as_ref().map(|body| &body[..]), Some(&b"Not found"[..]));
+}
+
+#[tokio::test]
+async fn should_keep_raw_body_on_json_error() {
+    let mut response = client::Response::new(hyper::Response::new(hyper::Body::from("{not json}")));
+
+    let (result, raw) = matsu!(response.json_with_raw::<serde_json::Value>()).expect("To read body");
+    assert!(result.is_err());
+    assert_eq!(raw, "{not json}");
+}
+
+#[cfg(feature = "nested_form")]
+#[tokio::test]
+async fn should_extract_nested_form() {
+    use std::collections::HashMap;
+
+    let mut response = client::Response::new(hyper::Response::new(hyper::Body::from("a[b]=1&a[c]=2&tags%5B0%5D=x&tags[1]=y")));
+
+    let form: HashMap<String, serde_json::Value> = matsu!(response.form_nested()).expect("To read form");
+    assert_eq!(form["a"]["b"], "1");
+    assert_eq!(form["a"]["c"], "2");
+    assert_eq!(form["tags"][0], "x");
+    assert_eq!(form["tags"][1], "y");
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_handle_redirect() {
+    pub struct SmolRedirect;
+
+    impl client::config::Config for SmolRedirect {
+        type Connector = client::config::DefaultConnector;
+        type Timer = client::config::DefaultTimer;
+
+        fn max_redirect_num() -> usize {
+            3
+        }
+    }
+
+    let server = yukikaze::test::Server::new();
+    let redirect_to = |location: String| move |_: &yukikaze::test::Request| redirect(http::StatusCode::FOUND, &location);
+    server.route(http::Method::GET, "/get", |_| yukikaze::test::response(http::StatusCode::OK, "done"));
+    for idx in 1..=3 {
+        let next = match idx {
+            1 => "/get".to_owned(),
+            idx => format!("/absolute-redirect/{}", idx - 1),
+        };
+        server.route(http::Method::GET, &format!("/absolute-redirect/{}", idx), redirect_to(server.url(&next)));
+    }
+    server.route(http::Method::GET, "/redirect/2", redirect_to("/relative-redirect/1".to_owned()));
+    server.route(http::Method::GET, "/relative-redirect/1", redirect_to("/get".to_owned()));
+
+    let client = client::Client::<SmolRedirect>::new();
+
+    let request = client::Request::get(server.url("/absolute-redirect/2")).expect("To create get request").empty();
+    let result = matsu!(client.redirect_request(request));
+    let result = result.expect("To get successful response");
+    assert!(result.is_success());
+
+    let request = client::Request::get(server.url("/redirect/2")).expect("To create get request").empty();
+    let result = matsu!(client.redirect_request(request));
+    let result = result.expect("To get successful response");
+    assert!(result.is_success());
+
+    let request = client::Request::get(server.url("/absolute-redirect/3")).expect("To create get request").empty();
+    let result = matsu!(client.redirect_request(request));
+    let result = result.expect("To get successful response");
+    assert!(result.is_redirect());
+    assert_eq!(server.hits(http::Method::GET, "/get"), 2);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn make_request() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", |request| match request.headers().get(http::header::AUTHORIZATION) {
+        Some(auth) if auth == "Basic TG9sa2E6UGFzcw==" => yukikaze::test::response(http::StatusCode::OK, "Yukikaze"),
+        _ => yukikaze::test::response(http::StatusCode::UNAUTHORIZED, ""),
+    });
+    let url = server.url("/");
+
+    let request = client::Request::get(&url).expect("To create get request")
+                                            .bearer_auth("lolka")
+                                            .basic_auth("Lolka", Some("Pass"))
+                                            .empty();
+
+    {
+        assert_eq!(request.method(), http::method::Method::GET);
+        assert_eq!(request.uri(), url.as_str());
+        assert_eq!(request.headers().len(), 1);
+        let auth = request.headers().get(http::header::AUTHORIZATION).expect("To have AUTHORIZATION header");
+        let auth = auth.to_str().expect("Convert AUTHORIZATION to str");
+        assert!(auth.starts_with("Basic "));
+        assert_eq!(auth, "Basic TG9sa2E6UGFzcw==");
+    }
+
+    let client = client::Client::default();
+
+    let result = matsu!(client.send(request));
+    let result = result.expect("To get without timeout");
+    println!("result={:?}", result);
+    let mut result = result.expect("To get without error");
+
+    assert_eq!(result.status().as_u16(), 200);
+    assert!(!result.is_error());
+    assert!(result.is_success());
+
+    let res = matsu!(result.text());
+    assert_eq!(res.expect("To read body"), "Yukikaze");
+    assert_eq!(server.hits(http::Method::GET, "/"), 1);
+}
+
+#[cfg(feature = "websocket")]
+#[tokio::test]
+async fn should_open_websocket() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let addr = serve(|req: hyper::Request<hyper::Body>| async move {
+        let key = req.headers().get("sec-websocket-key").expect("To have key").as_bytes().to_owned();
+        let mut ctx = ring::digest::Context::new(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY);
+        ctx.update(&key);
+        ctx.update(yukikaze::upgrade::websocket::GUID.as_bytes());
+        let challenge = data_encoding::BASE64.encode(ctx.finish().as_ref());
+
+        tokio::spawn(async move {
+            let mut upgraded = req.into_body().on_upgrade().await.expect("To upgrade");
+            let mut buffer = [0u8; 4];
+            upgraded.read_exact(&mut buffer).await.expect("To read");
+            upgraded.write_all(&buffer).await.expect("To write");
+        });
+
+        let response = hyper::Response::builder().status(hyper::StatusCode::SWITCHING_PROTOCOLS)
+                                                 .header("upgrade", "websocket")
+                                                 .header("connection", "Upgrade")
+                                                 .header("sec-websocket-accept", challenge.as_str())
+                                                 .body(hyper::Body::empty())
+                                                 .expect("To create response");
+        Ok::<_, hyper::Error>(response)
+    });
+
+    let client = client::Client::default();
+    let (response, handshake, mut upgraded) = matsu!(client.websocket(format!("http://{}/", addr), None)).expect("To open websocket");
+    assert!(response.is_upgrade());
+    assert_eq!(handshake, yukikaze::upgrade::WebsocketHandshake::default());
+
+    matsu!(upgraded.write_all(b"ping")).expect("To write");
+    let mut buffer = [0u8; 4];
+    matsu!(upgraded.read_exact(&mut buffer)).expect("To read");
+    assert_eq!(&buffer, b"ping");
+
+    //Stream, not response, keeps request in flight
+    drop(response);
+    assert_eq!(client.in_flight(), 1);
+    drop(upgraded);
+    assert_eq!(client.in_flight(), 0);
+}
+
+#[cfg(all(feature = "compu", feature = "test_server"))]
+///Registers `/brotli`, `/deflate` and `/gzip` routes with compressed body, mentioning encoding,
+///and `/html` with plain one.
+fn serve_compressed(server: &yukikaze::test::Server) {
+    use compu::encoder::{Encoder, EncoderOp};
+
+    fn compress<E: Encoder>(encoder: E, text: &str) -> bytes::Bytes {
+        let mut encoder = compu::compressor::memory::Compressor::new(encoder);
+        encoder.push(text.as_bytes(), EncoderOp::Finish);
+        bytes::Bytes::from(encoder.take())
+    }
+
+    let brotli = compress(compu::encoder::brotli::BrotliEncoder::default(), "Compressed by brotli");
+    let options = compu::encoder::zlib::ZlibOptions::default().mode(compu::encoder::zlib::ZlibMode::Zlib);
+    let deflate = compress(compu::encoder::zlib::ZlibEncoder::new(&options), "Compressed by deflate");
+    let options = compu::encoder::zlib::ZlibOptions::default().mode(compu::encoder::zlib::ZlibMode::Gzip);
+    let gzip = compress(compu::encoder::zlib::ZlibEncoder::new(&options), "Compressed by gzip");
+
+    for (path, encoding, body) in [("/brotli", "br", brotli), ("/deflate", "deflate", deflate), ("/gzip", "gzip", gzip)] {
+        server.route(http::Method::GET, path, move |_| {
+            let mut response = yukikaze::test::response(http::StatusCode::OK, body.clone());
+            response.headers_mut().insert(http::header::CONTENT_ENCODING, http::HeaderValue::from_static(encoding));
+            response
+        });
+    }
+    server.route(http::Method::GET, "/html", |_| yukikaze::test::response(http::StatusCode::OK, "<html><body>Plain html</body></html>"));
+}
+
+#[cfg(all(feature = "compu", feature = "test_server"))]
+#[tokio::test]
+async fn should_handle_compressed_bytes() {
+    let server = yukikaze::test::Server::new();
+    serve_compressed(&server);
+
+    let encodings = [
+        "brotli",
+        "deflate",
+        "gzip",
+        "html",
+    ];
+
+    for encoding in encodings.iter() {
+        println!("Encoding: {}", encoding);
+        let url = server.url(&format!("/{}", encoding));
+        let request = client::Request::get(url).expect("To create get request").empty();
+
+        let client = client::Client::default();
+
+        let result = matsu!(client.send(request)).expect("To get without timeout");
+        println!("result={:?}", result);
+        let mut response = result.expect("To get without error");
+        assert!(response.is_success());
+
+        let result = matsu!(response.text()).expect("Read body");
+        assert!(result.contains(encoding));
+        println!("Ok");
+    }
+}
+
+#[cfg(all(feature = "compu", feature = "test_server"))]
+#[tokio::test]
+async fn should_handle_compressed_file() {
+    use std::io::{Read};
+
+    let server = yukikaze::test::Server::new();
+    serve_compressed(&server);
+
+    let encodings = [
+        "brotli",
+        "deflate",
+        "gzip",
+        "html",
+    ];
+
+    for encoding in encodings.iter() {
+        println!("Encoding: {}", encoding);
+        let url = server.url(&format!("/{}", encoding));
+        let request = client::Request::get(url).expect("To create get request").empty();
+
+        let client = client::Client::default();
+
+        let result = matsu!(client.send(request)).expect("To get without timeout");
+        println!("result={:?}", result);
+        let mut response = result.expect("To get without error");
+        assert!(response.is_success());
+
+        let file = std::fs::File::create(encoding).expect("To create file");
+
+        let file = matsu!(response.file(file)).expect("Read body");
+
+        drop(file);
+        let mut file = std::fs::File::open(encoding).expect("To open file");
+        let mut result = String::new();
+        file.read_to_string(&mut result).expect("To read file");
+        assert!(result.contains(encoding));
+
+        let _ = std::fs::remove_file(encoding);
+
+        println!("Ok");
+    }
+}
+
+#[cfg(all(feature = "encoding", feature = "test_server"))]
+#[tokio::test]
+async fn decode_non_utf8() {
+    const TEXT: &str = "<html><body>攻略</body></html>";
+
+    let server = yukikaze::test::Server::new();
+    let (body, _, _) = yukikaze::encoding_rs::SHIFT_JIS.encode(TEXT);
+    let body = bytes::Bytes::from(body.into_owned());
+    server.route(http::Method::GET, "/game/kouryaku.html", move |_| yukikaze::test::response(http::StatusCode::OK, body.clone()));
+
+    let request = client::Request::get(server.url("/game/kouryaku.html")).expect("To create get request").empty();
+
+    let client = client::Client::default();
+
+    let result = matsu!(client.send(request)).expect("To get without timeout");
+    println!("result={:?}", result);
+    let mut response = result.expect("To get without error");
+    assert!(response.is_success());
+    //Pretend that it acctually sets Content-Type correctly
+    response.headers_mut().insert(yukikaze::header::CONTENT_TYPE, yukikaze::header::HeaderValue::from_static("text/html; charset=shift_jis"));
+
+    let res = matsu!(response.text());
+    assert_eq!(res.expect("To decode body"), TEXT);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_stream_multipart() {
+    use yukikaze::client::request::multipart;
+
+    fn create_form() -> multipart::Form {
+        let mut form = multipart::Form::new();
+        form.set_chunk_size(1024);
+        form.add_field("name".to_owned(), b"Yukikaze");
+        form.add_file_field("file".to_owned(), "big.bin".to_owned(), &mime::APPLICATION_OCTET_STREAM, &[b'y'; 4000]);
+        form
+    }
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::POST, "/", |request| {
+        let mut response = echo_body(request);
+        if let Some(len) = request.headers().get(http::header::CONTENT_LENGTH) {
+            response.headers_mut().insert("x-request-length", len.clone());
+        }
+        response
+    });
+
+    let (len, expected) = create_form().finish();
+
+    let request = client::request::Request::post(server.url("/")).expect("To create request").multipart(create_form());
+    let client = client::Client::default();
+    let mut response = matsu!(client.send(request)).expect("To get without timeout").expect("Successful");
+    assert!(response.is_success());
+    assert_eq!(response.headers().get("x-request-length").expect("To have length"), &len.to_string());
+    assert_eq!(response.body_size().expect("To have size").sent(), len);
+
+    let body = matsu!(response.body()).expect("Read body");
+    assert_eq!(body, expected);
+}
+
+#[tokio::test]
+async fn should_cancel_notified_read() {
+    use yukikaze::{extractor, header};
+
+    struct StopAfter(usize);
+
+    impl extractor::Notifier for StopAfter {
+        fn send(&mut self, num: usize) {
+            self.0 = self.0.saturating_sub(num);
+        }
+
+        fn should_continue(&mut self) -> bool {
+            self.0 > 0
+        }
+    }
+
+    let (mut sender, body) = hyper::Body::channel();
+    tokio::spawn(async move {
+        for _ in 0..16 {
+            if matsu!(sender.send_data(bytes::Bytes::from_static(b"Yukikaze"))).is_err() {
+                break;
+            }
+        }
+    });
+
+    match matsu!(extractor::raw_bytes_notify(body, header::ContentEncoding::Identity, None, StopAfter(24))) {
+        Err(extractor::BodyReadError::Cancelled) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let progress = extractor::Progress::new();
+    progress.cancel();
+    let body = hyper::Body::from("Yukikaze");
+    match matsu!(extractor::raw_bytes_notify(body, header::ContentEncoding::Identity, None, progress.clone())) {
+        Err(extractor::BodyReadError::Cancelled) => assert_eq!(progress.get(), 8),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let notify = extractor::Deadline::after(extractor::Noop, time::Duration::from_secs(0));
+    let body = hyper::Body::from("Yukikaze");
+    match matsu!(extractor::raw_bytes_notify(body, header::ContentEncoding::Identity, None, notify)) {
+        Err(extractor::BodyReadError::Cancelled) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[cfg(all(feature = "metrics", feature = "test_server"))]
+#[tokio::test]
+async fn should_collect_metrics() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::POST, "/", yukikaze);
+    server.route(http::Method::GET, "/missing", |_| yukikaze::test::response(http::StatusCode::NOT_FOUND, "Yukikaze"));
+
+    let registry = prometheus::Registry::new();
+    let metrics = client::metrics::Metrics::new(&registry).expect("To register metrics");
+    let client = client::builder::ClientBuilder::<InstrumentedCfg>::new().metrics(metrics.clone()).build();
+
+    let request = client::request::Request::post(server.url("/")).expect("To create request").body(Some("Kagero"));
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    matsu!(response.text()).expect("To read text");
+    let request = client::request::Request::get(server.url("/missing")).expect("To create request").empty();
+    matsu!(client.request(request)).expect("To get response");
+
+    assert_eq!(metrics.requests(client::response::StatusClass::Success), 1);
+    assert_eq!(metrics.requests(client::response::StatusClass::ClientError), 1);
+    assert_eq!(metrics.errors(), 0);
+
+    let mut text = Vec::new();
+    prometheus::Encoder::encode(&prometheus::TextEncoder::new(), &registry.gather(), &mut text).expect("To encode metrics");
+    let text = String::from_utf8(text).expect("To have UTF-8 text");
+    assert!(text.contains("yukikaze_request_duration_seconds_count 2\n"));
+    assert!(text.contains("yukikaze_request_body_bytes_total 6\n"));
+    //Body of second response is never read.
+    assert!(text.contains("yukikaze_response_body_bytes_total 8\n"));
+    assert!(text.contains("yukikaze_pool_requests_total 2\n"));
+}
+
+pub struct StrictRatioCfg;
+
+impl client::config::Config for StrictRatioCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn max_decompression_ratio() -> Option<u32> {
+        Some(100)
+    }
+}
+
+#[cfg(all(feature = "compu", feature = "test_server"))]
+#[tokio::test]
+async fn should_reject_decompression_bomb() {
+    use compu::encoder::{Encoder, EncoderOp};
+
+    const LEN: usize = 4 * 1024 * 1024;
+    //Below threshold, so ratio is not verified.
+    const SMALL_LEN: usize = 512 * 1024;
+
+    fn gzip(len: usize) -> impl Fn(&yukikaze::test::Request) -> yukikaze::test::Response + Send + Sync {
+        let options = compu::encoder::zlib::ZlibOptions::default().mode(compu::encoder::zlib::ZlibMode::Gzip);
+        let mut encoder = compu::compressor::memory::Compressor::new(compu::encoder::zlib::ZlibEncoder::new(&options));
+        encoder.push(&vec![0; len], EncoderOp::Finish);
+        let body = bytes::Bytes::from(encoder.take());
+
+        move |_| {
+            let mut response = yukikaze::test::response(http::StatusCode::OK, body.clone());
+            response.headers_mut().insert(http::header::CONTENT_ENCODING, http::HeaderValue::from_static("gzip"));
+            response
+        }
+    }
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", gzip(LEN));
+    server.route(http::Method::GET, "/small", gzip(SMALL_LEN));
+
+    let client = client::Client::<StrictRatioCfg>::new();
+    let request = client::Request::get(server.url("/")).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    match matsu!(response.body_limited(Some(LEN))) {
+        Err(yukikaze::extractor::BodyReadError::DecompressionBomb) => (),
+        result => panic!("Unexpected result: {:?}", result.map(|body| body.len())),
+    }
+
+    let request = client::Request::get(server.url("/small")).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let body = matsu!(response.body_limited(Some(LEN))).expect("To read body");
+    assert_eq!(body.len(), SMALL_LEN);
+}
+
+pub struct VerifyLengthCfg;
+
+impl client::config::Config for VerifyLengthCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn verify_content_length() -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn should_verify_content_length() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    //Chunked encoding takes precedence, so connection doesn't verify Content-Length on its own.
+    const RESPONSES: [&[u8]; 2] = [
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nContent-Length: 100\r\n\r\n8\r\nYukikaze\r\n0\r\n\r\n",
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nContent-Length: 4\r\n\r\n8\r\nYukikaze\r\n0\r\n\r\n",
+    ];
+
+    let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("To bind");
+    let url = format!("http://{}/", listener.local_addr().expect("To get address"));
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = match listener.accept().await {
+                Ok(socket) => socket,
+                Err(_) => break,
+            };
+
+            tokio::spawn(async move {
+                let mut buffer = [0u8; 1024];
+                while let Ok(len) = socket.read(&mut buffer).await {
+                    if len == 0 {
+                        break;
+                    }
+                    let response = match buffer.starts_with(b"GET /excess") {
+                        true => RESPONSES[1],
+                        false => RESPONSES[0],
+                    };
+                    if socket.write_all(response).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    let client = client::Client::<VerifyLengthCfg>::new();
+
+    let request = client::Request::get(&url).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    match matsu!(response.body_limited(Some(1024))) {
+        Err(yukikaze::extractor::BodyReadError::TruncatedBody(100, 8)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let request = client::Request::get(format!("{}excess", url)).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    match matsu!(response.body_limited(Some(1024))) {
+        Err(yukikaze::extractor::BodyReadError::ExcessBody(4, 8)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let client = client::Client::default();
+    let request = client::Request::get(&url).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.body_limited(Some(1024))).expect("To read body"), "Yukikaze");
+}
+
+pub struct OriginCfg;
+
+impl client::config::Config for OriginCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn default_headers(request: &mut client::request::Request) {
+        <client::config::DefaultCfg as client::config::Config>::default_headers(request);
+        request.headers_mut().insert(http::header::ORIGIN, http::header::HeaderValue::from_static("https://example.com"));
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_send_cors_preflight() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::OPTIONS, "/users", |req| {
+        assert_eq!(req.headers().get(http::header::ORIGIN).unwrap(), "https://example.com");
+        assert_eq!(req.headers().get(http::header::ACCESS_CONTROL_REQUEST_METHOD).unwrap(), "PUT");
+        assert_eq!(req.headers().get(http::header::ACCESS_CONTROL_REQUEST_HEADERS).unwrap(), "authorization,content-type");
+
+        let mut response = yukikaze::test::response(http::StatusCode::NO_CONTENT, "");
+        let headers = response.headers_mut();
+        headers.insert(http::header::ACCESS_CONTROL_ALLOW_ORIGIN, http::HeaderValue::from_static("https://example.com"));
+        headers.insert(http::header::ACCESS_CONTROL_ALLOW_METHODS, http::HeaderValue::from_static("GET, PUT"));
+        headers.insert(http::header::ACCESS_CONTROL_ALLOW_HEADERS, http::HeaderValue::from_static("Content-Type"));
+        headers.insert(http::header::ACCESS_CONTROL_MAX_AGE, http::HeaderValue::from_static("60"));
+        response
+    });
+
+    let client = client::Client::<OriginCfg>::new();
+    let headers = [http::header::CONTENT_TYPE, http::header::AUTHORIZATION, http::header::CONTENT_TYPE];
+    let preflight = matsu!(client.preflight(server.url("/users"), http::Method::PUT, &headers)).expect("To get preflight");
+
+    assert!(preflight.is_success());
+    assert!(preflight.allows_origin("https://example.com"));
+    assert!(preflight.allows_method(&http::Method::PUT));
+    assert!(!preflight.allows_method(&http::Method::DELETE));
+    assert!(preflight.allows_header(&http::header::CONTENT_TYPE));
+    assert!(!preflight.allows_header(&http::header::AUTHORIZATION));
+    assert_eq!(preflight.max_age, Some(time::Duration::from_secs(60)));
+    assert_eq!(server.hits(http::Method::OPTIONS, "/users"), 1);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_probe_capabilities() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::OPTIONS, "/files/", |_| {
+        let mut response = yukikaze::test::response(http::StatusCode::OK, "");
+        let headers = response.headers_mut();
+        headers.insert(http::header::ALLOW, http::HeaderValue::from_static("OPTIONS, GET, PUT, PATCH, PROPFIND"));
+        headers.insert(http::header::ACCEPT_RANGES, http::HeaderValue::from_static("bytes"));
+        headers.insert("accept-patch", http::HeaderValue::from_static("application/json-patch+json"));
+        headers.insert("dav", http::HeaderValue::from_static("1, 2"));
+        response
+    });
+
+    let client = client::Client::default();
+    let capabilities = matsu!(client.capabilities(server.url("/files/"))).expect("To get capabilities");
+    assert!(capabilities.is_success());
+    assert!(capabilities.allows_method(&http::Method::PATCH));
+    assert!(!capabilities.allows_method(&http::Method::DELETE));
+    assert!(capabilities.supports_ranges());
+    assert!(capabilities.accepts_patch(&"application/json-patch+json".parse().expect("Valid mime")));
+    assert!(capabilities.is_dav_class("2"));
+    assert_eq!(server.hits(http::Method::OPTIONS, "/files/"), 1);
+
+    let capabilities = matsu!(client.capabilities(server.url("/missing"))).expect("To get capabilities");
+    assert!(!capabilities.is_success());
+    assert!(capabilities.allow.is_empty());
+    assert!(!capabilities.is_dav());
+}
+
+#[cfg(all(feature = "test_server", feature = "webdav"))]
+#[tokio::test]
+async fn should_list_webdav_collection() {
+    use yukikaze::client::webdav;
+
+    let propfind = http::Method::from_bytes(b"PROPFIND").expect("Valid method");
+    let server = yukikaze::test::Server::new();
+    server.route(propfind.clone(), "/files/", |req| {
+        assert_eq!(req.headers().get("depth").unwrap(), "1");
+        assert_eq!(req.headers().get(http::header::CONTENT_TYPE).unwrap(), webdav::XML_CONTENT_TYPE);
+        assert!(std::str::from_utf8(req.body()).unwrap().contains("<displayname xmlns=\"DAV:\"/>"));
+
+        yukikaze::test::response(http::StatusCode::MULTI_STATUS, r#"<?xml version="1.0"?>
+<D:multistatus xmlns:D="DAV:">
+  <D:response><D:href>/files/</D:href><D:propstat><D:prop><D:displayname>files</D:displayname><D:resourcetype><D:collection/></D:resourcetype></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>
+  <D:response><D:href>/files/ship.txt</D:href><D:propstat><D:prop><D:displayname>ship.txt</D:displayname><D:resourcetype/></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>
+</D:multistatus>"#)
+    });
+    server.route(http::Method::from_bytes(b"MOVE").expect("Valid method"), "/files/ship.txt", |req| {
+        assert_eq!(req.headers().get("destination").unwrap(), "http://localhost/files/yukikaze.txt");
+        assert_eq!(req.headers().get("overwrite").unwrap(), "F");
+        yukikaze::test::response(http::StatusCode::CREATED, "")
+    });
+
+    let client = client::Client::default();
+    let request = webdav::propfind(server.url("/files/"), webdav::Depth::One).expect("To create request");
+    let request = webdav::with_xml(request, webdav::propfind_body(&[(webdav::DAV, "displayname"), (webdav::DAV, "resourcetype")]));
+    let mut response = matsu!(client.send(request)).expect("Not timed out").expect("To get response");
+    assert_eq!(response.status(), http::StatusCode::MULTI_STATUS);
+
+    let body = matsu!(response.text()).expect("To read body");
+    let multistatus = webdav::MultiStatus::parse(&body).expect("To parse multistatus");
+    assert_eq!(multistatus.responses.len(), 2);
+    assert!(multistatus.responses[0].is_collection());
+    assert!(!multistatus.responses[1].is_collection());
+    assert_eq!(multistatus.responses[1].prop(webdav::DAV, "displayname").unwrap().value, "ship.txt");
+
+    let request = webdav::move_to(server.url("/files/ship.txt"), "http://localhost/files/yukikaze.txt", false).expect("To create request").empty();
+    let response = matsu!(client.send(request)).expect("Not timed out").expect("To get response");
+    assert_eq!(response.status(), http::StatusCode::CREATED);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_call_grpc_web_unary() {
+    use yukikaze::client::{errors, grpc_web};
+
+    fn reply(messages: &[&[u8]], trailers: &str) -> yukikaze::test::Response {
+        let mut body = Vec::new();
+        for message in messages {
+            body.extend_from_slice(&grpc_web::frame(message));
+        }
+        body.push(0x80);
+        body.extend_from_slice(&(trailers.len() as u32).to_be_bytes());
+        body.extend_from_slice(trailers.as_bytes());
+
+        let mut response = yukikaze::test::response(http::StatusCode::OK, body);
+        response.headers_mut().insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static(grpc_web::CONTENT_TYPE));
+        response
+    }
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::POST, "/fleet.Fleet/GetShip", |req| {
+        assert_eq!(req.headers().get(http::header::CONTENT_TYPE).unwrap(), grpc_web::CONTENT_TYPE);
+        assert_eq!(req.body().as_ref(), &grpc_web::frame(b"yukikaze")[..]);
+        reply(&[b"kagero"], "grpc-status: 0\r\n")
+    });
+    server.route(http::Method::POST, "/fleet.Fleet/Sink", |_| reply(&[], "grpc-status: 9\r\ngrpc-message: Lucky%20ship\r\n"));
+    server.route(http::Method::POST, "/fleet.Fleet/Missing", |_| {
+        let mut response = yukikaze::test::response(http::StatusCode::OK, "");
+        response.headers_mut().insert("grpc-status", http::HeaderValue::from_static("12"));
+        response
+    });
+
+    let client = client::Client::default();
+    let result = matsu!(client.grpc_web_unary(server.url("/fleet.Fleet/GetShip"), b"yukikaze")).expect("To get reply");
+    assert_eq!(result.as_ref(), b"kagero");
+
+    match matsu!(client.grpc_web_unary(server.url("/fleet.Fleet/Sink"), b"yukikaze")) {
+        Err(errors::GrpcWebError::Grpc(status)) => {
+            assert_eq!(status.code, 9);
+            assert_eq!(status.message, "Lucky ship");
+        },
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    match matsu!(client.grpc_web_unary(server.url("/fleet.Fleet/Missing"), b"")) {
+        Err(errors::GrpcWebError::Grpc(status)) => assert_eq!(status.code, 12),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_upload_s3_multipart() {
+    use yukikaze::client::upload;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn query(req: &yukikaze::test::Request, key: &str) -> Option<String> {
+        req.uri().query()?.split('&').find_map(|pair| match pair.find('=') {
+            Some(idx) if &pair[..idx] == key => Some(pair[idx + 1..].to_owned()),
+            None if pair == key => Some(String::new()),
+            _ => None,
+        })
+    }
+
+    let server = yukikaze::test::Server::new();
+    let stored = Arc::new(Mutex::new(Vec::new()));
+    let is_failed = Arc::new(AtomicBool::new(false));
+
+    server.route(http::Method::POST, "/bucket/ship.bin", |req| match query(req, "uploads") {
+        Some(_) => yukikaze::test::response(http::StatusCode::OK, "<InitiateMultipartUploadResult><UploadId>id&amp;1</UploadId></InitiateMultipartUploadResult>"),
+        None => {
+            assert_eq!(query(req, "uploadId").as_deref(), Some("id%261"));
+            let body = std::str::from_utf8(req.body()).expect("UTF-8 body");
+            assert_eq!(body, "<CompleteMultipartUpload><Part><PartNumber>1</PartNumber><ETag>&quot;1&quot;</ETag></Part><Part><PartNumber>2</PartNumber><ETag>&quot;2&quot;</ETag></Part><Part><PartNumber>3</PartNumber><ETag>&quot;3&quot;</ETag></Part></CompleteMultipartUpload>");
+            yukikaze::test::response(http::StatusCode::OK, "<CompleteMultipartUploadResult><ETag>&quot;final&quot;</ETag></CompleteMultipartUploadResult>")
+        },
+    });
+    let parts = stored.clone();
+    let failed = is_failed.clone();
+    server.route(http::Method::PUT, "/bucket/ship.bin", move |req| {
+        let number = query(req, "partNumber").expect("Part number").parse::<usize>().expect("Valid number");
+        if number == 2 && !failed.swap(true, Ordering::SeqCst) {
+            return yukikaze::test::response(http::StatusCode::SERVICE_UNAVAILABLE, "");
+        }
+
+        parts.lock().unwrap().push((number, req.body().clone()));
+        let mut response = yukikaze::test::response(http::StatusCode::OK, "");
+        response.headers_mut().insert(http::header::ETAG, http::HeaderValue::from_str(&format!("\"{}\"", number)).unwrap());
+        response
+    });
+    server.route(http::Method::PUT, "/bucket/bad.bin", |_| yukikaze::test::response(http::StatusCode::FORBIDDEN, ""));
+    server.route(http::Method::POST, "/bucket/bad.bin", |_| yukikaze::test::response(http::StatusCode::OK, "<InitiateMultipartUploadResult><UploadId>bad</UploadId></InitiateMultipartUploadResult>"));
+    server.route(http::Method::DELETE, "/bucket/bad.bin", |_| yukikaze::test::response(http::StatusCode::NO_CONTENT, ""));
+
+    let options = upload::S3Options {
+        part_size: 4,
+        parallelism: 2,
+        retries: 1,
+        retry_delay: time::Duration::from_millis(0),
+    };
+    let client = client::Client::default();
+    let data = b"Yukikaze!!";
+
+    let result = matsu!(upload::multipart_s3_with(&client, &server.url("/bucket/ship.bin"), &data[..], &options)).expect("To upload");
+    assert_eq!(result.upload_id, "id&1");
+    assert_eq!(result.size, data.len() as u64);
+    assert_eq!(result.parts.iter().map(|part| part.size).collect::<Vec<_>>(), [4, 4, 2]);
+    assert_eq!(result.etag.as_deref(), Some("\"final\""));
+    assert_eq!(server.hits(http::Method::PUT, "/bucket/ship.bin"), 4);
+
+    let mut parts = stored.lock().unwrap().clone();
+    parts.sort_by_key(|part| part.0);
+    let uploaded = parts.into_iter().flat_map(|part| part.1.to_vec()).collect::<Vec<_>>();
+    assert_eq!(uploaded, &data[..]);
+
+    match matsu!(upload::multipart_s3_with(&client, &server.url("/bucket/bad.bin"), &data[..], &options)) {
+        Err(upload::UploadError::Call(client::errors::CallError::Status(error))) => assert_eq!(error.status, http::StatusCode::FORBIDDEN),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+    assert_eq!(server.hits(http::Method::DELETE, "/bucket/bad.bin"), 1);
+}
+
+#[tokio::test]
+async fn should_stream_json_array() {
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Ship {
+        name: String,
+        class: String,
+    }
+
+    let addr = serve(|_| async move {
+        let (mut sender, body) = hyper::Body::channel();
+        tokio::spawn(async move {
+            let chunks = ["[{\"name\": \"Yuki", "kaze\", \"class\": \"Kagero\"},", " {\"name\": \"Shigure\", \"class\": \"Shiratsuyu\"}]"];
+            for chunk in chunks.iter() {
+                sender.send_data(hyper::body::Bytes::from_static(chunk.as_bytes())).await.expect("To send chunk");
+            }
+        });
+        Ok::<_, hyper::Error>(hyper::Response::new(body))
+    });
+
+    let client = client::Client::default();
+    let request = client::request::Request::get(format!("http://{}/", addr)).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+
+    let mut ships = response.json_array_stream::<Ship>();
+    let ship = matsu!(ships.next()).expect("To have element").expect("To parse element");
+    assert_eq!(ship, Ship { name: "Yukikaze".to_owned(), class: "Kagero".to_owned() });
+    let ship = matsu!(ships.next()).expect("To have element").expect("To parse element");
+    assert_eq!(ship.name, "Shigure");
+    assert!(matsu!(ships.next()).is_none());
+    assert!(ships.is_finished());
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_resolve_relative_uri_against_base_url() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/api/v1/users", echo_uri);
+    server.route(http::Method::GET, "/v1/users", echo_uri);
+
+    let base_url = server.url("/api/").parse().expect("To parse base URL");
+    let client = client::Client::builder().base_url(Some(base_url))
+                                          .default_query(vec![("api_key".to_owned(), "s3cret key".to_owned())])
+                                          .build();
+
+    let request = client::request::Request::get("/v1/users?page=2").expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "/api/v1/users?page=2&api_key=s3cret+key");
+
+    let request = client::request::Request::get("/v1/users?api_key=other").expect("To create request").empty();
+    let mut response = matsu!(client.redirect_request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "/api/v1/users?api_key=other");
+
+    //Absolute URI is sent as it is
+    let request = client::request::Request::get(server.url("/v1/users")).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "/v1/users");
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_call_endpoint() {
+    use client::endpoint::Endpoint;
+    use std::borrow::Cow;
+
+    #[derive(serde::Serialize)]
+    struct NewShip {
+        name: &'static str,
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Created {
+        fleet: String,
+        name: String,
+    }
+
+    struct CreateShip {
+        fleet: &'static str,
+        ship: NewShip,
+    }
+
+    impl Endpoint for CreateShip {
+        type Query = ();
+        type Body = NewShip;
+        type Response = Created;
+
+        const METHOD: http::Method = http::Method::POST;
+        const PATH: &'static str = "/fleets/{fleet}/ships";
+
+        fn path_param(&self, name: &str) -> Option<Cow<'_, str>> {
+            match name {
+                "fleet" => Some(Cow::Borrowed(self.fleet)),
+                _ => None,
+            }
+        }
+
+        fn body(&self) -> Option<&Self::Body> {
+            Some(&self.ship)
+        }
+    }
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::POST, "/fleets/kido/ships", |req| {
+        let body = std::str::from_utf8(req.body()).expect("UTF-8 body");
+        yukikaze::test::response(http::StatusCode::OK, format!("{{\"fleet\":\"kido\",{}", &body[1..]))
+    });
+    server.route(http::Method::POST, "/fleets/missing/ships", |_| yukikaze::test::response(http::StatusCode::NOT_FOUND, "No such fleet"));
+
+    let client = client::Client::builder().base_url(Some(server.base_url().parse().expect("To parse base URL"))).build();
+
+    let endpoint = CreateShip {
+        fleet: "kido",
+        ship: NewShip { name: "Yukikaze" },
+    };
+    let created = matsu!(client.call(&endpoint)).expect("To call endpoint");
+    assert_eq!(created, Created { fleet: "kido".to_owned(), name: "Yukikaze".to_owned() });
+
+    let endpoint = CreateShip {
+        fleet: "missing",
+        ship: NewShip { name: "Yukikaze" },
+    };
+    match matsu!(client.call(&endpoint)) {
+        Err(client::errors::EndpointError::Call(client::errors::CallError::Status(error))) => {
+            assert_eq!(error.status, http::StatusCode::NOT_FOUND);
+            assert_eq!(error.body.as_ref().map(|body| &body[..]), Some(&b"No such fleet"[..]));
+        },
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+pub struct Http2Cfg;
+
+impl client::config::Config for Http2Cfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn config_hyper(builder: &mut hyper::client::Builder) -> &mut hyper::client::Builder {
+        builder.http2_only(true)
+    }
+}
+
+#[tokio::test]
+async fn should_send_trailers() {
+    use http_body::Body as HttpBody;
+
+    let addr = serve(|req: hyper::Request<hyper::Body>| async move {
+        let mut body = req.into_body();
+        let mut data = Vec::new();
+        while let Some(chunk) = body.data().await {
+            data.extend_from_slice(&chunk?);
+        }
+        assert_eq!(data, b"ship");
+
+        let trailers = body.trailers().await?.expect("To have trailers");
+        let checksum = trailers.get("x-checksum").expect("To have checksum").clone();
+        Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::from(checksum.as_bytes().to_vec())))
+    });
+
+    let mut trailers = http::HeaderMap::new();
+    trailers.insert("x-checksum", http::header::HeaderValue::from_static("1234"));
+    let create_request = || client::request::Request::post(format!("http://{}/", addr)).expect("To create request")
+                                                                                          .trailers(trailers.clone())
+                                                                                          .body(Some("ship"));
+
+    let client = client::Client::<Http2Cfg>::new();
+    let mut response = matsu!(client.request(create_request())).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "1234");
+
+    //HTTP/1 cannot transmit trailers
+    let client = client::Client::default();
+    assert!(matsu!(client.request(create_request())).is_err());
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_send_with_budget() {
+    let server = yukikaze::test::Server::new();
+    server.route_delayed(http::Method::GET, "/", core::time::Duration::from_millis(250), yukikaze);
+
+    let client = client::Client::builder().timeout(core::time::Duration::from_millis(100)).build();
+
+    let request = client::request::Request::get(server.url("/")).expect("To create request").empty();
+    let mut response = matsu!(client.send_with_budget(request, core::time::Duration::from_secs(5))).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "Yukikaze");
+
+    let request = client::request::Request::get(server.url("/")).expect("To create request").empty();
+    match matsu!(client.send_with_budget(request, core::time::Duration::from_millis(150))) {
+        Err(client::errors::BudgetError::Expired { budget, attempts }) => {
+            assert_eq!(budget, core::time::Duration::from_millis(150));
+            assert_eq!(attempts, 2);
+        },
+        result => panic!("Unexpected result: {:?}", result.map(|_| ())),
+    }
+}
+
+static CONNECTION_EVENTS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+pub struct EventsCfg;
+
+impl client::config::Config for EventsCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn instrument_connections() -> bool {
+        true
+    }
+
+    fn on_connection_event(event: &yukikaze::connector::events::ConnectionEvent<'_>) {
+        use yukikaze::connector::events::ConnectionEvent;
+
+        let event = match event {
+            ConnectionEvent::ConnectStart { host } => format!("start {}", host),
+            ConnectionEvent::Connected { host, addr, reused } => format!("connected {} {} {}", host, addr.is_some(), reused),
+            ConnectionEvent::TlsHandshakeDone { host, .. } => format!("tls {}", host),
+            ConnectionEvent::ConnectFailed { host } => format!("failed {}", host),
+            ConnectionEvent::ConnectionClosed { host, .. } => format!("closed {}", host),
+        };
+        CONNECTION_EVENTS.lock().unwrap().push(event);
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_report_connection_events() {
+    use yukikaze::async_timer::Oneshot;
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", yukikaze);
+
+    let client = client::Client::<EventsCfg>::new();
+    for _ in 0..2 {
+        let request = client::request::Request::get(server.url("/")).expect("To create request").empty();
+        let mut response = matsu!(client.request(request)).expect("To get response");
+        assert_eq!(matsu!(response.text()).expect("To read body"), "Yukikaze");
+    }
+    drop(client);
+
+    let host = server.base_url();
+    let expected = [format!("start {}", host), format!("connected {} true false", host), format!("connected {} true true", host), format!("closed {}", host)];
+    for _ in 0..50 {
+        if CONNECTION_EVENTS.lock().unwrap().len() == expected.len() {
+            break;
+        }
+        matsu!(client::config::DefaultTimer::new(core::time::Duration::from_millis(10)));
+    }
+    assert_eq!(*CONNECTION_EVENTS.lock().unwrap(), expected);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_cache_permanent_redirects() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/old", |_| redirect(http::StatusCode::MOVED_PERMANENTLY, "/new"));
+    server.route(http::Method::GET, "/new", echo_uri);
+
+    let client = client::Client::builder().redirect_cache(Some(client::config::RedirectCache::default())).build();
+    let old = server.url("/old");
+
+    for _ in 0..2 {
+        let request = client::request::Request::get(&old).expect("To create request").empty();
+        let mut response = matsu!(client.redirect_request(request)).expect("To get response");
+        assert_eq!(matsu!(response.text()).expect("To read body"), "/new");
+    }
+    assert_eq!(server.hits(http::Method::GET, "/old"), 1);
+
+    let redirects = client.permanent_redirects();
+    assert_eq!(redirects.get(&old).expect("To have redirect").to_string(), server.url("/new"));
+
+    client.clear_permanent_redirects();
+    assert!(client.permanent_redirects().is_empty());
+
+    let request = client::request::Request::get(&old).expect("To create request").empty();
+    matsu!(client.redirect_request(request)).expect("To get response");
+    assert_eq!(server.hits(http::Method::GET, "/old"), 2);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_extract_multipart_response() {
+    //Echoes form back, as some webhooks do
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::POST, "/", echo_body);
+    server.route(http::Method::GET, "/", echo_body);
+
+    let mut form = client::request::multipart::Form::new();
+    form.add_field("fleet".to_owned(), b"Destroyer Division 16");
+    form.add_file_field("ship".to_owned(), "yukikaze.json".to_owned(), &mime::APPLICATION_JSON, b"{\"name\":\"Yukikaze\"}");
+
+    let client = client::Client::default();
+    let request = client::request::Request::post(server.url("/")).expect("To create request").multipart(form);
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let form = matsu!(response.multipart()).expect("To parse multipart");
+
+    assert_eq!(form.len(), 2);
+    assert_eq!(form.get("fleet").and_then(|part| part.text()), Some("Destroyer Division 16"));
+    let ship = form.get("ship").expect("To have ship");
+    assert_eq!(ship.filename().as_deref(), Some("yukikaze.json"));
+    assert_eq!(ship.mime(), Some(mime::APPLICATION_JSON));
+    assert_eq!(ship.body(), "{\"name\":\"Yukikaze\"}");
+
+    let request = client::request::Request::get(server.url("/")).expect("To create request").json(&"ship").expect("To create JSON");
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    match matsu!(response.multipart()) {
+        Err(yukikaze::extractor::BodyReadError::MultipartError(yukikaze::extractor::MultipartError::NotMultipart)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_mirror_request() {
+    use std::sync::{Arc, Mutex};
+
+    let mirrored = Arc::new(Mutex::new(Vec::new()));
+    let shadow_mirrored = mirrored.clone();
+    let shadow = yukikaze::test::Server::new();
+    shadow.route(http::Method::POST, "/ships", move |req| {
+        let host = req.headers().get(http::header::HOST).cloned();
+        shadow_mirrored.lock().unwrap().push((req.method().clone(), host, req.body().clone()));
+        yukikaze::test::response(http::StatusCode::INTERNAL_SERVER_ERROR, "")
+    });
+    let shadow_addr = shadow.addr();
+
+    let primary = yukikaze::test::Server::new();
+    //Shadow request is dropped once primary completes, so let it reach shadow target first.
+    primary.route_delayed(http::Method::POST, "/ships", core::time::Duration::from_millis(100), |_| yukikaze::test::response(http::StatusCode::OK, "primary"));
+    primary.route(http::Method::GET, "/", |_| yukikaze::test::response(http::StatusCode::OK, "primary"));
+
+    let client = client::Client::default();
+    let request = client::request::Request::post(primary.url("/ships")).expect("To create request").body(Some("Yukikaze"));
+    let mut response = matsu!(client.mirror(request, |uri| format!("http://{}{}", shadow_addr, uri.path()).parse().ok())).expect("To get response");
+    assert!(response.is_success());
+    assert_eq!(matsu!(response.text()).expect("To read body"), "primary");
+
+    {
+        let mirrored = mirrored.lock().unwrap();
+        assert_eq!(mirrored.len(), 1);
+        assert_eq!(mirrored[0].0, http::Method::POST);
+        assert_eq!(mirrored[0].1.as_ref().unwrap(), &shadow_addr.to_string());
+        assert_eq!(mirrored[0].2, "Yukikaze");
+    }
+
+    let request = client::request::Request::get(primary.url("/")).expect("To create request").empty();
+    let response = matsu!(client.mirror(request, |_| None)).expect("To get response");
+    assert!(response.is_success());
+    assert_eq!(mirrored.lock().unwrap().len(), 1);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_not_wait_for_hanging_mirror() {
+    let shadow = yukikaze::test::Server::new();
+    shadow.route_delayed(http::Method::GET, "/", core::time::Duration::from_secs(10), |_| yukikaze::test::response(http::StatusCode::OK, "shadow"));
+    let shadow_addr = shadow.addr();
+
+    let primary = yukikaze::test::Server::new();
+    primary.route(http::Method::GET, "/", |_| yukikaze::test::response(http::StatusCode::OK, "primary"));
+
+    let client = client::Client::builder().timeout(core::time::Duration::from_secs(0)).build();
+    let request = client::request::Request::get(primary.url("/")).expect("To create request").empty();
+    let started = std::time::Instant::now();
+    let mut response = matsu!(client.mirror(request, |uri| format!("http://{}{}", shadow_addr, uri.path()).parse().ok())).expect("To get response");
+    assert!(started.elapsed() < core::time::Duration::from_secs(5));
+    assert_eq!(matsu!(response.text()).expect("To read body"), "primary");
+}
+
+#[tokio::test]
+async fn should_send_hedged_request() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use yukikaze::async_timer::Oneshot;
+
+    static REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+    //First request is stuck, while hedged one is answered immediately
+    let addr = serve(|_| async move {
+        let num = REQUESTS.fetch_add(1, Ordering::SeqCst);
+        if num == 0 {
+            matsu!(client::config::DefaultTimer::new(core::time::Duration::from_secs(5)));
+        }
+        Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::from(num.to_string())))
+    });
+
+    let client = client::Client::default();
+    let request = client::request::Request::get(format!("http://{}/", addr)).expect("To create request").empty();
+    let started = std::time::Instant::now();
+    let mut response = matsu!(client.send_hedged(request, core::time::Duration::from_millis(50), 2)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "1");
+    assert!(started.elapsed() < core::time::Duration::from_secs(5));
+    assert_eq!(REQUESTS.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_open_circuit_breaker() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", |_| yukikaze::test::response(http::StatusCode::INTERNAL_SERVER_ERROR, ""));
+
+    let settings = client::breaker::Settings {
+        min_requests: 2,
+        ..client::breaker::Settings::default()
+    };
+    let client = client::Client::builder().circuit_breaker(Some(settings)).build();
+    let url = server.url("/");
+
+    for _ in 0..2 {
+        let request = client::request::Request::get(&url).expect("To create request").empty();
+        let response = matsu!(client.request(request)).expect("To get response");
+        assert_eq!(response.status(), hyper::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let host = server.base_url();
+    assert_eq!(client.circuit_states().get(&host), Some(&client::breaker::State::Open));
+
+    let request = client::request::Request::get(&url).expect("To create request").empty();
+    match matsu!(client.request(request)) {
+        Err(client::Error::CircuitOpen(open)) => assert_eq!(open, host),
+        result => panic!("Unexpected result: {:?}", result.map(|response| response.status())),
+    }
+    assert_eq!(server.hits(http::Method::GET, "/"), 2);
+}
+
+#[cfg(feature = "compu")]
+#[tokio::test]
+async fn should_sniff_gzip_body() {
+    use compu::encoder::{Encoder, EncoderOp};
+
+    const TEXT: &str = "Compressed without Content-Encoding";
+
+    let options = compu::encoder::zlib::ZlibOptions::default().mode(compu::encoder::zlib::ZlibMode::Gzip);
+    let mut encoder = compu::compressor::memory::Compressor::new(compu::encoder::zlib::ZlibEncoder::new(&options));
+    encoder.push(TEXT.as_bytes(), EncoderOp::Finish);
+    let gzip = bytes::Bytes::from(encoder.take());
+
+    //Magic prefix is split between chunks
+    let addr = serve(move |req: hyper::Request<hyper::Body>| {
+        let gzip = gzip.clone();
+        async move {
+            if req.uri().path() == "/plain" {
+                return Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::from(TEXT)));
+            }
+
+            let (mut sender, body) = hyper::Body::channel();
+            tokio::spawn(async move {
+                sender.send_data(gzip.slice(..1)).await.expect("To send");
+                sender.send_data(gzip.slice(1..)).await.expect("To send");
+            });
+            Ok::<_, hyper::Error>(hyper::Response::new(body))
+        }
+    });
+
+    let client = client::Client::default();
+    let request = client::Request::get(format!("http://{}/", addr)).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let body = matsu!(response.body_sniffed()).expect("To read body");
+    assert_eq!(body.sniffed, Some(yukikaze::header::ContentEncoding::Gzip));
+    assert_eq!(body.data, TEXT);
+
+    let request = client::Request::get(format!("http://{}/plain", addr)).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let body = matsu!(response.body_sniffed()).expect("To read body");
+    assert!(!body.is_sniffed());
+    assert_eq!(body.data, TEXT);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_save_to_dir() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/attachment", |request| {
+        let mut response = echo_uri(request);
+        response.headers_mut().insert(http::header::CONTENT_DISPOSITION, http::HeaderValue::from_static("attachment; filename=\"../../evil.txt\""));
+        response
+    });
+    server.route(http::Method::GET, "/files/report%20v1.txt", echo_uri);
+    server.route(http::Method::GET, "/", echo_uri);
+
+    let dir = std::env::temp_dir().join(format!("yukikaze-save-{}", std::process::id())).join("nested");
+    let client = client::Client::default();
+
+    let request = client::Request::get(server.url("/attachment")).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let path = matsu!(response.save_to_dir(&dir)).expect("To save body");
+    assert_eq!(path, dir.join("evil.txt"));
+    assert_eq!(std::fs::read_to_string(&path).expect("To read file"), "/attachment");
+
+    let request = client::Request::get(server.url("/files/report%20v1.txt")).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let path = matsu!(response.save_to_dir(&dir)).expect("To save body");
+    assert_eq!(path, dir.join("report v1.txt"));
+
+    let request = client::Request::get(server.url("/")).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let path = matsu!(response.save_to_dir(&dir)).expect("To save body");
+    assert_eq!(path, dir.join("download"));
+
+    let _ = std::fs::remove_dir_all(dir.parent().expect("To have parent"));
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_authenticate_with_proxy() {
+    use yukikaze::connector::proxy::{self, ProxyAuthError, ProxyUri};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CHALLENGES: AtomicUsize = AtomicUsize::new(0);
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", yukikaze);
+    let url = server.url("/");
+
+    //user:password
+    let proxy_addr = serve(|req: hyper::Request<hyper::Body>| async move {
+        match req.headers().get(http::header::PROXY_AUTHORIZATION) {
+            Some(auth) if auth == "Basic dXNlcjpwYXNzd29yZA==" => match req.method() {
+                &hyper::Method::CONNECT => Ok::<_, hyper::Error>(tunnel(req)),
+                _ => forward(req).await,
+            },
+            _ => {
+                CHALLENGES.fetch_add(1, Ordering::SeqCst);
+                let mut response = hyper::Response::new(hyper::Body::from("Authenticate"));
+                *response.status_mut() = hyper::StatusCode::PROXY_AUTHENTICATION_REQUIRED;
+                response.headers_mut().insert(http::header::PROXY_AUTHENTICATE, http::HeaderValue::from_static("Basic realm=\"proxy\""));
+                Ok::<_, hyper::Error>(response)
+            },
+        }
+    });
+
+    let client = client::Client::default();
+
+    //Plain HTTP request carries credentials right away
+    let proxy_uri = ProxyUri::new(&format!("user:password@{}", proxy_addr)).expect("To parse proxy");
+    let request = client::Request::get(&url).expect("To create get request").via_proxy(Some(proxy_uri)).empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read text"), "Yukikaze");
+    assert_eq!(CHALLENGES.load(Ordering::SeqCst), 0);
+
+    let proxy_uri = ProxyUri::new(&proxy_addr.to_string()).expect("To parse proxy");
+    let request = client::Request::get(&url).expect("To create get request").via_proxy(Some(proxy_uri)).empty();
+    let response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(response.status(), http::StatusCode::PROXY_AUTHENTICATION_REQUIRED);
+    assert_eq!(CHALLENGES.load(Ordering::SeqCst), 1);
+
+    //Tunnel sends credentials only after challenge
+    let target = url.trim_start_matches("http://").trim_end_matches('/').to_owned();
+    let (host, port) = target.split_at(target.rfind(':').expect("To have port"));
+    let port = port[1..].parse::<u16>().expect("To parse port");
+
+    let proxy_uri = ProxyUri::new(&format!("user:password@{}", proxy_addr)).expect("To parse proxy");
+    let mut io = matsu!(tokio::net::TcpStream::connect(proxy_addr)).expect("To connect proxy");
+    matsu!(proxy::tunnel(&mut io, host, port, &proxy_uri)).expect("To establish tunnel");
+    assert_eq!(CHALLENGES.load(Ordering::SeqCst), 2);
+    matsu!(io.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")).expect("To write request");
+    let mut response = String::new();
+    matsu!(io.read_to_string(&mut response)).expect("To read response");
+    assert!(response.ends_with("Yukikaze"), "Unexpected response: {}", response);
+
+    let proxy_uri = ProxyUri::new(&proxy_addr.to_string()).expect("To parse proxy");
+    let mut io = matsu!(tokio::net::TcpStream::connect(proxy_addr)).expect("To connect proxy");
+    let error = matsu!(proxy::tunnel(&mut io, host, port, &proxy_uri)).expect_err("To fail");
+    assert!(matches!(ProxyAuthError::from_io(&error), Some(ProxyAuthError::MissingCredentials)), "Unexpected error: {}", error);
+
+    let proxy_uri = ProxyUri::new(&format!("user:wrong@{}", proxy_addr)).expect("To parse proxy");
+    let mut io = matsu!(tokio::net::TcpStream::connect(proxy_addr)).expect("To connect proxy");
+    let error = matsu!(proxy::tunnel(&mut io, host, port, &proxy_uri)).expect_err("To fail");
+    assert!(matches!(ProxyAuthError::from_io(&error), Some(ProxyAuthError::Rejected)), "Unexpected error: {}", error);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_authenticate_on_challenge() {
+    use yukikaze::client::auth;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CHALLENGES: AtomicUsize = AtomicUsize::new(0);
+
+    //user:password
+    let server = yukikaze::test::Server::new();
+    let challenge = |request: &yukikaze::test::Request| match request.headers().get(http::header::AUTHORIZATION) {
+        Some(auth) if auth == "Basic dXNlcjpwYXNzd29yZA==" => yukikaze(request),
+        _ => {
+            CHALLENGES.fetch_add(1, Ordering::SeqCst);
+            let mut response = yukikaze::test::response(http::StatusCode::UNAUTHORIZED, "Authenticate");
+            response.headers_mut().insert(http::header::WWW_AUTHENTICATE, http::HeaderValue::from_static("Bearer, Basic realm=\"api\""));
+            response
+        },
+    };
+    server.route(http::Method::POST, "/", challenge);
+    server.route(http::Method::GET, "/", challenge);
+    let url = server.url("/");
+
+    let client = client::Client::builder().authenticator(Arc::new(auth::Basic::new("user", Some("password")))).build();
+    let request = client::Request::post(&url).expect("To create request").body(Some("data"));
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert!(response.is_success());
+    assert_eq!(matsu!(response.text()).expect("To read body"), "Yukikaze");
+    assert_eq!(CHALLENGES.load(Ordering::SeqCst), 1);
+
+    let request = client::Request::get(&url).expect("To create request").empty();
+    let mut response = matsu!(client.send(request)).expect("To get without timeout").expect("To get response");
+    assert!(response.is_success());
+    assert_eq!(matsu!(response.text()).expect("To read body"), "Yukikaze");
+    assert_eq!(CHALLENGES.load(Ordering::SeqCst), 2);
+
+    //Rejected credentials are not retried
+    let client = client::Client::builder().authenticator(Arc::new(auth::Basic::new("user", Some("wrong")))).build();
+    let request = client::Request::get(&url).expect("To create request").empty();
+    let response = matsu!(client.redirect_request(request)).expect("To get response");
+    assert_eq!(response.status(), hyper::StatusCode::UNAUTHORIZED);
+    assert_eq!(CHALLENGES.load(Ordering::SeqCst), 4);
+
+    let client = client::Client::builder().authenticator(Arc::new(|_: &client::Request, challenges: &[yukikaze::header::AuthChallenge]| {
+        challenges.iter().find(|challenge| challenge.is_scheme("bearer")).map(|_| http::HeaderValue::from_static("Bearer token"))
+    })).build();
+    let request = client::Request::get(&url).expect("To create request").empty();
+    let response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(response.status(), hyper::StatusCode::UNAUTHORIZED);
+    assert_eq!(CHALLENGES.load(Ordering::SeqCst), 6);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_send_body_from_reader() {
+    let server = yukikaze::test::Server::new();
+    let framing = |request: &yukikaze::test::Request| {
+        let framing = match request.headers().get(http::header::CONTENT_LENGTH) {
+            Some(len) => format!("length={}", len.to_str().unwrap()),
+            None => format!("chunked={}", request.headers().contains_key(http::header::TRANSFER_ENCODING)),
+        };
+        yukikaze::test::response(http::StatusCode::OK, format!("{} {}", framing, String::from_utf8_lossy(request.body())))
+    };
+    server.route(http::Method::POST, "/", framing);
+    server.route(http::Method::PUT, "/", framing);
+    let url = server.url("/");
+
+    let client = client::Client::default();
+
+    let request = client::Request::post(&url).expect("To create request").body_reader(std::io::Cursor::new(b"Yukikaze".to_vec()), Some(8));
+    assert!(!request.is_retryable());
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "length=8 Yukikaze");
+
+    let request = client::Request::put(&url).expect("To create request").body_reader_chunked(&b"Yukikaze"[..], None, 3);
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "chunked=true Yukikaze");
+}
+
+pub struct Http2TunedCfg;
+
+impl client::config::Config for Http2TunedCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn http2() -> client::config::Http2 {
+        client::config::Http2 {
+            initial_stream_window_size: Some(1024 * 1024),
+            initial_connection_window_size: Some(4 * 1024 * 1024),
+            max_frame_size: Some(32 * 1024),
+            keep_alive_interval: Some(core::time::Duration::from_secs(10)),
+            keep_alive_while_idle: true,
+            ..Default::default()
+        }
+    }
+
+    fn config_hyper(builder: &mut hyper::client::Builder) -> &mut hyper::client::Builder {
+        builder.http2_only(true)
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_use_http2_settings() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", |request| yukikaze::test::response(http::StatusCode::OK, format!("{:?} {}", request.version(), "a".repeat(100_000))));
+
+    let client = client::Client::<Http2TunedCfg>::new();
+    let request = client::Request::get(server.url("/")).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let body = matsu!(response.body_limited(Some(200_000))).expect("To read body");
+    assert!(body.starts_with(b"HTTP/2.0 aaa"));
+    assert_eq!(body.len(), 100_009);
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_use_convenience_methods() {
+    use yukikaze::client::errors::{CallError, SimpleError};
+
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Ship {
+        name: String,
+    }
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/ship", |_| yukikaze::test::response(http::StatusCode::OK, "{\"name\":\"Yukikaze\"}"));
+    server.route(http::Method::POST, "/ship", echo_body);
+    server.route(http::Method::PUT, "/ship", echo_body);
+
+    let client = client::Client::default();
+    let url = server.url("/ship");
+
+    let mut response = matsu!(client.get(&url)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "{\"name\":\"Yukikaze\"}");
+
+    let ship: Ship = matsu!(client.get_json(&url)).expect("To get ship");
+    assert_eq!(ship.name, "Yukikaze");
+
+    let ship = Ship { name: "Shimakaze".to_owned() };
+    let echo: Ship = matsu!(client.post_json(&url, &ship)).expect("To post ship");
+    assert_eq!(echo, ship);
+
+    let mut response = matsu!(client.put_bytes(&url, "raw")).expect("To put bytes");
+    assert_eq!(matsu!(response.text()).expect("To read body"), "raw");
+
+    match matsu!(client.get(server.url("/unknown"))) {
+        Err(SimpleError::Call(CallError::Status(error))) => assert_eq!(error.status, http::StatusCode::NOT_FOUND),
+        result => panic!("Unexpected result: {:?}", result.map(|response| response.status())),
+    }
+    assert!(matches!(matsu!(client.get("not a uri")), Err(SimpleError::Call(CallError::Uri(_)))));
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_send_expect() {
+    use yukikaze::client::errors::{ApiError, CallError};
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Ship {
+        name: String,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Fault {
+        code: u32,
+    }
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/ship", |_| yukikaze::test::response(http::StatusCode::OK, "{\"name\":\"Yukikaze\"}"));
+    server.route(http::Method::GET, "/sunk", |_| yukikaze::test::response(http::StatusCode::GONE, "{\"code\":42}"));
+    server.route(http::Method::GET, "/crash", |_| yukikaze::test::response(http::StatusCode::INTERNAL_SERVER_ERROR, "Oops"));
+
+    let client = client::Client::default();
+
+    let request = client::Request::get(server.url("/ship")).expect("To create request").empty();
+    let ship = matsu!(client.send_expect::<Ship, Fault>(request)).expect("To get ship");
+    assert_eq!(ship.name, "Yukikaze");
+
+    let request = client::Request::get(server.url("/sunk")).expect("To create request").empty();
+    match matsu!(client.send_expect::<Ship, Fault>(request)) {
+        Err(ApiError::Api { status, error, .. }) => {
+            assert_eq!(status, http::StatusCode::GONE);
+            assert_eq!(error.code, 42);
+        },
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let request = client::Request::get(server.url("/crash")).expect("To create request").empty();
+    match matsu!(client.send_expect::<Ship, Fault>(request)) {
+        Err(ApiError::Call(CallError::Status(error))) => {
+            assert_eq!(error.status, http::StatusCode::INTERNAL_SERVER_ERROR);
+            assert_eq!(error.body.as_ref().map(|body| &body[..]), Some(&b"Oops"[..]));
+        },
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+pub struct SingleConnectionCfg;
+
+impl client::config::Config for SingleConnectionCfg {
+    type Connector = client::config::DefaultConnector;
+    type Timer = client::config::DefaultTimer;
+
+    fn max_connections_per_host() -> Option<usize> {
+        Some(1)
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_limit_connections_per_host() {
+    let server = yukikaze::test::Server::new();
+    server.route_delayed(http::Method::GET, "/", core::time::Duration::from_millis(50), |_| yukikaze::test::response(http::StatusCode::OK, "ok"));
+
+    let client = client::Client::<SingleConnectionCfg>::new();
+    let send = || {
+        let request = client::Request::get(server.url("/")).expect("To create request").empty();
+        client.request(request)
+    };
+
+    let (first, second, third) = tokio::join!(send(), send(), send());
+    for response in [first, second, third].iter() {
+        assert!(response.as_ref().expect("To get response").is_success());
+    }
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn should_read_body_chunks() {
+    let addr = serve(|_| async {
+        let (mut sender, body) = hyper::Body::channel();
+        tokio::spawn(async move {
+            for chunk in ["Yuki", "kaze", " is lucky"].iter() {
+                sender.send_data(hyper::body::Bytes::from_static(chunk.as_bytes())).await.expect("To send chunk");
+                tokio::time::delay_for(core::time::Duration::from_millis(10)).await;
+            }
+        });
+        Ok::<_, hyper::Error>(hyper::Response::new(body))
+    });
+
+    let client = client::Client::default();
+    let request = client::Request::get(format!("http://{}/", addr)).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    let chunks = matsu!(response.body_chunks()).expect("To read chunks");
+    assert!(chunks.len() > 1);
+    assert_eq!(chunks.concat(), b"Yukikaze is lucky");
+
+    let request = client::Request::get(format!("http://{}/", addr)).expect("To create request").empty();
+    let mut response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(matsu!(response.text()).expect("To read text"), "Yukikaze is lucky");
+}
+
+pub struct ChaosCfg;
+
+impl client::config::Config for ChaosCfg {
+    type Connector = yukikaze::connector::chaos::Chaos<client::config::DefaultConnector>;
+    type Timer = client::config::DefaultTimer;
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_inject_faults() {
+    use yukikaze::connector::chaos::{Chaos, Faults};
+
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/", |_| yukikaze::test::response(http::StatusCode::OK, "a".repeat(1024)));
+    let url = server.url("/");
+
+    let faults = Faults {
+        seed: 1,
+        error_rate: 1.0,
+        error_burst: 2,
+        ..Default::default()
+    };
+    let client = client::builder::ClientBuilder::<ChaosCfg>::new().connector(Chaos::new(Default::default(), faults)).build();
+    for _ in 0..2 {
+        let request = client::Request::get(&url).expect("To create request").empty();
+        let response = matsu!(client.request(request)).expect("To get response");
+        assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    let faults = Faults {
+        seed: 1,
+        truncate_rate: 1.0,
+        fault_after: 200,
+        ..Default::default()
+    };
+    let client = client::builder::ClientBuilder::<ChaosCfg>::new().connector(Chaos::new(Default::default(), faults)).build();
+    let request = client::Request::get(&url).expect("To create request").empty();
+    let result = match matsu!(client.request(request)) {
+        Ok(mut response) => matsu!(response.body()).map(|body| body.len()).map_err(|error| error.to_string()),
+        Err(error) => Err(error.to_string()),
+    };
+    assert!(result.is_err(), "Unexpected result: {:?}", result);
+
+    let faults = Faults {
+        seed: 1,
+        connect_failure_rate: 1.0,
+        ..Default::default()
+    };
+    let client = client::builder::ClientBuilder::<ChaosCfg>::new().connector(Chaos::new(Default::default(), faults)).build();
+    let request = client::Request::get(&url).expect("To create request").empty();
+    assert!(matsu!(client.request(request)).is_err());
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_prewarm_connections() {
+    let server = yukikaze::test::Server::new();
+    let delay = core::time::Duration::from_millis(20);
+    server.route_delayed(http::Method::HEAD, "/", delay, |_| yukikaze::test::response(http::StatusCode::OK, ""));
+    server.route_delayed(http::Method::GET, "/", delay, |_| yukikaze::test::response(http::StatusCode::OK, ""));
+
+    let client = client::Client::default();
+    let warmed = matsu!(client.prewarm(server.base_url(), 3)).expect("To prewarm");
+    assert_eq!(warmed, 3);
+    assert_eq!(server.connections(), 3);
+    assert_eq!(server.hits(http::Method::HEAD, "/"), 3);
+
+    let request = client::Request::get(server.url("/")).expect("To create request").empty();
+    let response = matsu!(client.request(request)).expect("To get response");
+    assert!(response.is_success());
+    assert_eq!(server.connections(), 3);
+
+    let client = client::Client::default();
+    match matsu!(client.prewarm("127.0.0.1:1", 2)) {
+        Err(client::errors::PrewarmError::Request(_)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[cfg(feature = "test_server")]
+#[tokio::test]
+async fn should_preserve_fragment_on_redirect() {
+    let server = yukikaze::test::Server::new();
+    server.route(http::Method::GET, "/moved", |_| redirect(http::StatusCode::FOUND, "/target"));
+    server.route(http::Method::GET, "/moved-fragment", |_| redirect(http::StatusCode::FOUND, "/target#section"));
+    server.route(http::Method::GET, "/target", |_| yukikaze::test::response(http::StatusCode::OK, ""));
+
+    let client = client::Client::default();
+
+    let request = client::Request::get(server.url("/moved#top")).expect("To create request").empty();
+    assert_eq!(request.extensions().get::<client::request::Fragment>(), Some(&client::request::Fragment("top".to_owned())));
+    let response = matsu!(client.redirect_request(request)).expect("To get response");
+    assert_eq!(response.effective_uri(), Some(server.url("/target#top")));
+    assert_eq!(response.uri().map(|uri| uri.path()), Some("/target"));
+    assert_eq!(response.original_uri().map(|uri| uri.path()), Some("/moved"));
+
+    let request = client::Request::get(server.url("/moved-fragment#top")).expect("To create request").empty();
+    let response = matsu!(client.redirect_request(request)).expect("To get response");
+    assert_eq!(response.effective_uri(), Some(server.url("/target#section")));
+
+    let request = client::Request::get(server.url("/target")).expect("To create request").empty();
+    let response = matsu!(client.request(request)).expect("To get response");
+    assert_eq!(response.effective_uri(), Some(server.url("/target")));
+    assert_eq!(response.uri(), response.original_uri());
 }